@@ -1,10 +1,209 @@
 //! Development tools for the game. This plugin is only enabled in dev builds.
 
-use bevy::{dev_tools::states::log_transitions, prelude::*};
+use std::collections::HashMap;
 
-use crate::screen::Screen;
+use bevy::{
+    dev_tools::states::log_transitions,
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+};
+
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        level_definition::EditorLevel,
+        spawn::level::{CurrentLevel, GameMode},
+        telemetry::{TelemetryEvent, TelemetryLog},
+        validation::LevelValidations,
+    },
+    screen::Screen,
+    ui::palette::LABEL_TEXT,
+};
+
+/// The name the level being edited is checked under
+const EDITOR_LEVEL_NAME: &str = "being edited";
+
+/// How big each square of the death heatmap is, in pixels
+const HEATMAP_CELL_SIZE: f32 = 32.0;
+
+/// How opaque the square with the most deaths in it is drawn
+const HEATMAP_MAX_ALPHA: f32 = 0.7;
+
+/// Draws the heatmap over the obstacles and the player
+const HEATMAP_Z: f32 = 10.0;
+
+/// Draws the frame time readout over everything else
+const FRAME_TIME_Z_INDEX: i32 = 100;
 
 pub(super) fn plugin(app: &mut App) {
     // Print state transitions in dev builds
     app.add_systems(Update, log_transitions::<Screen>);
+
+    // Check whether the level being edited can be cleared with F8, and log the result once it's known
+    app.add_systems(
+        Update,
+        validate_editor_level
+            .run_if(in_state(Screen::Editor).and_then(input_just_pressed(KeyCode::F8))),
+    );
+
+    // Show where recorded deaths happened in the current level with F9
+    app.init_resource::<ShowDeathHeatmap>();
+    app.add_systems(OnEnter(Screen::Playing), spawn_death_heatmap);
+    app.add_systems(
+        Update,
+        (
+            toggle_death_heatmap.run_if(input_just_pressed(KeyCode::F9)),
+            spawn_death_heatmap.run_if(
+                resource_changed::<ShowDeathHeatmap>
+                    .or_else(resource_changed::<CurrentLevel>)
+                    .or_else(resource_changed::<TelemetryLog>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Playing)),
+    );
+
+    // Show how long frames are taking in the corner with F10
+    app.add_plugins(FrameTimeDiagnosticsPlugin);
+    app.add_systems(
+        Update,
+        (
+            toggle_frame_time_display.run_if(input_just_pressed(KeyCode::F10)),
+            update_frame_time_display,
+        )
+            .chain(),
+    );
+}
+
+/// Whether the death heatmap is being shown
+#[derive(Resource, Debug, Default)]
+struct ShowDeathHeatmap(bool);
+
+/// One square of the death heatmap
+#[derive(Component, Debug)]
+struct DeathHeatmapCell;
+
+/// The text showing how long frames are taking
+#[derive(Component, Debug)]
+struct FrameTimeDisplay;
+
+fn validate_editor_level(
+    editor_level: Res<EditorLevel>,
+    mut validations: ResMut<LevelValidations>,
+) {
+    validations.check(EDITOR_LEVEL_NAME, &editor_level.0);
+}
+
+fn toggle_death_heatmap(mut show: ResMut<ShowDeathHeatmap>) {
+    show.0 = !show.0;
+}
+
+/// Replaces the heatmap with one for the current level, made from the deaths in the local telemetry.
+/// Each square is more opaque the more deaths happened in it, relative to the square with the most.
+fn spawn_death_heatmap(
+    show: Res<ShowDeathHeatmap>,
+    log: Res<TelemetryLog>,
+    game_mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+    cell_query: Query<Entity, With<DeathHeatmapCell>>,
+    mut commands: Commands,
+) {
+    for entity in &cell_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if !show.0 {
+        return;
+    }
+
+    let mut deaths_per_cell = HashMap::<IVec2, u32>::new();
+    for event in &log.events {
+        if let TelemetryEvent::Death {
+            mode, level, x, y, ..
+        } = event
+        {
+            if *mode == *game_mode && *level == current_level.0 {
+                let cell = (Vec2::new(*x, *y) / HEATMAP_CELL_SIZE).floor().as_ivec2();
+                *deaths_per_cell.entry(cell).or_default() += 1;
+            }
+        }
+    }
+
+    let Some(max_deaths) = deaths_per_cell.values().max().copied() else {
+        info!("no deaths recorded in level {}", current_level.0);
+        return;
+    };
+
+    for (cell, deaths) in deaths_per_cell {
+        let center = (cell.as_vec2() + 0.5) * HEATMAP_CELL_SIZE;
+        commands.spawn((
+            Name::new("Death heatmap cell"),
+            DeathHeatmapCell,
+            StateScoped(Screen::Playing),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(HEATMAP_CELL_SIZE)),
+                    color: Color::srgba(
+                        1.0,
+                        0.0,
+                        0.0,
+                        HEATMAP_MAX_ALPHA * deaths as f32 / max_deaths as f32,
+                    ),
+                    ..default()
+                },
+                transform: Transform::from_translation(center.extend(HEATMAP_Z)),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn toggle_frame_time_display(
+    display_query: Query<Entity, With<FrameTimeDisplay>>,
+    font_handles: Res<HandleMap<FontKey>>,
+    mut commands: Commands,
+) {
+    if display_query.is_empty() {
+        commands.spawn((
+            Name::new("Frame time display"),
+            FrameTimeDisplay,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: font_handles.get(FontKey::General),
+                    font_size: 16.0,
+                    color: LABEL_TEXT,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(5.0),
+                right: Val::Px(5.0),
+                ..default()
+            }),
+            ZIndex::Global(FRAME_TIME_Z_INDEX),
+        ));
+    } else {
+        for entity in &display_query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Shows the average frame time and frame rate over the last few frames
+fn update_frame_time_display(
+    diagnostics: Res<DiagnosticsStore>,
+    mut display_query: Query<&mut Text, With<FrameTimeDisplay>>,
+) {
+    let Some(frame_time) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    else {
+        return;
+    };
+
+    for mut text in &mut display_query {
+        text.sections[0].value = format!("{frame_time:.1} ms ({:.0} fps)", 1000.0 / frame_time);
+    }
 }
@@ -8,12 +8,16 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use super::movement::{MovementController, Paused};
+use super::{
+    movement::{GroundState, LeftGroundEvent, MovementController, Paused},
+    spawn::sequencer::Dead,
+};
 use crate::AppSet;
 
 pub(super) fn plugin(app: &mut App) {
     // Animate and play sound effects based on controls.
     app.register_type::<PlayerAnimation>();
+    app.observe(start_jumping_animation);
     app.add_systems(
         Update,
         (
@@ -25,12 +29,32 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-/// Update the sprite direction and animation state (idling/walking).
-fn update_animation_movement(mut player_query: Query<(&MovementController, &mut PlayerAnimation)>) {
-    for (controller, mut animation) in &mut player_query {
-        let animation_state = if controller.jumping {
-            PlayerAnimationState::Jumping
-        } else if controller.speed < f32::EPSILON {
+/// Switch to the jumping animation when the player leaves the ground.
+fn start_jumping_animation(
+    trigger: Trigger<LeftGroundEvent>,
+    mut animation_query: Query<&mut PlayerAnimation>,
+) {
+    if let Ok(mut animation) = animation_query.get_mut(trigger.event().player) {
+        animation.update_state(PlayerAnimationState::Jumping);
+    }
+}
+
+/// Update the animation state (idling/walking) while the player is on the ground.
+fn update_animation_movement(
+    mut player_query: Query<(&MovementController, &GroundState, &mut PlayerAnimation)>,
+    dead: Res<Dead>,
+) {
+    // the death sequence controls the animation once the player's dead
+    if dead.0 {
+        return;
+    }
+
+    for (controller, ground_state, mut animation) in &mut player_query {
+        if *ground_state == GroundState::Airborne {
+            continue;
+        }
+
+        let animation_state = if controller.speed < f32::EPSILON {
             PlayerAnimationState::Idling
         } else {
             PlayerAnimationState::Walking
@@ -44,8 +68,9 @@ fn update_animation_timer(
     time: Res<Time>,
     mut query: Query<&mut PlayerAnimation>,
     paused: Res<Paused>,
+    dead: Res<Dead>,
 ) {
-    if paused.0 {
+    if paused.0 && !dead.0 {
         return;
     }
 
@@ -78,6 +103,10 @@ pub enum PlayerAnimationState {
     Idling,
     Walking,
     Jumping,
+    /// Flashing from being hit, right when the player dies
+    Hurt,
+    /// Spinning while falling off the screen after dying
+    Tumbling,
 }
 
 impl PlayerAnimation {
@@ -118,6 +147,28 @@ impl PlayerAnimation {
         }
     }
 
+    const HURT_FRAMES: usize = 1;
+    const HURT_INTERVAL: Duration = Duration::from_millis(50);
+
+    fn hurt() -> Self {
+        Self {
+            timer: Timer::new(Self::HURT_INTERVAL, TimerMode::Repeating),
+            frame: 0,
+            state: PlayerAnimationState::Hurt,
+        }
+    }
+
+    const TUMBLING_FRAMES: usize = 3;
+    const TUMBLING_INTERVAL: Duration = Duration::from_millis(80);
+
+    fn tumbling() -> Self {
+        Self {
+            timer: Timer::new(Self::TUMBLING_INTERVAL, TimerMode::Repeating),
+            frame: 0,
+            state: PlayerAnimationState::Tumbling,
+        }
+    }
+
     pub fn new() -> Self {
         Self::idling()
     }
@@ -133,6 +184,8 @@ impl PlayerAnimation {
                 PlayerAnimationState::Idling => Self::IDLE_FRAMES,
                 PlayerAnimationState::Walking => Self::WALKING_FRAMES,
                 PlayerAnimationState::Jumping => Self::JUMPING_FRAMES,
+                PlayerAnimationState::Hurt => Self::HURT_FRAMES,
+                PlayerAnimationState::Tumbling => Self::TUMBLING_FRAMES,
             };
     }
 
@@ -143,6 +196,8 @@ impl PlayerAnimation {
                 PlayerAnimationState::Idling => *self = Self::idling(),
                 PlayerAnimationState::Walking => *self = Self::walking(),
                 PlayerAnimationState::Jumping => *self = Self::jumping(),
+                PlayerAnimationState::Hurt => *self = Self::hurt(),
+                PlayerAnimationState::Tumbling => *self = Self::tumbling(),
             }
         }
     }
@@ -158,6 +213,8 @@ impl PlayerAnimation {
             PlayerAnimationState::Idling => self.frame,
             PlayerAnimationState::Walking => 7 + self.frame,
             PlayerAnimationState::Jumping => 14 + self.frame,
+            PlayerAnimationState::Hurt => 21 + self.frame,
+            PlayerAnimationState::Tumbling => 22 + self.frame,
         }
     }
 }
@@ -1,36 +1,83 @@
+use std::marker::PhantomData;
+
 use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use serde::de::DeserializeOwned;
 
-use super::spawn::sequencer::NUM_SYNTH_NOTES;
+use super::{
+    credits::Credits,
+    day_cycle::DayCycle,
+    judgement::Judgements,
+    localization::{Language, Locale},
+    theme::LevelTheme,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<HandleMap<ImageKey>>();
     app.init_resource::<HandleMap<ImageKey>>();
+}
 
-    app.register_type::<HandleMap<SfxKey>>();
-    app.init_resource::<HandleMap<SfxKey>>();
-
-    app.register_type::<HandleMap<SoundtrackKey>>();
-    app.init_resource::<HandleMap<SoundtrackKey>>();
-
+/// Loads the assets that are only needed to show things to the player
+pub(super) fn presentation_plugin(app: &mut App) {
     app.register_type::<HandleMap<FontKey>>();
     app.init_resource::<HandleMap<FontKey>>();
+
+    app.register_type::<HandleMap<CreditsKey>>();
+    app.init_resource::<HandleMap<CreditsKey>>();
+
+    app.register_type::<HandleMap<JudgementsKey>>();
+    app.init_resource::<HandleMap<JudgementsKey>>();
+
+    app.register_type::<HandleMap<DayCycleKey>>();
+    app.init_resource::<HandleMap<DayCycleKey>>();
+
+    app.register_type::<HandleMap<Language>>();
+    app.init_resource::<HandleMap<Language>>();
+
+    app.register_type::<HandleMap<ThemeKey>>();
+    app.init_resource::<HandleMap<ThemeKey>>();
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
 pub enum ImageKey {
     Player,
+    PlayerFrost,
+    PlayerMoss,
+    PlayerMidnight,
     Box,
     Spikes,
+    BouncePad,
+    Portal,
+}
+
+impl ImageKey {
+    pub const ALL: [ImageKey; 8] = [
+        ImageKey::Player,
+        ImageKey::PlayerFrost,
+        ImageKey::PlayerMoss,
+        ImageKey::PlayerMidnight,
+        ImageKey::Box,
+        ImageKey::Spikes,
+        ImageKey::BouncePad,
+        ImageKey::Portal,
+    ];
 }
 
 impl AssetKey for ImageKey {
     type Asset = Image;
 }
 
+impl HandleMap<ImageKey> {
+    /// Gets a map with an empty handle for every image, for when nothing is going to be drawn
+    pub fn placeholders() -> Self {
+        ImageKey::ALL.map(|key| (key, Handle::default())).into()
+    }
+}
+
 impl FromWorld for HandleMap<ImageKey> {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
@@ -44,6 +91,33 @@ impl FromWorld for HandleMap<ImageKey> {
                     },
                 ),
             ),
+            (
+                ImageKey::PlayerFrost,
+                asset_server.load_with_settings(
+                    "images/bb_atlas_frost.png",
+                    |settings: &mut ImageLoaderSettings| {
+                        settings.sampler = ImageSampler::nearest();
+                    },
+                ),
+            ),
+            (
+                ImageKey::PlayerMoss,
+                asset_server.load_with_settings(
+                    "images/bb_atlas_moss.png",
+                    |settings: &mut ImageLoaderSettings| {
+                        settings.sampler = ImageSampler::nearest();
+                    },
+                ),
+            ),
+            (
+                ImageKey::PlayerMidnight,
+                asset_server.load_with_settings(
+                    "images/bb_atlas_midnight.png",
+                    |settings: &mut ImageLoaderSettings| {
+                        settings.sampler = ImageSampler::nearest();
+                    },
+                ),
+            ),
             (
                 ImageKey::Box,
                 asset_server.load_with_settings(
@@ -62,6 +136,24 @@ impl FromWorld for HandleMap<ImageKey> {
                     },
                 ),
             ),
+            (
+                ImageKey::BouncePad,
+                asset_server.load_with_settings(
+                    "images/bounce_pad.png",
+                    |settings: &mut ImageLoaderSettings| {
+                        settings.sampler = ImageSampler::nearest();
+                    },
+                ),
+            ),
+            (
+                ImageKey::Portal,
+                asset_server.load_with_settings(
+                    "images/portal.png",
+                    |settings: &mut ImageLoaderSettings| {
+                        settings.sampler = ImageSampler::nearest();
+                    },
+                ),
+            ),
         ]
         .into()
     }
@@ -72,38 +164,108 @@ pub enum SfxKey {
     Kick,
     Snare,
     HiHat,
+    /// A note on the synth's scale. These are synthesized rather than loaded, so they aren't in the handle map.
     Synth(usize),
+    Bounce,
+    Portal,
+    Land,
+    Death,
+    Riser,
+    Whoosh,
+}
+
+impl SfxKey {
+    /// Gets how much this sound's pitch randomly varies each time it plays, so it sounds less robotic played over and over
+    pub fn pitch_jitter(self) -> f32 {
+        match self {
+            SfxKey::HiHat => 0.05,
+            SfxKey::Kick | SfxKey::Snare | SfxKey::Bounce | SfxKey::Land => 0.02,
+            // synth notes are in tune with the scale, and the rest don't come up often enough to need it
+            SfxKey::Synth(_) | SfxKey::Portal | SfxKey::Death | SfxKey::Riser => 0.0,
+            SfxKey::Whoosh => 0.08,
+        }
+    }
 }
 
 impl AssetKey for SfxKey {
     type Asset = AudioSource;
 }
 
+/// The files each sound effect is loaded from. Sounds with more than one take turns playing each of them.
+const SFX_FILES: [(SfxKey, &[&str]); 9] = [
+    (SfxKey::Kick, &["audio/sfx/kick1.ogg"]),
+    (SfxKey::Snare, &["audio/sfx/snare1.ogg"]),
+    (SfxKey::HiHat, &["audio/sfx/hihat1.ogg"]),
+    (SfxKey::Bounce, &["audio/sfx/bounce.wav"]),
+    (SfxKey::Portal, &["audio/sfx/portal.wav"]),
+    (SfxKey::Land, &["audio/sfx/land.wav"]),
+    (SfxKey::Death, &["audio/sfx/death.wav"]),
+    (SfxKey::Riser, &["audio/sfx/riser.wav"]),
+    (SfxKey::Whoosh, &["audio/sfx/whoosh.wav"]),
+];
+
 impl FromWorld for HandleMap<SfxKey> {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
-        let mut map: HandleMap<SfxKey> = [
-            (SfxKey::Kick, asset_server.load("audio/sfx/kick1.ogg")),
-            (SfxKey::Snare, asset_server.load("audio/sfx/snare1.ogg")),
-            (SfxKey::HiHat, asset_server.load("audio/sfx/hihat1.ogg")),
-            (SfxKey::Synth(0), asset_server.load("audio/sfx/synth1.ogg")),
-        ]
-        .into();
+        // just the first file for each sound, for things that only need one of them, like exporting audio
+        SFX_FILES
+            .map(|(key, paths)| (key, asset_server.load(paths[0])))
+            .into()
+    }
+}
+
+/// Every file for each sound effect, along with which one each sound effect plays next.
+#[derive(Resource)]
+pub struct SampleBank {
+    variants: HashMap<SfxKey, Vec<Handle<AudioSource>>>,
+    next: HashMap<SfxKey, usize>,
+}
 
-        for i in 0..NUM_SYNTH_NOTES {
-            map.insert(
-                SfxKey::Synth(i),
-                asset_server.load(format!("audio/sfx/synth{i}.ogg")),
-            );
+impl FromWorld for SampleBank {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        SampleBank {
+            variants: SFX_FILES
+                .iter()
+                .map(|(key, paths)| {
+                    (
+                        *key,
+                        paths.iter().map(|path| asset_server.load(*path)).collect(),
+                    )
+                })
+                .collect(),
+            next: HashMap::new(),
         }
+    }
+}
+
+impl SampleBank {
+    pub fn all_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.variants
+            .values()
+            .flatten()
+            .all(|handle| asset_server.is_loaded_with_dependencies(handle))
+    }
 
-        map
+    /// Gets how many files the provided sound effect has
+    pub fn variant_count(&self, key: SfxKey) -> usize {
+        self.variants.get(&key).map_or(0, Vec::len)
+    }
+
+    /// Gets the file to play the provided sound effect from this time, going around through each of its files in turn
+    pub fn next(&mut self, key: SfxKey) -> Handle<AudioSource> {
+        let variants = &self.variants[&key];
+        let next = self.next.entry(key).or_default();
+        let handle = variants[*next % variants.len()].clone_weak();
+        *next = (*next + 1) % variants.len();
+        handle
     }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
 pub enum SoundtrackKey {
     Title,
+    Credits,
 }
 
 impl AssetKey for SoundtrackKey {
@@ -113,10 +275,16 @@ impl AssetKey for SoundtrackKey {
 impl FromWorld for HandleMap<SoundtrackKey> {
     fn from_world(world: &mut World) -> Self {
         let asset_server = world.resource::<AssetServer>();
-        [(
-            SoundtrackKey::Title,
-            asset_server.load("audio/soundtracks/title.ogg"),
-        )]
+        [
+            (
+                SoundtrackKey::Title,
+                asset_server.load("audio/soundtracks/title.ogg"),
+            ),
+            (
+                SoundtrackKey::Credits,
+                asset_server.load("audio/soundtracks/credits.wav"),
+            ),
+        ]
         .into()
     }
 }
@@ -148,6 +316,120 @@ impl FromWorld for HandleMap<FontKey> {
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+pub enum CreditsKey {
+    Credits,
+}
+
+impl AssetKey for CreditsKey {
+    type Asset = Credits;
+}
+
+impl FromWorld for HandleMap<CreditsKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [(CreditsKey::Credits, asset_server.load("credits.ron"))].into()
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+pub enum JudgementsKey {
+    Judgements,
+}
+
+impl AssetKey for JudgementsKey {
+    type Asset = Judgements;
+}
+
+impl FromWorld for HandleMap<JudgementsKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [(
+            JudgementsKey::Judgements,
+            asset_server.load("judgements.ron"),
+        )]
+        .into()
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+pub enum DayCycleKey {
+    DayCycle,
+}
+
+impl AssetKey for DayCycleKey {
+    type Asset = DayCycle;
+}
+
+impl FromWorld for HandleMap<DayCycleKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [(DayCycleKey::DayCycle, asset_server.load("day_cycle.ron"))].into()
+    }
+}
+
+/// The themes levels are drawn with.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+pub enum ThemeKey {
+    /// The theme for one of the campaign's levels
+    Campaign(u32),
+    Tutorial,
+    /// The theme generated levels are tinted from
+    Endless,
+    Boss,
+    /// The theme for levels made in the level editor, which bring their own background color
+    Custom,
+}
+
+impl ThemeKey {
+    pub const ALL: [ThemeKey; 8] = [
+        ThemeKey::Campaign(0),
+        ThemeKey::Campaign(1),
+        ThemeKey::Campaign(2),
+        ThemeKey::Campaign(3),
+        ThemeKey::Tutorial,
+        ThemeKey::Endless,
+        ThemeKey::Boss,
+        ThemeKey::Custom,
+    ];
+
+    fn path(self) -> String {
+        match self {
+            ThemeKey::Campaign(level) => format!("themes/campaign_{level}.theme.ron"),
+            ThemeKey::Tutorial => "themes/tutorial.theme.ron".to_string(),
+            ThemeKey::Endless => "themes/endless.theme.ron".to_string(),
+            ThemeKey::Boss => "themes/boss.theme.ron".to_string(),
+            ThemeKey::Custom => "themes/custom.theme.ron".to_string(),
+        }
+    }
+}
+
+impl AssetKey for ThemeKey {
+    type Asset = LevelTheme;
+}
+
+impl FromWorld for HandleMap<ThemeKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        ThemeKey::ALL
+            .map(|key| (key, asset_server.load(key.path())))
+            .into()
+    }
+}
+
+impl AssetKey for Language {
+    type Asset = Locale;
+}
+
+impl FromWorld for HandleMap<Language> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Language::ALL
+            .map(|language| (language, asset_server.load(language.path())))
+            .into()
+    }
+}
+
 pub trait AssetKey: Sized {
     type Asset: Asset;
 }
@@ -176,3 +458,69 @@ impl<K: AssetKey + Eq + std::hash::Hash> HandleMap<K> {
         self[&key].clone_weak()
     }
 }
+
+/// Loads any deserializable asset from a RON file with one of the provided extensions.
+pub struct RonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    phantom: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<A, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+/// An error encountered while loading a RON asset
+#[derive(Debug)]
+pub enum RonAssetLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for RonAssetLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RonAssetLoaderError::Io(e) => write!(f, "could not read asset: {e}"),
+            RonAssetLoaderError::Ron(e) => write!(f, "could not parse asset: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetLoaderError {}
+
+impl From<std::io::Error> for RonAssetLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        RonAssetLoaderError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetLoaderError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        RonAssetLoaderError::Ron(value)
+    }
+}
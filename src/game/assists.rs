@@ -0,0 +1,142 @@
+//! Options that make runs easier for players who want them, like lower gravity or surviving a few hits.
+//! They're part of the settings, and the game over panel points out which ones were on.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::AppSet;
+
+use super::{
+    localization::Localization,
+    movement::{Paused, PlaybackSpeed},
+    settings::Settings,
+    spawn::sequencer::{DeathEvent, ResetSequence},
+};
+
+/// How strong gravity is with reduced gravity on, relative to normal
+const REDUCED_GRAVITY_SCALE: f32 = 0.7;
+
+/// How fast the boss's attacks move with slower hazards on, relative to normal
+const SLOW_HAZARD_SCALE: f32 = 0.6;
+
+/// How many hits a runner can take without dying each run with extra health on
+const EXTRA_HITS: u32 = 2;
+
+/// How long a runner can't be hit again after taking a hit, so it has time to get clear of whatever hit it
+const HIT_GRACE_DURATION: Duration = Duration::from_secs(1);
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(ExtraHealth::full());
+    app.observe(take_hit);
+    app.observe(restore_health);
+    app.add_systems(Update, tick_hit_grace.in_set(AppSet::TickTimers));
+}
+
+/// The assists the player has turned on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[serde(default)]
+pub struct Assists {
+    /// Makes gravity weaker, so jumps go higher and last longer
+    pub reduced_gravity: bool,
+    /// Slows down the boss's attacks
+    pub slow_hazards: bool,
+    /// Lets runners take a few hits each run before dying
+    pub extra_health: bool,
+    /// Keeps runners from dying at all
+    pub invincible: bool,
+}
+
+impl Assists {
+    /// Gets how strong gravity is, relative to normal
+    pub fn gravity_scale(&self) -> f32 {
+        if self.reduced_gravity {
+            REDUCED_GRAVITY_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// Gets how fast the boss's attacks move, relative to normal
+    pub fn hazard_speed_scale(&self) -> f32 {
+        if self.slow_hazards {
+            SLOW_HAZARD_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// Gets the names of the assists that are on, in the player's language
+    pub fn active_names(&self, localization: &Localization) -> Vec<String> {
+        [
+            (self.reduced_gravity, "assists.reduced_gravity"),
+            (self.slow_hazards, "assists.slow_hazards"),
+            (self.extra_health, "assists.extra_health"),
+            (self.invincible, "assists.invincible"),
+        ]
+        .into_iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, key)| localization.get(key))
+        .collect()
+    }
+}
+
+/// Event that hurts the runner it targets, which kills it unless an assist saves it
+#[derive(Event, Debug)]
+pub struct RunnerHit;
+
+/// How many more hits runners can take this run with extra health on, and how long until they can be hit again
+#[derive(Resource, Debug)]
+pub struct ExtraHealth {
+    pub hits_left: u32,
+    grace: Timer,
+}
+
+impl ExtraHealth {
+    fn full() -> ExtraHealth {
+        let mut grace = Timer::new(HIT_GRACE_DURATION, TimerMode::Once);
+        grace.tick(HIT_GRACE_DURATION);
+        ExtraHealth {
+            hits_left: EXTRA_HITS,
+            grace,
+        }
+    }
+}
+
+/// Kills the runner that was hit, unless it's invincible, was hit too recently to be hit again, or has extra health left
+fn take_hit(
+    trigger: Trigger<RunnerHit>,
+    settings: Res<Settings>,
+    mut health: ResMut<ExtraHealth>,
+    mut commands: Commands,
+) {
+    let assists = &settings.assists;
+    if assists.invincible || !health.grace.finished() {
+        return;
+    }
+
+    if assists.extra_health && health.hits_left > 0 {
+        health.hits_left -= 1;
+        health.grace.reset();
+        return;
+    }
+
+    commands.trigger_targets(DeathEvent, trigger.entity());
+}
+
+fn restore_health(_trigger: Trigger<ResetSequence>, mut health: ResMut<ExtraHealth>) {
+    *health = ExtraHealth::full();
+}
+
+fn tick_hit_grace(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    paused: Res<Paused>,
+    mut health: ResMut<ExtraHealth>,
+) {
+    // runners can't get clear of anything while the run is paused
+    if !paused.0 && !health.grace.finished() {
+        health.grace.tick(playback_speed.delta(&time));
+    }
+}
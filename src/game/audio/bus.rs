@@ -0,0 +1,88 @@
+//! The bus gameplay sound effects play through, which ducks them while the game over panel or a menu is up.
+//! A stinger plays as the bus ducks, over the top of everything, and the sound effects come back up smoothly once
+//! play picks back up.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        assets::SfxKey,
+        spawn::sequencer::{GameOver, Victory},
+    },
+    screen::Screen,
+    AppSet,
+};
+
+use super::sfx::PlaySfx;
+
+/// How loud gameplay sound effects are while they're ducked, relative to normal
+const DUCKED_VOLUME: f32 = 0.3;
+
+/// How fast gameplay sound effects dip when they're ducked, in volume per second
+const DUCK_SPEED: f32 = 4.0;
+
+/// How fast gameplay sound effects come back up once they're not ducked anymore, in volume per second
+const RESTORE_SPEED: f32 = 1.5;
+
+/// The degrees of the synth's scale played together for the stinger, an octave below the synth
+const STINGER_DEGREES: [usize; 3] = [0, 2, 4];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SfxBus>();
+    app.add_systems(Update, duck_sfx_bus.in_set(AppSet::Update));
+}
+
+/// How loud gameplay sound effects are right now, and whether they're being ducked.
+/// Sound effects that are part of the interface don't go through this.
+#[derive(Resource, Debug, PartialEq)]
+pub struct SfxBus {
+    volume: f32,
+    ducked: bool,
+}
+
+impl Default for SfxBus {
+    fn default() -> Self {
+        // the game starts out in menus, and there's nothing to play a stinger over yet
+        SfxBus {
+            volume: DUCKED_VOLUME,
+            ducked: true,
+        }
+    }
+}
+
+impl SfxBus {
+    /// Gets how loud gameplay sound effects are right now, relative to normal
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+}
+
+/// Ducks gameplay sound effects while the game over panel or a menu is up, playing a stinger as they dip,
+/// and moves their volume towards where it should be
+fn duck_sfx_bus(
+    time: Res<Time>,
+    screen: Res<State<Screen>>,
+    panel_query: Query<(), Or<(With<GameOver>, With<Victory>)>>,
+    mut bus: ResMut<SfxBus>,
+    mut commands: Commands,
+) {
+    let ducked = *screen.get() != Screen::Playing || !panel_query.is_empty();
+    if ducked && !bus.ducked {
+        for degree in STINGER_DEGREES {
+            commands.trigger(
+                PlaySfx::new(SfxKey::Synth(degree))
+                    .with_speed(0.5)
+                    .unducked(),
+            );
+        }
+    }
+
+    let (target, speed) = if ducked {
+        (DUCKED_VOLUME, DUCK_SPEED)
+    } else {
+        (1.0, RESTORE_SPEED)
+    };
+    let step = speed * time.delta_seconds();
+    let volume = bus.volume + (target - bus.volume).clamp(-step, step);
+    bus.set_if_neq(SfxBus { volume, ducked });
+}
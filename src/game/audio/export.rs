@@ -0,0 +1,247 @@
+//! Rendering the current sequence to an audio file so players can keep the beats they make.
+//! On native builds, the file is saved to the user's data directory.
+//! On web builds, the browser downloads it.
+
+use std::time::Duration;
+
+use bevy::{
+    audio::{Decodable, Source},
+    prelude::*,
+    tasks::IoTaskPool,
+    utils::HashMap,
+};
+use rand::Rng;
+
+use crate::game::{
+    assets::{HandleMap, SfxKey},
+    settings::Settings,
+    spawn::sequencer::{accent_volume, Sequence, SequenceState},
+};
+
+use super::synth::{Synth, SynthNote};
+
+/// The sample rate of exported audio, in Hz
+const EXPORT_SAMPLE_RATE: u32 = 44100;
+
+/// How loud each note is in the mix, before the whole thing is normalized
+const NOTE_VOLUME: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(export_audio);
+}
+
+/// Trigger this event to render one loop of the current sequence at the current tempo to a WAV file.
+/// If any rows are at half time, the loop is long enough for them to get through all their steps.
+#[derive(Event, Debug)]
+pub struct ExportAudio;
+
+fn export_audio(
+    _trigger: Trigger<ExportAudio>,
+    sequence: Res<Sequence>,
+    sequence_state: Res<SequenceState>,
+    sfx_handles: Res<HandleMap<SfxKey>>,
+    audio_sources: Res<Assets<AudioSource>>,
+    synth: Res<Synth>,
+    settings: Res<Settings>,
+) {
+    // notes that might not play are rolled for just like they are during a run, so the export sounds like one loop of it
+    let mut rng = rand::thread_rng();
+    let ticks: Vec<Vec<(SfxKey, f32)>> = (0..sequence.loop_ticks())
+        .map(|tick| {
+            let rows: Vec<_> = sequence
+                .notes_at_tick(tick)
+                .filter(|(_, chance)| rng.gen::<f32>() < *chance)
+                .map(|(row, _)| row)
+                .collect();
+            // the same balancing as when the notes play during a run
+            let gain = 1.0 / (rows.len().max(1) as f32).sqrt();
+            rows.into_iter()
+                .map(|row| {
+                    let volume = gain * accent_volume(sequence.accent_at_tick(row, tick));
+                    (row.to_sfx_key(), volume)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut sources = HashMap::new();
+    for (key, _) in ticks.iter().flatten() {
+        let source = match key {
+            SfxKey::Synth(degree) => Some(NoteSource::Synth(synth.note(*degree))),
+            key => audio_sources
+                .get(&sfx_handles.get(*key))
+                .map(|source| NoteSource::Sample(source.clone(), settings.sound_bank.speed(*key))),
+        };
+        if let Some(source) = source {
+            sources.insert(*key, source);
+        }
+    }
+
+    let tick_interval = sequence_state.tick_interval();
+
+    // decoding and mixing takes a moment, so do it in the background
+    IoTaskPool::get()
+        .spawn(async move {
+            let samples = render_loop(&ticks, &sources, tick_interval);
+            save_wav(&encode_wav(&samples));
+        })
+        .detach();
+}
+
+/// Where the audio for a note comes from.
+enum NoteSource {
+    /// A sample, and how fast to play it relative to its normal speed
+    Sample(AudioSource, f32),
+    Synth(SynthNote),
+}
+
+/// Mixes one loop of the provided notes and their volumes, grouped by the tick they start on, into mono samples.
+/// Notes that ring past the end of the loop wrap around to the start, so the result loops seamlessly.
+fn render_loop(
+    ticks: &[Vec<(SfxKey, f32)>],
+    sources: &HashMap<SfxKey, NoteSource>,
+    tick_interval: Duration,
+) -> Vec<f32> {
+    let samples_per_tick = tick_interval.as_secs_f64() * f64::from(EXPORT_SAMPLE_RATE);
+    let loop_length = ((samples_per_tick * ticks.len() as f64).round() as usize).max(1);
+    let mut mix = vec![0.0; loop_length];
+
+    let decoded: HashMap<SfxKey, Vec<f32>> = sources
+        .iter()
+        .map(|(key, source)| (*key, decode(source)))
+        .collect();
+
+    for (tick, keys) in ticks.iter().enumerate() {
+        let start = (samples_per_tick * tick as f64).round() as usize;
+        for (key, volume) in keys {
+            let Some(note) = decoded.get(key) else {
+                continue;
+            };
+            for (i, sample) in note.iter().enumerate() {
+                mix[(start + i) % loop_length] += sample * NOTE_VOLUME * volume;
+            }
+        }
+    }
+
+    // avoid clipping when lots of notes play at once
+    let peak = mix
+        .iter()
+        .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+    if peak > 1.0 {
+        for sample in &mut mix {
+            *sample /= peak;
+        }
+    }
+
+    mix
+}
+
+/// Decodes the provided audio into mono samples at the export sample rate.
+fn decode(source: &NoteSource) -> Vec<f32> {
+    let (interleaved, channels, sample_rate, speed): (Vec<f32>, _, _, _) = match source {
+        NoteSource::Sample(source, speed) => {
+            let decoder = source.decoder();
+            let channels = decoder.channels();
+            let sample_rate = decoder.sample_rate();
+            (
+                decoder.convert_samples().collect(),
+                channels,
+                sample_rate,
+                *speed,
+            )
+        }
+        NoteSource::Synth(note) => {
+            let decoder = note.decoder();
+            let channels = decoder.channels();
+            let sample_rate = decoder.sample_rate();
+            (decoder.collect(), channels, sample_rate, 1.0)
+        }
+    };
+    let channels = usize::from(channels.max(1));
+
+    let mono: Vec<f32> = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    if (sample_rate == EXPORT_SAMPLE_RATE && speed == 1.0) || mono.is_empty() {
+        return mono;
+    }
+
+    // linear interpolation is plenty for short one-shot samples
+    // playing a sample at a different speed is the same as resampling it from a different rate
+    let ratio = f64::from(sample_rate) * f64::from(speed) / f64::from(EXPORT_SAMPLE_RATE);
+    let length = (mono.len() as f64 / ratio) as usize;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let fraction = (position - index as f64) as f32;
+            let current = mono[index];
+            let next = mono.get(index + 1).copied().unwrap_or(current);
+            current + (next - current) * fraction
+        })
+        .collect()
+}
+
+/// Encodes the provided mono samples as a 16-bit PCM WAV file.
+fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    const CHANNELS: u16 = 1;
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    let byte_rate = EXPORT_SAMPLE_RATE * u32::from(block_align);
+    let data_length = (samples.len() * usize::from(block_align)) as u32;
+
+    let mut bytes = Vec::with_capacity(44 + data_length as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_length).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes());
+    // PCM
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&CHANNELS.to_le_bytes());
+    bytes.extend_from_slice(&EXPORT_SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_length.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    bytes
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_wav(bytes: &[u8]) {
+    let dir = dirs::data_dir()
+        .unwrap_or_default()
+        .join("looprunner")
+        .join("exports");
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("could not create export directory {}: {e}", dir.display());
+        return;
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = dir.join(format!("loop-{timestamp}.wav"));
+    match std::fs::write(&path, bytes) {
+        Ok(()) => info!("exported audio to {}", path.display()),
+        Err(e) => warn!("could not export audio to {}: {e}", path.display()),
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn save_wav(bytes: &[u8]) {
+    if crate::persistence::download(bytes, "looprunner-loop.wav", "audio/wav").is_none() {
+        warn!("could not export audio: the browser wouldn't start the download");
+    }
+}
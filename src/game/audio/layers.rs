@@ -0,0 +1,218 @@
+//! Music layers that play under the sequence during a run, getting louder as the run goes on.
+//! The ambient pad fades in a little more with each level cleared, and drops out when the player dies.
+//! A shimmer an octave up follows how intense the run is.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{
+    audio::{AddAudioSource, Decodable, PlaybackMode, Source, Volume},
+    prelude::*,
+};
+
+use crate::{
+    game::{intensity::Intensity, spawn::sequencer::Dead, stats::RunStats},
+    screen::Screen,
+    AppSet,
+};
+
+use super::synth::Synth;
+
+/// The sample rate the pad is generated at, in Hz
+const PAD_SAMPLE_RATE: u32 = 44100;
+
+/// How long the pad takes to loop, in seconds
+const PAD_LOOP_LENGTH: f32 = 4.0;
+
+/// How loud each voice of the pad is, from 0 to 1
+const PAD_VOICE_AMPLITUDE: f32 = 0.08;
+
+/// The degrees of the synth's scale that make up the pad's chord, played an octave below the synth
+const PAD_CHORD: [usize; 3] = [0, 2, 4];
+
+/// How loud the pad gets once it's all the way in
+const PAD_MAX_VOLUME: f32 = 0.6;
+
+/// How many levels have to be cleared for the pad to be all the way in
+const LEVELS_FOR_FULL_PAD: u32 = 4;
+
+/// The degrees of the synth's scale that make up the shimmer's chord, played an octave above the synth
+const SHIMMER_CHORD: [usize; 2] = [0, 4];
+
+/// How loud the shimmer gets at full intensity
+const SHIMMER_MAX_VOLUME: f32 = 0.35;
+
+/// How fast the pad fades in, in volume per second
+const FADE_IN_SPEED: f32 = 0.1;
+
+/// How fast the shimmer fades in, in volume per second.
+/// It has to keep up with the intensity, which changes a lot faster than the number of levels cleared.
+const SHIMMER_FADE_IN_SPEED: f32 = 0.5;
+
+/// How fast the pad drops out, in volume per second
+const DROP_OUT_SPEED: f32 = 3.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<PadLoop>();
+    app.add_systems(OnEnter(Screen::Playing), spawn_layers);
+    app.add_systems(
+        Update,
+        fade_music_layers
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// A layer of music whose volume follows how the run is going.
+#[derive(Component, Debug)]
+struct MusicLayer {
+    kind: MusicLayerKind,
+    /// How loud the layer is right now
+    volume: f32,
+}
+
+/// What a layer of music follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MusicLayerKind {
+    /// How many levels have been cleared
+    Pad,
+    /// How intense the run is
+    Shimmer,
+}
+
+fn spawn_layers(synth: Res<Synth>, mut pads: ResMut<Assets<PadLoop>>, mut commands: Commands) {
+    let layers = [
+        (MusicLayerKind::Pad, "Pad layer", &PAD_CHORD[..], 0.5),
+        (
+            MusicLayerKind::Shimmer,
+            "Shimmer layer",
+            &SHIMMER_CHORD[..],
+            2.0,
+        ),
+    ];
+    for (kind, name, chord, octave) in layers {
+        let frequencies = chord
+            .iter()
+            .map(|degree| synth.scale.frequency(*degree) * octave)
+            .collect();
+        commands.spawn((
+            Name::new(name),
+            MusicLayer { kind, volume: 0.0 },
+            StateScoped(Screen::Playing),
+            AudioSourceBundle {
+                source: pads.add(PadLoop { frequencies }),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    volume: Volume::ZERO,
+                    ..default()
+                },
+            },
+        ));
+    }
+}
+
+/// Moves each layer towards how loud it should be: the pad gets louder the more levels have been cleared,
+/// the shimmer gets louder the more intense the run is, and both are silent once the player's dead
+fn fade_music_layers(
+    time: Res<Time>,
+    stats: Res<RunStats>,
+    intensity: Res<Intensity>,
+    dead: Res<Dead>,
+    mut layer_query: Query<(&mut MusicLayer, &AudioSink)>,
+) {
+    for (mut layer, sink) in &mut layer_query {
+        let target = match layer.kind {
+            _ if dead.0 => 0.0,
+            MusicLayerKind::Pad => {
+                (stats.levels_cleared as f32 / LEVELS_FOR_FULL_PAD as f32).min(1.0) * PAD_MAX_VOLUME
+            }
+            MusicLayerKind::Shimmer => intensity.level() * SHIMMER_MAX_VOLUME,
+        };
+        let speed = if target < layer.volume {
+            DROP_OUT_SPEED
+        } else {
+            match layer.kind {
+                MusicLayerKind::Pad => FADE_IN_SPEED,
+                MusicLayerKind::Shimmer => SHIMMER_FADE_IN_SPEED,
+            }
+        };
+        let step = speed * time.delta_seconds();
+        let volume = layer.volume + (target - layer.volume).clamp(-step, step);
+        if volume != layer.volume {
+            layer.volume = volume;
+            sink.set_volume(volume);
+        }
+    }
+}
+
+/// A soft, sustained chord that loops seamlessly, which can be played like any other audio source.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct PadLoop {
+    /// The pitch of each note in the chord, in Hz
+    pub frequencies: Vec<f32>,
+}
+
+impl Decodable for PadLoop {
+    type DecoderItem = f32;
+    type Decoder = PadLoopDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        // each voice fits a whole number of cycles into the loop, so there's no click when it goes back around.
+        // each note gets a pair of voices a cycle apart, which beat against each other slowly to keep the pad from sounding static.
+        let voices = self
+            .frequencies
+            .iter()
+            .flat_map(|frequency| {
+                let cycles = (frequency * PAD_LOOP_LENGTH).round();
+                [cycles, cycles + 1.0]
+            })
+            .map(|cycles| cycles / PAD_LOOP_LENGTH)
+            .collect();
+        PadLoopDecoder { voices, sample: 0 }
+    }
+}
+
+/// Generates the samples of a [`PadLoop`].
+pub struct PadLoopDecoder {
+    /// The frequency of each voice, in Hz
+    voices: Vec<f32>,
+    sample: u32,
+}
+
+impl Iterator for PadLoopDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.sample >= (PAD_LOOP_LENGTH * PAD_SAMPLE_RATE as f32) as u32 {
+            return None;
+        }
+
+        let time = self.sample as f32 / PAD_SAMPLE_RATE as f32;
+        self.sample += 1;
+
+        Some(
+            self.voices
+                .iter()
+                .map(|frequency| ((time * frequency).fract() * TAU).sin())
+                .sum::<f32>()
+                * PAD_VOICE_AMPLITUDE,
+        )
+    }
+}
+
+impl Source for PadLoopDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        PAD_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(PAD_LOOP_LENGTH))
+    }
+}
@@ -1,8 +1,29 @@
+pub mod bus;
+pub mod export;
+pub mod layers;
 pub mod sfx;
 pub mod soundtrack;
+pub mod synth;
 
 use bevy::prelude::*;
 
+use super::assets::{HandleMap, SampleBank, SfxKey, SoundtrackKey};
+
 pub fn plugin(app: &mut App) {
-    app.add_plugins((sfx::plugin, soundtrack::plugin));
+    // loaded here rather than with the rest of the assets, since there's nothing to load them with when there's no audio
+    app.register_type::<HandleMap<SfxKey>>();
+    app.init_resource::<HandleMap<SfxKey>>();
+    app.init_resource::<SampleBank>();
+
+    app.register_type::<HandleMap<SoundtrackKey>>();
+    app.init_resource::<HandleMap<SoundtrackKey>>();
+
+    app.add_plugins((
+        bus::plugin,
+        export::plugin,
+        layers::plugin,
+        sfx::plugin,
+        soundtrack::plugin,
+        synth::plugin,
+    ));
 }
@@ -1,29 +1,255 @@
+use std::time::Duration;
+
 use bevy::{
-    audio::{PlaybackMode, Volume},
+    audio::{PlaybackMode, SpatialScale, Volume},
     prelude::*,
 };
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    assets::{HandleMap, SampleBank, SfxKey},
+    localization::Localization,
+    settings::Settings,
+};
+
+use super::{
+    bus::SfxBus,
+    synth::{Synth, SynthNote},
+};
 
-use crate::game::assets::{HandleMap, SfxKey};
+/// The volume sound effects play at unless the event overrides it
+pub const DEFAULT_SFX_VOLUME: f32 = 0.5;
+
+/// The most copies of a single sound effect that can play at once.
+/// Playing another one stops the oldest.
+const MAX_VOICES_PER_SFX: usize = 4;
+
+/// How far from the center a fully panned sound is placed.
+/// With no spatial listener, the ears sit this far to either side of the origin.
+const PAN_DISTANCE: f32 = 2.0;
+
+/// Shrinks the distances used for panned sounds, so a centered sound is close enough to the ears to not get quieter.
+const PAN_SPATIAL_SCALE: f32 = 0.5;
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(play_sfx);
+    app.observe(warm_up_sfx);
+    app.add_systems(Update, apply_sfx_bus.run_if(resource_changed::<SfxBus>));
 }
 
 fn play_sfx(
     trigger: Trigger<PlaySfx>,
     mut commands: Commands,
+    mut sample_bank: ResMut<SampleBank>,
+    synth: Res<Synth>,
+    settings: Res<Settings>,
+    bus: Res<SfxBus>,
+    mut synth_notes: ResMut<Assets<SynthNote>>,
+    time: Res<Time>,
+    voice_query: Query<(Entity, &SfxVoice)>,
+) {
+    let event = trigger.event();
+
+    // make room for the new voice by stealing the oldest ones
+    let mut voices = voice_query
+        .iter()
+        .filter(|(_, voice)| voice.key == event.key)
+        .collect::<Vec<_>>();
+    if voices.len() >= MAX_VOICES_PER_SFX {
+        voices.sort_by_key(|(_, voice)| voice.started);
+        for (entity, _) in &voices[..=voices.len() - MAX_VOICES_PER_SFX] {
+            commands.entity(*entity).despawn();
+        }
+    }
+
+    let volume = event.volume.unwrap_or(DEFAULT_SFX_VOLUME);
+    let jitter = event.key.pitch_jitter();
+    let jitter = if jitter > 0.0 {
+        1.0 + rand::thread_rng().gen_range(-jitter..=jitter)
+    } else {
+        1.0
+    };
+    let bus_volume = if event.unducked { 1.0 } else { bus.volume() };
+    let settings = PlaybackSettings {
+        mode: PlaybackMode::Despawn,
+        volume: Volume::new(volume * bus_volume),
+        speed: event.speed.unwrap_or(1.0) * jitter * settings.sound_bank.speed(event.key),
+        spatial: event.pan.is_some(),
+        spatial_scale: event.pan.map(|_| SpatialScale::new(PAN_SPATIAL_SCALE)),
+        ..default()
+    };
+
+    let voice = SfxVoice {
+        key: event.key,
+        started: time.elapsed(),
+        volume,
+        unducked: event.unducked,
+    };
+    let mut entity = match event.key {
+        // synth notes aren't loaded from files, so generate a new one to play
+        SfxKey::Synth(degree) => commands.spawn(AudioSourceBundle {
+            source: synth_notes.add(synth.note(degree)),
+            settings,
+        }),
+        // sounds with more than one file take turns playing each of them, so the same one isn't heard over and over
+        key => commands.spawn(AudioSourceBundle {
+            source: sample_bank.next(key),
+            settings,
+        }),
+    };
+    entity.insert(voice);
+
+    // panning is done with spatial audio, so the sound needs a position relative to the ears
+    if let Some(pan) = event.pan {
+        entity.insert(TransformBundle::from_transform(Transform::from_xyz(
+            pan.clamp(-1.0, 1.0) * PAN_DISTANCE,
+            0.0,
+            0.0,
+        )));
+    }
+}
+
+/// Plays every file of every sound effect once without any volume, so each one has already been decoded by the time it's needed
+fn warm_up_sfx(
+    _trigger: Trigger<WarmUpSfx>,
     sfx_handles: Res<HandleMap<SfxKey>>,
+    sample_bank: Res<SampleBank>,
+    mut commands: Commands,
 ) {
-    commands.spawn(AudioSourceBundle {
-        source: sfx_handles.get(trigger.event().0),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Despawn,
-            volume: Volume::new(0.5),
-            ..default()
-        },
-    });
+    for key in sfx_handles.keys() {
+        for _ in 0..sample_bank.variant_count(*key) {
+            commands.trigger(PlaySfx::new(*key).with_volume(0.0));
+        }
+    }
+    // every synth note is played the same way, so one of them is enough
+    commands.trigger(PlaySfx::new(SfxKey::Synth(0)).with_volume(0.0));
+}
+
+/// Keeps the gameplay sound effects that are already playing in line with how loud the bus is now
+fn apply_sfx_bus(bus: Res<SfxBus>, voice_query: Query<(&SfxVoice, &AudioSink)>) {
+    for (voice, sink) in &voice_query {
+        if !voice.unducked {
+            sink.set_volume(voice.volume * bus.volume());
+        }
+    }
 }
 
+/// Whether every sound effect that's been played has gotten far enough to be heard, or has finished already
+pub fn all_sfx_started(voice_query: Query<Has<AudioSink>, With<SfxVoice>>) -> bool {
+    voice_query.iter().all(|started| started)
+}
+
+/// A set of drum sounds for the percussion rows to play.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Reflect,
+)]
+pub enum SoundBank {
+    #[default]
+    Standard,
+    /// Tuned down, for a heavier sound. Unlocked by beating a boss.
+    Heavy,
+}
+
+impl SoundBank {
+    pub const ALL: [SoundBank; 2] = [SoundBank::Standard, SoundBank::Heavy];
+
+    /// Gets the name of this sound bank, in the player's language
+    pub fn name(self, localization: &Localization) -> String {
+        match self {
+            SoundBank::Standard => localization.get("sound_bank.standard"),
+            SoundBank::Heavy => localization.get("sound_bank.heavy"),
+        }
+    }
+
+    /// Gets how fast to play the provided sound effect in this bank, relative to its normal speed.
+    /// Playing samples slower also tunes them down.
+    pub fn speed(self, key: SfxKey) -> f32 {
+        match (self, key) {
+            (SoundBank::Heavy, SfxKey::Kick) => 0.7,
+            (SoundBank::Heavy, SfxKey::Snare) => 0.8,
+            (SoundBank::Heavy, SfxKey::HiHat) => 0.85,
+            _ => 1.0,
+        }
+    }
+}
+
+/// A sound effect that's currently playing.
+#[derive(Component)]
+pub struct SfxVoice {
+    key: SfxKey,
+    /// When the sound started playing, relative to app startup
+    started: Duration,
+    /// How loud the sound plays, before the bus it goes through is taken into account
+    volume: f32,
+    /// Whether the sound skips the bus gameplay sound effects go through
+    unducked: bool,
+}
+
+/// Trigger this event to play every sound effect once, silently.
+/// On web, sounds hitch the first time they're played while they're decoded, so this gets that out of the way ahead of time.
+#[derive(Event, Debug)]
+pub struct WarmUpSfx;
+
 /// Trigger this event to play a single sound effect.
-#[derive(Event)]
-pub struct PlaySfx(pub SfxKey);
+/// Any settings that aren't overridden use the defaults for sound effects.
+#[derive(Event, Clone, Copy)]
+pub struct PlaySfx {
+    pub key: SfxKey,
+    /// The volume to play at, instead of the default
+    pub volume: Option<f32>,
+    /// The playback speed, which also changes the pitch. 1 is normal speed.
+    pub speed: Option<f32>,
+    /// Where to play the sound, from -1 (left) to 1 (right). Unpanned sounds play in both ears equally.
+    pub pan: Option<f32>,
+    /// Whether the sound is part of the interface rather than gameplay, so it isn't ducked with gameplay sounds
+    pub unducked: bool,
+}
+
+impl PlaySfx {
+    /// Plays the provided sound effect with the default settings
+    pub fn new(key: SfxKey) -> PlaySfx {
+        PlaySfx {
+            key,
+            volume: None,
+            speed: None,
+            pan: None,
+            unducked: false,
+        }
+    }
+
+    /// Overrides the volume
+    pub fn with_volume(mut self, volume: f32) -> PlaySfx {
+        self.volume = Some(volume);
+        self
+    }
+
+    /// Overrides the playback speed, which also changes the pitch
+    pub fn with_speed(mut self, speed: f32) -> PlaySfx {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Pans the sound to the left (negative) or right (positive)
+    pub fn with_pan(mut self, pan: f32) -> PlaySfx {
+        self.pan = Some(pan);
+        self
+    }
+
+    /// Keeps the sound from being ducked along with gameplay sounds, for sounds that are part of the interface
+    pub fn unducked(mut self) -> PlaySfx {
+        self.unducked = true;
+        self
+    }
+}
@@ -0,0 +1,222 @@
+//! Synthesized notes for the sequencer's synth rows.
+//! Notes are generated from a waveform and an ADSR envelope instead of being loaded from files,
+//! so they can be any pitch.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source},
+    prelude::*,
+};
+
+/// The sample rate synthesized notes are generated at, in Hz
+const SYNTH_SAMPLE_RATE: u32 = 44100;
+
+/// How loud synthesized notes are at the peak of their envelope, from 0 to 1
+const NOTE_AMPLITUDE: f32 = 0.3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_audio_source::<SynthNote>();
+    app.register_type::<Synth>();
+    app.init_resource::<Synth>();
+}
+
+/// The sound of the synth, and the scale its rows are tuned to.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct Synth {
+    pub waveform: Waveform,
+    pub envelope: Envelope,
+    /// How long notes are held before they're released, in seconds
+    pub note_length: f32,
+    pub scale: Scale,
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Synth {
+            waveform: Waveform::Triangle,
+            envelope: Envelope {
+                attack: 0.01,
+                decay: 0.1,
+                sustain: 0.8,
+                release: 0.15,
+            },
+            note_length: 0.3,
+            scale: Scale::major(196.0),
+        }
+    }
+}
+
+impl Synth {
+    /// Builds the note for the provided degree of the scale
+    pub fn note(&self, degree: usize) -> SynthNote {
+        SynthNote {
+            frequency: self.scale.frequency(degree),
+            waveform: self.waveform,
+            envelope: self.envelope,
+            length: self.note_length,
+        }
+    }
+}
+
+/// A musical scale, as the intervals of each degree above the root.
+/// Degrees past the end of the intervals continue into higher octaves.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct Scale {
+    /// The frequency of the first degree, in Hz
+    pub root_frequency: f32,
+    /// The number of semitones each degree is above the root, within one octave
+    pub intervals: Vec<u32>,
+}
+
+impl Scale {
+    /// A major scale starting at the provided frequency
+    pub fn major(root_frequency: f32) -> Scale {
+        Scale {
+            root_frequency,
+            intervals: vec![0, 2, 4, 5, 7, 9, 11],
+        }
+    }
+
+    /// Gets the frequency of the provided degree of this scale, in Hz
+    pub fn frequency(&self, degree: usize) -> f32 {
+        if self.intervals.is_empty() {
+            return self.root_frequency;
+        }
+
+        let octave = (degree / self.intervals.len()) as u32;
+        let semitones = octave * 12 + self.intervals[degree % self.intervals.len()];
+        self.root_frequency * 2.0_f32.powf(semitones as f32 / 12.0)
+    }
+}
+
+/// The basic shape of a synthesized sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+}
+
+impl Waveform {
+    /// Gets the value of this waveform at the provided point in its cycle, from 0 to 1
+    fn sample(self, phase: f32) -> f32 {
+        match self {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Triangle => 4.0 * (phase - 0.5).abs() - 1.0,
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+        }
+    }
+}
+
+/// How the volume of a note changes over time. Times are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Envelope {
+    /// How long it takes to go from silent to full volume
+    pub attack: f32,
+    /// How long it takes to go from full volume to the sustain level
+    pub decay: f32,
+    /// The volume while the note is held, from 0 to 1
+    pub sustain: f32,
+    /// How long it takes to go silent once the note is released
+    pub release: f32,
+}
+
+impl Envelope {
+    /// Gets the volume at the provided time for a note held for the provided length,
+    /// or `None` if the note has finished.
+    fn level(&self, time: f32, length: f32) -> Option<f32> {
+        if time < length {
+            return Some(self.held_level(time));
+        }
+
+        let released_for = time - length;
+        if released_for >= self.release {
+            return None;
+        }
+
+        Some(self.held_level(length) * (1.0 - released_for / self.release))
+    }
+
+    /// Gets the volume at the provided time, assuming the note hasn't been released yet
+    fn held_level(&self, time: f32) -> f32 {
+        if time < self.attack {
+            time / self.attack
+        } else if time < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (time - self.attack) / self.decay
+        } else {
+            self.sustain
+        }
+    }
+}
+
+/// A single synthesized note, which can be played like any other audio source.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct SynthNote {
+    /// The pitch of the note, in Hz
+    pub frequency: f32,
+    pub waveform: Waveform,
+    pub envelope: Envelope,
+    /// How long the note is held before it's released, in seconds
+    pub length: f32,
+}
+
+impl Decodable for SynthNote {
+    type DecoderItem = f32;
+    type Decoder = SynthNoteDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthNoteDecoder {
+            note: self.clone(),
+            sample: 0,
+        }
+    }
+}
+
+/// Generates the samples of a [`SynthNote`].
+pub struct SynthNoteDecoder {
+    note: SynthNote,
+    sample: u32,
+}
+
+impl Iterator for SynthNoteDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let time = self.sample as f32 / SYNTH_SAMPLE_RATE as f32;
+        let level = self.note.envelope.level(time, self.note.length)?;
+        let phase = (time * self.note.frequency).fract();
+        self.sample += 1;
+
+        Some(self.note.waveform.sample(phase) * level * NOTE_AMPLITUDE)
+    }
+}
+
+impl Source for SynthNoteDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SYNTH_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.note.length + self.note.envelope.release,
+        ))
+    }
+}
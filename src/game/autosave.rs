@@ -0,0 +1,52 @@
+//! Saving the sequence being worked on once it's been left alone for a moment, and putting it back the next time the game starts,
+//! so closing the game or refreshing the page doesn't lose it.
+
+use bevy::prelude::*;
+
+use crate::persistence;
+
+use super::spawn::sequencer::Sequence;
+
+const LAST_SESSION_SAVE_KEY: &str = "last_session";
+
+/// How long the sequence has to go without changing before it's saved, in seconds
+const AUTOSAVE_DELAY: f32 = 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    // the sequencer starts out empty, so this takes its place
+    if let Some(sequence) = persistence::load::<Sequence>(LAST_SESSION_SAVE_KEY) {
+        app.insert_resource(sequence);
+    }
+    app.init_resource::<PendingAutosave>();
+    app.add_systems(Update, autosave_sequence);
+    app.add_systems(Last, save_pending_sequence.run_if(on_event::<AppExit>()));
+}
+
+/// Counts down to saving the sequence after it changes, starting over whenever it changes again
+#[derive(Resource, Debug, Default)]
+struct PendingAutosave(Option<Timer>);
+
+fn autosave_sequence(
+    time: Res<Time>,
+    sequence: Res<Sequence>,
+    mut pending: ResMut<PendingAutosave>,
+) {
+    if sequence.is_changed() && !sequence.is_added() {
+        pending.0 = Some(Timer::from_seconds(AUTOSAVE_DELAY, TimerMode::Once));
+    }
+
+    let Some(timer) = &mut pending.0 else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        persistence::save(LAST_SESSION_SAVE_KEY, &*sequence);
+        pending.0 = None;
+    }
+}
+
+/// Saves the sequence right away if it changed since it was last saved, since there's no waiting around when the game is closing
+fn save_pending_sequence(sequence: Res<Sequence>, pending: Res<PendingAutosave>) {
+    if pending.0.is_some() {
+        persistence::save(LAST_SESSION_SAVE_KEY, &*sequence);
+    }
+}
@@ -0,0 +1,237 @@
+//! Faint lines in the level showing where the player will be on each upcoming beat, going by the speeds the sequence sets.
+//! These don't account for anything the player runs into, so they show where the sequence is taking the player rather than where they'll end up.
+
+use bevy::prelude::*;
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    coop::{Multiplayer, PlayerSlot, RowOwners},
+    movement::{MovementController, Paused, PlayerAction},
+    scrolling::ScrollPosition,
+    settings::Settings,
+    spawn::{
+        level::{GameMode, LevelWidth, SpawnChunk, SpawnObstacles, LEVEL_WIDTH, TOP_OF_FLOOR},
+        player::Player,
+        sequencer::{
+            Sequence, SequenceState, SequencerRow, BEATS_PER_GROUP, NUM_BEATS_IN_SEQUENCE,
+            TICKS_PER_BEAT,
+        },
+    },
+    versus::Opponent,
+};
+
+/// The color of the marker for each beat
+const MARKER_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.08);
+
+/// The color of the marker for the first beat in each group, so they're easier to count along with the sequencer
+const GROUP_START_MARKER_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.18);
+
+/// How wide each marker is, in pixels
+const MARKER_WIDTH: f32 = 2.0;
+
+/// Markers closer together than this are drawn as one, in pixels
+const MIN_MARKER_SPACING: f32 = 1.0;
+
+/// How far a marker can move before it's redrawn, in pixels
+const MARKER_TOLERANCE: f32 = 0.5;
+
+/// Markers go behind the runners but in front of the background
+const MARKER_Z: f32 = -0.9;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BeatMarkers>();
+    app.observe(mark_stale_on_level);
+    app.observe(mark_stale_on_chunk);
+    app.add_systems(
+        Update,
+        place_beat_markers
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Where the markers currently in the level are.
+#[derive(Resource, Debug, Default)]
+struct BeatMarkers {
+    /// The position of each marker, and whether it's at the start of a group of beats
+    positions: Vec<(f32, bool)>,
+    /// Whether the markers need to be placed again, even though the sequence hasn't changed
+    stale: bool,
+}
+
+impl BeatMarkers {
+    /// Whether the provided positions are close enough to the current ones that the markers can be left where they are
+    fn matches(&self, positions: &[(f32, bool)]) -> bool {
+        self.positions.len() == positions.len()
+            && self.positions.iter().zip(positions).all(
+                |((a, a_group_start), (b, b_group_start))| {
+                    (a - b).abs() <= MARKER_TOLERANCE && a_group_start == b_group_start
+                },
+            )
+    }
+}
+
+/// A line in the level where the player will be on one of the upcoming beats.
+#[derive(Component, Debug)]
+struct BeatMarker;
+
+/// The runner starts over at the left side of each new level, so the markers have to be placed from there
+fn mark_stale_on_level(_trigger: Trigger<SpawnObstacles>, mut markers: ResMut<BeatMarkers>) {
+    markers.stale = true;
+}
+
+/// More of the level has come up in scrolling mode, so the markers can go further along it
+fn mark_stale_on_chunk(_trigger: Trigger<SpawnChunk>, mut markers: ResMut<BeatMarkers>) {
+    markers.stale = true;
+}
+
+/// Places the markers for the player's runner. While the run is going, they're only placed again
+/// when something changes where the player is headed, so they stay put in the level instead of following the player along.
+fn place_beat_markers(
+    settings: Res<Settings>,
+    paused: Res<Paused>,
+    sequence: Res<Sequence>,
+    sequence_state: Res<SequenceState>,
+    multiplayer: Res<Multiplayer>,
+    row_owners: Res<RowOwners>,
+    game_mode: Res<GameMode>,
+    scroll_position: Res<ScrollPosition>,
+    level_width: Res<LevelWidth>,
+    runner_query: Query<(&Transform, &Player, &MovementController, &PlayerSlot), Without<Opponent>>,
+    marker_query: Query<Entity, With<BeatMarker>>,
+    mut markers: ResMut<BeatMarkers>,
+    mut commands: Commands,
+) {
+    if !paused.0 && !markers.stale && !sequence.is_changed() && !settings.is_changed() {
+        return;
+    }
+    markers.stale = false;
+
+    let runner = runner_query
+        .iter()
+        .find(|(.., slot)| **slot == PlayerSlot::One);
+    let positions = match runner {
+        Some((transform, player, controller, _)) if settings.beat_markers => {
+            // in co-op, the markers follow player 1
+            let controls = |row: SequencerRow| {
+                *multiplayer != Multiplayer::Coop || row_owners.controls(row, PlayerSlot::One)
+            };
+            // the level goes on past the view in scrolling mode, so the markers go a level's width past it
+            let end_x = if *game_mode == GameMode::Scrolling {
+                scroll_position.0 + (LEVEL_WIDTH * 1.5)
+            } else {
+                level_width.right_edge()
+            };
+            predict_beat_positions(
+                &sequence,
+                &sequence_state,
+                transform.translation.x + player.collider_offset.x,
+                controller.speed,
+                end_x,
+                controls,
+            )
+        }
+        _ => Vec::new(),
+    };
+
+    if markers.matches(&positions) {
+        return;
+    }
+
+    for entity in &marker_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    let top = LEVEL_WIDTH / 2.0;
+    for (x, group_start) in &positions {
+        let color = if *group_start {
+            GROUP_START_MARKER_COLOR
+        } else {
+            MARKER_COLOR
+        };
+        commands.spawn((
+            Name::new("Beat marker"),
+            BeatMarker,
+            StateScoped(Screen::Playing),
+            SpriteBundle {
+                transform: Transform::from_translation(Vec3::new(
+                    *x,
+                    (TOP_OF_FLOOR + top) / 2.0,
+                    MARKER_Z,
+                )),
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(MARKER_WIDTH, top - TOP_OF_FLOOR)),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+    markers.positions = positions;
+}
+
+/// Works out where a runner starting at the provided position and speed will be on each beat until they get past `end_x`,
+/// going by the speeds set by the notes that always play on rows that control them.
+fn predict_beat_positions(
+    sequence: &Sequence,
+    sequence_state: &SequenceState,
+    start_x: f32,
+    start_speed: f32,
+    end_x: f32,
+    controls: impl Fn(SequencerRow) -> bool,
+) -> Vec<(f32, bool)> {
+    // if more than one row sets the speed, the fastest one wins, same as when the notes are played
+    let speed_at = |tick: usize| {
+        sequence
+            .notes_at_tick(tick)
+            .filter(|(row, chance)| *chance >= 1.0 && controls(*row))
+            .filter_map(|(row, _)| match row.to_player_action() {
+                PlayerAction::SetSpeed(speed) => Some(speed),
+                _ => None,
+            })
+            .reduce(f32::max)
+    };
+    let group_start = |tick: usize| {
+        ((tick / TICKS_PER_BEAT) % NUM_BEATS_IN_SEQUENCE).is_multiple_of(BEATS_PER_GROUP)
+    };
+
+    let mut positions: Vec<(f32, bool)> = Vec::new();
+    let mut add_marker = |x: f32, tick: usize| {
+        let standing_still = positions
+            .last()
+            .is_some_and(|(last_x, _)| (x - last_x).abs() < MIN_MARKER_SPACING);
+        if tick.is_multiple_of(TICKS_PER_BEAT) && !standing_still {
+            positions.push((x, group_start(tick)));
+        }
+    };
+
+    let tick_interval = sequence_state.tick_interval().as_secs_f32();
+    let mut tick = sequence_state.tick();
+    let mut x = start_x;
+    let mut speed = start_speed;
+
+    // the notes on the current tick play as soon as the sequence starts, if they haven't been played yet
+    let mut until_next_tick = if !sequence_state.tick_played() {
+        speed = speed_at(tick).unwrap_or(speed);
+        add_marker(x, tick);
+        tick_interval
+    } else {
+        tick_interval - sequence_state.tick_elapsed().as_secs_f32()
+    };
+
+    // give up eventually if the player never gets out of the level
+    for _ in 0..(sequence.loop_ticks() * 2) {
+        x += speed * until_next_tick;
+        if x > end_x {
+            break;
+        }
+
+        tick = (tick + 1) % sequence.loop_ticks();
+        speed = speed_at(tick).unwrap_or(speed);
+        add_marker(x, tick);
+        until_next_tick = tick_interval;
+    }
+
+    positions
+}
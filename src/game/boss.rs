@@ -0,0 +1,389 @@
+//! Boss fights every few levels of endless mode, against a huge obstacle that attacks in time with the sequence.
+//! The boss wears out as the player keeps up their speed, and the level doesn't end until it's worn out all the way.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    assets::{HandleMap, ImageKey},
+    assists::RunnerHit,
+    audio::sfx::SoundBank,
+    movement::{Paused, PlaybackSpeed, TotalDistance},
+    progress::Progress,
+    settings::Settings,
+    spawn::{
+        level::{
+            GameMode, Obstacle, SpawnObstacles, BOX_SIZE, IMAGE_SCALE, LEVEL_WIDTH, SPIKES_HEIGHT,
+            SPIKES_IMAGE_SIZE, SPIKES_WIDTH, TOP_OF_FLOOR,
+        },
+        player::{Player, PLAYER_IMAGE_SIZE},
+        sequencer::{Dead, PlayBeat, SequenceState},
+    },
+    versus::Opponent,
+};
+
+/// How many levels apart boss fights are in endless mode
+pub const BOSS_LEVEL_INTERVAL: u32 = 8;
+
+/// How far the player has to run to wear the boss out, in pixels
+const BOSS_HEALTH: f32 = LEVEL_WIDTH * 3.0;
+
+const BOSS_SIZE: Vec2 = Vec2::splat(BOX_SIZE * 2.0);
+
+/// Where the boss hovers, up out of the player's reach at the far end of the level
+const BOSS_POSITION: Vec2 = Vec2::new(
+    (LEVEL_WIDTH / 2.0) - (BOX_SIZE * 1.5),
+    TOP_OF_FLOOR + (BOX_SIZE * 3.0),
+);
+
+const BOSS_COLOR: Color = Color::srgb(0.55, 0.1, 0.15);
+
+/// How far ahead of the runner out in front the boss's attacks start, in pixels.
+/// Everything moves along with the runners when the arena wraps around, so attacks reach them on the same beats every loop as long as they keep the same speed.
+const ATTACK_LEAD: f32 = LEVEL_WIDTH * 0.6;
+
+/// How many beats apart spike volleys are
+const VOLLEY_INTERVAL: usize = 16;
+
+/// How many spikes are in each volley
+const VOLLEY_SPIKES: usize = 3;
+
+/// How far apart the spikes in a volley are, in pixels
+const VOLLEY_SPACING: f32 = BOX_SIZE * 1.5;
+
+/// How fast volleys slide along the floor towards the player, in pixels/sec
+const VOLLEY_SPEED: f32 = 250.0;
+
+/// How many beats apart moving walls are
+const WALL_INTERVAL: usize = 16;
+
+/// The beat moving walls come on, counted from the start of each interval.
+/// This is halfway between volleys, so the player has time to land before the next wall.
+const WALL_OFFSET: usize = 8;
+
+/// The gap left between moving walls and the floor.
+/// A runner on the ground fits under it, but one that's jumping doesn't.
+const WALL_GAP: f32 = BOX_SIZE * 1.5;
+
+const WALL_SIZE: Vec2 = Vec2::new(BOX_SIZE, BOX_SIZE * 4.0);
+
+/// How fast moving walls slide towards the player, in pixels/sec
+const WALL_SPEED: f32 = 150.0;
+
+const WALL_COLOR: Color = Color::srgb(0.35, 0.05, 0.1);
+
+/// The sound bank the player gets for beating a boss for the first time
+const BOSS_REWARD: SoundBank = SoundBank::Heavy;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_boss);
+    app.observe(attack_on_beat);
+    app.add_systems(
+        Update,
+        (
+            wrap_arena,
+            move_hazards,
+            check_hazard_collisions,
+            wear_out_boss,
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Whether the provided level is a boss fight
+pub fn is_boss_level(game_mode: GameMode, level: u32) -> bool {
+    game_mode == GameMode::Endless && (level + 1).is_multiple_of(BOSS_LEVEL_INTERVAL)
+}
+
+/// Event sent when the player wears a boss out
+#[derive(Event, Debug)]
+pub struct BossDefeated {
+    /// The sound bank beating it unlocked, if the player didn't have it already
+    pub reward: Option<SoundBank>,
+}
+
+/// The huge obstacle that attacks the player during a boss fight.
+#[derive(Component, Debug)]
+pub struct Boss {
+    /// How much farther the player has to run to wear the boss out, in pixels
+    health: f32,
+    /// The total distance the player had run the last time the boss's health was updated
+    last_distance: f32,
+}
+
+impl Boss {
+    /// Gets how much of its health the boss has left, from 0 to 1
+    pub fn health_fraction(&self) -> f32 {
+        (self.health / BOSS_HEALTH).clamp(0.0, 1.0)
+    }
+
+    /// Gets which attacks the boss is using, based on how worn out it is
+    pub fn phase(&self) -> BossPhase {
+        let health = self.health_fraction();
+        if health > 2.0 / 3.0 {
+            BossPhase::Volleys
+        } else if health > 1.0 / 3.0 {
+            BossPhase::Walls
+        } else {
+            BossPhase::Frenzy
+        }
+    }
+}
+
+/// The stages of a boss fight, which each attack in a different way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossPhase {
+    /// Spikes slide along the floor, and have to be jumped over
+    Volleys,
+    /// Walls slide along just above the floor, and have to be stayed under
+    Walls,
+    /// Both at once
+    Frenzy,
+}
+
+/// Something the boss sent at the player, which kills any runner it touches.
+#[derive(Component, Debug)]
+struct BossHazard {
+    bounds: Vec2,
+    offset: Vec2,
+    /// How fast it moves, in pixels/sec
+    velocity: Vec2,
+}
+
+fn spawn_boss(
+    trigger: Trigger<SpawnObstacles>,
+    game_mode: Res<GameMode>,
+    distance: Res<TotalDistance>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    mut commands: Commands,
+) {
+    if !is_boss_level(*game_mode, trigger.event().level) {
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Boss"),
+        // despawned along with the rest of the obstacles when the next level is spawned
+        Obstacle,
+        Boss {
+            health: BOSS_HEALTH,
+            last_distance: distance.0,
+        },
+        SpriteBundle {
+            texture: image_handles.get(ImageKey::Box),
+            sprite: Sprite {
+                color: BOSS_COLOR,
+                custom_size: Some(BOSS_SIZE),
+                ..default()
+            },
+            transform: Transform::from_translation(BOSS_POSITION.extend(-0.5)),
+            ..default()
+        },
+    ));
+}
+
+/// Sends the attacks for the boss's current phase on the beats they're scripted for
+fn attack_on_beat(
+    _trigger: Trigger<PlayBeat>,
+    sequence_state: Res<SequenceState>,
+    boss_query: Query<&Boss>,
+    runner_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    mut commands: Commands,
+) {
+    let Ok(boss) = boss_query.get_single() else {
+        return;
+    };
+    let Some(leader_x) = runner_query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .max_by(f32::total_cmp)
+    else {
+        return;
+    };
+    let start_x = leader_x + ATTACK_LEAD;
+
+    let beat = sequence_state.beat();
+    let phase = boss.phase();
+    if phase != BossPhase::Walls && beat.is_multiple_of(VOLLEY_INTERVAL) {
+        spawn_volley(start_x, &image_handles, &mut commands);
+    }
+    if phase != BossPhase::Volleys && beat % WALL_INTERVAL == WALL_OFFSET {
+        spawn_wall(start_x, &mut commands);
+    }
+}
+
+/// Sends a row of spikes along the floor, starting from the provided X coordinate
+fn spawn_volley(start_x: f32, image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
+    for i in 0..VOLLEY_SPIKES {
+        let x = start_x + (VOLLEY_SPACING * i as f32);
+        commands.spawn((
+            Name::new("Boss spikes"),
+            Obstacle,
+            BossHazard {
+                bounds: Vec2::new(
+                    SPIKES_WIDTH - (4.0 * IMAGE_SCALE),
+                    SPIKES_HEIGHT - IMAGE_SCALE,
+                ),
+                offset: Vec2::new(0.0, -7.0 * IMAGE_SCALE),
+                velocity: Vec2::new(-VOLLEY_SPEED, 0.0),
+            },
+            SpriteBundle {
+                texture: image_handles.get(ImageKey::Spikes),
+                sprite: Sprite {
+                    color: BOSS_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec2::splat(IMAGE_SCALE).extend(1.0))
+                    .with_translation(Vec3::new(x, TOP_OF_FLOOR + (SPIKES_IMAGE_SIZE / 2.0), 0.0)),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Sends a wall hanging just above the floor, starting from the provided X coordinate
+fn spawn_wall(start_x: f32, commands: &mut Commands) {
+    let position = Vec2::new(start_x, TOP_OF_FLOOR + WALL_GAP + (WALL_SIZE.y / 2.0));
+    commands.spawn((
+        Name::new("Boss wall"),
+        Obstacle,
+        BossHazard {
+            bounds: WALL_SIZE,
+            offset: Vec2::ZERO,
+            velocity: Vec2::new(-WALL_SPEED, 0.0),
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: WALL_COLOR,
+                custom_size: Some(WALL_SIZE),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.0)),
+            ..default()
+        },
+    ));
+}
+
+/// Moves the runners back to the start of the level once the one out in front has left it, like between normal levels.
+/// The boss's attacks are moved back along with them, so they stay the same distance from the runners.
+fn wrap_arena(
+    mut wrap_query: Query<
+        (&mut Transform, Has<BossHazard>),
+        Or<(With<BossHazard>, (With<Player>, Without<Opponent>))>,
+    >,
+    boss_query: Query<(), With<Boss>>,
+) {
+    if boss_query.is_empty() {
+        return;
+    }
+
+    let Some(leader_x) = wrap_query
+        .iter()
+        .filter(|(_, is_hazard)| !is_hazard)
+        .map(|(transform, _)| transform.translation.x)
+        .max_by(f32::total_cmp)
+    else {
+        return;
+    };
+    if leader_x - (PLAYER_IMAGE_SIZE / 2.0) <= LEVEL_WIDTH / 2.0 {
+        return;
+    }
+
+    let start_x = (-LEVEL_WIDTH / 2.0) - (PLAYER_IMAGE_SIZE / 2.0);
+    for (mut transform, is_hazard) in &mut wrap_query {
+        if is_hazard {
+            transform.translation.x += start_x - leader_x;
+        } else {
+            // anyone lagging behind is pulled along with the leader
+            transform.translation.x = start_x;
+        }
+    }
+}
+
+/// Moves the boss's attacks along, and gets rid of them once they've gone off the left side of the level
+fn move_hazards(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    paused: Res<Paused>,
+    settings: Res<Settings>,
+    mut hazard_query: Query<(Entity, &BossHazard, &mut Transform)>,
+    mut commands: Commands,
+) {
+    if paused.0 {
+        return;
+    }
+
+    let speed_scale = settings.assists.hazard_speed_scale();
+    for (entity, hazard, mut transform) in &mut hazard_query {
+        transform.translation +=
+            (hazard.velocity * speed_scale * playback_speed.delta_seconds(&time)).extend(0.0);
+
+        let right_edge = transform.translation.x + hazard.offset.x + (hazard.bounds.x / 2.0);
+        if right_edge < -LEVEL_WIDTH / 2.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn check_hazard_collisions(
+    runner_query: Query<(Entity, &Transform, &Player), Without<Opponent>>,
+    hazard_query: Query<(&Transform, &BossHazard)>,
+    paused: Res<Paused>,
+    dead: Res<Dead>,
+    mut commands: Commands,
+) {
+    if paused.0 || dead.0 {
+        return;
+    }
+
+    for (runner, runner_transform, player) in &runner_query {
+        let runner_bounds = Rect::from_center_size(
+            runner_transform.translation.truncate() + player.collider_offset,
+            player.collider,
+        );
+        let hit = hazard_query.iter().any(|(transform, hazard)| {
+            let hazard_bounds = Rect::from_center_size(
+                transform.translation.truncate() + hazard.offset,
+                hazard.bounds,
+            );
+            !runner_bounds.intersect(hazard_bounds).is_empty()
+        });
+        if hit {
+            commands.trigger_targets(RunnerHit, runner);
+            return;
+        }
+    }
+}
+
+/// Takes however far the player has run since last frame off the boss's health, and gets rid of it once it's worn out
+fn wear_out_boss(
+    distance: Res<TotalDistance>,
+    progress: Res<Progress>,
+    dead: Res<Dead>,
+    mut boss_query: Query<(Entity, &mut Boss)>,
+    hazard_query: Query<Entity, With<BossHazard>>,
+    mut commands: Commands,
+) {
+    let Ok((entity, mut boss)) = boss_query.get_single_mut() else {
+        return;
+    };
+
+    if !dead.0 {
+        boss.health -= (distance.0 - boss.last_distance).max(0.0);
+    }
+    boss.last_distance = distance.0;
+    if boss.health > 0.0 {
+        return;
+    }
+
+    commands.entity(entity).despawn_recursive();
+    for hazard in &hazard_query {
+        commands.entity(hazard).despawn_recursive();
+    }
+    commands.trigger(BossDefeated {
+        reward: (!progress.has_sound_bank(BOSS_REWARD)).then_some(BOSS_REWARD),
+    });
+}
@@ -0,0 +1,304 @@
+//! The camera the level is seen through, which only draws inside the bounds of the level however wide the window is.
+//! The UI is drawn over it by the main camera.
+
+use bevy::{
+    prelude::*,
+    render::{camera::Viewport, view::RenderLayers},
+    window::{PrimaryWindow, WindowResized},
+};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    level_transition::LevelTransition,
+    movement::{MovementController, Paused},
+    photo_mode::PhotoMode,
+    scrolling::{update_scroll_position, ScrollPosition},
+    settings::Settings,
+    spawn::{
+        level::LEVEL_WIDTH,
+        player::Player,
+        sequencer::{PlayTick, PreRoll, Sequence, SequencerRow},
+    },
+};
+
+/// How far the camera zooms in while panning over the level before a run, relative to showing the whole level
+const PAN_ZOOM: f32 = 0.5;
+
+/// How much of the pan is spent sweeping across the level, with the rest spent zooming back out
+const PAN_SWEEP_FRACTION: f32 = 0.7;
+
+/// How hard each kick knocks the camera's zoom in, in zoom per second
+const KICK_ZOOM_IMPULSE: f32 = 0.4;
+
+/// How much further out the camera is zoomed once the runner out in front is going at least [`FULL_ZOOM_OUT_SPEED`],
+/// relative to its normal zoom
+const SPEED_ZOOM_OUT: f32 = 0.06;
+
+/// How fast the runner out in front has to be going for the camera to be all the way zoomed out, in pixels per second
+const FULL_ZOOM_OUT_SPEED: f32 = 400.0;
+
+/// How strongly the camera's zoom is pulled back towards where it should be
+const ZOOM_STIFFNESS: f32 = 90.0;
+
+/// How strongly the camera's zoom is kept from overshooting where it should be
+const ZOOM_DAMPING: f32 = 12.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_world_cameras);
+    app.add_systems(Update, fit_camera_to_level.in_set(AppSet::Update));
+    app.add_systems(
+        Update,
+        // after everything else that moves the camera, since this is on top of where it would otherwise be
+        pan_over_level
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(
+        Update,
+        follow_scroll_position
+            .after(pan_over_level)
+            .after(update_scroll_position)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.observe(kick_camera_zoom);
+    app.add_systems(
+        Update,
+        update_camera_rig
+            .after(pan_over_level)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), reset_camera_rig);
+}
+
+/// The camera that draws the level, as opposed to the one the UI is drawn with
+#[derive(Component, Debug)]
+pub struct WorldCamera;
+
+/// Zooms the camera along with the run: in a little on each kick, and slowly out as the runners speed up.
+/// The zoom springs towards where it should be, so it never jumps.
+#[derive(Component, Debug)]
+pub struct CameraRig {
+    /// How far out the camera is zoomed, relative to showing the whole level
+    zoom: f32,
+    /// How fast the zoom is changing, in zoom per second
+    velocity: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        CameraRig {
+            zoom: 1.0,
+            velocity: 0.0,
+        }
+    }
+}
+
+fn spawn_world_cameras(mut commands: Commands) {
+    // clears whatever the world camera doesn't cover
+    commands.spawn((
+        Name::new("Letterbox camera"),
+        Camera2dBundle {
+            camera: Camera {
+                order: -1,
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::none(),
+    ));
+    commands.spawn((
+        Name::new("World camera"),
+        WorldCamera,
+        CameraRig::default(),
+        Camera2dBundle::default(),
+    ));
+}
+
+/// Zooms the camera out when the window is too narrow to show the whole level,
+/// and keeps it from drawing past the edges of the level when the window is wider than it.
+fn fit_camera_to_level(
+    mut resize_events: EventReader<WindowResized>,
+    screen: Res<State<Screen>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&mut Camera, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+    let resized = resize_events.read().count() > 0;
+    if !resized && !screen.is_changed() {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if window.physical_width() == 0 || window.physical_height() == 0 {
+        return;
+    }
+
+    let scale = level_scale(window);
+    // the other screens don't show the level, so they get the whole window
+    let shows_level = matches!(screen.get(), Screen::Playing | Screen::Editor);
+    let level_width =
+        ((LEVEL_WIDTH / scale * window.scale_factor()).round() as u32).min(window.physical_width());
+
+    for (mut camera, mut projection) in &mut camera_query {
+        projection.scale = scale;
+        camera.viewport = shows_level.then(|| Viewport {
+            physical_position: UVec2::new((window.physical_width() - level_width) / 2, 0),
+            physical_size: UVec2::new(level_width, window.physical_height()),
+            ..default()
+        });
+    }
+}
+
+/// Gets how zoomed out the camera needs to be to fit the whole level in the window
+fn level_scale(window: &Window) -> f32 {
+    (LEVEL_WIDTH / window.width()).max(1.0)
+}
+
+/// Sweeps the camera across the level from the player's side while zoomed in, then zooms back out to show all of it.
+/// The bottom of the view stays put the whole time, so the floor never goes out of view.
+fn pan_over_level(
+    pre_roll: Res<PreRoll>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Some(progress) = pre_roll.pan_progress() else {
+        // put the camera back once the pan is over or called off
+        if pre_roll.is_changed() {
+            for (mut transform, mut projection) in &mut camera_query {
+                transform.translation.x = 0.0;
+                projection.scale = level_scale(window);
+            }
+        }
+        return;
+    };
+
+    let sweep = smoothstep(progress / PAN_SWEEP_FRACTION);
+    let zoom_out = smoothstep((progress - PAN_SWEEP_FRACTION) / (1.0 - PAN_SWEEP_FRACTION));
+    let zoom = PAN_ZOOM.lerp(1.0, zoom_out);
+    let scale = level_scale(window);
+    for (mut transform, mut projection) in &mut camera_query {
+        projection.scale = scale * zoom;
+        transform.translation.x = (-1.0).lerp(1.0, sweep) * LEVEL_WIDTH / 2.0 * (1.0 - zoom);
+        transform.translation.y -= window.height() * scale / 2.0 * (1.0 - zoom);
+    }
+}
+
+/// Knocks the camera's zoom in whenever a kick plays
+fn kick_camera_zoom(
+    trigger: Trigger<PlayTick>,
+    sequence: Res<Sequence>,
+    settings: Res<Settings>,
+    mut rig_query: Query<&mut CameraRig>,
+) {
+    if !settings.camera_zoom || settings.reduced_motion {
+        return;
+    }
+
+    if !sequence
+        .notes_at_tick(trigger.event().0)
+        .any(|(row, _)| row == SequencerRow::Kick)
+    {
+        return;
+    }
+
+    for mut rig in &mut rig_query {
+        rig.velocity -= KICK_ZOOM_IMPULSE;
+    }
+}
+
+/// Springs the camera's zoom towards how far out it should be for how fast the runners are going.
+/// The camera is left alone while something else is in charge of it, like the pan over the level before a run or photo mode.
+fn update_camera_rig(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    paused: Res<Paused>,
+    pre_roll: Res<PreRoll>,
+    photo_mode: Res<PhotoMode>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    runner_query: Query<&MovementController, With<Player>>,
+    mut camera_query: Query<(&mut CameraRig, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+    if pre_roll.pan_progress().is_some() || photo_mode.is_active() {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let scale = level_scale(window);
+
+    if !settings.camera_zoom || settings.reduced_motion {
+        for (mut rig, mut projection) in &mut camera_query {
+            if rig.zoom != 1.0 {
+                *rig = CameraRig::default();
+                projection.scale = scale;
+            }
+        }
+        return;
+    }
+
+    // the camera holds still while the run is paused
+    if paused.0 {
+        return;
+    }
+
+    let top_speed = runner_query
+        .iter()
+        .map(|controller| controller.speed)
+        .fold(0.0, f32::max);
+    let target = 1.0 + SPEED_ZOOM_OUT * (top_speed / FULL_ZOOM_OUT_SPEED).clamp(0.0, 1.0);
+
+    let delta = time.delta_seconds();
+    for (mut rig, mut projection) in &mut camera_query {
+        let acceleration = (ZOOM_STIFFNESS * (target - rig.zoom)) - (ZOOM_DAMPING * rig.velocity);
+        rig.velocity += acceleration * delta;
+        rig.zoom += rig.velocity * delta;
+        projection.scale = scale * rig.zoom;
+    }
+}
+
+/// Puts the zoom back to normal once the run is over, so the other screens aren't left zoomed
+fn reset_camera_rig(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&mut CameraRig, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    for (mut rig, mut projection) in &mut camera_query {
+        *rig = CameraRig::default();
+        projection.scale = level_scale(window);
+    }
+}
+
+/// Keeps the camera on the part of the level the player's in when it scrolls along with them,
+/// except while it's panning over the level before a run, sliding over to the next level, or being moved around in photo mode
+fn follow_scroll_position(
+    scroll_position: Res<ScrollPosition>,
+    pre_roll: Res<PreRoll>,
+    transition: Res<LevelTransition>,
+    photo_mode: Res<PhotoMode>,
+    mut camera_query: Query<&mut Transform, With<WorldCamera>>,
+) {
+    if pre_roll.pan_progress().is_some()
+        || matches!(*transition, LevelTransition::Sliding(_))
+        || photo_mode.is_active()
+    {
+        return;
+    }
+
+    for mut transform in &mut camera_query {
+        transform.translation.x = scroll_position.0;
+    }
+}
+
+/// Eases from 0 to 1 as `t` goes from 0 to 1, starting and ending slowly
+pub fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,214 @@
+//! Screenshots and recordings of runs. This plugin is only enabled with the `capture` feature,
+//! and only on native builds.
+//! Pressing F12 saves a screenshot, unless it's been rebound. While the sequence is playing, the last few seconds of the
+//! run are kept in memory at a low frame rate and resolution, and they're saved as a GIF when
+//! the player dies.
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*, render::view::screenshot::ScreenshotManager, tasks::IoTaskPool,
+    window::PrimaryWindow,
+};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::FilterType,
+    Delay, DynamicImage, Frame, RgbaImage,
+};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    input::{ActionInput, InputAction},
+    movement::Paused,
+    spawn::sequencer::{Dead, DeathEvent, ResetSequence},
+};
+
+/// How much of the run to keep around for recordings
+const RECORDING_LENGTH: Duration = Duration::from_secs(10);
+
+/// How many frames per second to record
+const RECORDING_FPS: u32 = 10;
+
+/// How much smaller recorded frames are than the window
+const RECORDING_DOWNSCALE: u32 = 3;
+
+/// How hard the GIF encoder tries to pick good colors, from 1 (best) to 30 (fastest)
+const GIF_ENCODING_SPEED: i32 = 10;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Recording {
+        frames: default(),
+        timer: Timer::new(Duration::from_secs(1) / RECORDING_FPS, TimerMode::Repeating),
+    });
+    app.observe(save_recording);
+    app.observe(clear_recording);
+    app.add_systems(
+        Update,
+        (
+            tick_recording_timer.in_set(AppSet::TickTimers),
+            save_screenshot.in_set(AppSet::RecordInput),
+            record_frame
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing)),
+        ),
+    );
+}
+
+/// The most recent frames of the run, oldest first.
+/// Frames are added from the render app's screenshot callbacks, so they're behind a lock.
+#[derive(Resource)]
+struct Recording {
+    frames: Arc<Mutex<VecDeque<RgbaImage>>>,
+    timer: Timer,
+}
+
+/// The directory captures are saved to
+fn captures_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_default()
+        .join("looprunner")
+        .join("captures")
+}
+
+/// Builds a path in the captures directory for a new capture with the provided prefix and extension,
+/// creating the directory if needed.
+fn new_capture_path(prefix: &str, extension: &str) -> Option<PathBuf> {
+    let dir = captures_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("could not create captures directory {}: {e}", dir.display());
+        return None;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    Some(dir.join(format!("{prefix}-{timestamp}.{extension}")))
+}
+
+fn save_screenshot(
+    input: ActionInput,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+) {
+    if !input.just_pressed(InputAction::SaveScreenshot) {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Some(path) = new_capture_path("screenshot", "png") else {
+        return;
+    };
+
+    if screenshot_manager
+        .save_screenshot_to_disk(window, path)
+        .is_err()
+    {
+        warn!("could not take screenshot: another one is already in progress");
+    }
+}
+
+fn tick_recording_timer(time: Res<Time>, mut recording: ResMut<Recording>) {
+    recording.timer.tick(time.delta());
+}
+
+/// Asks the render app for a copy of the current frame whenever the recording timer goes off.
+/// If a screenshot was already requested this frame, this frame just gets skipped.
+fn record_frame(
+    paused: Res<Paused>,
+    dead: Res<Dead>,
+    recording: Res<Recording>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+) {
+    if paused.0 || dead.0 || !recording.timer.just_finished() {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let frames = recording.frames.clone();
+    let _ = screenshot_manager.take_screenshot(window, move |image| {
+        let image = match image.try_into_dynamic() {
+            Ok(x) => x,
+            Err(e) => {
+                warn!("could not record frame: {e}");
+                return;
+            }
+        };
+
+        let frame = image.resize_exact(
+            image.width() / RECORDING_DOWNSCALE,
+            image.height() / RECORDING_DOWNSCALE,
+            FilterType::Nearest,
+        );
+        // the alpha channel of the window isn't meaningful, so drop it to keep the GIF opaque
+        let frame = DynamicImage::ImageRgb8(frame.to_rgb8()).into_rgba8();
+
+        let max_frames = (RECORDING_LENGTH.as_secs() as u32 * RECORDING_FPS) as usize;
+        let mut frames = frames.lock().unwrap_or_else(PoisonError::into_inner);
+        frames.push_back(frame);
+        while frames.len() > max_frames {
+            frames.pop_front();
+        }
+    });
+}
+
+fn clear_recording(_trigger: Trigger<ResetSequence>, recording: Res<Recording>) {
+    recording
+        .frames
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
+
+/// Saves the recorded frames as a GIF in the background.
+fn save_recording(_trigger: Trigger<DeathEvent>, recording: Res<Recording>) {
+    let frames: Vec<RgbaImage> = recording
+        .frames
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .drain(..)
+        .collect();
+    if frames.is_empty() {
+        return;
+    }
+
+    let Some(path) = new_capture_path("run", "gif") else {
+        return;
+    };
+
+    IoTaskPool::get()
+        .spawn(async move {
+            match write_gif(&path, frames) {
+                Ok(()) => info!("saved recording to {}", path.display()),
+                Err(e) => warn!("could not save recording to {}: {e}", path.display()),
+            }
+        })
+        .detach();
+}
+
+fn write_gif(path: &Path, frames: Vec<RgbaImage>) -> image::ImageResult<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = GifEncoder::new_with_speed(file, GIF_ENCODING_SPEED);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms(1000, RECORDING_FPS);
+    encoder.encode_frames(
+        frames
+            .into_iter()
+            .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+    )
+}
@@ -0,0 +1,233 @@
+//! The clock the sequence keeps time with.
+//! Rather than adding up frame times as they go by, it works out which tick it's on from how long it's been since it was started,
+//! so small errors in frame times can't build up over a long session and pull the beats off the grid.
+//! Bevy doesn't expose the audio device's clock, so the time it goes by is the app's time.
+
+use std::time::Duration;
+
+/// Counts ticks of a fixed length, going by how much time has passed since it started.
+/// Tick N always goes off at the time it started plus N tick intervals, as long as its speed and tempo stay the same.
+#[derive(Debug, Clone)]
+pub struct SequenceClock {
+    /// How long each tick lasts, at normal speed
+    tick_interval: Duration,
+    /// How fast the clock runs, relative to normal
+    speed: f32,
+    paused: bool,
+    /// The last app time the clock was told about
+    now: Duration,
+    /// The app time the clock was last started from, or had its speed or tempo changed at
+    anchor_time: Duration,
+    /// How many ticks had gone by at `anchor_time`
+    anchor_ticks: u64,
+    /// How far into its tick the clock was at `anchor_time`, at normal speed
+    anchor_progress: Duration,
+    /// How many ticks have been reported by [`SequenceClock::advance`]
+    ticks_reported: u64,
+}
+
+impl SequenceClock {
+    /// Creates a paused clock at the very start of its first tick
+    pub fn new(tick_interval: Duration) -> SequenceClock {
+        SequenceClock {
+            tick_interval,
+            speed: 1.0,
+            paused: true,
+            now: Duration::ZERO,
+            anchor_time: Duration::ZERO,
+            anchor_ticks: 0,
+            anchor_progress: Duration::ZERO,
+            ticks_reported: 0,
+        }
+    }
+
+    /// Gets how long each tick lasts, at normal speed
+    pub fn tick_interval(&self) -> Duration {
+        self.tick_interval
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Gets how long it's been since the last tick went off, at normal speed
+    pub fn tick_elapsed(&self) -> Duration {
+        self.position().1
+    }
+
+    /// Moves the clock up to the provided app time, and returns how many ticks went off since the last time it was moved
+    pub fn advance(&mut self, now: Duration) -> u64 {
+        self.now = now.max(self.now);
+        if self.paused {
+            // no time goes by for the clock while it's paused, so it picks up from the current time when it's unpaused
+            self.anchor_time = self.now;
+        }
+        let (ticks, _) = self.position();
+        let new_ticks = ticks.saturating_sub(self.ticks_reported);
+        self.ticks_reported = self.ticks_reported.max(ticks);
+        new_ticks
+    }
+
+    /// Stops the clock where it is
+    pub fn pause(&mut self) {
+        self.reanchor();
+        self.paused = true;
+    }
+
+    /// Starts the clock going again from where it was paused
+    pub fn unpause(&mut self) {
+        self.reanchor();
+        self.paused = false;
+    }
+
+    /// Changes how long each tick lasts. The tick the clock is partway through keeps the time that's already gone by.
+    pub fn set_tick_interval(&mut self, tick_interval: Duration) {
+        self.reanchor();
+        self.tick_interval = tick_interval;
+    }
+
+    /// Changes how fast the clock runs, relative to normal
+    pub fn set_speed(&mut self, speed: f32) {
+        if speed != self.speed {
+            self.reanchor();
+            self.speed = speed;
+        }
+    }
+
+    /// Moves the clock back to the very start of a tick, without pausing or unpausing it
+    pub fn reset(&mut self) {
+        self.anchor_time = self.now;
+        self.anchor_ticks = 0;
+        self.anchor_progress = Duration::ZERO;
+        self.ticks_reported = 0;
+    }
+
+    /// Gets how many ticks have gone by since the clock was reset, and how far into the current one it is, at normal speed
+    fn position(&self) -> (u64, Duration) {
+        let since_anchor = if self.paused {
+            Duration::ZERO
+        } else if self.speed == 1.0 {
+            // kept exact at normal speed, so even hours into a session the ticks go off right on time
+            self.now - self.anchor_time
+        } else {
+            // an f32 number of seconds can't hold a long time precisely enough to keep the ticks on time
+            (self.now - self.anchor_time).mul_f64(f64::from(self.speed))
+        };
+        let progress = self.anchor_progress + since_anchor;
+        if self.tick_interval.is_zero() {
+            return (self.anchor_ticks, progress);
+        }
+
+        let interval = self.tick_interval.as_nanos();
+        let ticks = self.anchor_ticks + (progress.as_nanos() / interval) as u64;
+        let into_tick = Duration::from_nanos((progress.as_nanos() % interval) as u64);
+        (ticks, into_tick)
+    }
+
+    /// Starts measuring from the current time, so changes to how the clock runs only affect ticks after now
+    fn reanchor(&mut self) {
+        let (ticks, into_tick) = self.position();
+        self.anchor_time = self.now;
+        self.anchor_ticks = ticks;
+        self.anchor_progress = into_tick;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Creates a clock that starts going at the provided app time
+    fn started_at(start: Duration) -> SequenceClock {
+        let mut clock = SequenceClock::new(INTERVAL);
+        clock.advance(start);
+        clock.unpause();
+        clock
+    }
+
+    #[test]
+    fn tick_goes_off_at_start_plus_its_intervals() {
+        let start = Duration::from_secs(1);
+        let mut clock = started_at(start);
+
+        assert_eq!(
+            clock.advance(start + INTERVAL * 5 - Duration::from_nanos(1)),
+            4
+        );
+        assert_eq!(clock.advance(start + INTERVAL * 5), 1);
+        assert_eq!(clock.tick_elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn ticks_stay_on_time_in_long_sessions() {
+        let interval = ms(125);
+        let hours = Duration::from_secs(10 * 60 * 60);
+        let ticks_in_hours = (hours.as_nanos() / interval.as_nanos()) as u64;
+
+        let mut clock = SequenceClock::new(interval);
+        clock.unpause();
+        assert_eq!(
+            clock.advance(hours + interval - Duration::from_micros(1)),
+            ticks_in_hours
+        );
+        assert_eq!(clock.advance(hours + interval), 1);
+
+        // at half speed, the same ticks take twice as long
+        let mut clock = SequenceClock::new(interval);
+        clock.set_speed(0.5);
+        clock.unpause();
+        assert_eq!(
+            clock.advance((hours + interval) * 2 - Duration::from_micros(1)),
+            ticks_in_hours
+        );
+        assert_eq!(clock.advance((hours + interval) * 2), 1);
+    }
+
+    #[test]
+    fn pausing_keeps_position() {
+        let mut clock = started_at(Duration::ZERO);
+        assert_eq!(clock.advance(ms(250)), 2);
+
+        clock.pause();
+        assert_eq!(clock.advance(ms(10_000)), 0);
+        assert_eq!(clock.tick_elapsed(), ms(50));
+
+        clock.unpause();
+        assert_eq!(clock.advance(ms(10_049)), 0);
+        assert_eq!(clock.advance(ms(10_050)), 1);
+    }
+
+    #[test]
+    fn changing_speed_only_affects_time_after_the_change() {
+        let mut clock = started_at(Duration::ZERO);
+        assert_eq!(clock.advance(ms(150)), 1);
+
+        // halfway through the second tick, so the rest of it takes 100ms at half speed.
+        // checked a millisecond to either side, since scaling by the speed can be off by a nanosecond
+        clock.set_speed(0.5);
+        assert_eq!(clock.advance(ms(249)), 0);
+        assert_eq!(clock.advance(ms(251)), 1);
+        assert_eq!(clock.advance(ms(449)), 0);
+        assert_eq!(clock.advance(ms(451)), 1);
+    }
+
+    #[test]
+    fn changing_tick_interval_only_affects_time_after_the_change() {
+        let mut clock = started_at(Duration::ZERO);
+        assert_eq!(clock.advance(ms(150)), 1);
+
+        // 50ms into the second tick, which now lasts 200ms
+        clock.set_tick_interval(ms(200));
+        assert_eq!(clock.tick_interval(), ms(200));
+        assert_eq!(clock.advance(ms(299)), 0);
+        assert_eq!(clock.advance(ms(300)), 1);
+        assert_eq!(clock.advance(ms(499)), 0);
+        assert_eq!(clock.advance(ms(500)), 1);
+    }
+}
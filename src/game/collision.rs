@@ -0,0 +1,90 @@
+//! A spatial hash of the level's colliders, so collision checks only look at the obstacles near the player.
+//! Obstacles only move when the next level's are moved into place, so the hash is only rebuilt when a new set of them is
+//! spawned, or when the floor is moved along under the view in scrolling mode.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::spawn::level::{PreSpawnObstacles, RectCollider, SpawnChunk, SpawnObstacles, BOX_SIZE};
+
+/// The width of each column of the hash, in pixels
+const CELL_WIDTH: f32 = BOX_SIZE * 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ColliderGrid>();
+    app.observe(mark_grid_stale);
+    app.observe(mark_grid_stale_on_pre_spawn);
+    app.observe(mark_grid_stale_on_chunk);
+}
+
+/// The colliders in the level, bucketed into columns by where they are horizontally.
+#[derive(Resource, Debug, Default)]
+pub struct ColliderGrid {
+    columns: HashMap<i32, Vec<Entity>>,
+    /// Whether obstacles have been spawned since the grid was last built
+    stale: bool,
+}
+
+impl ColliderGrid {
+    /// Gets every collider that overlaps the provided horizontal span, each only once
+    pub fn nearby(&self, min_x: f32, max_x: f32) -> Vec<Entity> {
+        let mut entities = (column_at(min_x)..=column_at(max_x))
+            .filter_map(|column| self.columns.get(&column))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        entities.sort_unstable();
+        entities.dedup();
+        entities
+    }
+
+    /// Has the grid built again before it's next used, for when a collider has been moved
+    pub fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
+    fn insert(&mut self, entity: Entity, min_x: f32, max_x: f32) {
+        for column in column_at(min_x)..=column_at(max_x) {
+            self.columns.entry(column).or_default().push(entity);
+        }
+    }
+}
+
+fn column_at(x: f32) -> i32 {
+    (x / CELL_WIDTH).floor() as i32
+}
+
+fn mark_grid_stale(_trigger: Trigger<SpawnObstacles>, mut grid: ResMut<ColliderGrid>) {
+    grid.mark_stale();
+}
+
+fn mark_grid_stale_on_pre_spawn(
+    _trigger: Trigger<PreSpawnObstacles>,
+    mut grid: ResMut<ColliderGrid>,
+) {
+    grid.stale = true;
+}
+
+fn mark_grid_stale_on_chunk(_trigger: Trigger<SpawnChunk>, mut grid: ResMut<ColliderGrid>) {
+    grid.stale = true;
+}
+
+/// Puts all the colliders in the grid again if any obstacles have been spawned since it was last built
+pub fn rebuild_collider_grid(
+    mut grid: ResMut<ColliderGrid>,
+    collider_query: Query<(Entity, &Transform, &RectCollider)>,
+) {
+    if !grid.stale {
+        return;
+    }
+
+    grid.columns.clear();
+    for (entity, transform, collider) in &collider_query {
+        let center_x = transform.translation.x + collider.offset.x;
+        grid.insert(
+            entity,
+            center_x - (collider.bounds.x / 2.0),
+            center_x + (collider.bounds.x / 2.0),
+        );
+    }
+    grid.stale = false;
+}
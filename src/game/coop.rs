@@ -0,0 +1,108 @@
+//! Local co-op, where two runners share the sequence but each one only listens to the rows it's been given.
+//! If either runner dies, the run is over for both of them.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::screen::Screen;
+
+use super::spawn::sequencer::SequencerRow;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Multiplayer>();
+    app.init_resource::<RowOwners>();
+    app.register_type::<PlayerSlot>();
+}
+
+/// Goes back to one runner whenever the player is back at the title screen
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Title), reset_multiplayer);
+}
+
+/// Who's running in a run.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Multiplayer {
+    /// One runner, controlled by every row
+    #[default]
+    Solo,
+    /// Two runners, each controlled by the rows it's been given
+    Coop,
+    /// One runner, controlled by every row, racing an opponent that plays its own sequence
+    Versus,
+}
+
+impl Multiplayer {
+    /// Gets the slots of the runners the players control in a run
+    pub fn slots(self) -> &'static [PlayerSlot] {
+        match self {
+            Multiplayer::Solo | Multiplayer::Versus => &[PlayerSlot::One],
+            Multiplayer::Coop => &[PlayerSlot::One, PlayerSlot::Two],
+        }
+    }
+}
+
+/// Which of the players a runner belongs to.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub enum PlayerSlot {
+    One,
+    Two,
+}
+
+/// Which runners a row of the sequence controls in co-op.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowOwner {
+    Both,
+    Only(PlayerSlot),
+}
+
+impl RowOwner {
+    /// Gets the owner after this one, in the order they're cycled through
+    fn next(self) -> RowOwner {
+        match self {
+            RowOwner::Both => RowOwner::Only(PlayerSlot::One),
+            RowOwner::Only(PlayerSlot::One) => RowOwner::Only(PlayerSlot::Two),
+            RowOwner::Only(PlayerSlot::Two) => RowOwner::Both,
+        }
+    }
+}
+
+/// Which runners each row of the sequence controls in co-op.
+/// By default, the hi-hat floats player 1, the snare dives player 2, and the rest of the rows control both of them.
+#[derive(Resource, Debug, Clone)]
+pub struct RowOwners(HashMap<SequencerRow, RowOwner>);
+
+impl Default for RowOwners {
+    fn default() -> Self {
+        RowOwners(HashMap::from([
+            (SequencerRow::HiHat, RowOwner::Only(PlayerSlot::One)),
+            (SequencerRow::Snare, RowOwner::Only(PlayerSlot::Two)),
+        ]))
+    }
+}
+
+impl RowOwners {
+    /// Gets which runners the provided row controls
+    pub fn owner(&self, row: SequencerRow) -> RowOwner {
+        self.0.get(&row).copied().unwrap_or(RowOwner::Both)
+    }
+
+    /// Gives the provided row to the next owner
+    pub fn cycle(&mut self, row: SequencerRow) {
+        self.0.insert(row, self.owner(row).next());
+    }
+
+    /// Whether the provided row controls the runner in the provided slot
+    pub fn controls(&self, row: SequencerRow, slot: PlayerSlot) -> bool {
+        match self.owner(row) {
+            RowOwner::Both => true,
+            RowOwner::Only(owner) => owner == slot,
+        }
+    }
+}
+
+/// Goes back to one runner when leaving a run, so only runs started in co-op have two
+fn reset_multiplayer(mut multiplayer: ResMut<Multiplayer>) {
+    *multiplayer = Multiplayer::Solo;
+}
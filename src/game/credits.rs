@@ -0,0 +1,48 @@
+//! Data-driven credits, loaded from a RON manifest in the assets folder.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::assets::RonAssetLoader;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Credits>();
+    app.register_asset_loader(RonAssetLoader::<Credits>::new(&["credits.ron"]));
+}
+
+/// The contents of the credits screen.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct Credits {
+    /// The tempo of the credits soundtrack, in beats per minute.
+    pub bpm: f32,
+    /// How far the credits scroll on each beat of the soundtrack, in pixels.
+    pub pixels_per_beat: f32,
+    pub sections: Vec<CreditsSection>,
+}
+
+impl Credits {
+    /// How fast the credits scroll, in pixels/sec
+    pub fn scroll_speed(&self) -> f32 {
+        self.pixels_per_beat * self.bpm / 60.0
+    }
+}
+
+/// A titled group of credits, like "Tools Used" or "Fonts".
+#[derive(Deserialize, Debug)]
+pub struct CreditsSection {
+    pub title: String,
+    pub entries: Vec<CreditsEntry>,
+}
+
+/// A single contributor, asset, or tool.
+#[derive(Deserialize, Debug)]
+pub struct CreditsEntry {
+    pub name: String,
+    /// What the contributor did, or what the asset is used for.
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub link: Option<String>,
+}
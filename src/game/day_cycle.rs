@@ -0,0 +1,166 @@
+//! The sky changing as a run goes on, from sunrise to night, so long runs have a sense of time passing.
+//! The colors along the way live in a RON file in the assets folder, keyed by how far the player has run.
+//! The more intense the run gets, the more saturated the colors get.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    assets::{DayCycleKey, HandleMap, RonAssetLoader},
+    intensity::Intensity,
+    movement::TotalDistance,
+    pulse::PulseOnBeat,
+    spawn::level::Background,
+};
+
+/// How much more saturated the sky gets at full intensity, as a fraction of its normal saturation
+const INTENSITY_SATURATION: f32 = 0.8;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<DayCycle>();
+    app.register_asset_loader(RonAssetLoader::<DayCycle>::new(&["day_cycle.ron"]));
+    app.add_systems(
+        Update,
+        apply_day_cycle
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), reset_sky);
+}
+
+/// The colors of the sky at different points in a run, in order of distance.
+/// Colors are sRGB components from 0 to 1.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct DayCycle {
+    pub keyframes: Vec<DayCycleKeyframe>,
+}
+
+/// What the sky looks like once the player has run a certain distance.
+#[derive(Deserialize, Debug)]
+pub struct DayCycleKeyframe {
+    /// How far into the run this keyframe is, in feet
+    pub feet: f32,
+    /// The color behind everything, shown wherever the level doesn't cover
+    pub clear_color: [f32; 3],
+    /// Multiplied with the colors of the level's background
+    pub background_tint: [f32; 3],
+    /// How the colors change on the way from the previous keyframe to this one
+    #[serde(default)]
+    pub curve: DayCycleCurve,
+}
+
+/// How colors blend from one keyframe to the next.
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub enum DayCycleCurve {
+    /// At a steady rate the whole way
+    #[default]
+    Linear,
+    /// Slowly at first and at the end, and quickly in the middle
+    Smooth,
+}
+
+impl DayCycleCurve {
+    /// Gets how far along the blend is, from 0 to 1, at the provided fraction of the way between the keyframes
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            DayCycleCurve::Linear => t,
+            DayCycleCurve::Smooth => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+impl DayCycle {
+    /// Gets the clear color and background tint at the provided distance, in feet.
+    /// Before the first keyframe and after the last one, the colors stay the same as that keyframe.
+    /// Returns `None` if there aren't any keyframes.
+    fn colors_at(&self, feet: f32) -> Option<(Color, Color)> {
+        let next_index = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.feet > feet)
+            .unwrap_or(self.keyframes.len());
+        let previous = next_index
+            .checked_sub(1)
+            .and_then(|index| self.keyframes.get(index));
+        let next = self.keyframes.get(next_index);
+
+        let (from, to, t) = match (previous, next) {
+            (Some(previous), Some(next)) => {
+                let t = (feet - previous.feet) / (next.feet - previous.feet);
+                (previous, next, next.curve.apply(t.clamp(0.0, 1.0)))
+            }
+            (Some(only), None) | (None, Some(only)) => (only, only, 0.0),
+            (None, None) => return None,
+        };
+
+        // blended in Oklab so the colors in between don't go muddy
+        let blend = |from: [f32; 3], to: [f32; 3]| {
+            let from = Oklaba::from(Srgba::rgb(from[0], from[1], from[2]));
+            let to = Oklaba::from(Srgba::rgb(to[0], to[1], to[2]));
+            Color::from(from.mix(&to, t))
+        };
+        Some((
+            blend(from.clear_color, to.clear_color),
+            blend(from.background_tint, to.background_tint),
+        ))
+    }
+}
+
+/// Makes a color more saturated, by the provided fraction of its saturation
+fn saturate(color: Color, amount: f32) -> Color {
+    let color = Oklcha::from(color);
+    Color::from(color.with_chroma(color.chroma * (1.0 + amount)))
+}
+
+/// Colors the sky for how far the player has run and how intense the run is
+fn apply_day_cycle(
+    distance: Res<TotalDistance>,
+    intensity: Res<Intensity>,
+    day_cycle_handles: Res<HandleMap<DayCycleKey>>,
+    day_cycles: Res<Assets<DayCycle>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut background_query: Query<(Ref<Background>, &mut Sprite, &mut PulseOnBeat)>,
+) {
+    let background_added = background_query
+        .iter()
+        .any(|(background, ..)| background.is_added());
+    if !distance.is_changed()
+        && !intensity.is_changed()
+        && !clear_color.is_changed()
+        && !background_added
+    {
+        return;
+    }
+
+    let Some((sky, tint)) = day_cycles
+        .get(&day_cycle_handles[&DayCycleKey::DayCycle])
+        .and_then(|day_cycle| day_cycle.colors_at(distance.feet() as f32))
+    else {
+        return;
+    };
+    let saturation = intensity.level() * INTENSITY_SATURATION;
+    let (sky, tint) = (saturate(sky, saturation), saturate(tint, saturation));
+
+    if clear_color.0 != sky {
+        clear_color.0 = sky;
+    }
+    for (_, mut sprite, mut pulse) in &mut background_query {
+        pulse.set_color(&mut sprite, tint);
+    }
+}
+
+/// Puts the sky back to how it is at the start of a run, so it doesn't stay dark after leaving one
+fn reset_sky(
+    day_cycle_handles: Res<HandleMap<DayCycleKey>>,
+    day_cycles: Res<Assets<DayCycle>>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    if let Some((sky, _)) = day_cycles
+        .get(&day_cycle_handles[&DayCycleKey::DayCycle])
+        .and_then(|day_cycle| day_cycle.colors_at(0.0))
+    {
+        clear_color.0 = sky;
+    }
+}
@@ -0,0 +1,118 @@
+//! The player's death: a moment frozen in place, then popping up and falling off the bottom of the screen.
+//! The game over panel only shows up once that's played out.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    animation::{PlayerAnimation, PlayerAnimationState},
+    assets::SfxKey,
+    audio::sfx::PlaySfx,
+    movement::GRAVITY,
+    scrolling::ScrollPosition,
+    spawn::{
+        player::Player,
+        sequencer::{DeathEvent, ShowGameOver},
+    },
+};
+
+/// How long the player stays frozen in place after getting hit
+const HURT_DURATION: Duration = Duration::from_millis(350);
+
+/// How long the player falls for before the game over panel shows up
+const FALL_DURATION: Duration = Duration::from_millis(1200);
+
+/// The vertical velocity the player pops up with before falling, in pixels/sec
+const POP_VELOCITY: f32 = 700.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(start_death_sequence);
+    app.add_systems(Update, advance_death_sequence.in_set(AppSet::Update));
+}
+
+/// Plays out the player's death, one phase after another.
+#[derive(Component, Debug)]
+struct DeathSequence {
+    phase: DeathPhase,
+    /// Counts down the current phase
+    timer: Timer,
+    /// In pixels/sec
+    vertical_velocity: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeathPhase {
+    /// Frozen in place, flashing from the hit
+    Hurt,
+    /// Popping up and then falling, without running into anything
+    Falling,
+}
+
+impl DeathSequence {
+    fn new() -> DeathSequence {
+        DeathSequence {
+            phase: DeathPhase::Hurt,
+            timer: Timer::new(HURT_DURATION, TimerMode::Once),
+            vertical_velocity: 0.0,
+        }
+    }
+}
+
+/// Starts the death sequence for the runner that died, or for every runner if it's not known which one did
+fn start_death_sequence(
+    trigger: Trigger<DeathEvent>,
+    mut player_query: Query<(Entity, &Transform, &mut PlayerAnimation), With<Player>>,
+    scroll_position: Res<ScrollPosition>,
+    mut commands: Commands,
+) {
+    let target = trigger.entity();
+    for (entity, transform, mut animation) in &mut player_query {
+        if target != Entity::PLACEHOLDER && entity != target {
+            continue;
+        }
+
+        animation.update_state(PlayerAnimationState::Hurt);
+        commands.entity(entity).insert(DeathSequence::new());
+        commands.trigger(
+            PlaySfx::new(SfxKey::Death).with_pan(scroll_position.pan_for(transform.translation.x)),
+        );
+    }
+}
+
+fn advance_death_sequence(
+    time: Res<Time>,
+    mut player_query: Query<(
+        Entity,
+        &mut DeathSequence,
+        &mut Transform,
+        &mut PlayerAnimation,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut sequence, mut transform, mut animation) in &mut player_query {
+        sequence.timer.tick(time.delta());
+
+        match sequence.phase {
+            DeathPhase::Hurt => {
+                if sequence.timer.finished() {
+                    sequence.phase = DeathPhase::Falling;
+                    sequence.timer = Timer::new(FALL_DURATION, TimerMode::Once);
+                    sequence.vertical_velocity = POP_VELOCITY;
+                    animation.update_state(PlayerAnimationState::Tumbling);
+                }
+            }
+            DeathPhase::Falling => {
+                transform.translation.y += sequence.vertical_velocity * time.delta_seconds();
+                sequence.vertical_velocity -= GRAVITY * time.delta_seconds();
+
+                if sequence.timer.finished() {
+                    commands.entity(entity).remove::<DeathSequence>();
+                    commands.trigger(ShowGameOver);
+                }
+            }
+        }
+    }
+}
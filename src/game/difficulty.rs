@@ -0,0 +1,94 @@
+//! How hard generated levels are, based on how far into the run the player is.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DifficultyCurve>();
+    app.init_resource::<DifficultyCurve>();
+}
+
+/// Tunable values that control how generated levels ramp up in difficulty.
+/// Each value starts at its base and moves by its per-level amount until it hits its limit.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct DifficultyCurve {
+    /// The number of obstacle groups in the first level
+    pub base_obstacles: f32,
+    /// How many more obstacle groups each level gets
+    pub obstacles_per_level: f32,
+    /// The most obstacle groups a level can have
+    pub max_obstacles: f32,
+
+    /// The chance for an obstacle group to include spikes in the first level
+    pub base_spike_chance: f32,
+    /// How much more likely spikes get each level
+    pub spike_chance_per_level: f32,
+    /// The highest chance for an obstacle group to include spikes
+    pub max_spike_chance: f32,
+
+    /// The gap between obstacle groups in the first level, in boxes
+    pub base_gap: f32,
+    /// How much the gap between obstacle groups shrinks each level, in boxes
+    pub gap_shrink_per_level: f32,
+    /// The smallest gap between obstacle groups, in boxes
+    pub min_gap: f32,
+
+    /// The time between beats in the first level, in seconds
+    pub base_beat_interval: f32,
+    /// How much the time between beats shrinks each level, in seconds
+    pub beat_interval_shrink_per_level: f32,
+    /// The shortest time between beats, in seconds
+    pub min_beat_interval: f32,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        DifficultyCurve {
+            base_obstacles: 2.0,
+            obstacles_per_level: 0.35,
+            max_obstacles: 7.0,
+
+            base_spike_chance: 0.2,
+            spike_chance_per_level: 0.05,
+            max_spike_chance: 0.8,
+
+            base_gap: 6.0,
+            gap_shrink_per_level: 0.25,
+            min_gap: 2.0,
+
+            base_beat_interval: 0.15,
+            beat_interval_shrink_per_level: 0.002,
+            min_beat_interval: 0.1,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    /// Gets the difficulty of the provided level
+    pub fn at(&self, level: u32) -> Difficulty {
+        let level = level as f32;
+        Difficulty {
+            obstacles: (self.base_obstacles + self.obstacles_per_level * level)
+                .min(self.max_obstacles)
+                .floor() as u32,
+            spike_chance: (self.base_spike_chance + self.spike_chance_per_level * level)
+                .min(self.max_spike_chance),
+            gap: (self.base_gap - self.gap_shrink_per_level * level).max(self.min_gap),
+            beat_interval: (self.base_beat_interval - self.beat_interval_shrink_per_level * level)
+                .max(self.min_beat_interval),
+        }
+    }
+}
+
+/// The difficulty of a single level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    /// The number of obstacle groups to spawn
+    pub obstacles: u32,
+    /// The chance for each obstacle group to include spikes, from 0 to 1
+    pub spike_chance: f32,
+    /// The gap between obstacle groups, in boxes
+    pub gap: f32,
+    /// The time between beats, in seconds
+    pub beat_interval: f32,
+}
@@ -0,0 +1,443 @@
+//! The heads-up display along the top of the screen while playing, showing how the run is going.
+
+use std::cmp::Ordering;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    screen::Screen,
+    ui::palette::{BOSS_HEALTH, BOSS_HEALTH_BACKGROUND, LABEL_TEXT, SPLIT_AHEAD, SPLIT_BEHIND},
+    AppSet,
+};
+
+use super::{
+    assets::{FontKey, HandleMap},
+    boss::Boss,
+    coop::{Multiplayer, PlayerSlot},
+    localization::Localization,
+    movement::{MovementController, PlaybackSpeed, TotalDistance},
+    spawn::{
+        level::{CurrentLevel, GameMode, CAMPAIGN_LENGTH},
+        sequencer::{SequenceState, BEATS_PER_GROUP},
+    },
+    splits::{format_run_time, format_split_delta, Split, Splits},
+    stats::RunStats,
+    versus::Opponent,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_hud);
+    app.add_systems(
+        Update,
+        update_hud
+            .run_if(
+                resource_changed::<CurrentLevel>
+                    .or_else(resource_changed::<SequenceState>)
+                    .or_else(resource_changed::<TotalDistance>)
+                    .or_else(resource_changed::<PlaybackSpeed>)
+                    .or_else(resource_changed::<Localization>)
+                    .or_else(resource_changed::<RunStats>)
+                    // the runner who's behind, or the opponent, can move without the total distance changing
+                    .or_else(not(resource_equals(Multiplayer::Solo))),
+            )
+            .in_set(AppSet::Update),
+    );
+    app.add_systems(Update, update_boss_health_bar.in_set(AppSet::Update));
+    app.add_systems(
+        Update,
+        update_splits_list
+            .run_if(resource_changed::<Splits>.or_else(resource_changed::<Localization>))
+            .in_set(AppSet::Update),
+    );
+}
+
+/// How wide the boss's health bar is when it's full
+const BOSS_HEALTH_BAR_WIDTH: f32 = 400.0;
+
+/// The most splits shown at once. Older ones are dropped off the top of the list.
+const MAX_SHOWN_SPLITS: usize = 5;
+
+#[derive(Event, Debug)]
+pub struct SpawnHud;
+
+/// A piece of the HUD, which keeps its text up to date with what it shows
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum HudField {
+    Level,
+    /// The bar and beat within it the sequence is on
+    Beat,
+    Tempo,
+    Distance,
+    /// The label on the boss's health bar
+    Boss,
+    /// How long the run has been going, not counting time spent paused
+    Timer,
+}
+
+/// The boss's health bar, which is only shown during boss fights
+#[derive(Component, Debug)]
+struct BossHealthBar;
+
+/// The part of the boss's health bar that shrinks as the boss wears out
+#[derive(Component, Debug)]
+struct BossHealthFill;
+
+/// The list of splits for the levels cleared so far
+#[derive(Component, Debug)]
+struct SplitsList;
+
+/// The resources the HUD shows
+#[derive(SystemParam)]
+struct HudSources<'w, 's> {
+    current_level: Res<'w, CurrentLevel>,
+    game_mode: Res<'w, GameMode>,
+    sequence_state: Res<'w, SequenceState>,
+    distance: Res<'w, TotalDistance>,
+    playback_speed: Res<'w, PlaybackSpeed>,
+    localization: Res<'w, Localization>,
+    multiplayer: Res<'w, Multiplayer>,
+    stats: Res<'w, RunStats>,
+    runner_query: Query<'w, 's, (&'static PlayerSlot, &'static MovementController)>,
+    opponent_query: Query<'w, 's, &'static MovementController, With<Opponent>>,
+}
+
+impl HudSources<'_, '_> {
+    /// Gets the text to show for a piece of the HUD
+    fn text(&self, field: HudField) -> String {
+        match field {
+            HudField::Level => {
+                let level = self.current_level.0 + 1;
+                if *self.game_mode == GameMode::Campaign {
+                    self.localization.format(
+                        "hud.campaign_level",
+                        &[("level", &level), ("total", &CAMPAIGN_LENGTH)],
+                    )
+                } else {
+                    self.localization.format("hud.level", &[("level", &level)])
+                }
+            }
+            HudField::Beat => {
+                let beat = self.sequence_state.beat();
+                self.localization.format(
+                    "hud.beat",
+                    &[
+                        ("bar", &(beat / BEATS_PER_GROUP + 1)),
+                        ("beat", &(beat % BEATS_PER_GROUP + 1)),
+                    ],
+                )
+            }
+            HudField::Tempo => {
+                let bpm = format!(
+                    "{:.0}",
+                    60.0 / self.sequence_state.beat_interval().as_secs_f32()
+                );
+                if self.playback_speed.is_slow_motion() {
+                    self.localization.format(
+                        "hud.tempo_slow_motion",
+                        &[("bpm", &bpm), ("speed", &self.playback_speed.chosen)],
+                    )
+                } else {
+                    self.localization.format("hud.tempo", &[("bpm", &bpm)])
+                }
+            }
+            HudField::Distance => match *self.multiplayer {
+                Multiplayer::Solo => self
+                    .localization
+                    .format("hud.distance", &[("distance", &*self.distance)]),
+                Multiplayer::Coop => self.coop_distance_text(),
+                Multiplayer::Versus => self.versus_distance_text(),
+            },
+            HudField::Boss => self.localization.get("hud.boss"),
+            HudField::Timer => self.localization.format(
+                "hud.timer",
+                &[("time", &format_run_time(self.stats.time_survived))],
+            ),
+        }
+    }
+
+    /// Gets the text showing how far each runner has gone, so the players can see who's ahead
+    fn coop_distance_text(&self) -> String {
+        let distance_of = |slot| {
+            let distance = self
+                .runner_query
+                .iter()
+                .find(|(runner_slot, _)| **runner_slot == slot)
+                .map_or(0.0, |(_, controller)| controller.distance);
+            TotalDistance(distance)
+        };
+        self.localization.format(
+            "hud.coop_distance",
+            &[
+                ("one", &distance_of(PlayerSlot::One)),
+                ("two", &distance_of(PlayerSlot::Two)),
+            ],
+        )
+    }
+
+    /// Gets the text showing how far the player and the opponent have gone, and who's ahead
+    fn versus_distance_text(&self) -> String {
+        let opponent = TotalDistance(
+            self.opponent_query
+                .iter()
+                .map(|controller| controller.distance)
+                .fold(0.0, f32::max),
+        );
+        let key = match self.distance.feet().cmp(&opponent.feet()) {
+            Ordering::Greater => "hud.versus_ahead",
+            Ordering::Less => "hud.versus_behind",
+            Ordering::Equal => "hud.versus_tied",
+        };
+        self.localization
+            .format(key, &[("player", &*self.distance), ("opponent", &opponent)])
+    }
+}
+
+fn spawn_hud(
+    _trigger: Trigger<SpawnHud>,
+    font_handles: Res<HandleMap<FontKey>>,
+    sources: HudSources,
+    mut commands: Commands,
+) {
+    commands
+        .spawn((
+            Name::new("HUD"),
+            StateScoped(Screen::Playing),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(35.0),
+                    top: Val::Px(5.0),
+                    left: Val::Px(5.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Start,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(30.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            for field in [
+                HudField::Level,
+                HudField::Beat,
+                HudField::Tempo,
+                HudField::Distance,
+            ] {
+                children.spawn((
+                    Name::new("HUD text"),
+                    field,
+                    TextBundle::from_section(
+                        sources.text(field),
+                        TextStyle {
+                            font: font_handles.get(FontKey::General),
+                            font_size: 30.0,
+                            color: LABEL_TEXT,
+                        },
+                    ),
+                ));
+            }
+        });
+
+    commands
+        .spawn((
+            Name::new("Splits"),
+            StateScoped(Screen::Playing),
+            NodeBundle {
+                style: Style {
+                    top: Val::Px(45.0),
+                    right: Val::Px(10.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::End,
+                    row_gap: Val::Px(2.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Run timer"),
+                HudField::Timer,
+                TextBundle::from_section(
+                    sources.text(HudField::Timer),
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 24.0,
+                        color: LABEL_TEXT,
+                    },
+                ),
+            ));
+            children.spawn((
+                Name::new("Splits list"),
+                SplitsList,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::End,
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+
+    commands
+        .spawn((
+            Name::new("Boss health bar"),
+            BossHealthBar,
+            StateScoped(Screen::Playing),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    top: Val::Px(45.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Boss health label"),
+                HudField::Boss,
+                TextBundle::from_section(
+                    sources.text(HudField::Boss),
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 24.0,
+                        color: LABEL_TEXT,
+                    },
+                ),
+            ));
+            children
+                .spawn((
+                    Name::new("Boss health background"),
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(BOSS_HEALTH_BAR_WIDTH),
+                            height: Val::Px(16.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(BOSS_HEALTH_BACKGROUND),
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.spawn((
+                        Name::new("Boss health fill"),
+                        BossHealthFill,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(BOSS_HEALTH),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn update_hud(sources: HudSources, mut field_query: Query<(&HudField, &mut Text)>) {
+    for (field, mut text) in &mut field_query {
+        let value = sources.text(*field);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Shows the boss's health bar during boss fights, filled up to how much health the boss has left
+fn update_boss_health_bar(
+    boss_query: Query<&Boss>,
+    mut bar_query: Query<&mut Visibility, With<BossHealthBar>>,
+    mut fill_query: Query<&mut Style, With<BossHealthFill>>,
+) {
+    let health = boss_query.get_single().ok().map(Boss::health_fraction);
+    let visibility = if health.is_some() {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut bar_visibility in &mut bar_query {
+        bar_visibility.set_if_neq(visibility);
+    }
+
+    let Some(health) = health else {
+        return;
+    };
+    let width = Val::Percent(health * 100.0);
+    for mut style in &mut fill_query {
+        if style.width != width {
+            style.width = width;
+        }
+    }
+}
+
+/// Shows the most recent splits, each with how it compares to the best split for the same level
+fn update_splits_list(
+    splits: Res<Splits>,
+    localization: Res<Localization>,
+    font_handles: Res<HandleMap<FontKey>>,
+    list_query: Query<Entity, With<SplitsList>>,
+    mut commands: Commands,
+) {
+    let shown = &splits.0[splits.0.len().saturating_sub(MAX_SHOWN_SPLITS)..];
+    for list in &list_query {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|children| {
+                for split in shown {
+                    children.spawn((
+                        Name::new("Split"),
+                        TextBundle::from_sections(split_text(split, &localization, &font_handles)),
+                    ));
+                }
+            });
+    }
+}
+
+/// Gets the text for a split, with its time in the usual color and how it compares to the best split colored by whether it's faster or slower
+fn split_text(
+    split: &Split,
+    localization: &Localization,
+    font_handles: &HandleMap<FontKey>,
+) -> Vec<TextSection> {
+    let style = |color| TextStyle {
+        font: font_handles.get(FontKey::General),
+        font_size: 20.0,
+        color,
+    };
+
+    let mut sections = vec![TextSection::new(
+        localization.format(
+            "hud.split",
+            &[
+                ("level", &(split.level + 1)),
+                ("time", &format_run_time(split.time)),
+            ],
+        ),
+        style(LABEL_TEXT),
+    )];
+    if let Some(delta) = split.delta {
+        let color = if delta <= 0.0 {
+            SPLIT_AHEAD
+        } else {
+            SPLIT_BEHIND
+        };
+        sections.push(TextSection::new(
+            format!(" {}", format_split_delta(delta)),
+            style(color),
+        ));
+    }
+    sections
+}
@@ -0,0 +1,314 @@
+//! Which keys and gamepad buttons do what, saved between sessions so players can change them.
+//! Navigating the UI, like moving focus with the arrow keys and pressing buttons with enter, always uses the same keys.
+
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, ReflectRef, VariantType},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+const INPUT_MAP_SAVE_KEY: &str = "input_map";
+
+pub(super) fn plugin(app: &mut App) {
+    let input_map = persistence::load::<InputMap>(INPUT_MAP_SAVE_KEY)
+        .map(InputMap::with_missing_defaults)
+        .unwrap_or_default();
+    app.insert_resource(input_map);
+    app.add_systems(Update, save_input_map.run_if(resource_changed::<InputMap>));
+}
+
+/// Something the player can do by pressing a key or gamepad button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InputAction {
+    LeaveRun,
+    ToggleSequencer,
+    PhotoMode,
+    PhotoPanUp,
+    PhotoPanLeft,
+    PhotoPanDown,
+    PhotoPanRight,
+    PhotoZoomIn,
+    PhotoZoomOut,
+    TakePhoto,
+    SaveScreenshot,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 11] = [
+        InputAction::LeaveRun,
+        InputAction::ToggleSequencer,
+        InputAction::PhotoMode,
+        InputAction::PhotoPanUp,
+        InputAction::PhotoPanLeft,
+        InputAction::PhotoPanDown,
+        InputAction::PhotoPanRight,
+        InputAction::PhotoZoomIn,
+        InputAction::PhotoZoomOut,
+        InputAction::TakePhoto,
+        InputAction::SaveScreenshot,
+    ];
+
+    /// Gets the key for the name of this action in the localization files
+    pub fn localization_key(self) -> &'static str {
+        match self {
+            InputAction::LeaveRun => "action.leave_run",
+            InputAction::ToggleSequencer => "action.toggle_sequencer",
+            InputAction::PhotoMode => "action.photo_mode",
+            InputAction::PhotoPanUp => "action.photo_pan_up",
+            InputAction::PhotoPanLeft => "action.photo_pan_left",
+            InputAction::PhotoPanDown => "action.photo_pan_down",
+            InputAction::PhotoPanRight => "action.photo_pan_right",
+            InputAction::PhotoZoomIn => "action.photo_zoom_in",
+            InputAction::PhotoZoomOut => "action.photo_zoom_out",
+            InputAction::TakePhoto => "action.take_photo",
+            InputAction::SaveScreenshot => "action.save_screenshot",
+        }
+    }
+
+    /// Whether this action does anything in this build of the game
+    pub fn is_available(self) -> bool {
+        self != InputAction::SaveScreenshot
+            || cfg!(all(feature = "capture", not(target_family = "wasm")))
+    }
+
+    fn default_bindings(self) -> Vec<InputBinding> {
+        use GamepadButtonType as Pad;
+        let (key, button) = match self {
+            InputAction::LeaveRun => (KeyCode::Escape, Some(Pad::Select)),
+            InputAction::ToggleSequencer => (KeyCode::Tab, Some(Pad::North)),
+            InputAction::PhotoMode => (KeyCode::KeyP, Some(Pad::West)),
+            InputAction::PhotoPanUp => (KeyCode::KeyW, Some(Pad::DPadUp)),
+            InputAction::PhotoPanLeft => (KeyCode::KeyA, Some(Pad::DPadLeft)),
+            InputAction::PhotoPanDown => (KeyCode::KeyS, Some(Pad::DPadDown)),
+            InputAction::PhotoPanRight => (KeyCode::KeyD, Some(Pad::DPadRight)),
+            InputAction::PhotoZoomIn => (KeyCode::KeyE, Some(Pad::RightTrigger)),
+            InputAction::PhotoZoomOut => (KeyCode::KeyQ, Some(Pad::LeftTrigger)),
+            InputAction::TakePhoto => (KeyCode::Space, Some(Pad::South)),
+            InputAction::SaveScreenshot => (KeyCode::F12, None),
+        };
+        [
+            Some(InputBinding::Key(key)),
+            button.map(InputBinding::Gamepad),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// A key or gamepad button that an action can be bound to.
+/// Saved as text, like `key:KeyP` or `gamepad:South`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum InputBinding {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+/// Whether a binding is for the keyboard or a gamepad. Each action can have one of each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindingKind {
+    Key,
+    Gamepad,
+}
+
+impl InputBinding {
+    pub fn kind(self) -> BindingKind {
+        match self {
+            InputBinding::Key(_) => BindingKind::Key,
+            InputBinding::Gamepad(_) => BindingKind::Gamepad,
+        }
+    }
+
+    /// Gets the name of the key or button to show to the player
+    pub fn label(self) -> String {
+        match self {
+            InputBinding::Key(key) => {
+                let name = variant_name(&key).unwrap_or_else(|| format!("{key:?}"));
+                // so the letter and number keys just show their letter or number
+                ["Key", "Digit"]
+                    .into_iter()
+                    .find_map(|prefix| name.strip_prefix(prefix).filter(|rest| !rest.is_empty()))
+                    .map_or(name.clone(), str::to_string)
+            }
+            InputBinding::Gamepad(button) => {
+                variant_name(&button).unwrap_or_else(|| format!("{button:?}"))
+            }
+        }
+    }
+}
+
+impl From<InputBinding> for String {
+    fn from(binding: InputBinding) -> String {
+        let (prefix, name) = match binding {
+            InputBinding::Key(key) => ("key", variant_name(&key)),
+            InputBinding::Gamepad(button) => ("gamepad", variant_name(&button)),
+        };
+        format!("{prefix}:{}", name.unwrap_or_default())
+    }
+}
+
+impl TryFrom<String> for InputBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<InputBinding, String> {
+        let binding = match value.split_once(':') {
+            Some(("key", name)) => from_variant_name(name).map(InputBinding::Key),
+            Some(("gamepad", name)) => from_variant_name(name).map(InputBinding::Gamepad),
+            _ => None,
+        };
+        binding.ok_or_else(|| format!("unknown binding {value}"))
+    }
+}
+
+/// Gets the name of the provided enum value, if it's a variant without any fields
+fn variant_name(value: &dyn Reflect) -> Option<String> {
+    match value.reflect_ref() {
+        ReflectRef::Enum(value) if value.variant_type() == VariantType::Unit => {
+            Some(value.variant_name().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Gets the enum variant without any fields that has the provided name
+fn from_variant_name<T: FromReflect>(name: &str) -> Option<T> {
+    T::from_reflect(&DynamicEnum::new(name.to_string(), DynamicVariant::Unit))
+}
+
+/// What each action is bound to.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputMap {
+    /// The keys and gamepad buttons each action is bound to, at most one of each
+    bindings: HashMap<InputAction, Vec<InputBinding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap {
+            bindings: InputAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl InputMap {
+    /// Binds any actions that weren't around when this was saved to their defaults
+    fn with_missing_defaults(mut self) -> InputMap {
+        for action in InputAction::ALL {
+            self.bindings
+                .entry(action)
+                .or_insert_with(|| action.default_bindings());
+        }
+        self
+    }
+
+    /// Gets the key or gamepad button the provided action is bound to, if it's bound to one
+    pub fn binding(&self, action: InputAction, kind: BindingKind) -> Option<InputBinding> {
+        self.bindings(action).find(|binding| binding.kind() == kind)
+    }
+
+    /// Gets everything the provided action is bound to
+    pub fn bindings(&self, action: InputAction) -> impl Iterator<Item = InputBinding> + '_ {
+        self.bindings.get(&action).into_iter().flatten().copied()
+    }
+
+    /// Binds the provided action to the provided key or button, replacing the key or button it was bound to before
+    pub fn bind(&mut self, action: InputAction, binding: InputBinding) {
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|existing| existing.kind() != binding.kind());
+        bindings.push(binding);
+    }
+
+    /// Takes the provided kind of binding off of the provided action
+    pub fn unbind(&mut self, action: InputAction, kind: BindingKind) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|existing| existing.kind() != kind);
+        }
+    }
+
+    /// Gets every key or button that more than one action is bound to, along with the actions bound to it
+    pub fn conflicts(&self) -> Vec<(InputBinding, Vec<InputAction>)> {
+        let mut actions_by_binding: HashMap<InputBinding, Vec<InputAction>> = HashMap::new();
+        for action in InputAction::ALL.into_iter().filter(|a| a.is_available()) {
+            for binding in self.bindings(action) {
+                actions_by_binding.entry(binding).or_default().push(action);
+            }
+        }
+
+        let mut conflicts = actions_by_binding
+            .into_iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .collect::<Vec<_>>();
+        conflicts.sort_by_key(|(_, actions)| actions[0]);
+        conflicts
+    }
+}
+
+fn save_input_map(input_map: Res<InputMap>) {
+    if !input_map.is_added() {
+        persistence::save(INPUT_MAP_SAVE_KEY, &*input_map);
+    }
+}
+
+/// Checks whether actions are being done, going by what they're bound to in the [`InputMap`].
+/// Any connected gamepad can be used.
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    input_map: Res<'w, InputMap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+}
+
+impl ActionInput<'_> {
+    /// Whether a key or button the provided action is bound to started being pressed this frame
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.any_binding(
+            action,
+            |keys, key| keys.just_pressed(key),
+            |buttons, button| buttons.just_pressed(button),
+        )
+    }
+
+    /// Whether a key or button the provided action is bound to is being held down
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.any_binding(
+            action,
+            |keys, key| keys.pressed(key),
+            |buttons, button| buttons.pressed(button),
+        )
+    }
+
+    fn any_binding(
+        &self,
+        action: InputAction,
+        check_key: impl Fn(&ButtonInput<KeyCode>, KeyCode) -> bool,
+        check_button: impl Fn(&ButtonInput<GamepadButton>, GamepadButton) -> bool,
+    ) -> bool {
+        self.input_map
+            .bindings(action)
+            .any(|binding| match binding {
+                InputBinding::Key(key) => check_key(&self.keys, key),
+                InputBinding::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                    check_button(
+                        &self.gamepad_buttons,
+                        GamepadButton::new(gamepad, button_type),
+                    )
+                }),
+            })
+    }
+}
+
+/// Run condition that's true on the frame a key or button the provided action is bound to starts being pressed
+pub fn action_just_pressed(action: InputAction) -> impl Fn(ActionInput) -> bool + Clone {
+    move |input: ActionInput| input.just_pressed(action)
+}
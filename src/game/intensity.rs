@@ -0,0 +1,228 @@
+//! How heated a run is getting, from 0 for calm to 1 for as intense as it gets.
+//! Getting past obstacles one after another without taking a hit builds it up, near misses give it a kick,
+//! and hits knock it back down. Anything that should escalate with the run reads it from here:
+//! the sky's saturation, the sparks trailing the runner, and the music's extra layer.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    assists::RunnerHit,
+    movement::{ObstacleCleared, Paused},
+    near_miss::NearMiss,
+    settings::Settings,
+    spawn::{
+        player::Player,
+        sequencer::{DeathEvent, ResetSequence},
+    },
+    theme::ActiveTheme,
+    versus::Opponent,
+};
+
+/// How much each obstacle gotten past raises the intensity, per obstacle in the current combo
+const COMBO_STEP: f32 = 0.01;
+
+/// The longest combo that keeps raising how much each obstacle is worth
+const MAX_COMBO_STEPS: u32 = 8;
+
+/// How much each near miss raises the intensity
+const NEAR_MISS_RAISE: f32 = 0.12;
+
+/// How much each hit lowers the intensity
+const HIT_DROP: f32 = 0.4;
+
+/// How fast the intensity settles back down on its own, per second
+const DECAY_RATE: f32 = 0.03;
+
+/// How many sparks trail each runner per second at full intensity
+const MAX_SPARK_RATE: f32 = 40.0;
+
+/// How long each spark lasts, in seconds
+const SPARK_DURATION: f32 = 0.5;
+
+/// How big each spark is, in pixels
+const SPARK_SIZE: f32 = 6.0;
+
+/// How fast sparks drift away from where they're emitted, in pixels/sec
+const SPARK_SPEED: f32 = 60.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Intensity>();
+    app.observe(build_combo);
+    app.observe(raise_on_near_miss);
+    app.observe(drop_on_hit);
+    app.observe(drop_on_death);
+    app.observe(reset_intensity);
+    app.add_systems(
+        Update,
+        settle_intensity
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnEnter(Screen::Playing), start_calm);
+}
+
+/// Trails sparks behind the runners, more of them the more intense the run is
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (emit_sparks, fade_sparks)
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// How intense the run is right now
+#[derive(Resource, Debug, Default)]
+pub struct Intensity {
+    /// From 0 for calm to 1 for as intense as it gets
+    level: f32,
+    /// How many obstacles have been gotten past since the last hit
+    combo: u32,
+}
+
+impl Intensity {
+    /// Gets how intense the run is, from 0 for calm to 1 for as intense as it gets
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Gets how many obstacles have been gotten past since the last hit
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    fn raise(&mut self, amount: f32) {
+        self.level = (self.level + amount).clamp(0.0, 1.0);
+    }
+}
+
+/// A bit of light trailing a runner
+#[derive(Component, Debug)]
+struct Spark {
+    timer: Timer,
+    velocity: Vec2,
+}
+
+fn build_combo(_trigger: Trigger<ObstacleCleared>, mut intensity: ResMut<Intensity>) {
+    intensity.combo += 1;
+    let steps = intensity.combo.min(MAX_COMBO_STEPS);
+    intensity.raise(COMBO_STEP * steps as f32);
+}
+
+fn raise_on_near_miss(_trigger: Trigger<NearMiss>, mut intensity: ResMut<Intensity>) {
+    intensity.raise(NEAR_MISS_RAISE);
+}
+
+fn drop_on_hit(
+    trigger: Trigger<RunnerHit>,
+    opponent_query: Query<(), With<Opponent>>,
+    mut intensity: ResMut<Intensity>,
+) {
+    if opponent_query.contains(trigger.entity()) {
+        return;
+    }
+
+    intensity.combo = 0;
+    intensity.raise(-HIT_DROP);
+}
+
+fn drop_on_death(
+    trigger: Trigger<DeathEvent>,
+    opponent_query: Query<(), With<Opponent>>,
+    mut intensity: ResMut<Intensity>,
+) {
+    if !opponent_query.contains(trigger.entity()) {
+        *intensity = Intensity::default();
+    }
+}
+
+fn reset_intensity(_trigger: Trigger<ResetSequence>, mut intensity: ResMut<Intensity>) {
+    *intensity = Intensity::default();
+}
+
+fn start_calm(mut intensity: ResMut<Intensity>) {
+    *intensity = Intensity::default();
+}
+
+/// Lets the intensity settle back down while nothing's happening to keep it up
+fn settle_intensity(time: Res<Time>, paused: Res<Paused>, mut intensity: ResMut<Intensity>) {
+    // only touched when there's something to settle, so readers can tell when it actually changes
+    if paused.0 || intensity.level <= 0.0 {
+        return;
+    }
+    intensity.raise(-DECAY_RATE * time.delta_seconds());
+}
+
+fn emit_sparks(
+    time: Res<Time>,
+    paused: Res<Paused>,
+    settings: Res<Settings>,
+    active_theme: Res<ActiveTheme>,
+    intensity: Res<Intensity>,
+    runner_query: Query<(&Transform, &Player), Without<Opponent>>,
+    // how many sparks are owed to each runner but haven't been emitted yet
+    mut pending_sparks: Local<f32>,
+    mut commands: Commands,
+) {
+    if paused.0 || settings.reduced_motion {
+        return;
+    }
+
+    *pending_sparks += intensity.level() * MAX_SPARK_RATE * time.delta_seconds();
+    let count = pending_sparks.floor();
+    *pending_sparks -= count;
+
+    let mut rng = rand::thread_rng();
+    for (transform, player) in &runner_query {
+        for _ in 0..count as u32 {
+            // from somewhere behind the runner, drifting back and out
+            let position = transform.translation.truncate()
+                + player.collider_offset
+                + Vec2::new(
+                    -player.collider.x / 2.0,
+                    rng.gen_range(-0.5..0.5) * player.collider.y,
+                );
+            let velocity = Vec2::from_angle(rng.gen_range(2.5..3.8)) * SPARK_SPEED;
+            commands.spawn((
+                Name::new("Spark"),
+                Spark {
+                    timer: Timer::from_seconds(SPARK_DURATION, TimerMode::Once),
+                    velocity,
+                },
+                StateScoped(Screen::Playing),
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: active_theme.theme.particle_color(),
+                        custom_size: Some(Vec2::splat(SPARK_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position.extend(0.4)),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+/// Moves the sparks along and fades them out, then gets rid of them once their time is up
+fn fade_sparks(
+    time: Res<Time>,
+    mut spark_query: Query<(Entity, &mut Spark, &mut Transform, &mut Sprite)>,
+    mut commands: Commands,
+) {
+    for (entity, mut spark, mut transform, mut sprite) in &mut spark_query {
+        spark.timer.tick(time.delta());
+        if spark.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation += (spark.velocity * time.delta_seconds()).extend(0.0);
+        let remaining = 1.0 - spark.timer.fraction();
+        sprite.color.set_alpha(remaining);
+        transform.scale = Vec3::splat(remaining);
+    }
+}
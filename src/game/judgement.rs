@@ -0,0 +1,102 @@
+//! Data-driven judgements of how a run went, shown on the game over panel.
+//! The rules live in a RON file in the assets folder, so new lines can be added without touching the code.
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use super::{assets::RonAssetLoader, stats::RunStats};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Judgements>();
+    app.register_asset_loader(RonAssetLoader::<Judgements>::new(&["judgements.ron"]));
+}
+
+/// The rules for judging runs, checked in order.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct Judgements {
+    pub rules: Vec<JudgementRule>,
+}
+
+impl Judgements {
+    /// Picks a line from the first rule the run meets, at random.
+    /// Returns the localization key of the line, or `None` if the run doesn't meet any of the rules.
+    pub fn judge(&self, run: &JudgedRun) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.conditions
+                    .iter()
+                    .all(|condition| condition.is_met(run))
+            })
+            .and_then(|rule| rule.lines.choose(&mut rand::thread_rng()))
+            .map(String::as_str)
+    }
+}
+
+/// Lines to pick from when a run meets every one of some conditions.
+#[derive(Deserialize, Debug)]
+pub struct JudgementRule {
+    /// A rule with no conditions matches every run
+    #[serde(default)]
+    pub conditions: Vec<JudgementCondition>,
+    /// The localization keys of the lines to pick from
+    pub lines: Vec<String>,
+}
+
+/// Something about a run that has to be true for a rule to match it.
+#[derive(Deserialize, Debug)]
+pub enum JudgementCondition {
+    AtLeast(JudgedStat, f32),
+    AtMost(JudgedStat, f32),
+}
+
+impl JudgementCondition {
+    fn is_met(&self, run: &JudgedRun) -> bool {
+        match self {
+            JudgementCondition::AtLeast(stat, min) => run.stat(*stat) >= *min,
+            JudgementCondition::AtMost(stat, max) => run.stat(*stat) <= *max,
+        }
+    }
+}
+
+/// A number about a run that rules can check.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum JudgedStat {
+    /// The index of the level the run ended on
+    Level,
+    /// How far the player ran, in feet
+    Distance,
+    BeatsPlayed,
+    LevelsCleared,
+    Jumps,
+    Dives,
+    /// How long the run lasted, in seconds
+    TimeSurvived,
+    /// The longest the player was in the air at once, in seconds
+    LongestAirTime,
+    BonusScore,
+}
+
+/// Everything about a run that can be judged
+pub struct JudgedRun<'a> {
+    pub level: u32,
+    pub distance: u32,
+    pub stats: &'a RunStats,
+}
+
+impl JudgedRun<'_> {
+    fn stat(&self, stat: JudgedStat) -> f32 {
+        match stat {
+            JudgedStat::Level => self.level as f32,
+            JudgedStat::Distance => self.distance as f32,
+            JudgedStat::BeatsPlayed => self.stats.beats_played as f32,
+            JudgedStat::LevelsCleared => self.stats.levels_cleared as f32,
+            JudgedStat::Jumps => self.stats.jumps as f32,
+            JudgedStat::Dives => self.stats.dives as f32,
+            JudgedStat::TimeSurvived => self.stats.time_survived.as_secs_f32(),
+            JudgedStat::LongestAirTime => self.stats.longest_air_time.as_secs_f32(),
+            JudgedStat::BonusScore => self.stats.bonus_score as f32,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! An online leaderboard. This plugin is only enabled with the `leaderboard` feature.
+//! When the player dies with a new best distance for this session, it's submitted along with a hash of
+//! the sequence that got them there. Either way, the top scores are shown on the game over panel.
+//! If the leaderboard can't be reached, the game over panel just says so.
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::assets::{FontKey, HandleMap},
+    ui::palette::LABEL_TEXT,
+    AppSet,
+};
+
+use super::{
+    localization::Localization,
+    movement::TotalDistance,
+    spawn::sequencer::{DeathEvent, GameOver, Sequence},
+};
+
+/// The number of scores to show
+const LEADERBOARD_SIZE: usize = 10;
+
+/// The leaderboard server to use if one isn't provided at compile time via `LOOPRUNNER_LEADERBOARD_URL`
+const DEFAULT_ENDPOINT: &str = "http://localhost:8080/leaderboard";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(LeaderboardConfig {
+        endpoint: option_env!("LOOPRUNNER_LEADERBOARD_URL")
+            .unwrap_or(DEFAULT_ENDPOINT)
+            .to_string(),
+    });
+    app.init_resource::<SessionBest>();
+    app.insert_resource(LeaderboardState::Idle);
+    app.observe(submit_score);
+    app.add_systems(
+        Update,
+        (
+            poll_leaderboard_task,
+            spawn_leaderboard_display,
+            update_leaderboard_display,
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Where the leaderboard lives.
+#[derive(Resource, Debug)]
+pub struct LeaderboardConfig {
+    /// The base URL of the leaderboard server.
+    /// Scores are posted to `{endpoint}/scores` and the top scores are fetched from `{endpoint}/top`.
+    pub endpoint: String,
+}
+
+/// The best score the player has gotten since starting the game.
+#[derive(Resource, Debug, Default)]
+struct SessionBest(Option<ScoreSubmission>);
+
+#[derive(Serialize, Debug, Clone)]
+struct ScoreSubmission {
+    distance: u32,
+    sequence_hash: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct LeaderboardEntry {
+    distance: u32,
+    sequence_hash: String,
+}
+
+#[derive(Resource)]
+enum LeaderboardState {
+    Idle,
+    Loading(Task<Result<Vec<LeaderboardEntry>, String>>),
+    Loaded(Vec<LeaderboardEntry>),
+    Unavailable,
+}
+
+/// The text on the game over panel that shows the leaderboard.
+#[derive(Component)]
+struct LeaderboardDisplay;
+
+fn submit_score(
+    _trigger: Trigger<DeathEvent>,
+    config: Res<LeaderboardConfig>,
+    distance: Res<TotalDistance>,
+    sequence: Res<Sequence>,
+    mut session_best: ResMut<SessionBest>,
+    mut state: ResMut<LeaderboardState>,
+) {
+    // only a new best is worth submitting, since the server already has anything worse
+    let improved = session_best
+        .0
+        .as_ref()
+        .is_none_or(|best| distance.feet() > best.distance);
+    if improved {
+        session_best.0 = Some(ScoreSubmission {
+            distance: distance.feet(),
+            sequence_hash: format!("{:016x}", sequence.fingerprint()),
+        });
+    }
+
+    let submission = session_best.0.clone().filter(|_| improved);
+    let endpoint = config.endpoint.clone();
+    let task = IoTaskPool::get().spawn(submit_and_fetch(endpoint, submission));
+    *state = LeaderboardState::Loading(task);
+}
+
+/// Submits the provided score, if there is one, and then fetches the top scores
+async fn submit_and_fetch(
+    endpoint: String,
+    submission: Option<ScoreSubmission>,
+) -> Result<Vec<LeaderboardEntry>, String> {
+    if let Some(submission) = submission {
+        let request = ehttp::Request::json(format!("{endpoint}/scores"), &submission)
+            .map_err(|e| e.to_string())?;
+        let response = ehttp::fetch_async(request).await?;
+        if !response.ok {
+            return Err(format!("{} {}", response.status, response.status_text));
+        }
+    }
+
+    let request = ehttp::Request::get(format!("{endpoint}/top?limit={LEADERBOARD_SIZE}"));
+    let response = ehttp::fetch_async(request).await?;
+    if !response.ok {
+        return Err(format!("{} {}", response.status, response.status_text));
+    }
+
+    let mut entries: Vec<LeaderboardEntry> = response.json().map_err(|e| e.to_string())?;
+    entries.truncate(LEADERBOARD_SIZE);
+    Ok(entries)
+}
+
+fn poll_leaderboard_task(mut state: ResMut<LeaderboardState>) {
+    let LeaderboardState::Loading(task) = &mut *state else {
+        return;
+    };
+    let Some(result) = block_on(future::poll_once(task)) else {
+        return;
+    };
+
+    *state = match result {
+        Ok(entries) => LeaderboardState::Loaded(entries),
+        Err(e) => {
+            warn!("could not reach leaderboard: {e}");
+            LeaderboardState::Unavailable
+        }
+    };
+}
+
+fn spawn_leaderboard_display(
+    game_over_query: Query<Entity, Added<GameOver>>,
+    font_handles: Res<HandleMap<FontKey>>,
+    mut commands: Commands,
+) {
+    for entity in &game_over_query {
+        commands.entity(entity).with_children(|children| {
+            children.spawn((
+                Name::new("Leaderboard text"),
+                LeaderboardDisplay,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 18.0,
+                        color: LABEL_TEXT,
+                    },
+                ),
+            ));
+        });
+    }
+}
+
+fn update_leaderboard_display(
+    state: Res<LeaderboardState>,
+    localization: Res<Localization>,
+    mut display_query: Query<(&mut Text, Ref<LeaderboardDisplay>)>,
+) {
+    for (mut text, display) in &mut display_query {
+        if !state.is_changed() && !display.is_added() {
+            continue;
+        }
+
+        text.sections[0].value = match &*state {
+            LeaderboardState::Idle => String::new(),
+            LeaderboardState::Loading(_) => localization.get("leaderboard.loading"),
+            LeaderboardState::Loaded(entries) => entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    localization.format(
+                        "leaderboard.entry",
+                        &[
+                            ("rank", &(i + 1)),
+                            ("distance", &entry.distance),
+                            ("hash", &entry.sequence_hash),
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            LeaderboardState::Unavailable => localization.get("leaderboard.unavailable"),
+        };
+    }
+}
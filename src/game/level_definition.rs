@@ -0,0 +1,283 @@
+//! Levels described as data, so they can be made in the level editor and saved to RON files.
+//! Custom levels are read from a folder in the user's data directory, or from files dropped onto the window.
+
+use bevy::{asset::ron, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::RonAssetLoader,
+    spawn::level::{BOX_SIZE, LEVEL_WIDTH, TOP_OF_FLOOR},
+};
+
+/// The extension level files are saved with
+pub const LEVEL_EXTENSION: &str = "level.ron";
+
+/// The furthest column from the center of the first screen of the level that obstacles can be placed in
+pub const MAX_COLUMN: i32 = ((LEVEL_WIDTH / 2.0 - BOX_SIZE / 2.0) / BOX_SIZE) as i32;
+
+/// The highest row above the floor that obstacles can be placed in
+pub const MAX_ROW: i32 = 7;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<LevelDefinition>();
+    app.register_asset_loader(RonAssetLoader::<LevelDefinition>::new(&[LEVEL_EXTENSION]));
+    app.init_resource::<EditorLevel>();
+    app.init_resource::<CustomLevels>();
+}
+
+/// A level's obstacles, laid out on a grid of box-sized cells.
+#[derive(Asset, TypePath, Serialize, Deserialize, Debug, Clone)]
+pub struct LevelDefinition {
+    /// The color behind the obstacles, as sRGB components from 0 to 1
+    pub background: [f32; 3],
+    /// How many screens long the level is. Levels saved before this was a thing are one screen long.
+    #[serde(default = "one_screen")]
+    pub screens: u32,
+    pub obstacles: Vec<ObstaclePlacement>,
+}
+
+fn one_screen() -> u32 {
+    1
+}
+
+impl Default for LevelDefinition {
+    fn default() -> Self {
+        LevelDefinition {
+            background: [0.5, 0.5, 0.5],
+            screens: one_screen(),
+            obstacles: Vec::new(),
+        }
+    }
+}
+
+impl LevelDefinition {
+    /// Reads a level from the contents of a level file, making sure it's one that can be played
+    pub fn from_ron(contents: &str) -> Result<LevelDefinition, LevelError> {
+        let level: LevelDefinition = ron::from_str(contents)?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    /// Checks for obstacles that are outside the level or on top of each other
+    fn validate(&self) -> Result<(), LevelError> {
+        for (i, obstacle) in self.obstacles.iter().enumerate() {
+            if !self.contains(obstacle.cell) {
+                return Err(LevelError::OutOfBounds(obstacle.cell));
+            }
+            if self.obstacles[..i]
+                .iter()
+                .any(|other| other.cell == obstacle.cell)
+            {
+                return Err(LevelError::Overlapping(obstacle.cell));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets how wide the level is, in pixels
+    pub fn width(&self) -> f32 {
+        self.screens.max(1) as f32 * LEVEL_WIDTH
+    }
+
+    /// Whether the provided cell is somewhere in this level that obstacles can be placed.
+    /// Levels more than one screen long have more columns off to the right.
+    fn contains(&self, cell: GridCell) -> bool {
+        let max_column =
+            ((self.width() - (LEVEL_WIDTH / 2.0) - (BOX_SIZE / 2.0)) / BOX_SIZE) as i32;
+        (-MAX_COLUMN..=max_column).contains(&cell.column) && (0..=MAX_ROW).contains(&cell.row)
+    }
+
+    pub fn background_color(&self) -> Color {
+        let [red, green, blue] = self.background;
+        Color::srgb(red, green, blue)
+    }
+
+    /// Gets the obstacle in the provided cell, if there is one
+    pub fn obstacle_at(&self, cell: GridCell) -> Option<&ObstaclePlacement> {
+        self.obstacles.iter().find(|obstacle| obstacle.cell == cell)
+    }
+
+    /// Puts an obstacle in a cell, replacing anything that was already there
+    pub fn place(&mut self, placement: ObstaclePlacement) {
+        self.remove(placement.cell);
+        self.obstacles.push(placement);
+    }
+
+    /// Takes the obstacle out of the provided cell, if there is one
+    pub fn remove(&mut self, cell: GridCell) -> Option<ObstaclePlacement> {
+        let index = self
+            .obstacles
+            .iter()
+            .position(|obstacle| obstacle.cell == cell)?;
+        Some(self.obstacles.remove(index))
+    }
+}
+
+/// An obstacle, and where it is in a level.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObstaclePlacement {
+    pub kind: ObstacleKind,
+    pub cell: GridCell,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum ObstacleKind {
+    Box,
+    /// Spikes sitting on top of whatever's below them
+    Spikes,
+}
+
+/// A box-sized cell of a level. Column 0 is in the middle of the first screen of the level, and row 0 sits on the floor.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridCell {
+    pub column: i32,
+    pub row: i32,
+}
+
+impl GridCell {
+    /// Gets the cell the provided position is in, if it's somewhere obstacles can be placed on the first screen of a level
+    pub fn at(position: Vec2) -> Option<GridCell> {
+        let cell = GridCell {
+            column: (position.x / BOX_SIZE).round() as i32,
+            row: ((position.y - TOP_OF_FLOOR) / BOX_SIZE).floor() as i32,
+        };
+        cell.is_in_bounds().then_some(cell)
+    }
+
+    /// Whether this cell is somewhere obstacles can be placed on the first screen of a level
+    pub fn is_in_bounds(self) -> bool {
+        self.column.abs() <= MAX_COLUMN && (0..=MAX_ROW).contains(&self.row)
+    }
+
+    /// Gets the position of the center of this cell
+    pub fn center(self) -> Vec2 {
+        Vec2::new(
+            self.column as f32 * BOX_SIZE,
+            TOP_OF_FLOOR + (self.row as f32 * BOX_SIZE) + (BOX_SIZE / 2.0),
+        )
+    }
+}
+
+/// The level being made in the level editor, which sticks around while it's being test played.
+#[derive(Resource, Debug, Default)]
+pub struct EditorLevel(pub LevelDefinition);
+
+/// A problem with a level file that keeps it from being played
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    OutOfBounds(GridCell),
+    Overlapping(GridCell),
+}
+
+impl std::fmt::Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "could not read level: {e}"),
+            LevelError::Ron(e) => write!(f, "could not parse level: {e}"),
+            LevelError::OutOfBounds(cell) => write!(
+                f,
+                "obstacle at column {}, row {} is outside the level",
+                cell.column, cell.row
+            ),
+            LevelError::Overlapping(cell) => write!(
+                f,
+                "more than one obstacle at column {}, row {}",
+                cell.column, cell.row
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<std::io::Error> for LevelError {
+    fn from(value: std::io::Error) -> Self {
+        LevelError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for LevelError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        LevelError::Ron(value)
+    }
+}
+
+/// Every custom level that's been found, including ones that couldn't be read.
+#[derive(Resource, Debug, Default)]
+pub struct CustomLevels(pub Vec<CustomLevel>);
+
+impl CustomLevels {
+    /// Gets the levels that can be played, in the order they're played in
+    pub fn playable(&self) -> impl Iterator<Item = &LevelDefinition> {
+        self.0
+            .iter()
+            .filter_map(|level| level.definition.as_ref().ok())
+    }
+
+    /// Gets the level played as the provided level number, going around through the playable levels over and over.
+    /// Returns `None` if there aren't any playable levels.
+    pub fn for_level(&self, level: u32) -> Option<&LevelDefinition> {
+        let count = self.playable().count().max(1);
+        self.playable().nth(level as usize % count)
+    }
+
+    /// Reads the level file at the provided path, replacing any level that was already read from it
+    pub fn read(&mut self, path: &std::path::Path) {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = file_name
+            .strip_suffix(&format!(".{LEVEL_EXTENSION}"))
+            .unwrap_or(&file_name)
+            .to_string();
+        let definition = std::fs::read_to_string(path)
+            .map_err(LevelError::from)
+            .and_then(|contents| LevelDefinition::from_ron(&contents));
+        if let Err(e) = &definition {
+            warn!("could not load custom level {}: {e}", path.display());
+        }
+
+        self.0.retain(|level| level.name != name);
+        self.0.push(CustomLevel { name, definition });
+    }
+
+    /// Reads every level file in the custom levels folder, forgetting any levels that were read before
+    #[cfg(not(target_family = "wasm"))]
+    pub fn scan(&mut self) {
+        self.0.clear();
+        let dir = custom_levels_dir();
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            // there just aren't any custom levels if the folder doesn't exist
+            return;
+        };
+
+        let mut paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.to_string_lossy().ends_with(LEVEL_EXTENSION))
+            .collect::<Vec<_>>();
+        paths.sort();
+        for path in paths {
+            self.read(&path);
+        }
+    }
+}
+
+/// A level file, and the level in it if it could be read
+#[derive(Debug)]
+pub struct CustomLevel {
+    pub name: String,
+    pub definition: Result<LevelDefinition, LevelError>,
+}
+
+/// Gets the folder custom levels are read from and saved to
+#[cfg(not(target_family = "wasm"))]
+pub fn custom_levels_dir() -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_default()
+        .join("looprunner")
+        .join("levels")
+}
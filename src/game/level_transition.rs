@@ -0,0 +1,175 @@
+//! Sliding from one level over to the next when the player runs off the right side, instead of cutting straight to it.
+//! The player is still moved back to the left side and the next level is still spawned the moment they leave, keeping
+//! their speed, so distances and spawning line up the same as ever. What changes is what's shown: a copy of the
+//! level that was just cleared is left to the left of the new one, where it would be if the levels were side by side,
+//! and the camera starts out looking at it, then pans over to the new level.
+
+use bevy::prelude::*;
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    camera::{smoothstep, WorldCamera},
+    movement::{Paused, PlaybackSpeed},
+    scrolling::ScrollPosition,
+    settings::Settings,
+    spawn::{
+        level::{
+            Background, Floor, GameMode, IncomingObstacle, LevelCleared, LevelWidth, Obstacle,
+            LEVEL_WIDTH,
+        },
+        sequencer::ResetSequence,
+    },
+};
+
+/// How long the camera takes to pan over to the next level, in seconds
+const TRANSITION_DURATION: f32 = 0.6;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelTransition>();
+    app.observe(start_level_transition);
+    app.observe(cancel_level_transition);
+    app.add_systems(
+        Update,
+        // after the player is moved back to the left side, so the camera moves along with them on the same frame
+        slide_to_next_level
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), end_level_transition);
+}
+
+/// Where the camera is in going from one level to the next.
+#[derive(Resource, Debug, Default)]
+pub enum LevelTransition {
+    /// Showing the current level, like normal
+    #[default]
+    Idle,
+    /// Panning from the level that was just cleared over to the new one
+    Sliding(Timer),
+}
+
+/// A copy of part of the level that was just cleared, only there to be looked at while the camera pans away from it
+#[derive(Component, Debug)]
+struct OutgoingLevel;
+
+/// Leaves a copy of the level that was just cleared to the left of the new one, and starts panning over from it
+fn start_level_transition(
+    _trigger: Trigger<LevelCleared>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+    level_width: Res<LevelWidth>,
+    level_query: Query<
+        (&Sprite, &Handle<Image>, &GlobalTransform, &Visibility),
+        (
+            Or<(With<Obstacle>, With<Background>, With<Floor>)>,
+            // the next level is already there off to the side, so it's not part of what was just cleared
+            Without<IncomingObstacle>,
+        ),
+    >,
+    outgoing_query: Query<Entity, With<OutgoingLevel>>,
+    mut transition: ResMut<LevelTransition>,
+    mut commands: Commands,
+) {
+    // a copy from a level before this one might still be around, if the player got through a level very quickly
+    for entity in &outgoing_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // the camera's already following the player over to the next level in scrolling mode
+    if settings.reduced_motion || *game_mode == GameMode::Scrolling {
+        return;
+    }
+
+    for (sprite, image, transform, visibility) in &level_query {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        // the next level hasn't been spawned yet, so this is still the width of the one that was just cleared
+        let mut transform = transform.compute_transform();
+        transform.translation.x -= level_width.0;
+        commands.spawn((
+            Name::new("Outgoing level"),
+            OutgoingLevel,
+            StateScoped(Screen::Playing),
+            SpriteBundle {
+                sprite: sprite.clone(),
+                texture: image.clone(),
+                transform,
+                ..default()
+            },
+        ));
+    }
+
+    *transition =
+        LevelTransition::Sliding(Timer::from_seconds(TRANSITION_DURATION, TimerMode::Once));
+}
+
+/// Pans the camera from the level that was just cleared over to the new one, at the same pace the run is going.
+/// It ends up wherever the view has followed the player to by then.
+fn slide_to_next_level(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    paused: Res<Paused>,
+    scroll_position: Res<ScrollPosition>,
+    mut transition: ResMut<LevelTransition>,
+    mut camera_query: Query<&mut Transform, With<WorldCamera>>,
+    outgoing_query: Query<Entity, With<OutgoingLevel>>,
+    mut commands: Commands,
+) {
+    let LevelTransition::Sliding(timer) = &mut *transition else {
+        return;
+    };
+    if !paused.0 {
+        timer.tick(playback_speed.delta(&time));
+    }
+
+    let offset = -LEVEL_WIDTH * (1.0 - smoothstep(timer.fraction()));
+    for mut transform in &mut camera_query {
+        transform.translation.x = scroll_position.0 + offset;
+    }
+
+    if timer.finished() {
+        for entity in &outgoing_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        *transition = LevelTransition::Idle;
+    }
+}
+
+/// Cuts straight to the start of the run when it's started over partway through sliding to the next level
+fn cancel_level_transition(
+    _trigger: Trigger<ResetSequence>,
+    mut transition: ResMut<LevelTransition>,
+    mut camera_query: Query<&mut Transform, With<WorldCamera>>,
+    outgoing_query: Query<Entity, With<OutgoingLevel>>,
+    mut commands: Commands,
+) {
+    if matches!(*transition, LevelTransition::Idle) {
+        return;
+    }
+
+    for mut transform in &mut camera_query {
+        transform.translation.x = 0.0;
+    }
+    for entity in &outgoing_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    *transition = LevelTransition::Idle;
+}
+
+/// The copy of the old level goes away along with the rest of the run, so the camera just needs to be put back
+fn end_level_transition(
+    mut transition: ResMut<LevelTransition>,
+    mut camera_query: Query<&mut Transform, With<WorldCamera>>,
+) {
+    if matches!(*transition, LevelTransition::Idle) {
+        return;
+    }
+
+    for mut transform in &mut camera_query {
+        transform.translation.x = 0.0;
+    }
+    *transition = LevelTransition::Idle;
+}
@@ -0,0 +1,89 @@
+//! Statistics about everything the player has done, saved between sessions.
+//! Only runs the player actually plays count, so runs simulated in the background don't show up here.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{persistence, screen::Screen, AppSet};
+
+use super::{
+    movement::TotalDistance,
+    spawn::sequencer::{DeathEvent, NotePlaced, SequencerRow},
+};
+
+const LIFETIME_STATS_SAVE_KEY: &str = "lifetime_stats";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(
+        persistence::load::<LifetimeStats>(LIFETIME_STATS_SAVE_KEY).unwrap_or_default(),
+    );
+    app.observe(count_death);
+    app.observe(count_placed_note);
+    app.add_systems(Update, track_distance.in_set(AppSet::Update));
+    app.add_systems(OnExit(Screen::Playing), save_lifetime_stats);
+    app.add_systems(Last, save_lifetime_stats.run_if(on_event::<AppExit>()));
+}
+
+/// Stats for every run the player has ever played.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    /// How far the player has run, in pixels
+    distance: f32,
+    pub deaths: u32,
+    /// How many notes the player has placed on each row
+    notes_placed: BTreeMap<SequencerRow, u32>,
+}
+
+impl LifetimeStats {
+    /// Gets how far the player has run, in feet
+    pub fn distance_feet(&self) -> u32 {
+        TotalDistance(self.distance).feet()
+    }
+
+    /// Gets how many notes the player has placed on every row put together
+    pub fn notes_placed(&self) -> u32 {
+        self.notes_placed.values().sum()
+    }
+
+    /// Gets the row the player has placed the most notes on, if they've placed any
+    pub fn favorite_row(&self) -> Option<SequencerRow> {
+        self.notes_placed
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(row, _)| *row)
+    }
+}
+
+fn count_death(_trigger: Trigger<DeathEvent>, mut stats: ResMut<LifetimeStats>) {
+    stats.deaths += 1;
+    persistence::save(LIFETIME_STATS_SAVE_KEY, &*stats);
+}
+
+fn count_placed_note(trigger: Trigger<NotePlaced>, mut stats: ResMut<LifetimeStats>) {
+    *stats.notes_placed.entry(trigger.event().0).or_default() += 1;
+}
+
+/// Adds however much further the player has run since the last frame
+fn track_distance(
+    distance: Res<TotalDistance>,
+    mut last_distance: Local<f32>,
+    mut stats: ResMut<LifetimeStats>,
+) {
+    if !distance.is_changed() {
+        return;
+    }
+
+    // the distance goes back to 0 whenever a run is started over
+    if distance.0 < *last_distance {
+        *last_distance = 0.0;
+    }
+    stats.distance += distance.0 - *last_distance;
+    *last_distance = distance.0;
+}
+
+fn save_lifetime_stats(stats: Res<LifetimeStats>) {
+    persistence::save(LIFETIME_STATS_SAVE_KEY, &*stats);
+}
@@ -0,0 +1,102 @@
+//! Text shown to the player in the language they picked, looked up by key from the language files in `assets/locales`.
+//! Anything missing from a language file is shown in English instead.
+
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    assets::{HandleMap, RonAssetLoader},
+    settings::Settings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Locale>();
+    app.register_asset_loader(RonAssetLoader::<Locale>::new(&["locale.ron"]));
+    app.init_resource::<Localization>();
+    app.add_systems(
+        Update,
+        apply_language
+            .run_if(resource_changed::<Settings>.or_else(on_event::<AssetEvent<Locale>>())),
+    );
+}
+
+/// A language the game can be played in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// Gets the name of this language, in itself
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    /// Gets the path of this language's file, relative to the assets folder
+    pub(super) fn path(self) -> &'static str {
+        match self {
+            Language::English => "locales/en.locale.ron",
+            Language::Spanish => "locales/es.locale.ron",
+        }
+    }
+}
+
+/// The text for every key in one language.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct Locale(HashMap<String, String>);
+
+/// The text for the language the player picked.
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct Localization {
+    strings: HashMap<String, String>,
+    /// The English text, for any keys the picked language is missing
+    fallback: HashMap<String, String>,
+}
+
+impl Localization {
+    /// Gets the text for the provided key, or the key itself if no language has text for it
+    pub fn get(&self, key: &str) -> String {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Gets the text for the provided key, with each `{name}` in it replaced by the matching argument
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter().fold(self.get(key), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+    }
+}
+
+/// Rebuilds the text whenever the language is changed or a language file is loaded
+fn apply_language(
+    settings: Res<Settings>,
+    locale_handles: Res<HandleMap<Language>>,
+    locales: Res<Assets<Locale>>,
+    mut localization: ResMut<Localization>,
+) {
+    let strings_for = |language| {
+        locales
+            .get(&locale_handles.get(language))
+            .map(|locale| locale.0.clone())
+            .unwrap_or_default()
+    };
+
+    localization.set_if_neq(Localization {
+        strings: strings_for(settings.language),
+        fallback: strings_for(Language::English),
+    });
+}
@@ -0,0 +1,130 @@
+//! Celebrations for making it another 100 feet and for crossing a level's finish line, which also earn bonus score,
+//! and for beating bosses.
+
+use bevy::prelude::*;
+
+use crate::{screen::Screen, ui::banner::Banners, AppSet};
+
+use super::{
+    assets::{FontKey, HandleMap, SfxKey},
+    audio::sfx::PlaySfx,
+    boss::BossDefeated,
+    localization::Localization,
+    movement::{FinishLineCrossed, TotalDistance},
+    spawn::sequencer::ResetSequence,
+    stats::RunStats,
+};
+
+/// How far apart milestones are, in feet
+const MILESTONE_INTERVAL: u32 = 100;
+
+/// The bonus score for reaching each milestone
+const MILESTONE_BONUS: u32 = 25;
+
+/// The bonus score for crossing the finish line at the end of a level
+const FINISH_LINE_BONUS: u32 = 50;
+
+/// The notes of the synth's scale played together for the fanfare when a finish line is crossed
+const FANFARE_DEGREES: [usize; 3] = [7, 9, 11];
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(NextMilestone(MILESTONE_INTERVAL));
+    app.observe(celebrate_milestone);
+    app.observe(reset_milestones);
+    app.observe(celebrate_finish_line);
+    app.observe(celebrate_boss_defeat);
+    app.add_systems(
+        Update,
+        check_milestones
+            .run_if(resource_changed::<TotalDistance>)
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Event sent when the player makes it past a milestone, with how far it is in feet
+#[derive(Event, Debug)]
+pub struct MilestoneReached(pub u32);
+
+/// The next distance to celebrate, in feet
+#[derive(Resource, Debug)]
+struct NextMilestone(u32);
+
+fn check_milestones(
+    distance: Res<TotalDistance>,
+    mut next_milestone: ResMut<NextMilestone>,
+    mut commands: Commands,
+) {
+    while distance.feet() >= next_milestone.0 {
+        commands.trigger(MilestoneReached(next_milestone.0));
+        next_milestone.0 += MILESTONE_INTERVAL;
+    }
+}
+
+fn reset_milestones(_trigger: Trigger<ResetSequence>, mut next_milestone: ResMut<NextMilestone>) {
+    next_milestone.0 = MILESTONE_INTERVAL;
+}
+
+fn celebrate_milestone(
+    trigger: Trigger<MilestoneReached>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut stats: ResMut<RunStats>,
+    mut commands: Commands,
+) {
+    stats.bonus_score += MILESTONE_BONUS;
+    commands.trigger(PlaySfx::new(SfxKey::Riser));
+    commands
+        .banner(
+            localization.format(
+                "milestone.banner",
+                &[("feet", &trigger.event().0), ("bonus", &MILESTONE_BONUS)],
+            ),
+            &font_handles,
+        )
+        .insert(StateScoped(Screen::Playing));
+}
+
+fn celebrate_finish_line(
+    trigger: Trigger<FinishLineCrossed>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut stats: ResMut<RunStats>,
+    mut commands: Commands,
+) {
+    stats.bonus_score += FINISH_LINE_BONUS;
+    commands.trigger(PlaySfx::new(SfxKey::Riser));
+    for degree in FANFARE_DEGREES {
+        commands.trigger(PlaySfx::new(SfxKey::Synth(degree)));
+    }
+    commands
+        .banner(
+            localization.format(
+                "finish_line.banner",
+                &[
+                    ("level", &(trigger.event().0 + 1)),
+                    ("bonus", &FINISH_LINE_BONUS),
+                ],
+            ),
+            &font_handles,
+        )
+        .insert(StateScoped(Screen::Playing));
+}
+
+fn celebrate_boss_defeat(
+    trigger: Trigger<BossDefeated>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    let text = match trigger.event().reward {
+        Some(bank) => localization.format(
+            "boss.defeated_unlock",
+            &[("bank", &bank.name(&localization))],
+        ),
+        None => localization.get("boss.defeated"),
+    };
+    commands.trigger(PlaySfx::new(SfxKey::Riser));
+    commands
+        .banner(text, &font_handles)
+        .insert(StateScoped(Screen::Playing));
+}
@@ -4,18 +4,132 @@ use bevy::prelude::*;
 
 mod animation;
 pub mod assets;
+pub mod assists;
 pub mod audio;
-mod movement;
+mod autosave;
+pub mod beat_markers;
+pub mod boss;
+pub mod camera;
+#[cfg(all(feature = "capture", not(target_family = "wasm")))]
+mod capture;
+pub mod clock;
+mod collision;
+pub mod coop;
+pub mod credits;
+pub mod day_cycle;
+mod death;
+pub mod difficulty;
+mod hud;
+pub mod input;
+pub mod intensity;
+pub mod judgement;
+#[cfg(feature = "leaderboard")]
+mod leaderboard;
+pub mod level_definition;
+pub mod level_transition;
+pub mod lifetime_stats;
+pub mod localization;
+mod milestones;
+pub mod movement;
+pub mod mutators;
+pub mod near_miss;
+pub mod note_trail;
+pub mod onboarding;
+pub mod pattern_library;
+pub mod photo_mode;
+pub mod progress;
+pub mod pulse;
+pub mod scenery;
+pub mod scrolling;
+pub mod settings;
+pub mod skins;
+pub mod song;
 pub mod spawn;
+pub mod splits;
+pub mod stats;
+pub mod suggest;
+pub mod telegraph;
+pub mod telemetry;
+pub mod theme;
+pub mod tutorial;
+pub mod validation;
+pub mod versus;
 
 const SHOW_COLLIDERS: bool = false;
 
-pub(super) fn plugin(app: &mut App) {
+/// The rules of the game: the sequence, movement, and levels.
+/// None of this needs a window, rendering, or audio.
+pub(super) fn logic_plugin(app: &mut App) {
+    app.add_plugins((
+        (assets::plugin, assists::plugin),
+        boss::plugin,
+        collision::plugin,
+        coop::plugin,
+        (difficulty::plugin, intensity::plugin),
+        level_definition::plugin,
+        (movement::plugin, mutators::plugin, near_miss::plugin),
+        (progress::plugin, scrolling::plugin),
+        settings::plugin,
+        song::plugin,
+        spawn::plugin,
+        (splits::plugin, stats::plugin),
+        tutorial::plugin,
+        validation::plugin,
+        versus::plugin,
+    ));
+}
+
+/// Everything that shows the game to the player and lets them play it, on top of [`logic_plugin`]
+pub(super) fn presentation_plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
         audio::plugin,
-        assets::plugin,
-        movement::plugin,
-        spawn::plugin,
+        autosave::plugin,
+        (
+            beat_markers::plugin,
+            camera::plugin,
+            coop::presentation_plugin,
+            input::plugin,
+            intensity::presentation_plugin,
+            level_transition::plugin,
+            photo_mode::plugin,
+        ),
+        (
+            credits::plugin,
+            day_cycle::plugin,
+            judgement::plugin,
+            localization::plugin,
+            theme::plugin,
+        ),
+        // after the credits, sky colors, judgements, languages, and themes, since they're some of the assets loaded here
+        assets::presentation_plugin,
+        death::plugin,
+        hud::plugin,
+        lifetime_stats::plugin,
+        (
+            milestones::plugin,
+            mutators::presentation_plugin,
+            near_miss::presentation_plugin,
+            note_trail::plugin,
+            onboarding::plugin,
+            pattern_library::plugin,
+            suggest::plugin,
+        ),
+        (
+            pulse::plugin,
+            scenery::plugin,
+            telegraph::plugin,
+            telemetry::plugin,
+        ),
+        (settings::presentation_plugin, skins::plugin),
+        song::presentation_plugin,
+        spawn::presentation_plugin,
+        tutorial::presentation_plugin,
     ));
+
+    #[cfg(feature = "leaderboard")]
+    app.add_plugins(leaderboard::plugin);
+
+    #[cfg(all(feature = "capture", not(target_family = "wasm")))]
+    app.add_plugins(capture::plugin);
 }
@@ -3,18 +3,36 @@
 //! If you want to move the player in a smoother way,
 //! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/latest/examples/movement/physics_in_fixed_timestep.rs).
 
+use std::time::Duration;
+
 use bevy::prelude::*;
 
 use crate::AppSet;
 
-use super::spawn::{
-    level::{CurrentLevel, RectCollider, SpawnObstacles, Spikes, LEVEL_WIDTH},
-    player::{Player, PLAYER_IMAGE_SIZE},
-    sequencer::{Dead, DeathEvent, PauseSequence, PlaySequence},
+use super::{
+    assets::SfxKey,
+    assists::RunnerHit,
+    audio::sfx::PlaySfx,
+    boss::Boss,
+    collision::{rebuild_collider_grid, ColliderGrid},
+    scrolling::ScrollPosition,
+    settings::Settings,
+    spawn::{
+        level::{
+            BouncePad, CurrentLevel, FinishLine, GameMode, IncomingLevel, IncomingObstacle,
+            LevelCleared, LevelWidth, Obstacle, Portal, PreSpawnObstacles, RectCollider,
+            SpawnObstacles, SpawnPortalFlash, Spikes, TriggerVolume, WindZone, CAMPAIGN_LENGTH,
+            FINISH_LINE_WIDTH, LEVEL_WIDTH,
+        },
+        player::{Player, PLAYER_IMAGE_SIZE, PLAYER_START},
+        sequencer::{Dead, PauseSequence, SequenceUnpaused, VictoryEvent},
+    },
+    stats::RunStats,
+    versus::Opponent,
 };
 
 /// Gravity in pixels/sec^2
-const GRAVITY: f32 = 2300.0;
+pub const GRAVITY: f32 = 2300.0;
 
 /// Jump velocity in pixels/sec
 const JUMP_VELOCITY: f32 = 800.0;
@@ -31,17 +49,36 @@ const DIVE_VELOCITY: f32 = -800.0;
 /// The minimum final velocity after a dive in pixels/sec
 const DIVE_LIMIT: f32 = -800.0;
 
+/// How fast the player has to be falling when they land for it to make a sound, in pixels/sec
+const LANDING_SOUND_MIN_SPEED: f32 = 300.0;
+
+/// How close the runner out in front has to get to the end of the level for the next level to be spawned, in pixels
+const PRE_SPAWN_DISTANCE: f32 = LEVEL_WIDTH / 4.0;
+
 pub(super) fn plugin(app: &mut App) {
     app.observe(do_player_action);
     app.observe(pause);
     app.observe(resume);
+    app.observe(play_landing_sound);
 
     app.insert_resource(TotalDistance(0.0));
     app.insert_resource(Paused(true));
+    app.insert_resource(PlaybackSpeed::NORMAL);
 
     app.add_systems(
         Update,
-        (apply_movement, check_spike_collisions, wrap_within_level)
+        (
+            rebuild_collider_grid,
+            apply_wind,
+            move_horizontally,
+            move_vertically,
+            update_ground_state,
+            enter_portals,
+            check_spike_collisions,
+            detect_cleared_obstacles,
+            pre_spawn_next_level,
+            wrap_within_level,
+        )
             .chain()
             .in_set(AppSet::Update),
     );
@@ -50,16 +87,65 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Resource, Debug)]
 pub struct TotalDistance(pub f32);
 
+/// How fast runs play back, relative to normal.
+/// This slows down the player and the sequence clock without slowing down the rest of the game along with them.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackSpeed {
+    /// How fast the player has chosen for runs to play back, relative to normal
+    pub chosen: f32,
+    /// How much a brief flourish, like the slow motion after a near miss, is slowing runs down on top of that
+    pub flourish: f32,
+}
+
+impl PlaybackSpeed {
+    /// How fast runs play back normally
+    pub const NORMAL: PlaybackSpeed = PlaybackSpeed {
+        chosen: 1.0,
+        flourish: 1.0,
+    };
+
+    /// How fast runs play back in slow motion
+    pub const SLOW_MOTION: f32 = 0.5;
+
+    /// Gets how fast runs are playing back right now, relative to normal
+    pub fn speed(&self) -> f32 {
+        self.chosen * self.flourish
+    }
+
+    /// Whether the player has chosen for runs to play back slower than normal
+    pub fn is_slow_motion(&self) -> bool {
+        self.chosen < 1.0
+    }
+
+    /// Gets how much time has passed for the run since the last frame
+    pub fn delta(&self, time: &Time) -> Duration {
+        time.delta().mul_f32(self.speed())
+    }
+
+    /// Gets how much time has passed for the run since the last frame, in seconds
+    pub fn delta_seconds(&self, time: &Time) -> f32 {
+        time.delta_seconds() * self.speed()
+    }
+}
+
+impl TotalDistance {
+    /// Gets the distance in feet
+    pub fn feet(&self) -> u32 {
+        ((self.0 / LEVEL_WIDTH) * 50.0).round() as u32
+    }
+}
+
 impl std::fmt::Display for TotalDistance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        (((self.0 / LEVEL_WIDTH) * 50.0).round() as u32).fmt(f)
+        self.feet().fmt(f)
     }
 }
 
 #[derive(Resource, Debug)]
 pub struct Paused(pub bool);
 
-/// Event that makes the player do something
+/// Event that makes a runner do something.
+/// This has to be targeted at the runner that does it, since each one can be controlled by a different sequence.
 #[derive(Event)]
 pub enum PlayerAction {
     SetSpeed(f32),
@@ -70,27 +156,37 @@ pub enum PlayerAction {
 
 fn do_player_action(
     trigger: Trigger<PlayerAction>,
-    mut movement_query: Query<&mut MovementController>,
+    mut movement_query: Query<(&mut MovementController, Has<Opponent>)>,
+    mut stats: ResMut<RunStats>,
 ) {
-    for mut controller in &mut movement_query {
-        match trigger.event() {
-            PlayerAction::SetSpeed(x) => controller.speed = *x,
-            PlayerAction::Jump => {
-                if !controller.jumping {
-                    controller.jumping = true;
-                    controller.vertical_velocity = JUMP_VELOCITY;
+    // the runner may have been despawned since the action was sent, if the run was started over
+    let Ok((mut controller, is_opponent)) = movement_query.get_mut(trigger.entity()) else {
+        return;
+    };
+
+    match trigger.event() {
+        PlayerAction::SetSpeed(x) => controller.speed = *x,
+        PlayerAction::Jump => {
+            if !controller.jumping {
+                controller.jumping = true;
+                controller.vertical_velocity = JUMP_VELOCITY;
+                if !is_opponent {
+                    stats.jumps += 1;
                 }
             }
-            PlayerAction::Float => {
-                if controller.jumping && controller.vertical_velocity < FLOAT_LIMIT {
-                    controller.vertical_velocity =
-                        (controller.vertical_velocity + FLOAT_VELOCITY).min(FLOAT_LIMIT);
-                }
+        }
+        PlayerAction::Float => {
+            if controller.jumping && controller.vertical_velocity < FLOAT_LIMIT {
+                controller.vertical_velocity =
+                    (controller.vertical_velocity + FLOAT_VELOCITY).min(FLOAT_LIMIT);
             }
-            PlayerAction::Dive => {
-                if controller.jumping && controller.vertical_velocity > DIVE_LIMIT {
-                    controller.vertical_velocity =
-                        (controller.vertical_velocity + DIVE_VELOCITY).max(DIVE_LIMIT);
+        }
+        PlayerAction::Dive => {
+            if controller.jumping && controller.vertical_velocity > DIVE_LIMIT {
+                controller.vertical_velocity =
+                    (controller.vertical_velocity + DIVE_VELOCITY).max(DIVE_LIMIT);
+                if !is_opponent {
+                    stats.dives += 1;
                 }
             }
         }
@@ -101,11 +197,7 @@ fn pause(_trigger: Trigger<PauseSequence>, mut paused: ResMut<Paused>) {
     paused.0 = true;
 }
 
-fn resume(_trigger: Trigger<PlaySequence>, mut paused: ResMut<Paused>, dead: Res<Dead>) {
-    if dead.0 {
-        return;
-    }
-
+fn resume(_trigger: Trigger<SequenceUnpaused>, mut paused: ResMut<Paused>) {
     paused.0 = false;
 }
 
@@ -115,6 +207,14 @@ pub struct MovementController {
     pub speed: f32,
     pub jumping: bool,
     pub vertical_velocity: f32,
+    /// How far this runner has gone this run, in pixels
+    pub distance: f32,
+}
+
+impl Default for MovementController {
+    fn default() -> Self {
+        MovementController::new()
+    }
 }
 
 impl MovementController {
@@ -123,182 +223,432 @@ impl MovementController {
             speed: 0.0,
             jumping: false,
             vertical_velocity: 0.0,
+            distance: 0.0,
+        }
+    }
+}
+
+/// What the player ran into when moving vertically, which decides what happens to their vertical velocity.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub enum VerticalContact {
+    /// There's nothing above or below the player to run into
+    #[default]
+    Clear,
+    /// The player moved through the air without hitting anything
+    Airborne,
+    /// The player was already resting on top of something, so they didn't move
+    Resting,
+    /// The player came down on top of something
+    Landed,
+    /// The player came down on a bounce pad, which launches them at the provided velocity
+    Bounced(f32),
+    /// The player hit their head on something
+    HitCeiling,
+}
+
+/// Gets the bounds of the player's collider
+fn player_rect(player: &Player, transform: &Transform) -> Rect {
+    Rect::from_center_size(
+        transform.translation.truncate() + player.collider_offset,
+        player.collider,
+    )
+}
+
+/// Gets the bounds of an obstacle's collider
+fn collider_rect(collider: &RectCollider, transform: &Transform) -> Rect {
+    Rect::from_center_size(
+        transform.translation.truncate() + collider.offset,
+        collider.bounds,
+    )
+}
+
+/// Blows the player around while they're in the air in any wind
+fn apply_wind(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    mut movement_query: Query<(&Player, &mut MovementController, &Transform)>,
+    wind_query: Query<(&Transform, &TriggerVolume, &WindZone), Without<Player>>,
+    paused: Res<Paused>,
+) {
+    if paused.0 {
+        return;
+    }
+
+    for (player, mut controller, player_transform) in &mut movement_query {
+        if !controller.jumping {
+            continue;
+        }
+
+        let player_center = player_transform.translation.truncate() + player.collider_offset;
+        for (transform, volume, wind) in &wind_query {
+            if overlaps(
+                player_center,
+                player.collider,
+                transform.translation.truncate(),
+                volume.bounds,
+            ) {
+                controller.vertical_velocity += wind.force.y * playback_speed.delta_seconds(&time);
+                controller.speed = (controller.speed
+                    + wind.force.x * playback_speed.delta_seconds(&time))
+                .max(0.0);
+            }
         }
     }
 }
 
-fn apply_movement(
+/// Moves the player rightwards until they run into a wall, and keeps track of how far they've gone
+fn move_horizontally(
     time: Res<Time>,
-    mut movement_query: Query<(&Player, &mut MovementController, &mut Transform)>,
+    playback_speed: Res<PlaybackSpeed>,
+    mut movement_query: Query<(
+        &Player,
+        &mut MovementController,
+        &mut Transform,
+        Has<Opponent>,
+    )>,
     collider_query: Query<(&Transform, &RectCollider), Without<Player>>,
+    grid: Res<ColliderGrid>,
     paused: Res<Paused>,
+    game_mode: Res<GameMode>,
     mut total_distance: ResMut<TotalDistance>,
 ) {
     if paused.0 {
         return;
     }
 
-    for (player, mut controller, mut player_transform) in &mut movement_query {
+    for (player, mut controller, mut player_transform, _) in &mut movement_query {
         // why import a physics library when I can just implement a bad one myself
-        let player_left_edge =
-            player_transform.translation.x + player.collider_offset.x - (player.collider.x / 2.0);
-        let player_right_edge =
-            player_transform.translation.x + player.collider_offset.x + (player.collider.x / 2.0);
-        let player_top =
-            player_transform.translation.y + player.collider_offset.y + (player.collider.y / 2.0);
-        let player_bottom =
-            player_transform.translation.y + player.collider_offset.y - (player.collider.y / 2.0);
+        let player_bounds = player_rect(player, &player_transform);
+        let proposed_x = player_transform.translation.x
+            + (controller.speed * playback_speed.delta_seconds(&time));
 
-        // find closest thing to run into when moving to the right
-        let mut left_of_closest_wall = None;
-        for (transform, collider) in &collider_query {
-            let obstacle_left_edge =
-                transform.translation.x + collider.offset.x - (collider.bounds.x / 2.0);
-            let obstacle_top =
-                transform.translation.y + collider.offset.y + (collider.bounds.y / 2.0);
-            let obstacle_bottom =
-                transform.translation.y + collider.offset.y - (collider.bounds.y / 2.0);
-
-            if !(player_bottom > obstacle_top || player_top < obstacle_bottom)
-                && player_right_edge <= obstacle_left_edge
-            {
+        // find closest thing to run into when moving to the right, out of the things close enough to reach this frame
+        let reach = player_bounds.max.x + (proposed_x - player_transform.translation.x);
+        let left_of_closest_wall = collider_query
+            .iter_many(grid.nearby(player_bounds.min.x, reach))
+            .map(|(transform, collider)| collider_rect(collider, transform))
+            .filter(|obstacle| {
                 // player is to the left of obstacle and at the same height
-                let distance_from_left_side_of_obstacle = obstacle_left_edge - player_right_edge;
-                if let Some(other_left) = left_of_closest_wall {
-                    let other_distance_from_left = other_left - player_right_edge;
-                    if distance_from_left_side_of_obstacle < other_distance_from_left {
-                        left_of_closest_wall = Some(obstacle_left_edge);
-                    }
-                } else {
-                    left_of_closest_wall = Some(obstacle_left_edge);
-                }
-            }
-        }
+                !(player_bounds.min.y > obstacle.max.y || player_bounds.max.y < obstacle.min.y)
+                    && player_bounds.max.x <= obstacle.min.x
+            })
+            .map(|obstacle| obstacle.min.x)
+            .min_by(f32::total_cmp);
 
         // move rightwards
         let original_x = player_transform.translation.x;
         if let Some(left_of_obstacle) = left_of_closest_wall {
-            let distance_from_left_of_obstacle = left_of_obstacle - player_right_edge;
-            if distance_from_left_of_obstacle > f32::EPSILON {
+            if left_of_obstacle - player_bounds.max.x > f32::EPSILON {
                 // player can move
-                let proposed_x =
-                    player_transform.translation.x + (controller.speed * time.delta_seconds());
                 let max_x = left_of_obstacle - player.collider_offset.x - (player.collider.x / 2.0);
                 player_transform.translation.x = proposed_x.min(max_x);
             }
         } else {
             // no walls to worry about running into
-            player_transform.translation.x += controller.speed * time.delta_seconds();
+            player_transform.translation.x = proposed_x;
         }
 
-        total_distance.0 += player_transform.translation.x - original_x;
+        controller.distance += player_transform.translation.x - original_x;
+    }
+
+    // with more than one runner, the run has gone as far as whoever's out in front, not counting the opponent in versus
+    let runners = movement_query
+        .iter()
+        .filter(|(.., is_opponent)| !is_opponent);
+    let furthest = if *game_mode == GameMode::Scrolling {
+        // nothing wraps around in scrolling mode, so how far the run has gone is just how far right it's gotten
+        runners
+            .map(|(_, _, transform, _)| transform.translation.x - PLAYER_START.x)
+            .fold(0.0, f32::max)
+    } else {
+        runners
+            .map(|(_, controller, ..)| controller.distance)
+            .fold(0.0, f32::max)
+    };
+    if furthest != total_distance.0 {
+        total_distance.0 = furthest;
+    }
+}
+
+/// Moves the player up or down until they run into a floor or ceiling, and records what they ran into
+fn move_vertically(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    mut movement_query: Query<(
+        &Player,
+        &MovementController,
+        &mut Transform,
+        &mut VerticalContact,
+    )>,
+    collider_query: Query<(&Transform, &RectCollider, Option<&BouncePad>), Without<Player>>,
+    grid: Res<ColliderGrid>,
+    paused: Res<Paused>,
+) {
+    if paused.0 {
+        return;
+    }
+
+    for (player, controller, mut player_transform, mut contact) in &mut movement_query {
+        let player_bounds = player_rect(player, &player_transform);
+        let falling = controller.vertical_velocity <= 0.0;
+        let proposed_y = player_transform.translation.y
+            + (controller.vertical_velocity * playback_speed.delta_seconds(&time));
 
         // find closest thing to run into when falling or jumping
-        let mut closest_floor_or_ceiling = None;
-        for (transform, collider) in &collider_query {
-            let obstacle_left_edge =
-                transform.translation.x + collider.offset.x - (collider.bounds.x / 2.0);
-            let obstacle_right_edge =
-                transform.translation.x + collider.offset.x + (collider.bounds.x / 2.0);
-            let obstacle_top =
-                transform.translation.y + collider.offset.y + (collider.bounds.y / 2.0);
-            let obstacle_bottom =
-                transform.translation.y + collider.offset.y - (collider.bounds.y / 2.0);
-
-            if controller.vertical_velocity <= 0.0 {
-                // falling
-                if !(player_left_edge > obstacle_right_edge
-                    || player_right_edge < obstacle_left_edge)
-                    && obstacle_top <= player_bottom
-                {
+        let closest = collider_query
+            .iter_many(grid.nearby(player_bounds.min.x, player_bounds.max.x))
+            .map(|(transform, collider, bounce_pad)| {
+                (collider_rect(collider, transform), bounce_pad)
+            })
+            .filter(|(obstacle, _)| {
+                !(player_bounds.min.x > obstacle.max.x || player_bounds.max.x < obstacle.min.x)
+            })
+            .filter_map(|(obstacle, bounce_pad)| {
+                if falling {
                     // player is above obstacle
-                    let distance_from_top_of_obstacle = player_bottom - obstacle_top;
-                    if let Some(other_top) = closest_floor_or_ceiling {
-                        let other_distance_from_top = player_bottom - other_top;
-                        if distance_from_top_of_obstacle < other_distance_from_top {
-                            closest_floor_or_ceiling = Some(obstacle_top);
-                        }
-                    } else {
-                        closest_floor_or_ceiling = Some(obstacle_top);
-                    }
-                }
-            } else {
-                // jumping
-                if !(player_left_edge > obstacle_right_edge
-                    || player_right_edge < obstacle_left_edge)
-                    && obstacle_bottom >= player_top
-                {
+                    (obstacle.max.y <= player_bounds.min.y).then_some((
+                        player_bounds.min.y - obstacle.max.y,
+                        obstacle.max.y,
+                        bounce_pad,
+                    ))
+                } else {
                     // player is below obstacle
-                    let distance_from_bottom_of_obstacle = obstacle_bottom - player_top;
-                    if let Some(other_bottom) = closest_floor_or_ceiling {
-                        let other_distance_from_bottom = other_bottom - player_top;
-                        if distance_from_bottom_of_obstacle < other_distance_from_bottom {
-                            closest_floor_or_ceiling = Some(obstacle_bottom);
-                        }
-                    } else {
-                        closest_floor_or_ceiling = Some(obstacle_bottom);
-                    }
+                    (obstacle.min.y >= player_bounds.max.y).then_some((
+                        obstacle.min.y - player_bounds.max.y,
+                        obstacle.min.y,
+                        None,
+                    ))
                 }
-            }
+            })
+            .min_by(|(a, _, _), (b, _, _)| a.total_cmp(b));
+
+        let Some((distance, closest_floor_or_ceiling, bounce_pad)) = closest else {
+            // nothing to run into
+            player_transform.translation.y = proposed_y;
+            *contact = VerticalContact::Clear;
+            continue;
+        };
+
+        if distance <= f32::EPSILON {
+            *contact = if falling {
+                VerticalContact::Resting
+            } else {
+                // player is smackin their head on the obstacle
+                VerticalContact::Airborne
+            };
+            continue;
         }
 
-        // move downwards or upwards
-        if let Some(closest_floor_or_ceiling) = closest_floor_or_ceiling {
-            if controller.vertical_velocity <= 0.0 {
-                // falling
-                let distance_from_top_of_obstacle = player_bottom - closest_floor_or_ceiling;
-                if distance_from_top_of_obstacle > f32::EPSILON {
-                    // player is in the air
-                    let proposed_y = player_transform.translation.y
-                        + (controller.vertical_velocity * time.delta_seconds());
-                    let min_y = closest_floor_or_ceiling - player.collider_offset.y
-                        + (player.collider.y / 2.0);
-                    player_transform.translation.y = proposed_y.max(min_y);
-                    if (player_transform.translation.y - min_y).abs() > f32::EPSILON {
-                        // player did not hit the obstacle
-                        controller.vertical_velocity -= GRAVITY * time.delta_seconds();
-                        controller.jumping = true;
-                    } else {
-                        // player hit the obstacle
-                        controller.vertical_velocity = 0.0;
-                        controller.jumping = false;
-                    }
-                }
+        if falling {
+            let min_y =
+                closest_floor_or_ceiling - player.collider_offset.y + (player.collider.y / 2.0);
+            player_transform.translation.y = proposed_y.max(min_y);
+            *contact = if (player_transform.translation.y - min_y).abs() > f32::EPSILON {
+                VerticalContact::Airborne
+            } else if let Some(bounce_pad) = bounce_pad {
+                VerticalContact::Bounced(bounce_pad.launch_velocity)
             } else {
-                // jumping
-                let distance_from_bottom_of_obstacle = closest_floor_or_ceiling - player_top;
-                if distance_from_bottom_of_obstacle > f32::EPSILON {
-                    // player has headroom
-                    let proposed_y = player_transform.translation.y
-                        + (controller.vertical_velocity * time.delta_seconds());
-                    let max_y = closest_floor_or_ceiling
-                        - player.collider_offset.y
-                        - (player.collider.y / 2.0);
-                    player_transform.translation.y = proposed_y.min(max_y);
-                    if (max_y - player_transform.translation.y).abs() > f32::EPSILON {
-                        // player did not hit the obstacle
-                        controller.vertical_velocity -= GRAVITY * time.delta_seconds();
-                    } else {
-                        // player hit the obstacle
-                        controller.vertical_velocity = 0.0;
-                    }
-                } else {
-                    // player is smackin their head on the obstacle
-                    controller.vertical_velocity -= GRAVITY * time.delta_seconds();
-                }
+                VerticalContact::Landed
+            };
+        } else {
+            let max_y =
+                closest_floor_or_ceiling - player.collider_offset.y - (player.collider.y / 2.0);
+            player_transform.translation.y = proposed_y.min(max_y);
+            *contact = if (max_y - player_transform.translation.y).abs() > f32::EPSILON {
+                VerticalContact::Airborne
+            } else {
+                VerticalContact::HitCeiling
+            };
+        }
+    }
+}
+
+/// Whether the player is standing on something or in the air.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum GroundState {
+    #[default]
+    Grounded,
+    Airborne,
+}
+
+/// Event sent when a player comes down on top of something
+#[derive(Event, Debug)]
+pub struct LandedEvent {
+    pub player: Entity,
+    /// How fast the player was falling when they landed, in pixels/sec
+    pub speed: f32,
+}
+
+/// Event sent when a player leaves the ground, whether by jumping, bouncing, or running off an edge
+#[derive(Event, Debug)]
+pub struct LeftGroundEvent {
+    pub player: Entity,
+}
+
+/// Applies gravity, landings, and bounces based on what the player ran into, and sends events when they land or leave the ground
+fn update_ground_state(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    mut movement_query: Query<(
+        Entity,
+        &mut MovementController,
+        &mut GroundState,
+        &Transform,
+        &VerticalContact,
+    )>,
+    paused: Res<Paused>,
+    scroll_position: Res<ScrollPosition>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
+    if paused.0 {
+        return;
+    }
+
+    let gravity = GRAVITY * settings.assists.gravity_scale();
+    for (player, mut controller, mut ground_state, transform, contact) in &mut movement_query {
+        let falling_speed = -controller.vertical_velocity;
+        match *contact {
+            VerticalContact::Clear => {
+                controller.vertical_velocity -= gravity * playback_speed.delta_seconds(&time);
+            }
+            VerticalContact::Airborne => {
+                controller.vertical_velocity -= gravity * playback_speed.delta_seconds(&time);
+                controller.jumping = true;
+            }
+            VerticalContact::Resting => (),
+            VerticalContact::Landed => {
+                controller.vertical_velocity = 0.0;
+                controller.jumping = false;
+            }
+            VerticalContact::Bounced(launch_velocity) => {
+                controller.vertical_velocity = launch_velocity;
+                controller.jumping = true;
+                commands.trigger(
+                    PlaySfx::new(SfxKey::Bounce)
+                        .with_pan(scroll_position.pan_for(transform.translation.x)),
+                );
+            }
+            VerticalContact::HitCeiling => {
+                controller.vertical_velocity = 0.0;
                 controller.jumping = true;
             }
+        }
+
+        let new_ground_state = if controller.jumping {
+            GroundState::Airborne
         } else {
-            // nothing to run into
-            player_transform.translation.y += controller.vertical_velocity * time.delta_seconds();
-            controller.vertical_velocity -= GRAVITY * time.delta_seconds();
+            GroundState::Grounded
+        };
+        if ground_state.set_if_neq(new_ground_state) {
+            match new_ground_state {
+                GroundState::Grounded => commands.trigger(LandedEvent {
+                    player,
+                    speed: falling_speed,
+                }),
+                GroundState::Airborne => commands.trigger(LeftGroundEvent { player }),
+            }
+        }
+    }
+}
+
+/// Plays a thud when the player lands hard enough
+fn play_landing_sound(
+    trigger: Trigger<LandedEvent>,
+    player_query: Query<&Transform, With<Player>>,
+    scroll_position: Res<ScrollPosition>,
+    mut commands: Commands,
+) {
+    let event = trigger.event();
+    if event.speed < LANDING_SOUND_MIN_SPEED {
+        return;
+    }
+    let Ok(transform) = player_query.get(event.player) else {
+        return;
+    };
+
+    commands.trigger(
+        PlaySfx::new(SfxKey::Land).with_pan(scroll_position.pan_for(transform.translation.x)),
+    );
+}
+
+/// Marks the portal a player just came out of, so they don't immediately get sent back through it.
+#[derive(Component, Debug)]
+pub struct ExitedPortal(Entity);
+
+fn enter_portals(
+    mut player_query: Query<(Entity, &Player, &mut Transform, Option<&ExitedPortal>)>,
+    portal_query: Query<(Entity, &Transform, &TriggerVolume, &Portal), Without<Player>>,
+    paused: Res<Paused>,
+    scroll_position: Res<ScrollPosition>,
+    mut commands: Commands,
+) {
+    if paused.0 {
+        return;
+    }
+
+    for (player_entity, player, mut player_transform, exited_portal) in &mut player_query {
+        let player_center = player_transform.translation.truncate() + player.collider_offset;
+        let touching = |portal_entity: Entity| {
+            portal_query
+                .get(portal_entity)
+                .is_ok_and(|(_, transform, volume, _)| {
+                    overlaps(
+                        player_center,
+                        player.collider,
+                        transform.translation.truncate(),
+                        volume.bounds,
+                    )
+                })
+        };
+
+        if let Some(exited_portal) = exited_portal {
+            if touching(exited_portal.0) {
+                continue;
+            }
+            commands.entity(player_entity).remove::<ExitedPortal>();
         }
+
+        let Some((_, portal_transform, _, portal)) = portal_query
+            .iter()
+            .find(|(entity, _, _, _)| touching(*entity))
+        else {
+            continue;
+        };
+        let Ok((_, twin_transform, _, _)) = portal_query.get(portal.twin) else {
+            continue;
+        };
+
+        // keep the player at the same spot relative to the portal, so they come out the way they went in
+        let offset =
+            player_transform.translation.truncate() - portal_transform.translation.truncate();
+        let destination = twin_transform.translation.truncate() + offset;
+        player_transform.translation.x = destination.x;
+        player_transform.translation.y = destination.y;
+
+        commands
+            .entity(player_entity)
+            .insert(ExitedPortal(portal.twin));
+        commands
+            .trigger(PlaySfx::new(SfxKey::Portal).with_pan(scroll_position.pan_for(destination.x)));
+        commands.trigger(SpawnPortalFlash(portal_transform.translation.truncate()));
+        commands.trigger(SpawnPortalFlash(twin_transform.translation.truncate()));
     }
 }
 
+/// Determines whether two rectangles overlap
+pub fn overlaps(a_center: Vec2, a_size: Vec2, b_center: Vec2, b_size: Vec2) -> bool {
+    let distance = (a_center - b_center).abs();
+    distance.x < (a_size.x + b_size.x) / 2.0 && distance.y < (a_size.y + b_size.y) / 2.0
+}
+
+/// Kills the first runner touching any spikes. One runner dying ends the run for everyone.
+/// The opponent in versus is a ghost, so spikes don't hurt it.
 fn check_spike_collisions(
-    player_query: Query<(&Transform, &Player), Without<Spikes>>,
+    player_query: Query<(Entity, &Transform, &Player), (Without<Spikes>, Without<Opponent>)>,
     spikes_query: Query<(&Transform, &RectCollider), With<Spikes>>,
+    grid: Res<ColliderGrid>,
     paused: Res<Paused>,
     dead: Res<Dead>,
     mut commands: Commands,
@@ -307,7 +657,7 @@ fn check_spike_collisions(
         return;
     }
 
-    for (player_transform, player) in &player_query {
+    for (runner, player_transform, player) in &player_query {
         let player_left_edge =
             player_transform.translation.x + player.collider_offset.x - (player.collider.x / 2.0);
         let player_right_edge =
@@ -317,7 +667,9 @@ fn check_spike_collisions(
         let player_bottom =
             player_transform.translation.y + player.collider_offset.y - (player.collider.y / 2.0);
 
-        for (spikes_transform, spikes_collider) in &spikes_query {
+        // spikes count when they're just touching the player, so look a little past the player's edges for them
+        let nearby = grid.nearby(player_left_edge - 1.0, player_right_edge + 1.0);
+        for (spikes_transform, spikes_collider) in spikes_query.iter_many(nearby) {
             let spikes_left_edge = spikes_transform.translation.x + spikes_collider.offset.x
                 - (spikes_collider.bounds.x / 2.0);
             let spikes_right_edge = spikes_transform.translation.x
@@ -329,39 +681,416 @@ fn check_spike_collisions(
             let spikes_bottom = spikes_transform.translation.y + spikes_collider.offset.y
                 - (spikes_collider.bounds.y / 2.0);
 
-            if ((spikes_left_edge - player_right_edge).abs() <= f32::EPSILON)
-                && !(player_bottom > spikes_top || player_top < spikes_bottom)
-            {
-                // player is touching left side of spikes
-                commands.trigger(DeathEvent);
+            // player is touching left side of spikes
+            let touching_side = ((spikes_left_edge - player_right_edge).abs() <= f32::EPSILON)
+                && !(player_bottom > spikes_top || player_top < spikes_bottom);
+            // player is touching top or bottom of spikes
+            let touching_top_or_bottom = (((player_bottom - spikes_top).abs() <= f32::EPSILON)
+                || (spikes_bottom - player_top).abs() <= f32::EPSILON)
+                && !(player_left_edge > spikes_right_edge || player_right_edge < spikes_left_edge);
+
+            if touching_side || touching_top_or_bottom {
+                commands.trigger_targets(RunnerHit, runner);
+                return;
             }
+        }
+    }
+}
 
-            if (((player_bottom - spikes_top).abs() <= f32::EPSILON)
-                || (spikes_bottom - player_top).abs() <= f32::EPSILON)
-                && !(player_left_edge > spikes_right_edge || player_right_edge < spikes_left_edge)
-            {
-                // player is touching top or bottom of spikes
-                commands.trigger(DeathEvent);
+/// The finish line the current level ends at, if it has one. The next level's is left out, since it isn't in play yet.
+pub(super) type FinishLineQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static Transform,
+    (With<FinishLine>, Without<IncomingObstacle>, Without<Player>),
+>;
+
+/// Event sent when the runner out in front crosses the finish line at the end of a level, with the level it ended
+#[derive(Event, Debug)]
+pub struct FinishLineCrossed(pub u32);
+
+/// Event sent when a runner first gets all the way past an obstacle each time through the level, with the obstacle.
+/// It's targeted at the runner that got past it.
+#[derive(Event, Debug)]
+pub struct ObstacleCleared(pub Entity);
+
+/// Marks an obstacle a runner has already gotten past this time through the level, so it's only cleared once
+#[derive(Component, Debug)]
+pub struct AlreadyCleared;
+
+/// Sends [`ObstacleCleared`] for each obstacle a runner's left edge has just made it past
+pub(super) fn detect_cleared_obstacles(
+    runner_query: Query<(Entity, &Transform, &Player), Without<Opponent>>,
+    obstacle_query: Query<
+        (Entity, &Transform, &RectCollider),
+        (
+            With<Obstacle>,
+            Without<IncomingObstacle>,
+            Without<AlreadyCleared>,
+        ),
+    >,
+    paused: Res<Paused>,
+    dead: Res<Dead>,
+    mut commands: Commands,
+) {
+    if paused.0 || dead.0 {
+        return;
+    }
+
+    let mut cleared = Vec::new();
+    for (runner, runner_transform, player) in &runner_query {
+        let runner_left_edge =
+            runner_transform.translation.x + player.collider_offset.x - (player.collider.x / 2.0);
+        for (obstacle, obstacle_transform, collider) in &obstacle_query {
+            let obstacle_right_edge =
+                obstacle_transform.translation.x + collider.offset.x + (collider.bounds.x / 2.0);
+            // in co-op, whichever runner gets past it first clears it
+            if runner_left_edge > obstacle_right_edge && !cleared.contains(&obstacle) {
+                cleared.push(obstacle);
+                commands.entity(obstacle).insert(AlreadyCleared);
+                commands.trigger_targets(ObstacleCleared(obstacle), runner);
             }
         }
     }
 }
 
+/// Gets how far to the right the runner out in front's left edge has to get for the current level to be over.
+/// That's wherever its finish line is, or the right edge of the level if it doesn't have one.
+pub(super) fn level_end(level_width: LevelWidth, finish_line_query: &FinishLineQuery) -> f32 {
+    finish_line_query
+        .iter()
+        .map(|transform| transform.translation.x + (FINISH_LINE_WIDTH / 2.0))
+        .min_by(f32::total_cmp)
+        .unwrap_or(level_width.right_edge())
+}
+
+/// Spawns the next level off to the right once the runner out in front is getting close to the end of this one,
+/// so it's all there already when they go through to it instead of popping in.
+/// The next tutorial step's level isn't picked until this one's done, so tutorial levels are left to be spawned as they come up.
+/// Scrolling mode streams its level in on its own.
+fn pre_spawn_next_level(
+    runner_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    finish_line_query: FinishLineQuery,
+    level_width: Res<LevelWidth>,
+    boss_query: Query<(), With<Boss>>,
+    current_level: Res<CurrentLevel>,
+    incoming_level: Res<IncomingLevel>,
+    game_mode: Res<GameMode>,
+    mut commands: Commands,
+) {
+    let next_level = current_level.0 + 1;
+    if incoming_level.0 == Some(next_level) || !boss_query.is_empty() {
+        return;
+    }
+    if matches!(*game_mode, GameMode::Tutorial | GameMode::Scrolling)
+        || (*game_mode == GameMode::Campaign && next_level >= CAMPAIGN_LENGTH)
+    {
+        return;
+    }
+
+    let Some(leader_x) = runner_query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .max_by(f32::total_cmp)
+    else {
+        return;
+    };
+    if leader_x >= level_end(*level_width, &finish_line_query) - PRE_SPAWN_DISTANCE {
+        commands.trigger(PreSpawnObstacles(next_level));
+    }
+}
+
+/// Moves on to the next level once the runner out in front has crossed the level's finish line, or left the level if
+/// it doesn't have one. Crossing a finish line is celebrated on top of that.
+/// Everyone is moved back so the leader carries on from the start of the next level, as far past it as they went past
+/// the end of this one. Anyone lagging behind is pulled along to the start of the next level with
+/// them, so nobody gets left behind in a level that's gone.
+/// The opponent in versus is left out of this, since it's racing the player through each level rather than running alongside them.
+/// Boss fights go around the same level instead, until the boss is worn out, so they do their own wrapping.
+/// In scrolling mode the level doesn't wrap at all.
 fn wrap_within_level(
-    mut wrap_query: Query<&mut Transform, With<Player>>,
+    mut wrap_query: Query<&mut Transform, (With<Player>, Without<Opponent>)>,
+    finish_line_query: FinishLineQuery,
+    level_width: Res<LevelWidth>,
+    boss_query: Query<(), With<Boss>>,
     mut current_level: ResMut<CurrentLevel>,
+    mut stats: ResMut<RunStats>,
+    game_mode: Res<GameMode>,
     mut commands: Commands,
 ) {
-    for mut transform in &mut wrap_query {
-        let player_left_edge = transform.translation.x - (PLAYER_IMAGE_SIZE / 2.0);
-        let level_right_edge = LEVEL_WIDTH / 2.0;
-        if player_left_edge > level_right_edge {
-            // player has fully left the level, move them back to the left side
-            let level_left_edge = -LEVEL_WIDTH / 2.0;
-            transform.translation.x = level_left_edge - (PLAYER_IMAGE_SIZE / 2.0);
-            // clear the current level and load the next one
-            current_level.0 += 1;
-            commands.trigger(SpawnObstacles(current_level.0));
+    if !boss_query.is_empty() || *game_mode == GameMode::Scrolling {
+        return;
+    }
+
+    let Some(leader_x) = wrap_query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .max_by(f32::total_cmp)
+    else {
+        return;
+    };
+
+    let player_left_edge = leader_x - (PLAYER_IMAGE_SIZE / 2.0);
+    let level_end = level_end(*level_width, &finish_line_query);
+    if player_left_edge > level_end {
+        // player has fully left the level, move them back to the left side.
+        // A level that ends short of the right edge is that much less of a way to go back.
+        let level_left_edge = -LEVEL_WIDTH / 2.0;
+        let start_x = level_left_edge - (PLAYER_IMAGE_SIZE / 2.0);
+        let wrap_distance =
+            level_width.next_level_offset() - (level_width.right_edge() - level_end);
+        for mut transform in &mut wrap_query {
+            transform.translation.x = (transform.translation.x - wrap_distance).max(start_x);
         }
+        // clear the current level and load the next one
+        if !finish_line_query.is_empty() {
+            commands.trigger(FinishLineCrossed(current_level.0));
+        }
+        commands.trigger(LevelCleared(current_level.0));
+        current_level.0 += 1;
+        stats.levels_cleared += 1;
+        commands.trigger(SpawnObstacles::new(current_level.0));
+
+        if *game_mode == GameMode::Campaign && current_level.0 == CAMPAIGN_LENGTH {
+            commands.trigger(VictoryEvent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// How much time passes in each test, which is about one frame
+    const FRAME: Duration = Duration::from_millis(16);
+
+    /// How big the runner's collider is in each test, in pixels
+    const RUNNER_SIZE: f32 = 20.0;
+
+    /// Builds a world with everything the movement systems need, with one frame's worth of time having passed
+    fn world() -> World {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(FRAME);
+        world.insert_resource(time);
+        world.insert_resource(PlaybackSpeed::NORMAL);
+        world.insert_resource(Paused(false));
+        world.insert_resource(GameMode::Campaign);
+        world.insert_resource(TotalDistance(0.0));
+        world.insert_resource(ScrollPosition::default());
+        world.insert_resource(Settings::default());
+        let mut grid = ColliderGrid::default();
+        grid.mark_stale();
+        world.insert_resource(grid);
+        world
+    }
+
+    fn spawn_runner(world: &mut World, position: Vec2, controller: MovementController) -> Entity {
+        let ground_state = if controller.jumping {
+            GroundState::Airborne
+        } else {
+            GroundState::Grounded
+        };
+        world
+            .spawn((
+                Player {
+                    collider: Vec2::splat(RUNNER_SIZE),
+                    collider_offset: Vec2::ZERO,
+                },
+                controller,
+                ground_state,
+                VerticalContact::default(),
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    }
+
+    fn spawn_box(world: &mut World, center: Vec2, size: Vec2) -> Entity {
+        world
+            .spawn((
+                RectCollider {
+                    bounds: size,
+                    offset: Vec2::ZERO,
+                },
+                Transform::from_translation(center.extend(0.0)),
+            ))
+            .id()
+    }
+
+    /// Moves the runners vertically, then applies whatever they ran into
+    fn move_and_land(world: &mut World) {
+        world.run_system_once(rebuild_collider_grid);
+        world.run_system_once(move_vertically);
+        world.run_system_once(update_ground_state);
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 0.001,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn wind_only_blows_airborne_runners() {
+        let mut world = world();
+        world.spawn((
+            TriggerVolume {
+                bounds: Vec2::splat(200.0),
+            },
+            WindZone {
+                force: Vec2::new(100.0, 500.0),
+            },
+            Transform::default(),
+        ));
+        let grounded = spawn_runner(
+            &mut world,
+            Vec2::ZERO,
+            MovementController {
+                speed: 50.0,
+                ..default()
+            },
+        );
+        let airborne = spawn_runner(
+            &mut world,
+            Vec2::ZERO,
+            MovementController {
+                speed: 50.0,
+                jumping: true,
+                ..default()
+            },
+        );
+
+        world.run_system_once(apply_wind);
+
+        let controller = world.get::<MovementController>(grounded).unwrap();
+        assert_close(controller.speed, 50.0);
+        assert_close(controller.vertical_velocity, 0.0);
+
+        let controller = world.get::<MovementController>(airborne).unwrap();
+        assert_close(controller.speed, 50.0 + 100.0 * FRAME.as_secs_f32());
+        assert_close(controller.vertical_velocity, 500.0 * FRAME.as_secs_f32());
+    }
+
+    #[test]
+    fn runner_stops_at_wall() {
+        let mut world = world();
+        spawn_box(&mut world, Vec2::new(100.0, 0.0), Vec2::splat(40.0));
+        // fast enough to go right through the wall in one frame if nothing stopped it
+        let runner = spawn_runner(
+            &mut world,
+            Vec2::ZERO,
+            MovementController {
+                speed: 10_000.0,
+                ..default()
+            },
+        );
+
+        world.run_system_once(rebuild_collider_grid);
+        world.run_system_once(move_horizontally);
+
+        let wall_left_edge = 100.0 - 20.0;
+        let expected_x = wall_left_edge - RUNNER_SIZE / 2.0;
+        let transform = world.get::<Transform>(runner).unwrap();
+        assert_close(transform.translation.x, expected_x);
+        assert_close(
+            world.get::<MovementController>(runner).unwrap().distance,
+            expected_x,
+        );
+        assert_close(world.resource::<TotalDistance>().0, expected_x);
+    }
+
+    #[test]
+    fn runner_lands_on_floor() {
+        let mut world = world();
+        spawn_box(&mut world, Vec2::ZERO, Vec2::new(100.0, 20.0));
+        let runner = spawn_runner(
+            &mut world,
+            Vec2::new(0.0, 30.0),
+            MovementController {
+                jumping: true,
+                vertical_velocity: -1000.0,
+                ..default()
+            },
+        );
+
+        move_and_land(&mut world);
+
+        let floor_top = 10.0;
+        let transform = world.get::<Transform>(runner).unwrap();
+        assert_close(transform.translation.y, floor_top + RUNNER_SIZE / 2.0);
+        assert_eq!(
+            *world.get::<VerticalContact>(runner).unwrap(),
+            VerticalContact::Landed
+        );
+        let controller = world.get::<MovementController>(runner).unwrap();
+        assert!(!controller.jumping);
+        assert_close(controller.vertical_velocity, 0.0);
+        assert_eq!(
+            *world.get::<GroundState>(runner).unwrap(),
+            GroundState::Grounded
+        );
+    }
+
+    #[test]
+    fn runner_bounces_on_bounce_pad() {
+        let mut world = world();
+        let pad = spawn_box(&mut world, Vec2::ZERO, Vec2::new(100.0, 20.0));
+        world.entity_mut(pad).insert(BouncePad {
+            launch_velocity: 900.0,
+        });
+        let runner = spawn_runner(
+            &mut world,
+            Vec2::new(0.0, 30.0),
+            MovementController {
+                jumping: true,
+                vertical_velocity: -1000.0,
+                ..default()
+            },
+        );
+
+        move_and_land(&mut world);
+
+        assert_eq!(
+            *world.get::<VerticalContact>(runner).unwrap(),
+            VerticalContact::Bounced(900.0)
+        );
+        let controller = world.get::<MovementController>(runner).unwrap();
+        assert!(controller.jumping);
+        assert_close(controller.vertical_velocity, 900.0);
+        assert_eq!(
+            *world.get::<GroundState>(runner).unwrap(),
+            GroundState::Airborne
+        );
+    }
+
+    #[test]
+    fn runner_hits_ceiling() {
+        let mut world = world();
+        spawn_box(&mut world, Vec2::new(0.0, 30.0), Vec2::new(100.0, 20.0));
+        let runner = spawn_runner(
+            &mut world,
+            Vec2::ZERO,
+            MovementController {
+                jumping: true,
+                vertical_velocity: 1000.0,
+                ..default()
+            },
+        );
+
+        move_and_land(&mut world);
+
+        let ceiling_bottom = 20.0;
+        let transform = world.get::<Transform>(runner).unwrap();
+        assert_close(transform.translation.y, ceiling_bottom - RUNNER_SIZE / 2.0);
+        assert_eq!(
+            *world.get::<VerticalContact>(runner).unwrap(),
+            VerticalContact::HitCeiling
+        );
+        let controller = world.get::<MovementController>(runner).unwrap();
+        assert!(controller.jumping);
+        assert_close(controller.vertical_velocity, 0.0);
     }
 }
@@ -0,0 +1,207 @@
+//! Optional twists on a run, unlocked by earning stars in the campaign and picked right before the run starts.
+//! Each one makes the run harder in its own way, and is worth a bigger score in return.
+
+use std::collections::{BTreeSet, HashMap};
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    localization::Localization,
+    movement::TotalDistance,
+    progress::Progress,
+    spawn::{
+        level::{IncomingObstacle, Obstacle},
+        player::Player,
+        sequencer::{ResetSequence, SequencerRow},
+    },
+    stats::RunStats,
+};
+
+/// How far from the nearest runner obstacles can be seen with fog on, in pixels
+const FOG_RADIUS: f32 = 300.0;
+
+/// The rows whose actions get shuffled around with shuffled rows on
+const SHUFFLED_ROWS: [SequencerRow; 3] =
+    [SequencerRow::Kick, SequencerRow::HiHat, SequencerRow::Snare];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Mutators>();
+    app.init_resource::<RowShuffle>();
+    app.observe(shuffle_rows);
+}
+
+/// Hides far away obstacles with fog on, and goes back to no mutators whenever the player is back at the title screen
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Title), clear_mutators);
+    app.add_systems(Update, apply_fog.in_set(AppSet::Update));
+}
+
+/// A twist on a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Mutator {
+    /// Levels are laid out back to front
+    Mirrored,
+    /// The sequence plays twice as fast
+    DoubleTempo,
+    /// The kick, hi-hat, and snare rows do each other's actions, shuffled each run
+    ShuffledRows,
+    /// Obstacles can only be seen up close
+    Fog,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 4] = [
+        Mutator::Mirrored,
+        Mutator::DoubleTempo,
+        Mutator::ShuffledRows,
+        Mutator::Fog,
+    ];
+
+    /// Gets how many campaign stars it takes to unlock this
+    pub fn stars_to_unlock(self) -> u32 {
+        match self {
+            Mutator::Mirrored => 3,
+            Mutator::DoubleTempo => 6,
+            Mutator::ShuffledRows => 8,
+            Mutator::Fog => 10,
+        }
+    }
+
+    /// Gets how much this adds to the score multiplier
+    pub fn score_bonus(self) -> f32 {
+        match self {
+            Mutator::Mirrored => 0.25,
+            Mutator::DoubleTempo => 0.5,
+            Mutator::ShuffledRows => 0.5,
+            Mutator::Fog => 0.25,
+        }
+    }
+
+    /// Gets the name of this in the player's language
+    pub fn name(self, localization: &Localization) -> String {
+        localization.get(match self {
+            Mutator::Mirrored => "mutators.mirrored",
+            Mutator::DoubleTempo => "mutators.double_tempo",
+            Mutator::ShuffledRows => "mutators.shuffled_rows",
+            Mutator::Fog => "mutators.fog",
+        })
+    }
+
+    /// Whether the player has earned enough stars to pick this
+    pub fn is_unlocked(self, progress: &Progress) -> bool {
+        progress.total_stars() >= self.stars_to_unlock()
+    }
+}
+
+/// The mutators on for the current run
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Mutators(pub BTreeSet<Mutator>);
+
+impl Mutators {
+    pub fn contains(&self, mutator: Mutator) -> bool {
+        self.0.contains(&mutator)
+    }
+
+    /// Gets how much the score is multiplied by, which goes up with each mutator that's on
+    pub fn score_multiplier(&self) -> f32 {
+        1.0 + self
+            .0
+            .iter()
+            .map(|mutator| mutator.score_bonus())
+            .sum::<f32>()
+    }
+
+    /// Gets the score for a run that went the provided distance and earned the provided bonus score
+    pub fn score(&self, distance: &TotalDistance, stats: &RunStats) -> u32 {
+        ((distance.feet() + stats.bonus_score) as f32 * self.score_multiplier()).round() as u32
+    }
+
+    /// Gets how many times faster the sequence plays than normal
+    pub fn tempo_multiplier(&self) -> f32 {
+        if self.contains(Mutator::DoubleTempo) {
+            2.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Which row's action each row does this run
+#[derive(Resource, Debug, Default, Clone)]
+pub struct RowShuffle {
+    /// The row whose action each row does. Rows that aren't in here do their own.
+    actions: HashMap<SequencerRow, SequencerRow>,
+    /// Whether the same shuffle is kept when a run starts over instead of being dealt out again,
+    /// so simulated runs play out the way the player's would
+    kept: bool,
+}
+
+impl RowShuffle {
+    /// Gets the row whose action the provided row does this run
+    pub fn acting_row(&self, row: SequencerRow) -> SequencerRow {
+        self.actions.get(&row).copied().unwrap_or(row)
+    }
+
+    /// Gets this shuffle, kept for every run from now on
+    pub(super) fn kept(self) -> RowShuffle {
+        RowShuffle { kept: true, ..self }
+    }
+}
+
+/// Deals the rows' actions back out at the start of each run, if shuffled rows is on
+fn shuffle_rows(
+    _trigger: Trigger<ResetSequence>,
+    mutators: Res<Mutators>,
+    mut shuffle: ResMut<RowShuffle>,
+) {
+    if shuffle.kept {
+        return;
+    }
+
+    shuffle.actions.clear();
+    if !mutators.contains(Mutator::ShuffledRows) {
+        return;
+    }
+
+    let mut actions = SHUFFLED_ROWS;
+    actions.shuffle(&mut rand::thread_rng());
+    shuffle
+        .actions
+        .extend(SHUFFLED_ROWS.into_iter().zip(actions));
+}
+
+fn clear_mutators(mut mutators: ResMut<Mutators>) {
+    mutators.0.clear();
+}
+
+/// Hides obstacles that are too far from every runner to see through the fog, and shows them again once they're close enough
+fn apply_fog(
+    mutators: Res<Mutators>,
+    runner_query: Query<&Transform, With<Player>>,
+    mut obstacle_query: Query<
+        (&Transform, &mut Visibility),
+        (With<Obstacle>, Without<IncomingObstacle>, Without<Player>),
+    >,
+) {
+    let fog = mutators.contains(Mutator::Fog);
+    if !fog && !mutators.is_changed() {
+        return;
+    }
+
+    for (transform, mut visibility) in &mut obstacle_query {
+        let position = transform.translation.truncate();
+        let in_view = !fog
+            || runner_query
+                .iter()
+                .any(|runner| runner.translation.truncate().distance(position) <= FOG_RADIUS);
+        visibility.set_if_neq(if in_view {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
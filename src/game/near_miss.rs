@@ -0,0 +1,173 @@
+//! Rewards for getting past spikes with only a few pixels to spare, for players who like to cut it close.
+//! Each near miss is worth bonus score, and is played up with a whoosh and a moment of slow motion for the run.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    assets::SfxKey,
+    audio::sfx::PlaySfx,
+    collision::ColliderGrid,
+    movement::{detect_cleared_obstacles, ObstacleCleared, PlaybackSpeed},
+    settings::Settings,
+    spawn::{
+        level::{IncomingObstacle, RectCollider, Spikes},
+        player::Player,
+    },
+    stats::RunStats,
+    versus::Opponent,
+};
+
+/// How close a runner has to get to spikes for getting past them to count as a near miss, in pixels
+const NEAR_MISS_DISTANCE: f32 = 12.0;
+
+/// The bonus score for each near miss
+const NEAR_MISS_BONUS: u32 = 10;
+
+/// How fast the run plays during the slow motion after a near miss, relative to the speed the player chose
+const FLOURISH_SPEED: f32 = 0.4;
+
+/// How long the slow motion after a near miss lasts. This isn't slowed down along with the run.
+const FLOURISH_DURATION: Duration = Duration::from_millis(350);
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(check_near_miss);
+    app.observe(reward_near_miss);
+    app.add_systems(
+        Update,
+        track_closest_approach
+            .before(detect_cleared_obstacles)
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Plays up near misses with a whoosh and a moment of slow motion
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.init_resource::<Flourish>();
+    app.observe(play_near_miss_flourish);
+    app.add_systems(Update, end_flourish);
+    app.add_systems(OnExit(Screen::Playing), cut_flourish_short);
+}
+
+/// Event sent when a runner gets past spikes with only a few pixels to spare, targeted at that runner
+#[derive(Event, Debug)]
+pub struct NearMiss {
+    /// The spikes that were nearly hit
+    pub spikes: Entity,
+    /// How close the runner got to them, in pixels
+    pub distance: f32,
+}
+
+/// The closest any runner has come to these spikes, in pixels
+#[derive(Component, Debug)]
+pub struct ClosestApproach(f32);
+
+/// How much longer the slow motion after a near miss lasts, if it's going
+#[derive(Resource, Debug, Default)]
+struct Flourish(Option<Timer>);
+
+/// Keeps track of how close runners get to the spikes near them
+fn track_closest_approach(
+    runner_query: Query<(&Transform, &Player), Without<Opponent>>,
+    mut spikes_query: Query<
+        (
+            Entity,
+            &Transform,
+            &RectCollider,
+            Option<&mut ClosestApproach>,
+        ),
+        (With<Spikes>, Without<IncomingObstacle>),
+    >,
+    grid: Res<ColliderGrid>,
+    mut commands: Commands,
+) {
+    for (runner_transform, player) in &runner_query {
+        let runner_center = runner_transform.translation.truncate() + player.collider_offset;
+        let nearby = grid.nearby(
+            runner_center.x - (player.collider.x / 2.0) - NEAR_MISS_DISTANCE,
+            runner_center.x + (player.collider.x / 2.0) + NEAR_MISS_DISTANCE,
+        );
+        let mut spikes = spikes_query.iter_many_mut(nearby);
+        while let Some((entity, spikes_transform, collider, closest)) = spikes.fetch_next() {
+            let spikes_center = spikes_transform.translation.truncate() + collider.offset;
+            let gap = ((runner_center - spikes_center).abs()
+                - (player.collider + collider.bounds) / 2.0)
+                .max(Vec2::ZERO)
+                .length();
+            match closest {
+                Some(mut closest) => closest.0 = closest.0.min(gap),
+                None => {
+                    commands.entity(entity).insert(ClosestApproach(gap));
+                }
+            }
+        }
+    }
+}
+
+/// Counts getting past spikes as a near miss if the runner came close to them without touching them
+fn check_near_miss(
+    trigger: Trigger<ObstacleCleared>,
+    spikes_query: Query<&ClosestApproach, With<Spikes>>,
+    mut commands: Commands,
+) {
+    let spikes = trigger.event().0;
+    let Ok(closest) = spikes_query.get(spikes) else {
+        return;
+    };
+
+    // touching them would have been a hit, even if an assist kept the runner going
+    if closest.0 > f32::EPSILON && closest.0 <= NEAR_MISS_DISTANCE {
+        commands.trigger_targets(
+            NearMiss {
+                spikes,
+                distance: closest.0,
+            },
+            trigger.entity(),
+        );
+    }
+}
+
+fn reward_near_miss(_trigger: Trigger<NearMiss>, mut stats: ResMut<RunStats>) {
+    stats.bonus_score += NEAR_MISS_BONUS;
+}
+
+fn play_near_miss_flourish(
+    _trigger: Trigger<NearMiss>,
+    settings: Res<Settings>,
+    mut flourish: ResMut<Flourish>,
+    mut playback_speed: ResMut<PlaybackSpeed>,
+    mut commands: Commands,
+) {
+    commands.trigger(PlaySfx::new(SfxKey::Whoosh));
+
+    if settings.reduced_motion {
+        return;
+    }
+    playback_speed.flourish = FLOURISH_SPEED;
+    flourish.0 = Some(Timer::new(FLOURISH_DURATION, TimerMode::Once));
+}
+
+/// Brings the run back up to speed once the slow motion is over
+fn end_flourish(
+    time: Res<Time>,
+    mut flourish: ResMut<Flourish>,
+    mut playback_speed: ResMut<PlaybackSpeed>,
+) {
+    let Some(timer) = &mut flourish.0 else {
+        return;
+    };
+    timer.tick(time.delta());
+    if timer.finished() {
+        flourish.0 = None;
+        playback_speed.flourish = 1.0;
+    }
+}
+
+fn cut_flourish_short(mut flourish: ResMut<Flourish>, mut playback_speed: ResMut<PlaybackSpeed>) {
+    if flourish.0.take().is_some() {
+        playback_speed.flourish = 1.0;
+    }
+}
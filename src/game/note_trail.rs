@@ -0,0 +1,244 @@
+//! Icons that pop up over obstacles the runner gets past, showing which row's note got it over or under them.
+//! This way the player can see what each note they placed actually did.
+//! A note gets the credit if it was the last one to make the runner jump, float, or dive, and it played shortly before
+//! the runner made it past the obstacle.
+
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    movement::{ObstacleCleared, PlayerAction},
+    mutators::RowShuffle,
+    spawn::{
+        level::{RectCollider, SpawnObstacles},
+        player::Player,
+        sequencer::{ResetSequence, SequencerRow},
+    },
+    versus::Opponent,
+};
+
+/// How long before getting past an obstacle a note can have played and still get the credit for it, in seconds
+const CREDIT_WINDOW: f32 = 1.0;
+
+/// How long icons stay up, in seconds
+const ICON_DURATION: f32 = 1.0;
+
+/// How far icons float up while they're up, in pixels
+const ICON_RISE: f32 = 24.0;
+
+/// How far above the top of an obstacle icons start out, in pixels
+const ICON_GAP: f32 = 16.0;
+
+/// How many pixels wide and tall the images icons are drawn with are
+const ICON_IMAGE_SIZE: u32 = 12;
+
+/// How big icons are drawn, in pixels
+const ICON_SIZE: f32 = 30.0;
+
+const ICON_COLOR: Color = Color::srgb(0.95, 0.85, 0.35);
+
+/// The rows whose notes can get the runner past obstacles
+const CREDITED_ROWS: [SequencerRow; 3] =
+    [SequencerRow::Kick, SequencerRow::HiHat, SequencerRow::Snare];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RowIconImages>();
+    app.init_resource::<LastNotes>();
+    app.observe(remember_note);
+    app.observe(forget_notes);
+    app.observe(credit_cleared_obstacle);
+    app.observe(clear_icons);
+    app.add_systems(
+        Update,
+        fade_icons
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// The last note that made each runner do something, and when it played
+#[derive(Resource, Debug, Default)]
+struct LastNotes(HashMap<Entity, CreditedNote>);
+
+#[derive(Debug, Clone, Copy)]
+struct CreditedNote {
+    row: SequencerRow,
+    /// How long after the app started the note played, in seconds
+    played_at: f32,
+}
+
+/// An icon over an obstacle, showing which row got the runner past it
+#[derive(Component, Debug)]
+struct NoteIcon {
+    timer: Timer,
+    /// Where the icon started out
+    start: Vec3,
+}
+
+/// The images icons are drawn with, one for each row that can get the runner past an obstacle
+#[derive(Resource, Debug)]
+struct RowIconImages(HashMap<SequencerRow, Handle<Image>>);
+
+impl FromWorld for RowIconImages {
+    fn from_world(world: &mut World) -> Self {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let size = ICON_IMAGE_SIZE as f32;
+        RowIconImages(
+            CREDITED_ROWS
+                .into_iter()
+                .map(|row| {
+                    let data = (0..ICON_IMAGE_SIZE * ICON_IMAGE_SIZE)
+                        .flat_map(|i| {
+                            let x = (i % ICON_IMAGE_SIZE) as f32 + 0.5 - (size / 2.0);
+                            // the image's rows go from top to bottom
+                            let y = (size / 2.0) - ((i / ICON_IMAGE_SIZE) as f32 + 0.5);
+                            let inside = match row {
+                                // a triangle pointing up, for jumping
+                                SequencerRow::Kick => x.abs() <= (size / 2.0 - y) / 2.0,
+                                // a triangle pointing down, for diving
+                                SequencerRow::Snare => x.abs() <= (size / 2.0 + y) / 2.0,
+                                // a diamond, for floating
+                                _ => x.abs() + y.abs() <= size / 2.0,
+                            };
+                            [255, 255, 255, if inside { 255 } else { 0 }]
+                        })
+                        .collect();
+                    let image = Image::new(
+                        Extent3d {
+                            width: ICON_IMAGE_SIZE,
+                            height: ICON_IMAGE_SIZE,
+                            depth_or_array_layers: 1,
+                        },
+                        TextureDimension::D2,
+                        data,
+                        TextureFormat::Rgba8UnormSrgb,
+                        RenderAssetUsages::RENDER_WORLD,
+                    );
+                    (row, images.add(image))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Remembers which row's note made a runner do something, so it can get the credit for what that gets the runner past
+fn remember_note(
+    trigger: Trigger<PlayerAction>,
+    time: Res<Time>,
+    row_shuffle: Res<RowShuffle>,
+    runner_query: Query<(), (With<Player>, Without<Opponent>)>,
+    mut last_notes: ResMut<LastNotes>,
+) {
+    let runner = trigger.entity();
+    if !runner_query.contains(runner) {
+        return;
+    }
+
+    // with shuffled rows on, the row that played isn't the one whose action the runner did
+    let action = trigger.event();
+    let Some(row) = CREDITED_ROWS.into_iter().find(|row| {
+        std::mem::discriminant(&row_shuffle.acting_row(*row).to_player_action())
+            == std::mem::discriminant(action)
+    }) else {
+        return;
+    };
+
+    last_notes.0.insert(
+        runner,
+        CreditedNote {
+            row,
+            played_at: time.elapsed_seconds(),
+        },
+    );
+}
+
+fn forget_notes(_trigger: Trigger<ResetSequence>, mut last_notes: ResMut<LastNotes>) {
+    last_notes.0.clear();
+}
+
+/// Puts an icon over an obstacle a runner has just gotten past, for the note that got it there
+fn credit_cleared_obstacle(
+    trigger: Trigger<ObstacleCleared>,
+    time: Res<Time>,
+    last_notes: Res<LastNotes>,
+    icon_images: Res<RowIconImages>,
+    obstacle_query: Query<(&Transform, &RectCollider)>,
+    mut commands: Commands,
+) {
+    let Some(note) = last_notes.0.get(&trigger.entity()) else {
+        return;
+    };
+    if time.elapsed_seconds() - note.played_at > CREDIT_WINDOW {
+        return;
+    }
+    let Some(image) = icon_images.0.get(&note.row) else {
+        return;
+    };
+    let Ok((obstacle_transform, collider)) = obstacle_query.get(trigger.event().0) else {
+        return;
+    };
+
+    let center = obstacle_transform.translation.truncate() + collider.offset;
+    let start = Vec3::new(
+        center.x,
+        center.y + (collider.bounds.y / 2.0) + ICON_GAP + (ICON_SIZE / 2.0),
+        0.9,
+    );
+    commands.spawn((
+        Name::new("Note icon"),
+        NoteIcon {
+            timer: Timer::from_seconds(ICON_DURATION, TimerMode::Once),
+            start,
+        },
+        StateScoped(Screen::Playing),
+        SpriteBundle {
+            texture: image.clone(),
+            sprite: Sprite {
+                color: ICON_COLOR,
+                custom_size: Some(Vec2::splat(ICON_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(start),
+            ..default()
+        },
+    ));
+}
+
+/// Floats the icons up and fades them out, then gets rid of them once their time is up
+fn fade_icons(
+    time: Res<Time>,
+    mut icon_query: Query<(Entity, &mut NoteIcon, &mut Transform, &mut Sprite)>,
+    mut commands: Commands,
+) {
+    for (entity, mut icon, mut transform, mut sprite) in &mut icon_query {
+        icon.timer.tick(time.delta());
+        if icon.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let fraction = icon.timer.fraction();
+        transform.translation = icon.start + Vec3::Y * (ICON_RISE * fraction);
+        sprite.color = ICON_COLOR.with_alpha(1.0 - fraction);
+    }
+}
+
+/// Gets rid of any icons once a level is spawned, since the obstacles they were over are gone
+fn clear_icons(
+    _trigger: Trigger<SpawnObstacles>,
+    icon_query: Query<Entity, With<NoteIcon>>,
+    mut commands: Commands,
+) {
+    for entity in &icon_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,303 @@
+//! Hints shown during the player's very first campaign run, pointing out which row of the sequencer gets them past
+//! each kind of obstacle as they come up to it. Once they've been shown they don't come back, unless the player asks
+//! for them again from the settings screen.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence,
+    screen::Screen,
+    ui::palette::{LABEL_TEXT, TOOLTIP_BACKGROUND},
+    AppSet,
+};
+
+use super::{
+    assets::{FontKey, HandleMap},
+    localization::Localization,
+    spawn::{
+        level::{FinishLine, GameMode, Obstacle, Spikes},
+        player::Player,
+        sequencer::{Sequence, SequencerRow, SequencerRowNode},
+    },
+    versus::Opponent,
+};
+
+const ONBOARDING_SAVE_KEY: &str = "onboarding";
+
+/// How close an obstacle has to get to the player for its hint to show up, in pixels
+const HINT_DISTANCE: f32 = 300.0;
+
+/// How far above the row it points at the hint is, in pixels
+const HINT_GAP: f32 = 6.0;
+
+/// How big the arrow at the bottom of the hint is, in pixels
+const HINT_ARROW_SIZE: f32 = 14.0;
+
+/// The hints there are, in the order they're checked
+const HINTS: [Hint; 2] = [
+    Hint {
+        obstacle: HintObstacle::Box,
+        row: SequencerRow::Kick,
+        text: "hints.box",
+    },
+    Hint {
+        obstacle: HintObstacle::Spikes,
+        row: SequencerRow::Kick,
+        text: "hints.spikes",
+    },
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(persistence::load::<Onboarding>(ONBOARDING_SAVE_KEY).unwrap_or_default());
+    app.add_systems(
+        Update,
+        save_onboarding.run_if(resource_changed::<Onboarding>),
+    );
+    app.add_systems(
+        OnEnter(Screen::Playing),
+        start_hints.run_if(resource_equals(GameMode::Campaign).and_then(not_seen_tutorial)),
+    );
+    app.add_systems(
+        Update,
+        (update_hints, show_hint)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing).and_then(resource_exists::<HintProgress>)),
+    );
+    app.add_systems(OnExit(Screen::Playing), stop_hints);
+}
+
+/// Whether the player has been through the first-run hints, saved between sessions.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Onboarding {
+    pub seen_tutorial: bool,
+}
+
+fn not_seen_tutorial(onboarding: Res<Onboarding>) -> bool {
+    !onboarding.seen_tutorial
+}
+
+fn save_onboarding(onboarding: Res<Onboarding>) {
+    if !onboarding.is_added() {
+        persistence::save(ONBOARDING_SAVE_KEY, &*onboarding);
+    }
+}
+
+/// Something to point out when the player first comes up to a certain kind of obstacle.
+struct Hint {
+    obstacle: HintObstacle,
+    /// The row the hint points at
+    row: SequencerRow,
+    /// The key of the hint's text in the localization files
+    text: &'static str,
+}
+
+/// The kinds of obstacles hints are shown for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HintObstacle {
+    Box,
+    Spikes,
+}
+
+/// Which hints have been shown so far this run. Only exists during the first run.
+#[derive(Resource, Debug, Default)]
+struct HintProgress {
+    /// The index in [`HINTS`] of the hint being shown, along with the obstacle it's for
+    current: Option<(usize, Entity)>,
+    /// Whether each hint in [`HINTS`] has been shown, or doesn't need to be
+    done: [bool; HINTS.len()],
+}
+
+/// The popup showing the current hint
+#[derive(Component, Debug)]
+struct HintPopup;
+
+#[derive(Component, Debug)]
+struct HintText;
+
+/// Starts showing hints on the player's first run, and remembers that it's been shown so it won't be again
+fn start_hints(
+    font_handles: Res<HandleMap<FontKey>>,
+    mut onboarding: ResMut<Onboarding>,
+    mut commands: Commands,
+) {
+    onboarding.seen_tutorial = true;
+    commands.init_resource::<HintProgress>();
+    commands
+        .spawn((
+            Name::new("Hint"),
+            HintPopup,
+            StateScoped(Screen::Playing),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Start,
+                    max_width: Val::Px(360.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(i32::MAX - 1),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(TOOLTIP_BACKGROUND),
+                    border_radius: BorderRadius::all(Val::Px(5.0)),
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.spawn((
+                        Name::new("Hint text"),
+                        HintText,
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: font_handles.get(FontKey::General),
+                                font_size: 22.0,
+                                color: LABEL_TEXT,
+                            },
+                        ),
+                    ));
+                });
+            // a square turned on its corner, half tucked under the box so it points down at the row
+            children.spawn((
+                Name::new("Hint arrow"),
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(HINT_ARROW_SIZE),
+                        height: Val::Px(HINT_ARROW_SIZE),
+                        margin: UiRect {
+                            left: Val::Px(HINT_ARROW_SIZE),
+                            top: Val::Px(-HINT_ARROW_SIZE / 2.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: BackgroundColor(TOOLTIP_BACKGROUND),
+                    transform: Transform::from_rotation(Quat::from_rotation_z(
+                        std::f32::consts::FRAC_PI_4,
+                    )),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Picks a hint to show when the player comes up to an obstacle they haven't been told about,
+/// and finishes it once they've placed a note on the row it points at or gotten past the obstacle
+fn update_hints(
+    sequence: Res<Sequence>,
+    mut progress: ResMut<HintProgress>,
+    player_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    obstacle_query: Query<(Entity, &Transform, Has<Spikes>), (With<Obstacle>, Without<FinishLine>)>,
+) {
+    let has_note = |row: SequencerRow| {
+        sequence
+            .beats()
+            .flat_map(|rows| rows.keys())
+            .any(|placed| *placed == row)
+    };
+    let Some(player_x) = player_query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .reduce(f32::max)
+    else {
+        return;
+    };
+
+    if let Some((index, obstacle)) = progress.current {
+        let passed = obstacle_query
+            .get(obstacle)
+            .map_or(true, |(_, transform, _)| transform.translation.x < player_x);
+        if passed || has_note(HINTS[index].row) {
+            progress.done[index] = true;
+            progress.current = None;
+        }
+        return;
+    }
+
+    for (index, hint) in HINTS.iter().enumerate() {
+        if progress.done[index] {
+            continue;
+        }
+
+        let upcoming = obstacle_query
+            .iter()
+            .filter(|(_, _, spikes)| *spikes == (hint.obstacle == HintObstacle::Spikes))
+            .map(|(entity, transform, _)| (entity, transform.translation.x - player_x))
+            .filter(|(_, ahead)| (0.0..HINT_DISTANCE).contains(ahead))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+        let Some((obstacle, _)) = upcoming else {
+            continue;
+        };
+
+        // no need to point out a row the player already knows to use
+        if has_note(hint.row) {
+            progress.done[index] = true;
+        } else {
+            progress.current = Some((index, obstacle));
+            return;
+        }
+    }
+}
+
+/// Shows the current hint just above the row it points at
+fn show_hint(
+    progress: Res<HintProgress>,
+    localization: Res<Localization>,
+    ui_scale: Res<UiScale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    row_query: Query<(&SequencerRowNode, &Node, &GlobalTransform, &ViewVisibility)>,
+    mut popup_query: Query<(&mut Style, &mut Visibility), With<HintPopup>>,
+    mut text_query: Query<&mut Text, With<HintText>>,
+) {
+    let Ok((mut style, mut visibility)) = popup_query.get_single_mut() else {
+        return;
+    };
+    let window_height = window_query
+        .get_single()
+        .map_or(0.0, |window| window.height());
+
+    // the row can't be pointed at while the sequencer is collapsed
+    let target = progress.current.and_then(|(index, _)| {
+        let hint = &HINTS[index];
+        row_query
+            .iter()
+            .find(|(row_node, node, _, view_visibility)| {
+                row_node.0 == Some(hint.row) && view_visibility.get() && node.size().y > 0.0
+            })
+            .map(|(_, node, transform, _)| (hint, node.logical_rect(transform)))
+    });
+    let Some((hint, row_rect)) = target else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let text = localization.get(hint.text);
+    for mut hint_text in &mut text_query {
+        if hint_text.sections[0].value != text {
+            hint_text.sections[0].value.clone_from(&text);
+        }
+    }
+
+    let left = Val::Px(row_rect.min.x / ui_scale.0);
+    let bottom = Val::Px((window_height - row_rect.min.y) / ui_scale.0 + HINT_GAP);
+    if style.left != left || style.bottom != bottom {
+        style.left = left;
+        style.bottom = bottom;
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+fn stop_hints(mut commands: Commands) {
+    commands.remove_resource::<HintProgress>();
+}
@@ -0,0 +1,153 @@
+//! Patterns the player has saved to come back to later, kept between sessions.
+//! Each one has a name the player can change and the date it was saved, and can be drawn as a tiny picture of its grid.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    utils::SystemTime,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence,
+    ui::{banner::Banners, palette::BeatPalette},
+};
+
+use super::{
+    assets::{FontKey, HandleMap},
+    localization::Localization,
+    spawn::sequencer::{is_shaded_beat, Sequence, SequencerRow, NUM_BEATS_IN_SEQUENCE},
+};
+
+const PATTERN_LIBRARY_SAVE_KEY: &str = "pattern_library";
+
+/// The longest a pattern's name can be, in characters
+pub const MAX_PATTERN_NAME_LENGTH: usize = 24;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(
+        persistence::load::<PatternLibrary>(PATTERN_LIBRARY_SAVE_KEY).unwrap_or_default(),
+    );
+    app.observe(save_pattern);
+    app.add_systems(
+        Update,
+        save_pattern_library.run_if(resource_changed::<PatternLibrary>),
+    );
+}
+
+/// Event that saves the current sequence to the pattern library under a new name
+#[derive(Event, Debug)]
+pub struct SavePattern;
+
+/// Every pattern the player has saved, oldest first.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatternLibrary(pub Vec<SavedPattern>);
+
+/// A pattern in the library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPattern {
+    pub name: String,
+    /// When the pattern was saved, in seconds since the Unix epoch
+    saved_at: u64,
+    pub sequence: Sequence,
+}
+
+impl SavedPattern {
+    /// Gets the day the pattern was saved on, like 2024-07-21. This goes by UTC rather than the player's time zone.
+    pub fn saved_date(&self) -> String {
+        let (year, month, day) = civil_from_days((self.saved_at / SECONDS_PER_DAY) as i64);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Gets the year, month, and day of the provided number of days since the Unix epoch, in the proleptic Gregorian calendar.
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Draws a tiny picture of the provided sequence's grid, one pixel for each beat of each row, in the same colors as the beat buttons.
+/// It's meant to be scaled up, so it's sampled without smoothing.
+pub fn pattern_thumbnail(sequence: &Sequence, beat_palette: &BeatPalette) -> Image {
+    let rows = SequencerRow::grid_order().collect::<Vec<_>>();
+    let data = rows
+        .iter()
+        .flat_map(|row| (0..NUM_BEATS_IN_SEQUENCE).map(move |beat| (*row, beat)))
+        .flat_map(|(row, beat)| {
+            let color = match sequence.chance(row, beat) {
+                Some(chance) if chance < 1.0 => beat_palette.maybe,
+                Some(_) => beat_palette.active,
+                None if is_shaded_beat(beat) => beat_palette.shaded_inactive,
+                None => beat_palette.inactive,
+            };
+            color.to_srgba().to_u8_array()
+        })
+        .collect();
+
+    let mut image = Image::new(
+        Extent3d {
+            width: NUM_BEATS_IN_SEQUENCE as u32,
+            height: rows.len() as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+/// Adds the current sequence to the library, named after how many patterns there are
+fn save_pattern(
+    _trigger: Trigger<SavePattern>,
+    sequence: Res<Sequence>,
+    localization: Res<Localization>,
+    font_handles: Res<HandleMap<FontKey>>,
+    mut library: ResMut<PatternLibrary>,
+    mut commands: Commands,
+) {
+    let name = localization.format(
+        "pattern_library.default_name",
+        &[("number", &(library.0.len() + 1))],
+    );
+    let saved_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    commands.banner(
+        localization.format("pattern_library.saved", &[("name", &name)]),
+        &font_handles,
+    );
+    library.0.push(SavedPattern {
+        name,
+        saved_at,
+        sequence: sequence.clone(),
+    });
+}
+
+fn save_pattern_library(library: Res<PatternLibrary>) {
+    persistence::save(PATTERN_LIBRARY_SAVE_KEY, &*library);
+}
@@ -0,0 +1,279 @@
+//! A photo mode for while the run is paused, where the camera comes loose from the level so it can be moved around freely
+//! with the UI out of the way, to take screenshots.
+//! By default, P goes in and out of photo mode, WASD or dragging with the mouse pans, Q/E or the scroll wheel zooms, and space takes a screenshot.
+
+use std::path::PathBuf;
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::view::screenshot::ScreenshotManager,
+    window::PrimaryWindow,
+};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    camera::WorldCamera,
+    input::{ActionInput, InputAction},
+    movement::Paused,
+    spawn::{
+        level::LEVEL_WIDTH,
+        sequencer::{animate_sequencer_collapse, SequenceUnpaused},
+    },
+};
+
+/// How fast the camera pans with the keyboard, in pixels/sec at normal zoom
+const PAN_SPEED: f32 = 600.0;
+
+/// How much the camera zooms per second with the keyboard
+const ZOOM_SPEED: f32 = 2.0;
+
+/// How much the camera zooms for each line scrolled with the mouse wheel
+const SCROLL_ZOOM_STEP: f32 = 1.1;
+
+/// How far the camera can zoom in and out, relative to how zoomed in it was when photo mode started
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 2.0;
+
+/// How far the camera can pan in each direction from where it was when photo mode started, in pixels
+const MAX_PAN: f32 = LEVEL_WIDTH / 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PhotoMode>();
+    app.observe(enter_photo_mode);
+    app.observe(leave_photo_mode);
+    app.observe(leave_photo_mode_on_unpause);
+    app.add_systems(OnExit(Screen::Playing), leave_photo_mode_on_exit);
+    app.add_systems(
+        Update,
+        (
+            toggle_photo_mode.in_set(AppSet::RecordInput),
+            (
+                // the sequencer's collapse animation moves the camera too, when it isn't in photo mode
+                move_photo_camera.after(animate_sequencer_collapse),
+                take_photo,
+            )
+                .in_set(AppSet::Update)
+                .run_if(in_photo_mode),
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Event that starts photo mode
+#[derive(Event, Debug)]
+pub struct EnterPhotoMode;
+
+/// Event that ends photo mode, if it's on
+#[derive(Event, Debug)]
+pub struct LeavePhotoMode;
+
+/// How things were before photo mode was started, so they can be put back once it's over.
+#[derive(Resource, Debug, Default)]
+pub struct PhotoMode(Option<PhotoModeState>);
+
+impl PhotoMode {
+    /// Whether the player is in photo mode
+    pub fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+#[derive(Debug)]
+struct PhotoModeState {
+    camera_transform: Transform,
+    camera_scale: f32,
+    /// The UI that was hidden, and how visible it was before that
+    hidden_ui: Vec<(Entity, Visibility)>,
+}
+
+fn in_photo_mode(photo_mode: Res<PhotoMode>) -> bool {
+    photo_mode.is_active()
+}
+
+fn toggle_photo_mode(
+    input: ActionInput,
+    paused: Res<Paused>,
+    photo_mode: Res<PhotoMode>,
+    mut commands: Commands,
+) {
+    if !input.just_pressed(InputAction::PhotoMode) {
+        return;
+    }
+
+    if photo_mode.is_active() {
+        commands.trigger(LeavePhotoMode);
+    } else if paused.0 {
+        commands.trigger(EnterPhotoMode);
+    }
+}
+
+/// Remembers where the camera is, and hides all the UI
+fn enter_photo_mode(
+    _trigger: Trigger<EnterPhotoMode>,
+    mut photo_mode: ResMut<PhotoMode>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<WorldCamera>>,
+    mut ui_query: Query<(Entity, &mut Visibility), (With<Node>, Without<Parent>)>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    let mut hidden_ui = Vec::new();
+    for (entity, mut visibility) in &mut ui_query {
+        hidden_ui.push((entity, *visibility));
+        *visibility = Visibility::Hidden;
+    }
+
+    photo_mode.0 = Some(PhotoModeState {
+        camera_transform: *camera_transform,
+        camera_scale: projection.scale,
+        hidden_ui,
+    });
+}
+
+/// Puts the camera back where it was, and shows the UI again
+fn leave_photo_mode(
+    _trigger: Trigger<LeavePhotoMode>,
+    mut photo_mode: ResMut<PhotoMode>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<WorldCamera>>,
+    mut visibility_query: Query<&mut Visibility>,
+) {
+    let Some(state) = photo_mode.0.take() else {
+        return;
+    };
+
+    for (mut transform, mut projection) in &mut camera_query {
+        *transform = state.camera_transform;
+        projection.scale = state.camera_scale;
+    }
+
+    for (entity, visibility) in state.hidden_ui {
+        // some of the UI may have been despawned in the meantime
+        if let Ok(mut current) = visibility_query.get_mut(entity) {
+            *current = visibility;
+        }
+    }
+}
+
+/// Photo mode is only for while the run is paused, so it's over as soon as the run starts going again
+fn leave_photo_mode_on_unpause(_trigger: Trigger<SequenceUnpaused>, mut commands: Commands) {
+    commands.trigger(LeavePhotoMode);
+}
+
+/// Makes sure the UI isn't left hidden when leaving the run from photo mode
+fn leave_photo_mode_on_exit(mut commands: Commands) {
+    commands.trigger(LeavePhotoMode);
+}
+
+/// Pans and zooms the camera with the keyboard or the mouse
+fn move_photo_camera(
+    time: Res<Time>,
+    input: ActionInput,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut wheel_events: EventReader<MouseWheel>,
+    photo_mode: Res<PhotoMode>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+    let Some(state) = &photo_mode.0 else {
+        return;
+    };
+
+    let mut pan = Vec2::ZERO;
+    for (action, direction) in [
+        (InputAction::PhotoPanUp, Vec2::Y),
+        (InputAction::PhotoPanLeft, Vec2::NEG_X),
+        (InputAction::PhotoPanDown, Vec2::NEG_Y),
+        (InputAction::PhotoPanRight, Vec2::X),
+    ] {
+        if input.pressed(action) {
+            pan += direction;
+        }
+    }
+    let mut pan = pan.normalize_or_zero() * PAN_SPEED * time.delta_seconds();
+
+    // the level moves along with the mouse while it's dragged
+    let drag = motion_events.read().map(|event| event.delta).sum::<Vec2>();
+    if mouse.pressed(MouseButton::Left) {
+        pan += Vec2::new(-drag.x, drag.y);
+    }
+
+    let mut zoom = 1.0;
+    if input.pressed(InputAction::PhotoZoomOut) {
+        zoom *= ZOOM_SPEED.powf(time.delta_seconds());
+    }
+    if input.pressed(InputAction::PhotoZoomIn) {
+        zoom /= ZOOM_SPEED.powf(time.delta_seconds());
+    }
+    for event in wheel_events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            // a line is about 20 pixels
+            MouseScrollUnit::Pixel => event.y / 20.0,
+        };
+        zoom /= SCROLL_ZOOM_STEP.powf(lines);
+    }
+
+    for (mut transform, mut projection) in &mut camera_query {
+        // pan by the same amount on screen however zoomed in the camera is
+        let moved = transform.translation.truncate() + (pan * projection.scale);
+        let min = state.camera_transform.translation.truncate() - Vec2::splat(MAX_PAN);
+        let max = state.camera_transform.translation.truncate() + Vec2::splat(MAX_PAN);
+        transform.translation = moved.clamp(min, max).extend(transform.translation.z);
+
+        projection.scale = (projection.scale * zoom)
+            .clamp(state.camera_scale * MIN_ZOOM, state.camera_scale * MAX_ZOOM);
+    }
+}
+
+fn take_photo(
+    input: ActionInput,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+) {
+    if !input.just_pressed(InputAction::TakePhoto) {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Some(path) = new_photo_path() else {
+        return;
+    };
+
+    if screenshot_manager
+        .save_screenshot_to_disk(window, path)
+        .is_err()
+    {
+        warn!("could not take photo: another screenshot is already in progress");
+    }
+}
+
+/// Builds a path in the user's data directory for a new photo, making the directory if it doesn't exist yet.
+/// Returns `None` if the directory couldn't be made.
+#[cfg(not(target_family = "wasm"))]
+fn new_photo_path() -> Option<PathBuf> {
+    let dir = dirs::data_dir()
+        .unwrap_or_default()
+        .join("looprunner")
+        .join("photos");
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("could not create photo directory {}: {e}", dir.display());
+        return None;
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    Some(dir.join(format!("photo-{timestamp}.png")))
+}
+
+/// Gets the name of a new photo. The browser downloads it, so there's no directory to put it in.
+#[cfg(target_family = "wasm")]
+fn new_photo_path() -> Option<PathBuf> {
+    Some(PathBuf::from("looprunner-photo.png"))
+}
@@ -0,0 +1,154 @@
+//! Star ratings for each level and unlocked rewards, saved between sessions.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{self, Saver};
+
+use super::{
+    audio::sfx::SoundBank,
+    boss::BossDefeated,
+    skins::Skin,
+    spawn::{
+        level::{CurrentLevel, GameMode, LevelCleared, TOTAL_LEVELS},
+        sequencer::{DeathEvent, Sequence},
+    },
+};
+
+const PROGRESS_SAVE_KEY: &str = "progress";
+
+/// The most notes a sequence can have while still earning the star for a simple pattern
+pub const STAR_NOTE_BUDGET: usize = 24;
+
+/// The most stars a level can be rated
+pub const MAX_STARS: u8 = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(persistence::load::<Progress>(PROGRESS_SAVE_KEY).unwrap_or_default());
+    app.init_resource::<LevelDeaths>();
+    app.observe(count_level_death);
+    app.observe(rate_cleared_level);
+    app.observe(unlock_boss_reward);
+}
+
+/// The best star rating earned on each campaign level, and the rewards earned so far.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct Progress {
+    best_stars: BTreeMap<u32, u8>,
+    /// The sound banks the player has unlocked, other than the standard one
+    #[serde(default)]
+    sound_banks: BTreeSet<SoundBank>,
+    /// The skins the player has unlocked, other than the standard one
+    #[serde(default)]
+    skins: BTreeSet<Skin>,
+}
+
+impl Progress {
+    /// Gets the best number of stars earned on the provided level, or 0 if it's never been cleared
+    pub fn stars(&self, level: u32) -> u8 {
+        self.best_stars
+            .get(&(level % TOTAL_LEVELS))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Gets the number of stars earned across every level
+    pub fn total_stars(&self) -> u32 {
+        self.best_stars
+            .values()
+            .map(|stars| u32::from(*stars))
+            .sum()
+    }
+
+    /// Whether the player can pick the provided sound bank
+    pub fn has_sound_bank(&self, bank: SoundBank) -> bool {
+        bank == SoundBank::Standard || self.sound_banks.contains(&bank)
+    }
+
+    /// Whether the player can pick the provided skin
+    pub fn has_skin(&self, skin: Skin) -> bool {
+        skin == Skin::Standard || self.skins.contains(&skin)
+    }
+
+    /// Unlocks the provided skin, returning whether the player didn't have it already
+    pub fn unlock_skin(&mut self, skin: Skin, saver: &Saver) -> bool {
+        if self.has_skin(skin) {
+            return false;
+        }
+
+        self.skins.insert(skin);
+        saver.save(PROGRESS_SAVE_KEY, &*self);
+        true
+    }
+}
+
+/// The number of times the player has died on each level since they last cleared it.
+#[derive(Resource, Debug, Default)]
+struct LevelDeaths(BTreeMap<u32, u32>);
+
+/// Determines the star rating for clearing a level.
+/// Clearing it at all is worth one star, clearing it without dying is worth another,
+/// and clearing it with a simple pattern is worth the last one.
+fn rate(deaths: u32, active_notes: usize) -> u8 {
+    let mut stars = 1;
+    if deaths == 0 {
+        stars += 1;
+    }
+    if active_notes <= STAR_NOTE_BUDGET {
+        stars += 1;
+    }
+    stars
+}
+
+fn count_level_death(
+    _trigger: Trigger<DeathEvent>,
+    current_level: Res<CurrentLevel>,
+    game_mode: Res<GameMode>,
+    mut level_deaths: ResMut<LevelDeaths>,
+) {
+    if *game_mode != GameMode::Campaign {
+        return;
+    }
+
+    *level_deaths
+        .0
+        .entry(current_level.0 % TOTAL_LEVELS)
+        .or_default() += 1;
+}
+
+fn rate_cleared_level(
+    trigger: Trigger<LevelCleared>,
+    game_mode: Res<GameMode>,
+    sequence: Res<Sequence>,
+    mut level_deaths: ResMut<LevelDeaths>,
+    mut progress: ResMut<Progress>,
+    saver: Saver,
+) {
+    if *game_mode != GameMode::Campaign {
+        return;
+    }
+
+    let level = trigger.event().0 % TOTAL_LEVELS;
+    let deaths = level_deaths.0.remove(&level).unwrap_or(0);
+    let stars = rate(deaths, sequence.num_active_notes());
+    if stars > progress.stars(level) {
+        progress.best_stars.insert(level, stars);
+        saver.save(PROGRESS_SAVE_KEY, &*progress);
+    }
+}
+
+fn unlock_boss_reward(
+    trigger: Trigger<BossDefeated>,
+    mut progress: ResMut<Progress>,
+    saver: Saver,
+) {
+    let Some(bank) = trigger.event().reward else {
+        return;
+    };
+
+    if progress.sound_banks.insert(bank) {
+        saver.save(PROGRESS_SAVE_KEY, &*progress);
+    }
+}
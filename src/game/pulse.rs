@@ -0,0 +1,119 @@
+//! Makes things in the world pulse in time with the sequence.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    settings::Settings,
+    spawn::sequencer::{PlayTick, Sequence},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PulseOnBeat>();
+    app.observe(start_pulses);
+    app.add_systems(Update, update_pulses.in_set(AppSet::Update));
+}
+
+/// Makes an entity briefly grow and brighten whenever any notes play.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct PulseOnBeat {
+    /// How much bigger the entity gets at the start of a pulse, as a fraction of its normal size
+    pub scale: f32,
+    /// How much brighter the entity gets at the start of a pulse, as a fraction of its normal brightness
+    pub brightness: f32,
+    /// How long a pulse takes to fade out, in seconds
+    pub duration: f32,
+    /// How much of the current pulse is left, in seconds
+    remaining: f32,
+    /// The entity's scale and color when it isn't pulsing, recorded the first time it pulses
+    resting: Option<(Vec3, Color)>,
+}
+
+impl PulseOnBeat {
+    pub fn new(scale: f32, brightness: f32, duration: f32) -> PulseOnBeat {
+        PulseOnBeat {
+            scale,
+            brightness,
+            duration,
+            remaining: 0.0,
+            resting: None,
+        }
+    }
+
+    /// A subtle pulse for obstacles, so they bob along with the music without getting in the way
+    pub fn obstacle() -> PulseOnBeat {
+        PulseOnBeat::new(0.06, 0.25, 0.12)
+    }
+
+    /// A gentle brightening for the background
+    pub fn background() -> PulseOnBeat {
+        PulseOnBeat::new(0.0, 0.15, 0.2)
+    }
+
+    /// Changes the color of a pulsing entity's sprite. If it's partway through a pulse, the new color is used once the pulse is over,
+    /// rather than being overwritten by the pulse.
+    pub fn set_color(&mut self, sprite: &mut Sprite, color: Color) {
+        match &mut self.resting {
+            Some((_, resting_color)) => *resting_color = color,
+            None => sprite.color = color,
+        }
+    }
+}
+
+fn start_pulses(
+    trigger: Trigger<PlayTick>,
+    sequence: Res<Sequence>,
+    settings: Res<Settings>,
+    mut pulse_query: Query<&mut PulseOnBeat>,
+) {
+    if settings.reduced_flashing {
+        return;
+    }
+
+    if sequence.notes_at_tick(trigger.event().0).next().is_none() {
+        return;
+    }
+
+    for mut pulse in &mut pulse_query {
+        pulse.remaining = pulse.duration;
+    }
+}
+
+fn update_pulses(
+    time: Res<Time>,
+    mut pulse_query: Query<(&mut PulseOnBeat, &mut Transform, &mut Sprite)>,
+) {
+    for (mut pulse, mut transform, mut sprite) in &mut pulse_query {
+        if pulse.remaining <= 0.0 && pulse.resting.is_none() {
+            continue;
+        }
+
+        let (resting_scale, resting_color) =
+            *pulse.resting.get_or_insert((transform.scale, sprite.color));
+
+        pulse.remaining = (pulse.remaining - time.delta_seconds()).max(0.0);
+        let strength = if pulse.duration > 0.0 {
+            pulse.remaining / pulse.duration
+        } else {
+            0.0
+        };
+
+        transform.scale = resting_scale * (1.0 + pulse.scale * strength);
+
+        let brightness = 1.0 + pulse.brightness * strength;
+        let resting_color = resting_color.to_linear();
+        sprite.color = LinearRgba {
+            red: resting_color.red * brightness,
+            green: resting_color.green * brightness,
+            blue: resting_color.blue * brightness,
+            alpha: resting_color.alpha,
+        }
+        .into();
+
+        if pulse.remaining <= 0.0 {
+            pulse.resting = None;
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! Decorations in the background of a level, like clouds and a city skyline, that scroll by slower than the level does so it seems to have depth.
+//! What's in the background comes from the level's theme. The same pieces are reused from level to level,
+//! so changing levels only rearranges them instead of spawning new ones.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::screen::Screen;
+
+use super::{
+    movement::TotalDistance,
+    scrolling::{update_scroll_position, ScrollPosition},
+    settings::Settings,
+    spawn::level::{LEVEL_WIDTH, TOP_OF_FLOOR},
+    theme::ActiveTheme,
+};
+
+/// How far past each side of the level pieces go before they loop back around to the other side, in pixels
+const SCENERY_MARGIN: f32 = 300.0;
+
+/// How wide the strip the pieces loop around in is, in pixels
+const SCENERY_SPAN: f32 = LEVEL_WIDTH + (SCENERY_MARGIN * 2.0);
+
+/// Clouds go behind the skyline
+const CLOUD_Z: f32 = -0.97;
+const SKYLINE_Z: f32 = -0.95;
+
+/// How many pixels wide and tall the image clouds are drawn with is
+const CLOUD_IMAGE_SIZE: u32 = 32;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SceneryPool>();
+    app.init_resource::<CloudImage>();
+    app.add_systems(
+        Update,
+        (
+            lay_out_scenery.run_if(resource_changed::<ActiveTheme>),
+            scroll_scenery,
+        )
+            .chain()
+            // after the view has followed the player, so the pieces stay in step with it
+            .after(update_scroll_position)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(OnExit(Screen::Playing), empty_scenery_pool);
+}
+
+/// A group of decorations in a level's background.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SceneryLayer {
+    pub kind: SceneryKind,
+    /// The color of the pieces, as sRGB components from 0 to 1
+    pub color: [f32; 3],
+    /// How see-through the pieces are, from 0 for invisible to 1 for solid
+    #[serde(default = "SceneryLayer::default_opacity")]
+    pub opacity: f32,
+    /// How many pieces there are
+    pub count: usize,
+    /// How fast the pieces scroll by compared to the player running, from 0 for not at all to 1 for as fast as the level
+    pub parallax: f32,
+    /// How fast the pieces move to the left on their own, in pixels/sec
+    #[serde(default)]
+    pub drift: f32,
+}
+
+impl SceneryLayer {
+    fn default_opacity() -> f32 {
+        1.0
+    }
+}
+
+/// The kinds of decorations there can be in a level's background.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneryKind {
+    /// Soft puffs up in the sky
+    Clouds,
+    /// Buildings standing on the floor
+    Skyline,
+}
+
+/// One decoration in the background.
+#[derive(Component, Debug)]
+struct SceneryPiece {
+    /// Where the piece is relative to the middle of the view, in pixels
+    x: f32,
+    parallax: f32,
+    drift: f32,
+    width: f32,
+}
+
+/// Every piece of scenery that's been spawned, whether it's in use or not.
+#[derive(Resource, Debug, Default)]
+struct SceneryPool(Vec<Entity>);
+
+/// The soft round image clouds are drawn with.
+#[derive(Resource, Debug)]
+struct CloudImage(Handle<Image>);
+
+impl FromWorld for CloudImage {
+    fn from_world(world: &mut World) -> Self {
+        // white in the middle, fading out towards the edges
+        let center = (CLOUD_IMAGE_SIZE as f32 - 1.0) / 2.0;
+        let data = (0..CLOUD_IMAGE_SIZE * CLOUD_IMAGE_SIZE)
+            .flat_map(|i| {
+                let offset = Vec2::new(
+                    (i % CLOUD_IMAGE_SIZE) as f32 - center,
+                    (i / CLOUD_IMAGE_SIZE) as f32 - center,
+                );
+                let distance = (offset.length() / center).min(1.0);
+                let alpha = 1.0 - distance * distance;
+                [255, 255, 255, (alpha * 255.0) as u8]
+            })
+            .collect();
+
+        let mut image = Image::new(
+            Extent3d {
+                width: CLOUD_IMAGE_SIZE,
+                height: CLOUD_IMAGE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::linear();
+        CloudImage(world.resource_mut::<Assets<Image>>().add(image))
+    }
+}
+
+/// Arranges the scenery for the level's theme, reusing the pieces that are already there and hiding any that aren't needed
+fn lay_out_scenery(
+    active_theme: Res<ActiveTheme>,
+    scroll_position: Res<ScrollPosition>,
+    cloud_image: Res<CloudImage>,
+    mut pool: ResMut<SceneryPool>,
+    mut piece_query: Query<(
+        &mut SceneryPiece,
+        &mut Sprite,
+        &mut Handle<Image>,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    mut commands: Commands,
+) {
+    let mut rng = rand::thread_rng();
+    let pieces = active_theme.theme.scenery.iter().flat_map(|layer| {
+        (0..layer.count).map(move |i| (layer, (i as f32 + 0.5) / layer.count as f32))
+    });
+
+    let mut used = 0;
+    for (layer, spread) in pieces {
+        let size = match layer.kind {
+            SceneryKind::Clouds => {
+                Vec2::new(rng.gen_range(120.0..260.0), rng.gen_range(40.0..80.0))
+            }
+            SceneryKind::Skyline => {
+                Vec2::new(rng.gen_range(60.0..160.0), rng.gen_range(80.0..300.0))
+            }
+        };
+        // spread out evenly along the strip, with a bit of wiggle so they don't look lined up
+        let x = -SCENERY_SPAN / 2.0
+            + (spread * SCENERY_SPAN)
+            + rng.gen_range(-0.25..0.25) * (SCENERY_SPAN / layer.count as f32);
+        let (y, z, texture) = match layer.kind {
+            SceneryKind::Clouds => (
+                rng.gen_range((TOP_OF_FLOOR + 350.0)..(LEVEL_WIDTH / 2.0 - 50.0)),
+                CLOUD_Z,
+                cloud_image.0.clone(),
+            ),
+            SceneryKind::Skyline => (TOP_OF_FLOOR + (size.y / 2.0), SKYLINE_Z, Handle::default()),
+        };
+
+        let [red, green, blue] = layer.color;
+        let new_piece = SceneryPiece {
+            x,
+            parallax: layer.parallax,
+            drift: layer.drift,
+            width: size.x,
+        };
+        let new_sprite = Sprite {
+            color: Color::srgba(red, green, blue, layer.opacity),
+            custom_size: Some(size),
+            ..default()
+        };
+        let new_transform = Transform::from_xyz(scroll_position.0 + x, y, z);
+
+        match pool
+            .0
+            .get(used)
+            .and_then(|entity| piece_query.get_mut(*entity).ok())
+        {
+            Some((mut piece, mut sprite, mut image, mut transform, mut visibility)) => {
+                *piece = new_piece;
+                *sprite = new_sprite;
+                *image = texture;
+                *transform = new_transform;
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                let entity = commands
+                    .spawn((
+                        Name::new("Scenery"),
+                        new_piece,
+                        StateScoped(Screen::Playing),
+                        SpriteBundle {
+                            sprite: new_sprite,
+                            texture,
+                            transform: new_transform,
+                            ..default()
+                        },
+                    ))
+                    .id();
+                pool.0.push(entity);
+            }
+        }
+        used += 1;
+    }
+
+    for entity in pool.0.iter().skip(used) {
+        if let Ok((.., mut visibility)) = piece_query.get_mut(*entity) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// Scrolls the scenery along as the player runs, looping pieces that go off the left side back around to the right.
+/// The pieces stay with the view when it follows the player in scrolling mode.
+fn scroll_scenery(
+    time: Res<Time>,
+    distance: Res<TotalDistance>,
+    settings: Res<Settings>,
+    scroll_position: Res<ScrollPosition>,
+    mut last_distance: Local<f32>,
+    mut piece_query: Query<(&mut SceneryPiece, &mut Transform)>,
+) {
+    // the distance goes back to 0 when the run starts over, which isn't the player running backwards
+    let moved = (distance.0 - *last_distance).max(0.0);
+    *last_distance = distance.0;
+
+    for (mut piece, mut transform) in &mut piece_query {
+        if !settings.reduced_motion {
+            piece.x -= (piece.parallax * moved) + (piece.drift * time.delta_seconds());
+            if piece.x + (piece.width / 2.0) < -SCENERY_SPAN / 2.0 {
+                piece.x += SCENERY_SPAN + piece.width;
+            }
+        }
+        transform.translation.x = scroll_position.0 + piece.x;
+    }
+}
+
+/// The pieces are despawned along with the rest of the run, so forget about them
+fn empty_scenery_pool(mut pool: ResMut<SceneryPool>) {
+    pool.0.clear();
+}
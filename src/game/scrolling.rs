@@ -0,0 +1,199 @@
+//! Scrolling mode, where the level never wraps around. It goes on and on to the right instead, a stretch at a time,
+//! with new stretches streaming in ahead of the player and old ones going away once they're left behind.
+//! Each stretch is laid out like one of the levels endless mode generates, and the view follows the player along.
+//! The view follows the player through levels more than one screen long in the other modes the same way, stopping at
+//! the ends of the level.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    collision::ColliderGrid,
+    spawn::{
+        level::{
+            Background, CurrentLevel, DespawnChunk, Floor, GameMode, LevelCleared, LevelWidth,
+            SpawnChunk, SpawnObstacles, LEVEL_WIDTH,
+        },
+        player::{Player, PLAYER_IMAGE_SIZE},
+    },
+    stats::RunStats,
+    versus::Opponent,
+};
+
+/// How far ahead of the runner out in front stretches of the level are spawned, in pixels
+const SPAWN_AHEAD: f32 = LEVEL_WIDTH * 1.5;
+
+/// How far behind the runner out in front stretches of the level have to be to go away, in pixels
+const DESPAWN_BEHIND: f32 = LEVEL_WIDTH;
+
+/// How far behind the middle of the view the runner out in front is kept once the view starts following them, in pixels
+const LEAD_DISTANCE: f32 = LEVEL_WIDTH / 4.0;
+
+/// How far the view can get from the middle of the floor before the floor is moved back under it, in pixels.
+/// The floor sticks out past each side of the level by more than this, so its ends never come into view.
+const FLOOR_SLACK: f32 = 200.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ScrollPosition>();
+    app.init_resource::<StreamedChunks>();
+    app.observe(reset_scrolling);
+    app.add_systems(
+        Update,
+        // after the player has moved, so the view and the level keep up with where they are now
+        (
+            (stream_chunks, advance_level).run_if(resource_equals(GameMode::Scrolling)),
+            update_scroll_position,
+            keep_floor_in_view,
+        )
+            .chain()
+            .after(AppSet::Update),
+    );
+}
+
+/// How far to the right the view has followed the player, in pixels.
+/// This stays at 0 outside of scrolling mode, unless the level is more than one screen long.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct ScrollPosition(pub f32);
+
+impl ScrollPosition {
+    /// Gets how far to pan a sound coming from the provided horizontal position in the level, going by what's in view
+    pub fn pan_for(&self, x: f32) -> f32 {
+        (x - self.0) / (LEVEL_WIDTH / 2.0)
+    }
+}
+
+/// Which stretches of the level have been spawned so far in scrolling mode.
+/// Each one is one level wide, with the one the run started on right where a level normally is.
+#[derive(Resource, Debug, Default)]
+struct StreamedChunks {
+    /// The level the run started on
+    first: u32,
+    /// The oldest level whose stretch is still around
+    oldest: u32,
+    /// The next level to spawn a stretch for
+    next: u32,
+}
+
+impl StreamedChunks {
+    /// Gets how far to the right of where a level normally is the stretch for the provided level goes, in pixels
+    fn offset(&self, level: u32) -> f32 {
+        (level - self.first) as f32 * LEVEL_WIDTH
+    }
+}
+
+/// Goes back to the start of the level when the run starts over, putting the floor back under it
+fn reset_scrolling(
+    trigger: Trigger<SpawnObstacles>,
+    mut chunks: ResMut<StreamedChunks>,
+    mut scroll_position: ResMut<ScrollPosition>,
+    mut floor_query: Query<&mut Transform, With<Floor>>,
+) {
+    let level = trigger.event().level;
+    *chunks = StreamedChunks {
+        first: level,
+        oldest: level,
+        next: level,
+    };
+    scroll_position.set_if_neq(ScrollPosition(0.0));
+    for mut transform in &mut floor_query {
+        transform.translation.x = 0.0;
+    }
+}
+
+/// Spawns the stretches of the level coming up, and gets rid of the ones that have been left behind
+fn stream_chunks(
+    runner_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    mut chunks: ResMut<StreamedChunks>,
+    mut commands: Commands,
+) {
+    let Some(leader_x) = leader_x(&runner_query) else {
+        return;
+    };
+
+    while chunks.offset(chunks.next) - (LEVEL_WIDTH / 2.0) < leader_x + SPAWN_AHEAD {
+        commands.trigger(SpawnChunk {
+            level: chunks.next,
+            x: chunks.offset(chunks.next),
+        });
+        chunks.next += 1;
+    }
+
+    while chunks.oldest < chunks.next
+        && chunks.offset(chunks.oldest) + (LEVEL_WIDTH / 2.0) < leader_x - DESPAWN_BEHIND
+    {
+        commands.trigger(DespawnChunk(chunks.oldest));
+        chunks.oldest += 1;
+    }
+}
+
+/// Counts each stretch of the level as cleared once the runner out in front has fully left it,
+/// the same as they would a level in the other modes
+fn advance_level(
+    runner_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    chunks: Res<StreamedChunks>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut stats: ResMut<RunStats>,
+    mut commands: Commands,
+) {
+    let Some(leader_x) = leader_x(&runner_query) else {
+        return;
+    };
+
+    let player_left_edge = leader_x - (PLAYER_IMAGE_SIZE / 2.0);
+    while player_left_edge > chunks.offset(current_level.0) + (LEVEL_WIDTH / 2.0) {
+        commands.trigger(LevelCleared(current_level.0));
+        current_level.0 += 1;
+        stats.levels_cleared += 1;
+    }
+}
+
+/// Follows the runner out in front once they're far enough along, keeping the level ahead of them in view.
+/// Outside of scrolling mode this stops once the right edge of the level is in view.
+pub fn update_scroll_position(
+    runner_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    game_mode: Res<GameMode>,
+    level_width: Res<LevelWidth>,
+    mut scroll_position: ResMut<ScrollPosition>,
+) {
+    let Some(leader_x) = leader_x(&runner_query) else {
+        return;
+    };
+
+    let furthest = if *game_mode == GameMode::Scrolling {
+        f32::INFINITY
+    } else {
+        level_width.0 - LEVEL_WIDTH
+    };
+    scroll_position.set_if_neq(ScrollPosition(
+        (leader_x + LEAD_DISTANCE).clamp(0.0, furthest),
+    ));
+}
+
+/// Keeps the background behind the view, and moves the floor along under it whenever it's gotten far enough away
+fn keep_floor_in_view(
+    scroll_position: Res<ScrollPosition>,
+    mut grid: ResMut<ColliderGrid>,
+    mut background_query: Query<&mut Transform, (With<Background>, Without<Floor>)>,
+    mut floor_query: Query<&mut Transform, (With<Floor>, Without<Background>)>,
+) {
+    for mut transform in &mut background_query {
+        transform.translation.x = scroll_position.0;
+    }
+
+    for mut transform in &mut floor_query {
+        if (transform.translation.x - scroll_position.0).abs() > FLOOR_SLACK {
+            transform.translation.x = scroll_position.0;
+            // the floor is a collider like any other
+            grid.mark_stale();
+        }
+    }
+}
+
+/// Gets where the runner out in front is, not counting the opponent in versus
+fn leader_x(runner_query: &Query<&Transform, (With<Player>, Without<Opponent>)>) -> Option<f32> {
+    runner_query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .max_by(f32::total_cmp)
+}
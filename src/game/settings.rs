@@ -0,0 +1,104 @@
+//! Player preferences, saved between sessions.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence::{self, Saver},
+    ui::palette::BeatPalette,
+};
+
+use super::{assists::Assists, audio::sfx::SoundBank, localization::Language, skins::Skin};
+
+const SETTINGS_SAVE_KEY: &str = "settings";
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Settings>();
+    app.insert_resource(persistence::load::<Settings>(SETTINGS_SAVE_KEY).unwrap_or_default());
+    app.add_systems(Update, save_settings.run_if(resource_changed::<Settings>));
+}
+
+/// Applies the settings that change how the game looks
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.insert_resource(BeatPalette::STANDARD);
+    app.add_systems(Update, apply_settings.run_if(resource_changed::<Settings>));
+}
+
+/// Accessibility, display, and sound options.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Turns off effects that flash in time with the music, like pulsing obstacles and portal flashes
+    pub reduced_flashing: bool,
+    /// Uses colors for the sequencer that are easier to tell apart with color vision deficiencies
+    pub colorblind_palette: bool,
+    /// How big the UI is drawn, relative to its normal size
+    pub ui_scale: f32,
+    /// Skips animations that aren't needed to play, like fading between screens
+    pub reduced_motion: bool,
+    /// Zooms the camera in a little on each kick, and out as the runners speed up
+    pub camera_zoom: bool,
+    /// Quietly plays a row's sound when hovering over one of its empty beats
+    pub preview_on_hover: bool,
+    /// Starts the run over on its own after a short countdown when the player dies
+    pub auto_restart: bool,
+    /// Draws lines in the level showing where the player will be on each upcoming beat
+    pub beat_markers: bool,
+    /// Counts in a few beats before a paused run picks back up
+    pub resume_count_in: bool,
+    /// The language all the text is shown in
+    pub language: Language,
+    /// The drum sounds the percussion rows play with
+    pub sound_bank: SoundBank,
+    /// How the runner looks
+    pub skin: Skin,
+    /// Records anonymous events about how runs go, like where the player died, to help with tuning the levels
+    pub telemetry: bool,
+    /// Options that make runs easier
+    pub assists: Assists,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            reduced_flashing: false,
+            colorblind_palette: false,
+            ui_scale: 1.0,
+            reduced_motion: false,
+            camera_zoom: true,
+            preview_on_hover: false,
+            auto_restart: false,
+            beat_markers: true,
+            resume_count_in: true,
+            language: Language::English,
+            sound_bank: SoundBank::Standard,
+            skin: Skin::Standard,
+            telemetry: false,
+            assists: Assists::default(),
+        }
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut beat_palette: ResMut<BeatPalette>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let palette = if settings.colorblind_palette {
+        BeatPalette::COLORBLIND
+    } else {
+        BeatPalette::STANDARD
+    };
+    beat_palette.set_if_neq(palette);
+
+    if ui_scale.0 != settings.ui_scale {
+        ui_scale.0 = settings.ui_scale;
+    }
+}
+
+fn save_settings(settings: Res<Settings>, saver: Saver) {
+    if !settings.is_added() {
+        saver.save(SETTINGS_SAVE_KEY, &*settings);
+    }
+}
@@ -0,0 +1,169 @@
+//! Alternate looks for the runner, unlocked by doing well and picked on the cosmetics screen.
+//! Every skin is laid out just like the standard sprite sheet, so they all animate the same way.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{persistence::Saver, screen::Screen, ui::banner::Banners};
+
+use super::{
+    assets::{FontKey, HandleMap, ImageKey},
+    boss::BossDefeated,
+    localization::Localization,
+    milestones::MilestoneReached,
+    progress::{Progress, MAX_STARS},
+    spawn::level::TOTAL_LEVELS,
+};
+
+/// How far the player has to make it in one run to unlock the frost skin, in feet
+const FROST_DISTANCE: u32 = 500;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(unlock_distance_skin);
+    app.observe(unlock_boss_skin);
+    app.add_systems(
+        Update,
+        unlock_star_skin.run_if(resource_changed::<Progress>),
+    );
+}
+
+/// A look for the runner
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Reflect,
+)]
+pub enum Skin {
+    #[default]
+    Standard,
+    /// Unlocked by making it far enough in one run
+    Frost,
+    /// Unlocked by beating a boss
+    Moss,
+    /// Unlocked by earning every star in the campaign
+    Midnight,
+}
+
+impl Skin {
+    pub const ALL: [Skin; 4] = [Skin::Standard, Skin::Frost, Skin::Moss, Skin::Midnight];
+
+    /// Gets the name of this skin, in the player's language
+    pub fn name(self, localization: &Localization) -> String {
+        match self {
+            Skin::Standard => localization.get("skin.standard"),
+            Skin::Frost => localization.get("skin.frost"),
+            Skin::Moss => localization.get("skin.moss"),
+            Skin::Midnight => localization.get("skin.midnight"),
+        }
+    }
+
+    /// Describes how to unlock this skin, in the player's language
+    pub fn unlock_hint(self, localization: &Localization) -> String {
+        match self {
+            Skin::Standard => String::new(),
+            Skin::Frost => localization.format("skin.frost_hint", &[("feet", &FROST_DISTANCE)]),
+            Skin::Moss => localization.get("skin.moss_hint"),
+            Skin::Midnight => localization.get("skin.midnight_hint"),
+        }
+    }
+
+    /// Gets the sprite sheet the runner is drawn with when wearing this skin
+    pub fn image_key(self) -> ImageKey {
+        match self {
+            Skin::Standard => ImageKey::Player,
+            Skin::Frost => ImageKey::PlayerFrost,
+            Skin::Moss => ImageKey::PlayerMoss,
+            Skin::Midnight => ImageKey::PlayerMidnight,
+        }
+    }
+}
+
+/// Unlocks the provided skin if the player doesn't have it yet, and lets them know about it
+fn unlock(
+    skin: Skin,
+    progress: &mut Progress,
+    saver: &Saver,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+    commands: &mut Commands,
+) {
+    if !progress.unlock_skin(skin, saver) {
+        return;
+    }
+
+    commands
+        .banner(
+            localization.format("skin.unlocked", &[("skin", &skin.name(localization))]),
+            font_handles,
+        )
+        .insert(StateScoped(Screen::Playing));
+}
+
+fn unlock_distance_skin(
+    trigger: Trigger<MilestoneReached>,
+    mut progress: ResMut<Progress>,
+    saver: Saver,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    if trigger.event().0 >= FROST_DISTANCE {
+        unlock(
+            Skin::Frost,
+            &mut progress,
+            &saver,
+            &font_handles,
+            &localization,
+            &mut commands,
+        );
+    }
+}
+
+fn unlock_boss_skin(
+    _trigger: Trigger<BossDefeated>,
+    mut progress: ResMut<Progress>,
+    saver: Saver,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    unlock(
+        Skin::Moss,
+        &mut progress,
+        &saver,
+        &font_handles,
+        &localization,
+        &mut commands,
+    );
+}
+
+fn unlock_star_skin(
+    mut progress: ResMut<Progress>,
+    saver: Saver,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    // checked before touching the progress, so unlocking doesn't count as it changing again
+    if !progress.has_skin(Skin::Midnight)
+        && progress.total_stars() >= TOTAL_LEVELS * u32::from(MAX_STARS)
+    {
+        unlock(
+            Skin::Midnight,
+            &mut progress,
+            &saver,
+            &font_handles,
+            &localization,
+            &mut commands,
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Song mode, where the sequencer plays through an arrangement of patterns instead of looping the same one.
+//! The patterns live in a small bank, and whichever one is being edited or played is swapped into [`Sequence`].
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+use crate::{
+    screen::Screen,
+    ui::{
+        interaction::{ButtonActivated, Enabled, InteractionPalette},
+        palette::{
+            greyed_out, BUTTON_HOVERED_BACKGROUND, BUTTON_PRESSED_BACKGROUND, NODE_BACKGROUND,
+            SELECTED_BUTTON_BACKGROUND,
+        },
+        tooltip::Tooltip,
+        widgets::Widgets,
+    },
+};
+
+use super::{
+    assets::{FontKey, HandleMap},
+    localization::Localization,
+    spawn::sequencer::{
+        RefreshBeatButtons, ResetSequence, Sequence, SequenceLooped, SequenceStarted,
+        SetBeatButtonsEnabled,
+    },
+};
+
+/// How many patterns there are in the bank
+pub const NUM_PATTERNS: usize = 4;
+
+/// The most slots a song can have
+const MAX_SONG_LENGTH: usize = 16;
+
+/// The width of the buttons for patterns and song slots, in pixels
+const SONG_BUTTON_WIDTH: f32 = 35.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Song::new());
+    app.observe(start_song);
+    app.observe(advance_song);
+    app.observe(rewind_song);
+}
+
+/// The bar for editing patterns and arranging them into a song
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.register_type::<SongAction>();
+    app.observe(set_pattern_buttons_enabled);
+    app.add_systems(
+        Update,
+        (
+            handle_song_action.run_if(in_state(Screen::Playing)),
+            update_song_bar,
+        )
+            .chain(),
+    );
+}
+
+/// The bank of patterns, and the order they're played in when song mode is on.
+#[derive(Resource)]
+pub struct Song {
+    /// Every pattern in the bank. The one that's loaded is out in [`Sequence`], so its spot here is out of date.
+    patterns: Vec<Sequence>,
+    /// Which pattern is currently in [`Sequence`]
+    loaded: usize,
+    /// The patterns to play, in order
+    arrangement: Vec<usize>,
+    /// Which slot of the arrangement is playing
+    position: usize,
+    /// Whether the sequencer plays through the arrangement, rather than looping the loaded pattern
+    enabled: bool,
+}
+
+impl Song {
+    fn new() -> Song {
+        Song {
+            patterns: (0..NUM_PATTERNS).map(|_| Sequence::new()).collect(),
+            loaded: 0,
+            arrangement: vec![0],
+            position: 0,
+            enabled: false,
+        }
+    }
+
+    /// Puts the loaded pattern back in the bank and swaps the provided one into the sequence
+    fn load(&mut self, pattern: usize, sequence: &mut Sequence) {
+        if pattern == self.loaded {
+            return;
+        }
+
+        std::mem::swap(&mut self.patterns[self.loaded], sequence);
+        std::mem::swap(&mut self.patterns[pattern], sequence);
+        self.loaded = pattern;
+    }
+}
+
+/// Gets the letter a pattern is shown as
+fn pattern_name(pattern: usize) -> String {
+    char::from(b'A' + pattern as u8).to_string()
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SongAction {
+    /// Loads a pattern into the sequencer so it can be edited
+    EditPattern(usize),
+    ToggleSongMode,
+    /// Changes which pattern is played in a slot of the arrangement
+    CycleSlot(usize),
+    AddSlot,
+    RemoveSlot,
+}
+
+/// Holds the buttons for each slot of the arrangement, which are respawned whenever it changes
+#[derive(Component)]
+struct SongSlots;
+
+/// Loads the first pattern of the song when the sequence starts from the top
+fn start_song(
+    _trigger: Trigger<SequenceStarted>,
+    mut song: ResMut<Song>,
+    mut sequence: ResMut<Sequence>,
+    mut commands: Commands,
+) {
+    if !song.enabled {
+        return;
+    }
+
+    song.position = 0;
+    let pattern = song.arrangement[0];
+    song.load(pattern, &mut sequence);
+    commands.trigger(RefreshBeatButtons);
+}
+
+/// Moves on to the next pattern of the song each time the sequence loops
+fn advance_song(
+    _trigger: Trigger<SequenceLooped>,
+    mut song: ResMut<Song>,
+    mut sequence: ResMut<Sequence>,
+    mut commands: Commands,
+) {
+    if !song.enabled {
+        return;
+    }
+
+    song.position = (song.position + 1) % song.arrangement.len();
+    let pattern = song.arrangement[song.position];
+    song.load(pattern, &mut sequence);
+    commands.trigger(RefreshBeatButtons);
+}
+
+fn rewind_song(_trigger: Trigger<ResetSequence>, mut song: ResMut<Song>) {
+    song.position = 0;
+}
+
+/// Spawns the bar for picking which pattern to edit and arranging patterns into a song
+pub fn spawn_song_bar(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    parent
+        .spawn((
+            Name::new("Song bar"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    left: Val::Px(5.0),
+                    justify_content: JustifyContent::Start,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(5.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children
+                .label(localization.get("song.pattern"), font_handles)
+                .insert(Tooltip(localization.get("song.pattern_tooltip")));
+            for pattern in 0..NUM_PATTERNS {
+                spawn_song_button(children, pattern_name(pattern), font_handles)
+                    .insert(SongAction::EditPattern(pattern));
+            }
+
+            children
+                .label(localization.get("song.song"), font_handles)
+                .insert(Tooltip(localization.get("song.song_tooltip")));
+            children
+                .small_button(localization.get("common.off"), font_handles)
+                .insert(SongAction::ToggleSongMode);
+            children.spawn((
+                Name::new("Song slots"),
+                SongSlots,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(3.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+            spawn_song_button(children, "+", font_handles).insert(SongAction::AddSlot);
+            spawn_song_button(children, "-", font_handles).insert(SongAction::RemoveSlot);
+        });
+}
+
+fn spawn_song_button<'a>(
+    parent: &'a mut ChildBuilder,
+    text: impl Into<String>,
+    font_handles: &HandleMap<FontKey>,
+) -> EntityCommands<'a> {
+    let mut entity = parent.small_button(text, font_handles);
+    entity.insert(Style {
+        width: Val::Px(SONG_BUTTON_WIDTH),
+        height: Val::Px(30.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    });
+    entity
+}
+
+/// Gets the colors for a pattern or slot button, which stays highlighted while it's the one in use
+fn song_button_palette(selected: bool) -> InteractionPalette {
+    let none = if selected {
+        SELECTED_BUTTON_BACKGROUND
+    } else {
+        NODE_BACKGROUND
+    };
+    InteractionPalette {
+        none,
+        hovered: BUTTON_HOVERED_BACKGROUND,
+        pressed: BUTTON_PRESSED_BACKGROUND,
+        disabled: greyed_out(none),
+    }
+}
+
+fn handle_song_action(
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&SongAction>,
+    mut song: ResMut<Song>,
+    mut sequence: ResMut<Sequence>,
+    mut commands: Commands,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match *action {
+            SongAction::EditPattern(pattern) => {
+                song.load(pattern, &mut sequence);
+                commands.trigger(RefreshBeatButtons);
+            }
+            SongAction::ToggleSongMode => song.enabled = !song.enabled,
+            SongAction::CycleSlot(slot) => {
+                if let Some(pattern) = song.arrangement.get_mut(slot) {
+                    *pattern = (*pattern + 1) % NUM_PATTERNS;
+                }
+            }
+            SongAction::AddSlot => {
+                if song.arrangement.len() < MAX_SONG_LENGTH {
+                    let last = song.arrangement.last().copied().unwrap_or_default();
+                    song.arrangement.push(last);
+                }
+            }
+            SongAction::RemoveSlot => {
+                if song.arrangement.len() > 1 {
+                    song.arrangement.pop();
+                    song.position = song.position.min(song.arrangement.len() - 1);
+                }
+            }
+        }
+    }
+}
+
+/// Highlights the loaded pattern and playing slot, and respawns the slot buttons to match the arrangement
+fn update_song_bar(
+    song: Res<Song>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    added_slots_query: Query<(), Added<SongSlots>>,
+    mut button_query: Query<(
+        &SongAction,
+        &Interaction,
+        &Children,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    mut text_query: Query<&mut Text>,
+    slots_query: Query<Entity, With<SongSlots>>,
+    mut commands: Commands,
+) {
+    if !song.is_changed() && added_slots_query.is_empty() {
+        return;
+    }
+
+    for (action, interaction, children, mut palette, mut background_color) in &mut button_query {
+        match action {
+            SongAction::EditPattern(pattern) => {
+                *palette = song_button_palette(*pattern == song.loaded);
+                if matches!(interaction, Interaction::None) {
+                    *background_color = BackgroundColor(palette.none);
+                }
+            }
+            SongAction::ToggleSongMode => {
+                let mut texts = text_query.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
+                    text.sections[0].value = localization.get(if song.enabled {
+                        "common.on"
+                    } else {
+                        "common.off"
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    for entity in &slots_query {
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|children| {
+                for (slot, pattern) in song.arrangement.iter().enumerate() {
+                    let palette = song_button_palette(song.enabled && slot == song.position);
+                    spawn_song_button(children, pattern_name(*pattern), &font_handles).insert((
+                        SongAction::CycleSlot(slot),
+                        BackgroundColor(palette.none),
+                        palette,
+                    ));
+                }
+            });
+    }
+}
+
+/// Keeps the pattern from being switched out while the sequence is playing, along with the beat buttons
+fn set_pattern_buttons_enabled(
+    trigger: Trigger<SetBeatButtonsEnabled>,
+    mut button_query: Query<(&SongAction, &mut Enabled)>,
+) {
+    for (action, mut enabled) in &mut button_query {
+        if matches!(action, SongAction::EditPattern(_)) {
+            enabled.0 = trigger.event().0;
+        }
+    }
+}
@@ -1,72 +1,319 @@
 //! Spawn the main level by triggering other observers.
 
-use bevy::prelude::*;
+use bevy::{
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     game::{
-        assets::{FontKey, HandleMap, ImageKey},
-        movement::TotalDistance,
+        assets::{HandleMap, ImageKey},
+        boss::is_boss_level,
+        difficulty::{Difficulty, DifficultyCurve},
+        hud::SpawnHud,
+        level_definition::{CustomLevels, EditorLevel, LevelDefinition, ObstacleKind},
+        movement::AlreadyCleared,
+        mutators::{Mutator, Mutators},
+        near_miss::ClosestApproach,
+        pulse::PulseOnBeat,
+        settings::Settings,
+        theme::ActiveTheme,
+        tutorial::Tutorial,
         SHOW_COLLIDERS,
     },
-    ui::palette::LABEL_TEXT,
     AppSet,
 };
 
-use super::{player::SpawnPlayer, sequencer::SpawnSequencer};
+use super::{
+    player::{SpawnPlayer, PLAYER_IMAGE_SIZE},
+    sequencer::SpawnSequencer,
+};
 
 /// The Y coordinate of the floor
 pub const FLOOR_Y: f32 = 100.0;
 
-/// The width of the level, in pixels
+/// The width of the view, in pixels. Most levels are this wide too, but some go on for a few of these.
 pub const LEVEL_WIDTH: f32 = 1280.0;
 
 /// The thickness of the floor, in pixels
 pub const FLOOR_HEIGHT: f32 = 2.0;
 
-const IMAGE_SCALE: f32 = 3.0;
+pub const IMAGE_SCALE: f32 = 3.0;
 
 const BOX_RAW_IMAGE_SIZE: f32 = 19.0;
-const BOX_SIZE: f32 = BOX_RAW_IMAGE_SIZE * IMAGE_SCALE;
+pub const BOX_SIZE: f32 = BOX_RAW_IMAGE_SIZE * IMAGE_SCALE;
 
 const SPIKES_RAW_IMAGE_SIZE: f32 = 19.0;
-const SPIKES_IMAGE_SIZE: f32 = SPIKES_RAW_IMAGE_SIZE * IMAGE_SCALE;
-const SPIKES_WIDTH: f32 = SPIKES_IMAGE_SIZE;
-const SPIKES_HEIGHT: f32 = 6.0 * IMAGE_SCALE;
+pub const SPIKES_IMAGE_SIZE: f32 = SPIKES_RAW_IMAGE_SIZE * IMAGE_SCALE;
+pub const SPIKES_WIDTH: f32 = SPIKES_IMAGE_SIZE;
+pub const SPIKES_HEIGHT: f32 = 6.0 * IMAGE_SCALE;
+
+const BOUNCE_PAD_RAW_WIDTH: f32 = 19.0;
+const BOUNCE_PAD_RAW_HEIGHT: f32 = 6.0;
+const BOUNCE_PAD_WIDTH: f32 = BOUNCE_PAD_RAW_WIDTH * IMAGE_SCALE;
+const BOUNCE_PAD_HEIGHT: f32 = BOUNCE_PAD_RAW_HEIGHT * IMAGE_SCALE;
+
+/// The height of generated updrafts, in pixels
+const UPDRAFT_HEIGHT: f32 = BOX_SIZE * 5.0;
+
+/// The upwards acceleration of generated updrafts, in pixels/sec^2
+const UPDRAFT_FORCE: f32 = 1500.0;
+
+/// The default velocity a bounce pad launches the player with, in pixels/sec
+pub const BOUNCE_PAD_VELOCITY: f32 = 1200.0;
+
+const PORTAL_RAW_WIDTH: f32 = 13.0;
+const PORTAL_RAW_HEIGHT: f32 = 21.0;
+const PORTAL_SIZE: Vec2 = Vec2::new(
+    PORTAL_RAW_WIDTH * IMAGE_SCALE,
+    PORTAL_RAW_HEIGHT * IMAGE_SCALE,
+);
+
+/// How wide the finish line at the end of a campaign level is, in pixels
+pub const FINISH_LINE_WIDTH: f32 = 4.0 * IMAGE_SCALE;
+
+/// How far up from the floor the finish line goes, in pixels
+const FINISH_LINE_HEIGHT: f32 = BOX_SIZE * 8.0;
+
+/// How long the flash shown when the player goes through a portal lasts, in seconds
+const PORTAL_FLASH_DURATION: f32 = 0.3;
 
-const TOP_OF_FLOOR: f32 = FLOOR_Y + (FLOOR_HEIGHT / 2.0);
+pub const TOP_OF_FLOOR: f32 = FLOOR_Y + (FLOOR_HEIGHT / 2.0);
 
 pub const TOTAL_LEVELS: u32 = 4;
 
+/// How many times the campaign goes through every level before it's won
+pub const CAMPAIGN_LOOPS: u32 = 2;
+
+/// The number of levels that need to be cleared to win the campaign
+pub const CAMPAIGN_LENGTH: u32 = TOTAL_LEVELS * CAMPAIGN_LOOPS;
+
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_level);
-    app.observe(spawn_distance_display);
+    app.init_resource::<ObstaclePool>();
     app.observe(spawn_obstacles);
+    app.init_resource::<IncomingLevel>();
+    app.init_resource::<LevelWidth>();
+    app.observe(pre_spawn_obstacles);
+    app.observe(spawn_chunk);
+    app.observe(despawn_chunk);
     app.insert_resource(CurrentLevel(0));
+    app.insert_resource(StartingLevel(0));
+    app.insert_resource(GameMode::Campaign);
+    app.init_resource::<RunMode>();
+}
 
-    app.add_systems(Update, update_distance_display.in_set(AppSet::Update));
+/// Effects that don't change how the level plays out
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.observe(spawn_portal_flash);
+    app.add_systems(Update, fade_portal_flashes.in_set(AppSet::Update));
 }
 
 #[derive(Event, Debug)]
 pub struct SpawnLevel;
 
+/// Event that spawns the provided level's obstacles, clearing away the last level's
 #[derive(Event, Debug)]
-pub struct SpawnDistanceDisplay;
+pub struct SpawnObstacles {
+    pub level: u32,
+    /// How the level's layout is changed as it's spawned
+    pub transform: LevelTransform,
+}
+
+impl SpawnObstacles {
+    /// Spawns the provided level the way around it was made
+    pub fn new(level: u32) -> SpawnObstacles {
+        SpawnObstacles {
+            level,
+            transform: LevelTransform::default(),
+        }
+    }
 
+    pub fn with_transform(mut self, transform: LevelTransform) -> SpawnObstacles {
+        self.transform = transform;
+        self
+    }
+}
+
+/// A change made to everything in a level as it's spawned, so the same layout can be reused in different forms
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelTransform {
+    /// Whether the level is flipped left to right
+    pub flip_x: bool,
+    /// How far up everything is moved, in pixels
+    pub y_offset: f32,
+}
+
+impl LevelTransform {
+    /// Whether this leaves levels just as they are
+    pub fn is_identity(self) -> bool {
+        self == LevelTransform::default()
+    }
+
+    /// Gets the provided placement, changed by this in a level with the provided width
+    pub fn apply(self, placement: Placement, level_width: f32) -> Placement {
+        let placement = if self.flip_x {
+            placement.mirrored((level_width - LEVEL_WIDTH) / 2.0)
+        } else {
+            placement
+        };
+        placement.raised(self.y_offset)
+    }
+}
+
+/// Event that spawns the provided level's obstacles ahead of time, [`LevelWidth::next_level_offset`] to the right of where they go.
+/// When that level's [`SpawnObstacles`] comes, they're moved into place instead of being spawned then.
 #[derive(Event, Debug)]
-pub struct SpawnObstacles(pub u32);
+pub struct PreSpawnObstacles(pub u32);
+
+/// Event that spawns one stretch of the level in scrolling mode, laid out like the provided level and moved over to the right by `x` pixels
+#[derive(Event, Debug)]
+pub struct SpawnChunk {
+    pub level: u32,
+    pub x: f32,
+}
+
+/// Event that gets rid of the stretch of the level in scrolling mode that's laid out like the provided level
+#[derive(Event, Debug)]
+pub struct DespawnChunk(pub u32);
+
+/// Event that shows a flash of light at the provided position
+#[derive(Event, Debug)]
+pub struct SpawnPortalFlash(pub Vec2);
+
+/// Event sent when the player makes it all the way through a level
+#[derive(Event, Debug)]
+pub struct LevelCleared(pub u32);
 
 #[derive(Resource, Debug)]
 pub struct CurrentLevel(pub u32);
 
-#[derive(Component)]
-pub struct DistanceDisplayText;
+/// How wide the current level is, in pixels. The level starts at the left edge of the view and goes on to the right.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct LevelWidth(pub f32);
+
+impl Default for LevelWidth {
+    fn default() -> Self {
+        LevelWidth(LEVEL_WIDTH)
+    }
+}
+
+impl LevelWidth {
+    /// Gets the horizontal position of the right edge of the level
+    pub fn right_edge(self) -> f32 {
+        self.0 - (LEVEL_WIDTH / 2.0)
+    }
+
+    /// Gets how far to the right the next level's obstacles are spawned ahead of time, in pixels.
+    /// This is as far as the player goes from the start of this level to the start of the next, since they go all the
+    /// way past the right edge before being moved back to the left one.
+    pub fn next_level_offset(self) -> f32 {
+        self.0 + PLAYER_IMAGE_SIZE
+    }
+}
+
+/// The level that runs start on
+#[derive(Resource, Debug)]
+pub struct StartingLevel(pub u32);
+
+/// The game mode the current run was started in, so starting it over goes back to that mode even after keeping going
+/// past the end of the campaign has switched it to endless. `None` when there's no run on the playing screen.
+#[derive(Resource, Debug, Default)]
+pub struct RunMode(pub Option<GameMode>);
+
+/// Whether the run ends after [`CAMPAIGN_LENGTH`] levels, goes on forever, goes on forever through one level that
+/// never wraps around, is teaching the player how to play, is trying out a level from the level editor, or goes
+/// through the custom levels
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    Campaign,
+    Endless,
+    Scrolling,
+    Tutorial,
+    EditorTest,
+    Custom,
+}
 
 #[derive(Component)]
 pub struct Obstacle;
 
+/// An obstacle spawned ahead of time for the next level, waiting off to the right until the player goes through to it
+#[derive(Component, Debug)]
+pub struct IncomingObstacle {
+    /// How far to the right of where it goes it was spawned, in pixels
+    offset: f32,
+}
+
+/// The level whose obstacles have been spawned ahead of time, if any
+#[derive(Resource, Debug, Default)]
+pub struct IncomingLevel(pub Option<u32>);
+
+/// Where a level ends. Once the runner out in front has all the way crossed it, the level is cleared.
+/// Levels without one end at the right edge.
+#[derive(Component, Debug)]
+pub struct FinishLine;
+
+/// An obstacle in one stretch of the level in scrolling mode, going by the level that stretch is laid out like
+#[derive(Component, Debug)]
+pub struct Chunk(pub u32);
+
 #[derive(Component)]
 pub struct Background;
 
+/// An obstacle that gets put back in the [`ObstaclePool`] when its level is over, instead of being despawned.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum PooledObstacle {
+    Box,
+    Spikes,
+}
+
+/// Obstacles left over from previous levels, hidden away until they're needed again.
+/// Reusing them is cheaper than despawning a whole level and spawning the next one from scratch every time the player gets through one.
+#[derive(Resource, Debug, Default)]
+struct ObstaclePool {
+    boxes: Vec<Entity>,
+    spikes: Vec<Entity>,
+}
+
+impl ObstaclePool {
+    /// Gets an obstacle of the provided kind to set up, reusing one from the pool if there are any
+    fn take<'a>(&mut self, kind: PooledObstacle, commands: &'a mut Commands) -> EntityCommands<'a> {
+        let pooled = match kind {
+            PooledObstacle::Box => self.boxes.pop(),
+            PooledObstacle::Spikes => self.spikes.pop(),
+        };
+        match pooled {
+            Some(entity) => commands.entity(entity),
+            None => commands.spawn(kind),
+        }
+    }
+
+    /// Hides the provided obstacle away until it's needed again.
+    /// Everything that makes it part of the level is taken off, so it can't be collided with while it's in the pool.
+    fn put_back(&mut self, entity: Entity, kind: PooledObstacle, commands: &mut Commands) {
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .remove::<(
+                Obstacle,
+                IncomingObstacle,
+                Chunk,
+                Spikes,
+                RectCollider,
+                PulseOnBeat,
+                AlreadyCleared,
+                ClosestApproach,
+            )>()
+            .insert(Visibility::Hidden);
+        match kind {
+            PooledObstacle::Box => self.boxes.push(entity),
+            PooledObstacle::Spikes => self.spikes.push(entity),
+        }
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct RectCollider {
     pub bounds: Vec2,
@@ -79,6 +326,38 @@ pub struct Floor;
 #[derive(Component)]
 pub struct Spikes;
 
+/// A non-solid area that reacts to the player overlapping it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TriggerVolume {
+    pub bounds: Vec2,
+}
+
+/// A trigger volume that pushes the player around while they're in the air inside it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WindZone {
+    /// The acceleration applied to the player, in pixels/sec^2
+    pub force: Vec2,
+}
+
+/// A trigger volume that sends the player to its twin portal.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Portal {
+    pub twin: Entity,
+}
+
+/// A flash of light that fades out after the player goes through a portal.
+#[derive(Component, Debug)]
+pub struct PortalFlash {
+    timer: Timer,
+}
+
+/// An obstacle that launches the player upwards when they land on it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BouncePad {
+    /// The vertical velocity the player is launched with, in pixels/sec
+    pub launch_velocity: f32,
+}
+
 fn spawn_level(
     _trigger: Trigger<SpawnLevel>,
     current_level: Res<CurrentLevel>,
@@ -86,8 +365,8 @@ fn spawn_level(
 ) {
     commands.trigger(SpawnPlayer);
     commands.trigger(SpawnSequencer);
-    commands.trigger(SpawnDistanceDisplay);
-    commands.trigger(SpawnObstacles(current_level.0));
+    commands.trigger(SpawnHud);
+    commands.trigger(SpawnObstacles::new(current_level.0));
 
     commands.spawn((
         Name::new("Floor"),
@@ -107,230 +386,581 @@ fn spawn_level(
         },
     ));
 
-    let curtain_width = 5000.0;
-    let curtain_height = 5000.0;
-    let curtain_center_distance = (curtain_width / 2.0) + (LEVEL_WIDTH / 2.0);
-    commands.spawn((
-        Name::new("Left curtain"),
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(curtain_width, curtain_height)),
-                color: Color::BLACK,
-                ..default()
+    commands.insert_resource(ClearColor(Color::srgb(0.35, 0.35, 0.35)));
+}
+
+/// Something that goes in a level, and where it goes.
+/// Levels are laid out as a list of these before anything is spawned, so what's in a level can be looked at ahead of time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    Box(Vec2),
+    /// Spikes sitting on top of whatever's below them
+    FloorSpikes(Vec2),
+    /// Spikes sticking out to the left of whatever's to their right
+    WallSpikes(Vec2),
+    BouncePad {
+        position: Vec2,
+        launch_velocity: f32,
+    },
+    WindZone {
+        position: Vec2,
+        size: Vec2,
+        force: Vec2,
+    },
+    /// Two portals that lead to each other
+    PortalPair(Vec2, Vec2),
+    /// The line the level ends at, going by its horizontal position
+    FinishLine(f32),
+}
+
+impl Placement {
+    /// Gets where this is, if it's something that ends the run when it's touched
+    pub fn hazard_position(self) -> Option<Vec2> {
+        match self {
+            Placement::FloorSpikes(position) | Placement::WallSpikes(position) => Some(position),
+            _ => None,
+        }
+    }
+
+    /// Gets this moved over to the right by the provided number of pixels
+    fn shifted(self, x: f32) -> Placement {
+        self.moved(Vec2::new(x, 0.0))
+    }
+
+    /// Gets this moved up by the provided number of pixels
+    fn raised(self, y: f32) -> Placement {
+        self.moved(Vec2::new(0.0, y))
+    }
+
+    /// Gets this moved by the provided offset. Finish lines only go by their horizontal position, so they're moved
+    /// over by the horizontal part of it.
+    fn moved(self, offset: Vec2) -> Placement {
+        match self {
+            Placement::Box(position) => Placement::Box(position + offset),
+            Placement::FloorSpikes(position) => Placement::FloorSpikes(position + offset),
+            Placement::WallSpikes(position) => Placement::WallSpikes(position + offset),
+            Placement::BouncePad {
+                position,
+                launch_velocity,
+            } => Placement::BouncePad {
+                position: position + offset,
+                launch_velocity,
             },
-            transform: Transform::from_translation(Vec3::new(-curtain_center_distance, 0.0, 1.0)),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Name::new("Right curtain"),
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(curtain_width, curtain_height)),
-                color: Color::BLACK,
-                ..default()
+            Placement::WindZone {
+                position,
+                size,
+                force,
+            } => Placement::WindZone {
+                position: position + offset,
+                size,
+                force,
             },
-            transform: Transform::from_translation(Vec3::new(curtain_center_distance, 0.0, 1.0)),
-            ..default()
-        },
-    ));
+            Placement::PortalPair(first_position, second_position) => {
+                Placement::PortalPair(first_position + offset, second_position + offset)
+            }
+            Placement::FinishLine(line_x) => Placement::FinishLine(line_x + offset.x),
+        }
+    }
 
-    commands.insert_resource(ClearColor(Color::srgb(0.35, 0.35, 0.35)));
+    /// Gets this flipped to the other side of the provided horizontal position, facing the other way.
+    /// Finish lines stay where they are, since the level still ends in the same place.
+    fn mirrored(self, center_x: f32) -> Placement {
+        let flip = |position: Vec2| Vec2::new((2.0 * center_x) - position.x, position.y);
+        match self {
+            Placement::Box(position) => Placement::Box(flip(position)),
+            Placement::FloorSpikes(position) => Placement::FloorSpikes(flip(position)),
+            Placement::WallSpikes(position) => Placement::WallSpikes(flip(position)),
+            Placement::BouncePad {
+                position,
+                launch_velocity,
+            } => Placement::BouncePad {
+                position: flip(position),
+                launch_velocity,
+            },
+            Placement::WindZone {
+                position,
+                size,
+                force,
+            } => Placement::WindZone {
+                position: flip(position),
+                size,
+                force: Vec2::new(-force.x, force.y),
+            },
+            Placement::PortalPair(first_position, second_position) => {
+                Placement::PortalPair(flip(first_position), flip(second_position))
+            }
+            Placement::FinishLine(line_x) => Placement::FinishLine(line_x),
+        }
+    }
 }
 
-fn spawn_distance_display(
-    _trigger: Trigger<SpawnDistanceDisplay>,
-    font_handles: Res<HandleMap<FontKey>>,
-    mut commands: Commands,
-) {
-    let mut entity = commands.spawn((
-        Name::new("Distance display"),
-        NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                height: Val::Px(35.0),
-                top: Val::Px(5.0),
-                left: Val::Px(5.0),
-                position_type: PositionType::Absolute,
-                justify_content: JustifyContent::Start,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            ..default()
-        },
-    ));
-    entity.with_children(|children| {
-        children.spawn((
-            Name::new("Distance display text"),
-            DistanceDisplayText,
-            TextBundle::from_section(
-                "Distance: 0",
-                TextStyle {
-                    font: font_handles.get(FontKey::General),
-                    font_size: 30.0,
-                    color: LABEL_TEXT,
-                },
-            ),
-        ));
-    });
+/// Works out what goes in each level.
+#[derive(SystemParam)]
+pub struct LevelLayouts<'w> {
+    game_mode: Res<'w, GameMode>,
+    difficulty_curve: Res<'w, DifficultyCurve>,
+    tutorial: Res<'w, Tutorial>,
+    editor_level: Res<'w, EditorLevel>,
+    custom_levels: Res<'w, CustomLevels>,
+    mutators: Res<'w, Mutators>,
 }
 
-fn update_distance_display(
-    mut distance_display_text_query: Query<&mut Text, With<DistanceDisplayText>>,
-    total_distance: Res<TotalDistance>,
-) {
-    for mut text in &mut distance_display_text_query {
-        text.sections[0].value = format!("Distance: {}", *total_distance);
+impl LevelLayouts<'_> {
+    /// Gets everything in the provided level. Boss levels don't have anything, since the boss brings its own obstacles.
+    pub fn layout(&self, level: u32) -> Vec<Placement> {
+        if is_boss_level(*self.game_mode, level) {
+            return Vec::new();
+        }
+
+        let placements = self.unmirrored_layout(level);
+        if !self.mutators.contains(Mutator::Mirrored) {
+            return placements;
+        }
+
+        let transform = LevelTransform {
+            flip_x: true,
+            ..default()
+        };
+        let width = self.width(level);
+        placements
+            .into_iter()
+            .map(|placement| transform.apply(placement, width))
+            .collect()
+    }
+
+    /// Gets everything in the provided level, the way around it was made
+    fn unmirrored_layout(&self, level: u32) -> Vec<Placement> {
+        match *self.game_mode {
+            // the stretches of the level in scrolling mode are the same as the levels endless mode generates
+            GameMode::Endless | GameMode::Scrolling => {
+                generated_level(level, self.difficulty_curve.at(level))
+            }
+            GameMode::Tutorial => tutorial_level(self.tutorial.step),
+            GameMode::EditorTest => defined_level(&self.editor_level.0),
+            GameMode::Custom => match self.custom_levels.for_level(level) {
+                Some(definition) => defined_level(definition),
+                None => defined_level(&LevelDefinition::default()),
+            },
+            GameMode::Campaign => {
+                let mut placements = match level % TOTAL_LEVELS {
+                    0 => level_0(),
+                    1 => level_1(),
+                    2 => level_2(),
+                    3 => level_3(),
+                    _ => unreachable!(),
+                };
+                // right up against the right edge, so the level ends where it always has
+                placements.push(Placement::FinishLine(
+                    LevelWidth(self.width(level)).right_edge() - (FINISH_LINE_WIDTH / 2.0),
+                ));
+                placements
+            }
+        }
+    }
+
+    /// Gets how wide the provided level is, in pixels. Only levels made as level files can be more than one screen long.
+    pub fn width(&self, level: u32) -> f32 {
+        match *self.game_mode {
+            GameMode::EditorTest => self.editor_level.0.width(),
+            GameMode::Custom => self
+                .custom_levels
+                .for_level(level)
+                .map_or(LEVEL_WIDTH, LevelDefinition::width),
+            _ => LEVEL_WIDTH,
+        }
     }
 }
 
 fn spawn_obstacles(
     trigger: Trigger<SpawnObstacles>,
-    existing_obstacles_query: Query<Entity, With<Obstacle>>,
+    existing_obstacles_query: Query<
+        (Entity, Option<&PooledObstacle>),
+        (With<Obstacle>, Without<IncomingObstacle>),
+    >,
+    mut incoming_obstacles_query: Query<(
+        Entity,
+        Option<&PooledObstacle>,
+        &IncomingObstacle,
+        &mut Transform,
+    )>,
     background_query: Query<Entity, With<Background>>,
     image_handles: Res<HandleMap<ImageKey>>,
+    layouts: LevelLayouts,
+    mut incoming_level: ResMut<IncomingLevel>,
+    mut level_width: ResMut<LevelWidth>,
+    mut pool: ResMut<ObstaclePool>,
     mut commands: Commands,
 ) {
-    for existing_obstacle in &existing_obstacles_query {
-        commands.entity(existing_obstacle).despawn_recursive();
+    let SpawnObstacles { level, transform } = *trigger.event();
+    level_width.set_if_neq(LevelWidth(layouts.width(level)));
+    for (existing_obstacle, pooled) in &existing_obstacles_query {
+        match pooled {
+            Some(kind) => pool.put_back(existing_obstacle, *kind, &mut commands),
+            None => commands.entity(existing_obstacle).despawn_recursive(),
+        }
     }
 
     for background in &background_query {
         commands.entity(background).despawn_recursive();
     }
 
-    match trigger.event().0 % TOTAL_LEVELS {
-        0 => spawn_level_0(&image_handles, &mut commands),
-        1 => spawn_level_1(&image_handles, &mut commands),
-        2 => spawn_level_2(&image_handles, &mut commands),
-        3 => spawn_level_3(&image_handles, &mut commands),
-        _ => unreachable!(),
+    // the background is colored in by the level's theme
+    spawn_background(&mut commands);
+
+    // if this level was already spawned off to the side, it just needs to be moved into place.
+    // Levels are only spawned ahead of time the way around they were made, though.
+    let already_spawned = incoming_level.0.take() == Some(level) && transform.is_identity();
+    for (incoming_obstacle, pooled, incoming, mut transform) in &mut incoming_obstacles_query {
+        if already_spawned {
+            transform.translation.x -= incoming.offset;
+            commands
+                .entity(incoming_obstacle)
+                .remove::<IncomingObstacle>();
+        } else {
+            match pooled {
+                Some(kind) => pool.put_back(incoming_obstacle, *kind, &mut commands),
+                None => commands.entity(incoming_obstacle).despawn_recursive(),
+            }
+        }
+    }
+    // the level streams in a stretch at a time in scrolling mode instead
+    if already_spawned || *layouts.game_mode == GameMode::Scrolling {
+        return;
+    }
+
+    for placement in layouts.layout(level) {
+        spawn_placement(
+            transform.apply(placement, level_width.0),
+            &image_handles,
+            &mut pool,
+            &mut commands,
+        );
+    }
+}
+
+/// Spawns the next level's obstacles off to the right of the current level, so they're already there by the time the player gets to them
+fn pre_spawn_obstacles(
+    trigger: Trigger<PreSpawnObstacles>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    layouts: LevelLayouts,
+    level_width: Res<LevelWidth>,
+    mut incoming_level: ResMut<IncomingLevel>,
+    mut pool: ResMut<ObstaclePool>,
+    mut commands: Commands,
+) {
+    let level = trigger.event().0;
+    if incoming_level.0 == Some(level) {
+        return;
+    }
+    incoming_level.0 = Some(level);
+
+    let offset = level_width.next_level_offset();
+    for placement in layouts.layout(level) {
+        for entity in spawn_placement(
+            placement.shifted(offset),
+            &image_handles,
+            &mut pool,
+            &mut commands,
+        ) {
+            commands.entity(entity).insert(IncomingObstacle { offset });
+        }
+    }
+}
+
+/// Spawns a stretch of the level in scrolling mode, off to the right of the ones before it
+fn spawn_chunk(
+    trigger: Trigger<SpawnChunk>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    layouts: LevelLayouts,
+    mut pool: ResMut<ObstaclePool>,
+    mut commands: Commands,
+) {
+    let SpawnChunk { level, x } = *trigger.event();
+    for placement in layouts.layout(level) {
+        for entity in spawn_placement(
+            placement.shifted(x),
+            &image_handles,
+            &mut pool,
+            &mut commands,
+        ) {
+            commands.entity(entity).insert(Chunk(level));
+        }
+    }
+}
+
+/// Gets rid of a stretch of the level in scrolling mode once it's been left behind
+fn despawn_chunk(
+    trigger: Trigger<DespawnChunk>,
+    chunk_query: Query<(Entity, &Chunk, Option<&PooledObstacle>)>,
+    mut pool: ResMut<ObstaclePool>,
+    mut commands: Commands,
+) {
+    for (entity, chunk, pooled) in &chunk_query {
+        if chunk.0 != trigger.event().0 {
+            continue;
+        }
+        match pooled {
+            Some(kind) => pool.put_back(entity, *kind, &mut commands),
+            None => commands.entity(entity).despawn_recursive(),
+        }
     }
 }
 
-fn spawn_level_0(image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
-    spawn_background(Color::srgb(0.6, 0.4, 0.4), commands);
+/// Spawns what's at the provided placement, giving back everything that was spawned for it
+fn spawn_placement(
+    placement: Placement,
+    image_handles: &HandleMap<ImageKey>,
+    pool: &mut ObstaclePool,
+    commands: &mut Commands,
+) -> Vec<Entity> {
+    match placement {
+        Placement::Box(position) => vec![spawn_box(position, image_handles, pool, commands)],
+        Placement::FloorSpikes(position) => {
+            vec![spawn_floor_spikes(position, image_handles, pool, commands)]
+        }
+        Placement::WallSpikes(position) => {
+            vec![spawn_wall_spikes(position, image_handles, pool, commands)]
+        }
+        Placement::BouncePad {
+            position,
+            launch_velocity,
+        } => vec![spawn_bounce_pad(
+            position,
+            launch_velocity,
+            image_handles,
+            commands,
+        )],
+        Placement::WindZone {
+            position,
+            size,
+            force,
+        } => vec![spawn_wind_zone(position, size, force, commands)],
+        Placement::PortalPair(first_position, second_position) => {
+            spawn_portal_pair(first_position, second_position, image_handles, commands).to_vec()
+        }
+        Placement::FinishLine(x) => vec![spawn_finish_line(x, commands)],
+    }
+}
 
-    spawn_box(
-        Vec2::new(0.0, TOP_OF_FLOOR + (BOX_SIZE / 2.0)),
-        image_handles,
-        commands,
-    );
-    spawn_floor_spikes(
-        Vec2::new(
+fn level_0() -> Vec<Placement> {
+    vec![
+        Placement::Box(Vec2::new(0.0, TOP_OF_FLOOR + (BOX_SIZE / 2.0))),
+        Placement::FloorSpikes(Vec2::new(
             (BOX_SIZE / 2.0) + (SPIKES_IMAGE_SIZE / 2.0),
             TOP_OF_FLOOR + (SPIKES_IMAGE_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
-}
-
-fn spawn_level_1(image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
-    spawn_background(Color::srgb(0.4, 0.6, 0.4), commands);
-
-    spawn_box_with_spikes_on_side(
-        Vec2::new(-BOX_SIZE, TOP_OF_FLOOR + (BOX_SIZE / 2.0)),
-        image_handles,
-        commands,
-    );
-    spawn_floor_spikes(
-        Vec2::new(
-            -BOX_SIZE,
-            TOP_OF_FLOOR + BOX_SIZE + (SPIKES_IMAGE_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
-}
-
-fn spawn_level_2(image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
-    spawn_background(Color::srgb(0.4, 0.4, 0.6), commands);
-
-    spawn_box(
-        Vec2::new(BOX_SIZE * -3.0, TOP_OF_FLOOR + (BOX_SIZE / 2.0)),
-        image_handles,
-        commands,
-    );
-
-    spawn_box_with_spikes_on_side(
-        Vec2::new(0.0, TOP_OF_FLOOR + (BOX_SIZE * 3.0)),
-        image_handles,
-        commands,
-    );
-
-    spawn_box(
-        Vec2::new(BOX_SIZE * 3.0, TOP_OF_FLOOR + (BOX_SIZE / 2.0)),
-        image_handles,
-        commands,
-    );
-}
-
-fn spawn_level_3(image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
-    spawn_background(Color::srgb(0.6, 0.6, 0.4), commands);
-
-    spawn_box(
-        Vec2::new(BOX_SIZE * -4.0, TOP_OF_FLOOR + (BOX_SIZE / 2.0)),
-        image_handles,
-        commands,
-    );
-    spawn_box(
-        Vec2::new(BOX_SIZE * -3.0, TOP_OF_FLOOR + BOX_SIZE + (BOX_SIZE / 2.0)),
-        image_handles,
-        commands,
-    );
-    spawn_box(
-        Vec2::new(
+        )),
+    ]
+}
+
+fn level_1() -> Vec<Placement> {
+    let mut placements = Vec::new();
+    placements.extend(box_with_spikes_on_side(Vec2::new(
+        -BOX_SIZE,
+        TOP_OF_FLOOR + (BOX_SIZE / 2.0),
+    )));
+    placements.push(Placement::FloorSpikes(Vec2::new(
+        -BOX_SIZE,
+        TOP_OF_FLOOR + BOX_SIZE + (SPIKES_IMAGE_SIZE / 2.0),
+    )));
+    placements
+}
+
+fn level_2() -> Vec<Placement> {
+    let mut placements = vec![Placement::Box(Vec2::new(
+        BOX_SIZE * -3.0,
+        TOP_OF_FLOOR + (BOX_SIZE / 2.0),
+    ))];
+    placements.extend(box_with_spikes_on_side(Vec2::new(
+        0.0,
+        TOP_OF_FLOOR + (BOX_SIZE * 3.0),
+    )));
+    placements.push(Placement::Box(Vec2::new(
+        BOX_SIZE * 3.0,
+        TOP_OF_FLOOR + (BOX_SIZE / 2.0),
+    )));
+    placements
+}
+
+fn level_3() -> Vec<Placement> {
+    let mut placements = vec![
+        Placement::Box(Vec2::new(BOX_SIZE * -4.0, TOP_OF_FLOOR + (BOX_SIZE / 2.0))),
+        Placement::Box(Vec2::new(
+            BOX_SIZE * -3.0,
+            TOP_OF_FLOOR + BOX_SIZE + (BOX_SIZE / 2.0),
+        )),
+        Placement::Box(Vec2::new(
             BOX_SIZE * -2.0,
             TOP_OF_FLOOR + (BOX_SIZE * 2.0) + (BOX_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
-
-    spawn_box_with_spikes_on_side(
-        Vec2::new(
+        )),
+    ];
+    for boxes_above_floor in [5.0, 4.0, 3.0, 2.0] {
+        placements.extend(box_with_spikes_on_side(Vec2::new(
             BOX_SIZE * 2.0,
-            TOP_OF_FLOOR + (BOX_SIZE * 5.0) + (BOX_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
+            TOP_OF_FLOOR + (BOX_SIZE * boxes_above_floor) + (BOX_SIZE / 2.0),
+        )));
+    }
+    placements
+}
 
-    spawn_box_with_spikes_on_side(
-        Vec2::new(
-            BOX_SIZE * 2.0,
-            TOP_OF_FLOOR + (BOX_SIZE * 4.0) + (BOX_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
-    spawn_box_with_spikes_on_side(
-        Vec2::new(
-            BOX_SIZE * 2.0,
-            TOP_OF_FLOOR + (BOX_SIZE * 3.0) + (BOX_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
-    spawn_box_with_spikes_on_side(
-        Vec2::new(
-            BOX_SIZE * 2.0,
-            TOP_OF_FLOOR + (BOX_SIZE * 2.0) + (BOX_SIZE / 2.0),
-        ),
-        image_handles,
-        commands,
-    );
+/// Lays out the level for the provided step of the tutorial, or an empty level once it's done
+fn tutorial_level(step: usize) -> Vec<Placement> {
+    match step {
+        // just run
+        0 => Vec::new(),
+        // jump
+        1 => vec![Placement::Box(Vec2::new(
+            0.0,
+            TOP_OF_FLOOR + (BOX_SIZE / 2.0),
+        ))],
+        // float
+        2 => (-2..=2)
+            .map(|i| {
+                Placement::FloorSpikes(Vec2::new(
+                    SPIKES_WIDTH * i as f32,
+                    TOP_OF_FLOOR + (SPIKES_IMAGE_SIZE / 2.0),
+                ))
+            })
+            .collect(),
+        // dive
+        3 => {
+            let mut placements = vec![Placement::Box(Vec2::new(
+                BOX_SIZE * -3.0,
+                TOP_OF_FLOOR + (BOX_SIZE / 2.0),
+            ))];
+            placements.extend(box_with_spikes_on_side(Vec2::new(
+                BOX_SIZE,
+                TOP_OF_FLOOR + (BOX_SIZE * 2.0) + (BOX_SIZE / 2.0),
+            )));
+            placements
+        }
+        _ => Vec::new(),
+    }
 }
 
-fn spawn_background(color: Color, commands: &mut Commands) {
+/// Lays out a level that was made in the level editor
+fn defined_level(definition: &LevelDefinition) -> Vec<Placement> {
+    definition
+        .obstacles
+        .iter()
+        .map(|obstacle| {
+            let position = obstacle.cell.center();
+            match obstacle.kind {
+                ObstacleKind::Box => Placement::Box(position),
+                ObstacleKind::Spikes => Placement::FloorSpikes(position),
+            }
+        })
+        .collect()
+}
+
+/// Lays out a randomly generated level. The same level number always generates the same level.
+fn generated_level(level: u32, difficulty: Difficulty) -> Vec<Placement> {
+    let mut rng = StdRng::seed_from_u64(level.into());
+    let mut placements = Vec::new();
+
+    // the theme draws the hue from its own generator with the same seed, so it isn't needed here,
+    // but it's still drawn first so each level number lays out the same as it did before there were themes
+    generated_level_hue(&mut rng);
+
+    // leave some room at the start of the level so the player isn't immediately in trouble
+    let mut x = (-LEVEL_WIDTH / 2.0) + (BOX_SIZE * 4.0);
+    let last_x = (LEVEL_WIDTH / 2.0) - (BOX_SIZE * 2.0);
+    for _ in 0..difficulty.obstacles {
+        if x > last_x {
+            break;
+        }
+
+        // sometimes add a shortcut past the obstacle group
+        if difficulty.gap >= 3.0 && rng.gen_bool(0.1) {
+            placements.push(Placement::PortalPair(
+                Vec2::new(x - (BOX_SIZE * 1.5), TOP_OF_FLOOR + (PORTAL_SIZE.y / 2.0)),
+                Vec2::new(
+                    x + (BOX_SIZE * (1.0 + difficulty.gap / 2.0)),
+                    TOP_OF_FLOOR + (BOX_SIZE * 3.0) + (PORTAL_SIZE.y / 2.0),
+                ),
+            ));
+        }
+
+        let with_spikes = rng.gen_bool(difficulty.spike_chance.clamp(0.0, 1.0).into());
+        let stack_height = rng.gen_range(1..=2);
+        match (with_spikes, rng.gen_range(0..3)) {
+            (false, 0) => placements.push(Placement::BouncePad {
+                position: Vec2::new(x, TOP_OF_FLOOR + (BOUNCE_PAD_HEIGHT / 2.0)),
+                launch_velocity: BOUNCE_PAD_VELOCITY,
+            }),
+            (false, _) => placements.extend(box_stack(x, stack_height)),
+            (true, 0) => placements.push(Placement::FloorSpikes(Vec2::new(
+                x,
+                TOP_OF_FLOOR + (SPIKES_IMAGE_SIZE / 2.0),
+            ))),
+            (true, 1) => {
+                placements.extend(box_stack(x, stack_height));
+                placements.push(Placement::FloorSpikes(Vec2::new(
+                    x,
+                    TOP_OF_FLOOR + (BOX_SIZE * stack_height as f32) + (SPIKES_IMAGE_SIZE / 2.0),
+                )));
+            }
+            (true, _) => {
+                let boxes_above_floor = rng.gen_range(2..=3);
+                placements.extend(box_with_spikes_on_side(Vec2::new(
+                    x,
+                    TOP_OF_FLOOR + (BOX_SIZE * boxes_above_floor as f32) + (BOX_SIZE / 2.0),
+                )));
+            }
+        }
+
+        // sometimes put an updraft in the gap after the obstacle group
+        if difficulty.gap >= 3.0 && rng.gen_bool(0.15) {
+            let width = BOX_SIZE * (difficulty.gap - 1.0);
+            placements.push(Placement::WindZone {
+                position: Vec2::new(
+                    x + (BOX_SIZE / 2.0) + (BOX_SIZE * difficulty.gap / 2.0),
+                    TOP_OF_FLOOR + (UPDRAFT_HEIGHT / 2.0),
+                ),
+                size: Vec2::new(width, UPDRAFT_HEIGHT),
+                force: Vec2::new(0.0, UPDRAFT_FORCE),
+            });
+        }
+
+        x += BOX_SIZE * (1.0 + difficulty.gap);
+    }
+
+    placements
+}
+
+/// Lays out a column of boxes sitting on the floor
+fn box_stack(x: f32, height: u32) -> impl Iterator<Item = Placement> {
+    (0..height).map(move |i| {
+        Placement::Box(Vec2::new(
+            x,
+            TOP_OF_FLOOR + (BOX_SIZE * i as f32) + (BOX_SIZE / 2.0),
+        ))
+    })
+}
+
+/// Lays out a box with spikes on its left side
+fn box_with_spikes_on_side(position: Vec2) -> [Placement; 2] {
+    [
+        Placement::Box(position),
+        Placement::WallSpikes(Vec2::new(
+            position.x - (BOX_SIZE / 2.0) - (SPIKES_IMAGE_SIZE / 2.0),
+            position.y,
+        )),
+    ]
+}
+
+/// Picks the hue of a generated level, from the random number generator the rest of the level is generated with
+pub fn generated_level_hue(rng: &mut StdRng) -> f32 {
+    rng.gen_range(0.0..360.0)
+}
+
+fn spawn_background(commands: &mut Commands) {
     commands.spawn((
         Name::new("Background"),
         Background,
+        PulseOnBeat::background(),
         SpriteBundle {
             transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.0)),
             sprite: Sprite {
-                color,
                 custom_size: Some(Vec2::new(LEVEL_WIDTH, LEVEL_WIDTH)),
                 ..default()
             },
@@ -339,15 +969,21 @@ fn spawn_background(color: Color, commands: &mut Commands) {
     ));
 }
 
-fn spawn_box(position: Vec2, image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
+fn spawn_box(
+    position: Vec2,
+    image_handles: &HandleMap<ImageKey>,
+    pool: &mut ObstaclePool,
+    commands: &mut Commands,
+) -> Entity {
     let collider = RectCollider {
         bounds: Vec2::new(BOX_SIZE, BOX_SIZE),
         offset: Vec2::ZERO,
     };
-    commands
-        .spawn((
+    pool.take(PooledObstacle::Box, commands)
+        .insert((
             Name::new("Box"),
             Obstacle,
+            PulseOnBeat::obstacle(),
             SpriteBundle {
                 texture: image_handles.get(ImageKey::Box),
                 transform: Transform::from_scale(Vec2::splat(IMAGE_SCALE).extend(1.0))
@@ -373,14 +1009,16 @@ fn spawn_box(position: Vec2, image_handles: &HandleMap<ImageKey>, commands: &mut
                     },
                 ));
             }
-        });
+        })
+        .id()
 }
 
 fn spawn_floor_spikes(
     position: Vec2,
     image_handles: &HandleMap<ImageKey>,
+    pool: &mut ObstaclePool,
     commands: &mut Commands,
-) {
+) -> Entity {
     let collider = RectCollider {
         bounds: Vec2::new(
             SPIKES_WIDTH - (4.0 * IMAGE_SCALE),
@@ -388,10 +1026,11 @@ fn spawn_floor_spikes(
         ),
         offset: Vec2::new(0.0, -7.0 * IMAGE_SCALE),
     };
-    commands
-        .spawn((
+    pool.take(PooledObstacle::Spikes, commands)
+        .insert((
             Name::new("Spikes"),
             Obstacle,
+            PulseOnBeat::obstacle(),
             Spikes,
             SpriteBundle {
                 texture: image_handles.get(ImageKey::Spikes),
@@ -418,10 +1057,16 @@ fn spawn_floor_spikes(
                     },
                 ));
             }
-        });
+        })
+        .id()
 }
 
-fn spawn_wall_spikes(position: Vec2, image_handles: &HandleMap<ImageKey>, commands: &mut Commands) {
+fn spawn_wall_spikes(
+    position: Vec2,
+    image_handles: &HandleMap<ImageKey>,
+    pool: &mut ObstaclePool,
+    commands: &mut Commands,
+) -> Entity {
     let collider = RectCollider {
         bounds: Vec2::new(
             SPIKES_HEIGHT - IMAGE_SCALE,
@@ -429,10 +1074,11 @@ fn spawn_wall_spikes(position: Vec2, image_handles: &HandleMap<ImageKey>, comman
         ),
         offset: Vec2::new(7.0 * IMAGE_SCALE, 0.0),
     };
-    commands
-        .spawn((
+    pool.take(PooledObstacle::Spikes, commands)
+        .insert((
             Name::new("Spikes"),
             Obstacle,
+            PulseOnBeat::obstacle(),
             Spikes,
             SpriteBundle {
                 texture: image_handles.get(ImageKey::Spikes),
@@ -462,21 +1108,173 @@ fn spawn_wall_spikes(position: Vec2, image_handles: &HandleMap<ImageKey>, comman
                     },
                 ));
             }
-        });
+        })
+        .id()
 }
 
-fn spawn_box_with_spikes_on_side(
+fn spawn_wind_zone(position: Vec2, size: Vec2, force: Vec2, commands: &mut Commands) -> Entity {
+    commands
+        .spawn((
+            Name::new("Wind zone"),
+            Obstacle,
+            WindZone { force },
+            TriggerVolume { bounds: size },
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    color: Color::srgba(0.8, 0.9, 1.0, 0.15),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(-0.5)),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+fn spawn_finish_line(x: f32, commands: &mut Commands) -> Entity {
+    commands
+        .spawn((
+            Name::new("Finish line"),
+            Obstacle,
+            FinishLine,
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(FINISH_LINE_WIDTH, FINISH_LINE_HEIGHT)),
+                    color: Color::srgba(1.0, 1.0, 1.0, 0.6),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, TOP_OF_FLOOR + (FINISH_LINE_HEIGHT / 2.0), -0.5),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+/// Spawns two portals that lead to each other
+fn spawn_portal_pair(
+    first_position: Vec2,
+    second_position: Vec2,
+    image_handles: &HandleMap<ImageKey>,
+    commands: &mut Commands,
+) -> [Entity; 2] {
+    let first = spawn_portal(first_position, image_handles, commands);
+    let second = spawn_portal(second_position, image_handles, commands);
+    commands.entity(first).insert(Portal { twin: second });
+    commands.entity(second).insert(Portal { twin: first });
+    [first, second]
+}
+
+fn spawn_portal(
     position: Vec2,
     image_handles: &HandleMap<ImageKey>,
     commands: &mut Commands,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new("Portal"),
+            Obstacle,
+            PulseOnBeat::obstacle(),
+            TriggerVolume {
+                bounds: PORTAL_SIZE,
+            },
+            SpriteBundle {
+                texture: image_handles.get(ImageKey::Portal),
+                transform: Transform::from_scale(Vec2::splat(IMAGE_SCALE).extend(1.0))
+                    .with_translation(position.extend(-0.5)),
+                ..Default::default()
+            },
+        ))
+        .id()
+}
+
+fn spawn_portal_flash(
+    trigger: Trigger<SpawnPortalFlash>,
+    settings: Res<Settings>,
+    active_theme: Res<ActiveTheme>,
+    mut commands: Commands,
 ) {
-    spawn_box(position, image_handles, commands);
-    spawn_wall_spikes(
-        Vec2::new(
-            position.x - (BOX_SIZE / 2.0) - (SPIKES_IMAGE_SIZE / 2.0),
-            position.y,
-        ),
-        image_handles,
-        commands,
-    );
+    if settings.reduced_flashing {
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Portal flash"),
+        Obstacle,
+        PortalFlash {
+            timer: Timer::from_seconds(PORTAL_FLASH_DURATION, TimerMode::Once),
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(PORTAL_SIZE * 1.5),
+                color: active_theme.theme.particle_color().with_alpha(0.8),
+                ..default()
+            },
+            transform: Transform::from_translation(trigger.event().0.extend(0.5)),
+            ..default()
+        },
+    ));
+}
+
+fn fade_portal_flashes(
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut PortalFlash, &mut Sprite, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, mut flash, mut sprite, mut transform) in &mut flash_query {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = 1.0 - flash.timer.fraction();
+        sprite.color.set_alpha(0.8 * remaining);
+        transform.scale = Vec3::splat(0.5 + remaining * 0.5);
+    }
+}
+
+fn spawn_bounce_pad(
+    position: Vec2,
+    launch_velocity: f32,
+    image_handles: &HandleMap<ImageKey>,
+    commands: &mut Commands,
+) -> Entity {
+    let collider = RectCollider {
+        bounds: Vec2::new(BOUNCE_PAD_WIDTH, BOUNCE_PAD_HEIGHT),
+        offset: Vec2::ZERO,
+    };
+    commands
+        .spawn((
+            Name::new("Bounce pad"),
+            Obstacle,
+            PulseOnBeat::obstacle(),
+            BouncePad { launch_velocity },
+            SpriteBundle {
+                texture: image_handles.get(ImageKey::BouncePad),
+                transform: Transform::from_scale(Vec2::splat(IMAGE_SCALE).extend(1.0))
+                    .with_translation(Vec3::new(position.x, position.y, 0.0)),
+                ..Default::default()
+            },
+            collider.clone(),
+        ))
+        .with_children(|children| {
+            if SHOW_COLLIDERS {
+                children.spawn((
+                    Name::new("Bounce pad collider visualization"),
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(collider.bounds / IMAGE_SCALE),
+                            color: Color::srgba(0.0, 1.0, 0.0, 0.3),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            (collider.offset / IMAGE_SCALE).extend(1.0),
+                        ),
+                        ..default()
+                    },
+                ));
+            }
+        })
+        .id()
 }
@@ -11,3 +11,7 @@ pub mod sequencer;
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((level::plugin, player::plugin, sequencer::plugin));
 }
+
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.add_plugins((level::presentation_plugin, sequencer::presentation_plugin));
+}
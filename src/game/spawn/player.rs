@@ -1,12 +1,15 @@
 //! Spawn the player.
 
-use bevy::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
 
 use crate::{
     game::{
         animation::PlayerAnimation,
         assets::{HandleMap, ImageKey},
-        movement::MovementController,
+        coop::{Multiplayer, PlayerSlot},
+        movement::{GroundState, MovementController, VerticalContact},
+        settings::Settings,
+        versus::Opponent,
         SHOW_COLLIDERS,
     },
     screen::Screen,
@@ -18,6 +21,27 @@ const PLAYER_SCALE: f32 = 3.0;
 const PLAYER_RAW_IMAGE_SIZE: f32 = 24.0;
 pub const PLAYER_IMAGE_SIZE: f32 = PLAYER_RAW_IMAGE_SIZE * PLAYER_SCALE;
 
+const COLLIDER_SIZE: Vec2 = Vec2::new(7.5 * PLAYER_SCALE, 21.0 * PLAYER_SCALE);
+const COLLIDER_OFFSET: Vec2 = Vec2::new(5.5 * PLAYER_SCALE, -1.5 * PLAYER_SCALE);
+
+/// Where runners start each level, standing on the floor at the left edge
+pub const PLAYER_START: Vec2 = Vec2::new(
+    (-LEVEL_WIDTH / 2.0) + (PLAYER_IMAGE_SIZE / 2.0),
+    FLOOR_Y - COLLIDER_OFFSET.y + (COLLIDER_SIZE.y / 2.0) + 1.0,
+);
+
+/// How far behind player 1 player 2 starts in co-op, in pixels, so they don't start out on top of each other
+const PLAYER_TWO_START_OFFSET: f32 = 30.0;
+
+/// The color player 2's sprite is tinted in co-op, to tell them apart
+const PLAYER_TWO_TINT: Color = Color::srgb(1.0, 0.7, 0.45);
+
+/// The color the opponent's sprite is tinted in versus, so it's see-through like a ghost
+const OPPONENT_TINT: Color = Color::srgba(1.0, 1.0, 1.0, 0.45);
+
+/// How far back the opponent is drawn, so it stays behind the player and in front of the background
+pub const OPPONENT_Z: f32 = -0.5;
+
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_player);
     app.register_type::<Player>();
@@ -39,6 +63,8 @@ fn spawn_player(
     image_handles: Res<HandleMap<ImageKey>>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     existing_player_query: Query<Entity, With<Player>>,
+    multiplayer: Res<Multiplayer>,
+    settings: Res<Settings>,
 ) {
     // despawn any existing player(s)
     for existing_player in &existing_player_query {
@@ -52,56 +78,92 @@ fn spawn_player(
     let layout = TextureAtlasLayout::from_grid(
         UVec2::splat(PLAYER_RAW_IMAGE_SIZE as u32),
         7,
-        3,
+        4,
         Some(UVec2::splat(0)),
         None,
     );
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
+
+    for &slot in multiplayer.slots() {
+        let (name, start_offset, color) = match slot {
+            PlayerSlot::One => ("Player", 0.0, Color::WHITE),
+            PlayerSlot::Two => ("Player 2", -PLAYER_TWO_START_OFFSET, PLAYER_TWO_TINT),
+        };
+        spawn_runner(
+            &mut commands,
+            name,
+            (PLAYER_START + Vec2::new(start_offset, 0.0)).extend(0.0),
+            color,
+            image_handles.get(settings.skin.image_key()),
+            &texture_atlas_layout,
+        )
+        .insert(slot);
+    }
+
+    if *multiplayer == Multiplayer::Versus {
+        spawn_runner(
+            &mut commands,
+            "Opponent",
+            PLAYER_START.extend(OPPONENT_Z),
+            OPPONENT_TINT,
+            // the opponent always looks the same, so it's easy to tell apart from the player
+            image_handles.get(ImageKey::Player),
+            &texture_atlas_layout,
+        )
+        .insert(Opponent::default());
+    }
+}
+
+/// Spawns a runner at the provided position, drawn with the provided sprite sheet tinted the provided color
+fn spawn_runner<'a>(
+    commands: &'a mut Commands,
+    name: &'static str,
+    translation: Vec3,
+    color: Color,
+    texture: Handle<Image>,
+    texture_atlas_layout: &Handle<TextureAtlasLayout>,
+) -> EntityCommands<'a> {
     let player_animation = PlayerAnimation::new();
-    let collider_size = Vec2::new(7.5 * PLAYER_SCALE, 21.0 * PLAYER_SCALE);
-    let collider_offset = Vec2::new(5.5 * PLAYER_SCALE, -1.5 * PLAYER_SCALE);
-
-    commands
-        .spawn((
-            Name::new("Player"),
-            Player {
-                collider: collider_size,
-                collider_offset,
-            },
-            SpriteBundle {
-                texture: image_handles.get(ImageKey::Player),
-                transform: Transform::from_scale(Vec2::splat(PLAYER_SCALE).extend(1.0))
-                    .with_translation(Vec3::new(
-                        (-LEVEL_WIDTH / 2.0) + (PLAYER_IMAGE_SIZE / 2.0),
-                        FLOOR_Y - collider_offset.y + (collider_size.y / 2.0) + 1.0,
-                        0.0,
-                    )),
-                ..Default::default()
-            },
-            TextureAtlas {
-                layout: texture_atlas_layout.clone(),
-                index: player_animation.get_atlas_index(),
-            },
-            MovementController::new(),
-            player_animation,
-            StateScoped(Screen::Playing),
-        ))
-        .with_children(|children| {
-            if SHOW_COLLIDERS {
-                children.spawn((
-                    Name::new("Player collider visualization"),
-                    SpriteBundle {
-                        sprite: Sprite {
-                            custom_size: Some(collider_size / PLAYER_SCALE),
-                            color: Color::srgba(0.0, 1.0, 0.0, 0.3),
-                            ..default()
-                        },
-                        transform: Transform::from_translation(
-                            (collider_offset / PLAYER_SCALE).extend(1.0),
-                        ),
+    let mut runner = commands.spawn((
+        Name::new(name),
+        Player {
+            collider: COLLIDER_SIZE,
+            collider_offset: COLLIDER_OFFSET,
+        },
+        SpriteBundle {
+            sprite: Sprite { color, ..default() },
+            texture,
+            transform: Transform::from_scale(Vec2::splat(PLAYER_SCALE).extend(1.0))
+                .with_translation(translation),
+            ..Default::default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layout.clone(),
+            index: player_animation.get_atlas_index(),
+        },
+        MovementController::new(),
+        VerticalContact::default(),
+        GroundState::default(),
+        player_animation,
+        StateScoped(Screen::Playing),
+    ));
+    runner.with_children(|children| {
+        if SHOW_COLLIDERS {
+            children.spawn((
+                Name::new("Player collider visualization"),
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(COLLIDER_SIZE / PLAYER_SCALE),
+                        color: Color::srgba(0.0, 1.0, 0.0, 0.3),
                         ..default()
                     },
-                ));
-            }
-        });
+                    transform: Transform::from_translation(
+                        (COLLIDER_OFFSET / PLAYER_SCALE).extend(1.0),
+                    ),
+                    ..default()
+                },
+            ));
+        }
+    });
+    runner
 }
@@ -1,57 +1,265 @@
 //! Spawn the sequencer.
 
-use std::collections::HashSet;
+use std::{collections::HashMap, time::Duration};
 
-use bevy::prelude::*;
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     game::{
-        assets::{FontKey, HandleMap, SfxKey},
-        audio::sfx::PlaySfx,
-        movement::{PlayerAction, TotalDistance},
+        assets::{FontKey, HandleMap, JudgementsKey, SfxKey},
+        audio::{
+            export::ExportAudio,
+            sfx::{PlaySfx, DEFAULT_SFX_VOLUME},
+        },
+        camera::WorldCamera,
+        clock::SequenceClock,
+        coop::{Multiplayer, PlayerSlot, RowOwner, RowOwners},
+        difficulty::DifficultyCurve,
+        input::{ActionInput, BindingKind, InputAction, InputBinding, InputMap},
+        judgement::{JudgedRun, Judgements},
+        localization::Localization,
+        movement::{PlaybackSpeed, PlayerAction, TotalDistance},
+        mutators::{Mutators, RowShuffle},
+        pattern_library::SavePattern,
+        photo_mode::PhotoMode,
+        settings::Settings,
+        song::spawn_song_bar,
+        stats::RunStats,
+        suggest::{SuggestSequence, SuggestedBeats},
+        theme::ActiveTheme,
     },
     screen::Screen,
     ui::{
-        interaction::{Enabled, InteractionPalette, InteractionQuery},
+        focus::FocusScope,
+        interaction::{ButtonActivated, Enabled, HoverEntered, InteractionPalette, Repeatable},
         palette::{
-            ACTIVE_BEAT_BUTTON, HOVERED_ACTIVE_BEAT_BUTTON, HOVERED_INACTIVE_BEAT_BUTTON,
-            INACTIVE_BEAT_BUTTON, PLAYING_ACTIVE_BEAT_BUTTON, PLAYING_INACTIVE_BEAT_BUTTON,
+            greyed_out, BeatPalette, BEAT_NUMBER_TEXT, BUTTON_HOVERED_BACKGROUND,
+            BUTTON_PRESSED_BACKGROUND, BUTTON_TEXT, DISABLED_BUTTON_BACKGROUND,
+            INACTIVE_BEAT_BUTTON, LABEL_TEXT, NODE_BACKGROUND, PLAYER_ONE_ROW, PLAYER_TWO_ROW,
+            PLAYHEAD, SELECTED_BUTTON_BACKGROUND,
         },
-        widgets::Widgets,
+        tooltip::Tooltip,
+        widgets::{Containers, Widgets},
     },
     AppSet,
 };
 
 use super::{
-    level::{CurrentLevel, SpawnObstacles},
+    level::{CurrentLevel, GameMode, RunMode, SpawnLevel, SpawnObstacles, StartingLevel},
     player::SpawnPlayer,
 };
 
 pub const NUM_SYNTH_NOTES: usize = 8;
 pub const NUM_BEATS_IN_SEQUENCE: usize = 32;
 
+/// How many ticks of the sequence clock there are in each beat, so double-time rows have somewhere to land
+pub const TICKS_PER_BEAT: usize = 2;
+
+/// How many ticks it takes for every row to get back to the start of its pattern at the same time.
+/// Half-time rows take two loops of the sequence to get through all their beats.
+const TICKS_PER_CYCLE: usize = NUM_BEATS_IN_SEQUENCE * TICKS_PER_BEAT * 2;
+
+/// The chance of a "maybe" note playing each time the sequence loops around
+pub const MAYBE_CHANCE: f32 = 0.5;
+
 const SPEED_MULTIPLIER: f32 = 50.0;
 
+/// The width of the label at the start of each sequencer row, in pixels
+const ROW_LABEL_WIDTH: f32 = 100.0;
+
+/// The space between beat buttons, in pixels
+const BEAT_BUTTON_GAP: f32 = 3.0;
+
+/// The width of the buttons that fill in a whole row at once, in pixels
+const ROW_TOOL_WIDTH: f32 = 45.0;
+
+/// The width of the button that changes how fast a row goes through its steps, in pixels
+const ROW_RATE_WIDTH: f32 = 35.0;
+
+/// The width of the buttons that shift a row's beats earlier or later, in pixels
+const ROW_SHIFT_WIDTH: f32 = 20.0;
+
+/// The width of the button that plays a row on its own, in pixels
+const ROW_PREVIEW_WIDTH: f32 = 40.0;
+
+/// The width of the button that picks which runners a row controls in co-op, in pixels.
+/// It takes its room from the row's label, which is never that long.
+const ROW_OWNER_WIDTH: f32 = 30.0;
+
+/// The width of everything before the first beat in a row, in pixels
+const ROW_HEADER_WIDTH: f32 = ROW_LABEL_WIDTH
+    + (ROW_TOOL_WIDTH + BEAT_BUTTON_GAP) * 2.0
+    + (ROW_SHIFT_WIDTH + BEAT_BUTTON_GAP) * 2.0
+    + ROW_RATE_WIDTH
+    + BEAT_BUTTON_GAP
+    + ROW_PREVIEW_WIDTH
+    + BEAT_BUTTON_GAP;
+
+/// The height of the velocity lane under the grid, in pixels
+const VELOCITY_LANE_HEIGHT: f32 = 36.0;
+
+/// How many beats the row tools fill in before the player picks a different number
+const DEFAULT_ROW_FILL_HITS: usize = 8;
+
+/// The width of the slider for picking how many beats the row tools fill in, in pixels
+const HITS_SLIDER_WIDTH: f32 = 128.0;
+
+/// The biggest beat buttons get when there's plenty of room, in pixels
+const MAX_BEAT_BUTTON_SIZE: f32 = 30.0;
+
+/// The smallest beat buttons get before the grid is split into two banks, in pixels
+const MIN_BEAT_BUTTON_SIZE: f32 = 18.0;
+
+/// The number of beats in each group. Every other group is shaded, and groups are spaced apart,
+/// to make it easier to keep track of where you are in the bar.
+pub const BEATS_PER_GROUP: usize = 4;
+
+/// The extra space between groups of beats, in pixels
+const GROUP_SEPARATOR_WIDTH: f32 = 5.0;
+
+/// How many beats the grid can be zoomed in to show at a time, from all of them to the fewest
+const ZOOM_LEVELS: [usize; 3] = [
+    NUM_BEATS_IN_SEQUENCE,
+    NUM_BEATS_IN_SEQUENCE / 2,
+    NUM_BEATS_IN_SEQUENCE / 4,
+];
+
+/// The height of the bar with the play/pause/stop buttons, in pixels.
+/// This part of the sequencer stays on screen when it's collapsed.
+const CONTROLS_HEIGHT: f32 = 40.0;
+
+/// How long it takes the sequencer to slide all the way in or out, in seconds
+const COLLAPSE_DURATION: f32 = 0.25;
+
+/// How many beats are counted down before the run starts over on its own
+const RESTART_COUNTDOWN_BEATS: u32 = 3;
+
+/// How many beats are counted in before a run starts
+const PRE_ROLL_BEATS: u32 = 4;
+
+/// How many beats are counted in before a paused run picks back up, when that's turned on
+const RESUME_COUNT_IN_BEATS: u32 = 4;
+
+/// How long the camera takes to pan over the level before the count-in, in seconds
+const PRE_ROLL_PAN_DURATION: f32 = 2.0;
+
+/// How many accent levels a note can have, including not being accented
+const ACCENT_LEVELS: u8 = 3;
+
+/// How much louder a note gets for each accent level, relative to its normal volume
+const ACCENT_VOLUME_STEP: f32 = 0.4;
+
+/// How much an accented beat button is lightened for each accent level, from 0 to 1
+const ACCENT_HIGHLIGHT_STEP: f32 = 0.2;
+
+/// How long the cursor has to stay on an empty beat before its row's sound is previewed, in seconds
+const HOVER_PREVIEW_DELAY: f32 = 0.15;
+
+/// How loud previewed sounds are, relative to the sequence playing them
+const HOVER_PREVIEW_VOLUME: f32 = 0.4;
+
+/// How many bars a row is looped for when it's played on its own. A bar is one time through the row's steps.
+const ROW_PREVIEW_BARS: usize = 2;
+
 pub(super) fn plugin(app: &mut App) {
-    app.observe(spawn_sequencer);
     app.observe(play_sequence);
     app.observe(pause_sequence);
     app.observe(reset_sequence);
-    app.observe(play_beat);
+    app.observe(reset_pre_roll);
+    app.observe(play_tick);
     app.observe(handle_death);
+    app.observe(handle_victory);
+    app.observe(apply_level_tempo);
+    app.insert_resource(Sequence::new());
+    app.insert_resource(SequenceState::new());
+    app.insert_resource(Dead(false));
+    app.init_resource::<PreRoll>();
+    app.add_systems(OnEnter(Screen::Playing), remember_run_mode);
+    app.add_systems(OnExit(Screen::Playing), forget_run_mode);
+    app.add_systems(
+        Update,
+        (
+            update_sequence_timer.in_set(AppSet::TickTimers),
+            advance_pre_roll
+                .in_set(AppSet::TickTimers)
+                .run_if(pre_roll_running),
+        ),
+    );
+}
+
+/// The sequencer UI, and the panels shown at the end of a run
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.observe(spawn_sequencer);
+    app.observe(show_game_over);
+    app.observe(show_victory);
+    app.observe(close_end_of_run_panels);
     app.observe(set_beat_buttons_enabled);
+    app.observe(toggle_sequencer_collapsed);
+    app.observe(refresh_beat_buttons);
     app.register_type::<Sequencer>();
+    app.register_type::<SequencerCollapse>();
     app.register_type::<GameAction>();
     app.register_type::<SequencerAction>();
-    app.insert_resource(Sequence::new());
-    app.insert_resource(SequenceState::new());
-    app.insert_resource(Dead(false));
+    app.insert_resource(RowFillHits(DEFAULT_ROW_FILL_HITS));
+    app.init_resource::<HoverPreview>();
+    app.init_resource::<RowPreview>();
+    app.init_resource::<VelocityLane>();
+    app.init_resource::<GridZoom>();
+    app.add_systems(OnExit(Screen::Playing), stop_row_preview);
     app.add_systems(Update, handle_game_action.run_if(in_state(Screen::Playing)));
     app.add_systems(
         Update,
         (
             handle_sequencer_action.run_if(in_state(Screen::Playing)),
-            update_sequence_timer.in_set(AppSet::TickTimers),
+            handle_beat_button_mouse_buttons.run_if(in_state(Screen::Playing)),
+            handle_row_tool.run_if(in_state(Screen::Playing)),
+            update_row_owner_buttons.run_if(resource_changed::<RowOwners>),
+            drag_hits_slider
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(Screen::Playing)),
+            update_hits_slider.run_if(resource_changed::<RowFillHits>),
+            apply_beat_palette.run_if(resource_changed::<BeatPalette>),
+            apply_theme_accent.in_set(AppSet::Update),
+            layout_beat_grid.in_set(AppSet::Update),
+            collapse_on_tab
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(Screen::Playing)),
+            scrub_timeline
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(Screen::Playing)),
+            animate_sequencer_collapse.in_set(AppSet::Update),
+            update_toggle_buttons.in_set(AppSet::Update),
+            move_playhead.in_set(AppSet::Update),
+            count_down_restart.in_set(AppSet::Update),
+            show_pre_roll
+                .in_set(AppSet::Update)
+                .run_if(resource_changed::<PreRoll>),
+            (start_hover_preview, play_hover_preview)
+                .chain()
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing).and_then(preview_on_hover_enabled)),
+            play_row_preview
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Playing)),
+            update_row_preview_buttons.run_if(resource_changed::<RowPreview>),
+        ),
+    );
+    app.add_systems(
+        Update,
+        (
+            handle_velocity_lane_button.run_if(in_state(Screen::Playing)),
+            drag_velocity_lane
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(Screen::Playing)),
+            (update_velocity_lane_button, update_velocity_bars).in_set(AppSet::Update),
+            follow_playback_page.in_set(AppSet::Update),
+            update_zoom_controls.run_if(resource_changed::<GridZoom>),
         ),
     );
 }
@@ -59,30 +267,332 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Event, Debug)]
 pub struct SpawnSequencer;
 
+/// Event sent when a runner dies, targeted at that runner
 #[derive(Event, Debug)]
 pub struct DeathEvent;
 
+/// Event that shows the game over panel, once the player's death has played out
+#[derive(Event, Debug)]
+pub struct ShowGameOver;
+
+/// Event that ends the campaign in victory
+#[derive(Event, Debug)]
+pub struct VictoryEvent;
+
 #[derive(Event, Debug)]
 pub struct SetBeatButtonsEnabled(pub bool);
 
+/// Event that slides the sequencer off the bottom of the screen, or back on if it's already off
+#[derive(Event, Debug)]
+pub struct ToggleSequencerCollapsed;
+
+/// Event that updates the beat buttons to match the current sequence, for when it's been swapped out
+#[derive(Event, Debug)]
+pub struct RefreshBeatButtons;
+
+/// Event sent when the player turns on a note on the provided row that was off
+#[derive(Event, Debug)]
+pub struct NotePlaced(pub SequencerRow);
+
 #[derive(Resource)]
 pub struct Dead(pub bool);
 
+/// How many beats the randomize and euclidean row tools fill in
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+struct RowFillHits(usize);
+
 #[derive(Component)]
 pub struct GameOver;
 
+/// Counts down the beats until the run starts over on its own, when auto restart is on
+#[derive(Component, Debug)]
+struct RestartCountdown {
+    /// Goes off every beat
+    timer: Timer,
+    beats_left: u32,
+}
+
+/// The lead-up to a run, where the camera pans over the level and then a few beats are counted in before anything moves.
+/// Runs picking back up after being paused can be counted in too.
+#[derive(Resource, Debug, Default)]
+pub enum PreRoll {
+    /// The run hasn't been started from the beginning yet
+    #[default]
+    Waiting,
+    /// The camera is panning over the level
+    Panning(Timer),
+    /// Counting in the beats before the run starts, or picks back up
+    CountingIn {
+        /// Goes off every beat
+        timer: Timer,
+        beats_left: u32,
+    },
+    /// The run has started
+    Done,
+    /// The run was paused partway through, and picks back up from the same beat when it's played again
+    Paused,
+}
+
+impl PreRoll {
+    fn counting_in(beat_interval: Duration, beats: u32) -> PreRoll {
+        PreRoll::CountingIn {
+            timer: Timer::new(beat_interval, TimerMode::Repeating),
+            beats_left: beats,
+        }
+    }
+
+    /// Gets how far along the camera is in panning over the level, from 0 to 1, if it's panning
+    pub fn pan_progress(&self) -> Option<f32> {
+        match self {
+            PreRoll::Panning(timer) => Some(timer.fraction()),
+            _ => None,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self, PreRoll::Panning(_) | PreRoll::CountingIn { .. })
+    }
+}
+
+/// Holds what's shown on screen during the pre-roll
+#[derive(Component)]
+struct PreRollDisplay;
+
+/// The text shown during the pre-roll
+#[derive(Component)]
+struct PreRollText;
+
+#[derive(Component)]
+pub struct Victory;
+
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
 pub struct Sequencer;
 
-/// The current sequence, ordered by beats. If a row appears in the set for a given beat, then that instrument is active on that beat.
-#[derive(Resource)]
-pub struct Sequence(Vec<HashSet<SequencerRow>>);
+/// Whether the sequencer is collapsed, and how far along it is in sliding there.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct SequencerCollapse {
+    pub collapsed: bool,
+    /// 0 when the sequencer is all the way out, 1 when it's all the way collapsed
+    progress: f32,
+}
+
+/// The current sequence, ordered by step. If a row appears in the map for a given step, then that instrument is active on that step,
+/// and it plays with the chance it's mapped to, from 0 to 1.
+/// Each row goes through its steps at its own rate, so a step isn't always the same length as a beat.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct Sequence {
+    steps: Vec<HashMap<SequencerRow, f32>>,
+    /// How fast each row goes through its steps. Rows that aren't in here go at [`StepRate::Normal`].
+    rates: HashMap<SequencerRow, StepRate>,
+    /// How accented the notes on each step are. Notes that aren't in here aren't accented.
+    accents: HashMap<(usize, SequencerRow), u8>,
+    /// Whether the last beat is left silent, so the notes at the end of the sequence don't run straight into the ones at the start when it loops
+    loop_fill: bool,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Sequence::new()
+    }
+}
 
 impl Sequence {
     /// Creates a sequence with all the notes off
-    fn new() -> Sequence {
-        Sequence((0..NUM_BEATS_IN_SEQUENCE).map(|_| HashSet::new()).collect())
+    pub fn new() -> Sequence {
+        Sequence {
+            steps: (0..NUM_BEATS_IN_SEQUENCE).map(|_| HashMap::new()).collect(),
+            rates: HashMap::new(),
+            accents: HashMap::new(),
+            loop_fill: false,
+        }
+    }
+
+    /// Turns on the note on the provided row and step, so it plays every time
+    pub fn add_note(&mut self, row: SequencerRow, step: usize) {
+        self.steps[step].insert(row, 1.0);
+    }
+
+    /// Gets the notes that are on for each step and their chances of playing, in order
+    pub fn beats(&self) -> impl Iterator<Item = &HashMap<SequencerRow, f32>> {
+        self.steps.iter()
+    }
+
+    /// Gets the chance of the note on the provided row and step playing, or `None` if it's off
+    pub fn chance(&self, row: SequencerRow, step: usize) -> Option<f32> {
+        self.steps[step].get(&row).copied()
+    }
+
+    /// Gets how accented the note on the provided row and step is, from 0 for not accented to [`ACCENT_LEVELS`] - 1
+    fn accent(&self, row: SequencerRow, step: usize) -> u8 {
+        self.accents.get(&(step, row)).copied().unwrap_or(0)
+    }
+
+    /// Gets how accented the note on the provided row that starts on the provided tick of the sequence clock is
+    pub fn accent_at_tick(&self, row: SequencerRow, tick: usize) -> u8 {
+        self.rate(row)
+            .step_at(tick)
+            .map_or(0, |step| self.accent(row, step))
+    }
+
+    /// Gets how fast the provided row goes through its steps
+    pub fn rate(&self, row: SequencerRow) -> StepRate {
+        self.rates.get(&row).copied().unwrap_or_default()
+    }
+
+    /// Gets the notes that start on the provided tick of the sequence clock, and their chances of playing
+    pub fn notes_at_tick(&self, tick: usize) -> impl Iterator<Item = (SequencerRow, f32)> + '_ {
+        let muted = self.loop_fill
+            && (tick / TICKS_PER_BEAT) % NUM_BEATS_IN_SEQUENCE == NUM_BEATS_IN_SEQUENCE - 1;
+        StepRate::ALL
+            .into_iter()
+            .filter_map(move |rate| rate.step_at(tick).map(|step| (rate, step)))
+            .flat_map(move |(rate, step)| {
+                self.steps[step]
+                    .iter()
+                    .filter(move |(row, _)| self.rate(**row) == rate)
+                    .map(|(row, chance)| (*row, *chance))
+            })
+            .filter(move |_| !muted)
+    }
+
+    /// Whether the last beat is left silent before the sequence loops
+    pub fn loop_fill(&self) -> bool {
+        self.loop_fill
+    }
+
+    /// Gets how many ticks it takes for the whole sequence to loop seamlessly
+    pub fn loop_ticks(&self) -> usize {
+        if self.rates.values().any(|rate| *rate == StepRate::Half) {
+            TICKS_PER_CYCLE
+        } else {
+            NUM_BEATS_IN_SEQUENCE * TICKS_PER_BEAT
+        }
+    }
+
+    /// Turns the note for the provided button on or off to match it
+    fn set(&mut self, button: &BeatButton) {
+        match button.chance() {
+            Some(chance) => self.steps[button.beat].insert(button.row, chance),
+            None => self.steps[button.beat].remove(&button.row),
+        };
+        if button.active && button.accent > 0 {
+            self.accents
+                .insert((button.beat, button.row), button.accent);
+        } else {
+            self.accents.remove(&(button.beat, button.row));
+        }
+    }
+
+    /// Moves the provided row on to the next step rate, and returns the new rate
+    fn cycle_rate(&mut self, row: SequencerRow) -> StepRate {
+        let rate = self.rate(row).next();
+        if rate == StepRate::Normal {
+            self.rates.remove(&row);
+        } else {
+            self.rates.insert(row, rate);
+        }
+        rate
+    }
+
+    /// Gets the total number of notes that are on across all steps
+    pub fn num_active_notes(&self) -> usize {
+        self.steps.iter().map(|beat| beat.len()).sum()
+    }
+
+    /// Gets a hash of which notes are on, which is the same for identical sequences across builds and platforms
+    #[cfg(feature = "leaderboard")]
+    pub fn fingerprint(&self) -> u64 {
+        // FNV-1a
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut add = |byte: u8| {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        };
+        let row_byte = |row: &SequencerRow| match row {
+            SequencerRow::SynthNote(x) => *x as u8,
+            SequencerRow::HiHat => u8::MAX - 2,
+            SequencerRow::Snare => u8::MAX - 1,
+            SequencerRow::Kick => u8::MAX,
+        };
+        for (beat, rows) in self.steps.iter().enumerate() {
+            let mut rows = rows.iter().collect::<Vec<_>>();
+            rows.sort_by_key(|(row, _)| **row);
+            for (row, chance) in rows {
+                add(beat as u8);
+                add(row_byte(row));
+                // notes that always play don't add anything, so their fingerprints stay the same as before maybe notes existed
+                if *chance < 1.0 {
+                    add((chance * 100.0).round() as u8);
+                }
+            }
+        }
+        // same for rows at the normal rate
+        let mut rates = self.rates.iter().collect::<Vec<_>>();
+        rates.sort_by_key(|(row, _)| **row);
+        for (row, rate) in rates {
+            add(row_byte(row));
+            add(rate.ticks_per_step() as u8);
+        }
+        // same for sequences without the loop fill
+        if self.loop_fill {
+            add(u8::MAX);
+        }
+        hash
+    }
+}
+
+/// How fast a row goes through its steps, compared to the beat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StepRate {
+    /// One step every two beats
+    Half,
+    /// One step every beat
+    #[default]
+    Normal,
+    /// Two steps every beat
+    Double,
+}
+
+impl StepRate {
+    const ALL: [StepRate; 3] = [StepRate::Half, StepRate::Normal, StepRate::Double];
+
+    /// Gets how many ticks of the sequence clock each step lasts
+    fn ticks_per_step(self) -> usize {
+        match self {
+            StepRate::Half => TICKS_PER_BEAT * 2,
+            StepRate::Normal => TICKS_PER_BEAT,
+            StepRate::Double => TICKS_PER_BEAT / 2,
+        }
+    }
+
+    /// Gets the step a row at this rate is on at the provided tick
+    pub fn current_step(self, tick: usize) -> usize {
+        (tick / self.ticks_per_step()) % NUM_BEATS_IN_SEQUENCE
+    }
+
+    /// Gets the step that starts on the provided tick for a row at this rate, if there is one
+    fn step_at(self, tick: usize) -> Option<usize> {
+        tick.is_multiple_of(self.ticks_per_step())
+            .then(|| self.current_step(tick))
+    }
+
+    /// Gets the rate after this one, in the order they're cycled through
+    fn next(self) -> StepRate {
+        match self {
+            StepRate::Normal => StepRate::Double,
+            StepRate::Double => StepRate::Half,
+            StepRate::Half => StepRate::Normal,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            StepRate::Half => "1/2x",
+            StepRate::Normal => "1x",
+            StepRate::Double => "2x",
+        }
     }
 }
 
@@ -90,11 +600,20 @@ fn spawn_sequencer(
     _trigger: Trigger<SpawnSequencer>,
     mut commands: Commands,
     font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    beat_palette: Res<BeatPalette>,
+    multiplayer: Res<Multiplayer>,
+    row_owners: Res<RowOwners>,
+    input_map: Res<InputMap>,
+    zoom: Res<GridZoom>,
 ) {
+    // which runners each row controls only matters when there's more than one of them
+    let row_owners = (*multiplayer == Multiplayer::Coop).then_some(&*row_owners);
     commands
         .spawn((
             Name::new("Sequencer UI Root"),
             Sequencer,
+            SequencerCollapse::default(),
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
@@ -112,10 +631,28 @@ fn spawn_sequencer(
             },
         ))
         .with_children(|children| {
-            spawn_controls(children, &font_handles);
-            spawn_synth_section(children, &font_handles);
-            spawn_percussion_section(children, &font_handles);
+            spawn_controls(children, &font_handles, &localization, &input_map, &zoom);
+            spawn_song_bar(children, &font_handles, &localization);
+            spawn_beat_numbers(children, &font_handles, &localization);
+            spawn_synth_section(
+                children,
+                &font_handles,
+                &localization,
+                &beat_palette,
+                row_owners,
+            );
+            spawn_percussion_section(
+                children,
+                &font_handles,
+                &localization,
+                &beat_palette,
+                row_owners,
+            );
+            spawn_velocity_lane(children, &font_handles, &localization);
         });
+
+    // the sequence carries over from the last run, or was loaded from the pattern library
+    commands.trigger(RefreshBeatButtons);
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
@@ -124,34 +661,163 @@ enum GameAction {
     Play,
     Pause,
     Stop,
+    KeepGoing,
+    ExportAudio,
+    ToggleCollapsed,
+    ToggleSlowMotion,
+    ToggleLoopFill,
+    SavePattern,
+    Suggest,
+    CycleZoom,
+    PreviousPage,
+    NextPage,
 }
 
-fn handle_game_action(mut button_query: InteractionQuery<&GameAction>, mut commands: Commands) {
-    for (interaction, action) in &mut button_query {
-        if matches!(interaction, Interaction::Pressed) {
-            match action {
-                GameAction::Play => commands.trigger(PlaySequence),
-                GameAction::Pause => commands.trigger(PauseSequence),
-                GameAction::Stop => commands.trigger(ResetSequence),
+fn handle_game_action(
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&GameAction>,
+    victory_query: Query<Entity, With<Victory>>,
+    mut game_mode: ResMut<GameMode>,
+    mut playback_speed: ResMut<PlaybackSpeed>,
+    mut sequence: ResMut<Sequence>,
+    mut zoom: ResMut<GridZoom>,
+    mut commands: Commands,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            GameAction::Play => commands.trigger(PlaySequence),
+            GameAction::Pause => commands.trigger(PauseSequence),
+            GameAction::Stop => commands.trigger(ResetSequence),
+            GameAction::KeepGoing => {
+                for entity in &victory_query {
+                    commands.entity(entity).despawn_recursive();
+                }
+                *game_mode = GameMode::Endless;
+                commands.trigger(PlaySequence);
             }
+            GameAction::ExportAudio => commands.trigger(ExportAudio),
+            GameAction::ToggleCollapsed => commands.trigger(ToggleSequencerCollapsed),
+            GameAction::ToggleSlowMotion => {
+                playback_speed.chosen = if playback_speed.is_slow_motion() {
+                    1.0
+                } else {
+                    PlaybackSpeed::SLOW_MOTION
+                };
+            }
+            GameAction::ToggleLoopFill => sequence.loop_fill = !sequence.loop_fill,
+            GameAction::SavePattern => commands.trigger(SavePattern),
+            GameAction::Suggest => commands.trigger(SuggestSequence),
+            GameAction::CycleZoom => zoom.cycle(),
+            GameAction::PreviousPage => zoom.turn_page(-1),
+            GameAction::NextPage => zoom.turn_page(1),
+        }
+    }
+}
+
+/// Keeps the slow motion button highlighted while runs are being played back in slow motion,
+/// and the loop fill button highlighted while the sequence has a loop fill
+fn update_toggle_buttons(
+    playback_speed: Res<PlaybackSpeed>,
+    sequence: Res<Sequence>,
+    mut button_query: Query<(Ref<GameAction>, &mut InteractionPalette)>,
+) {
+    for (action, mut palette) in &mut button_query {
+        let selected = match *action {
+            GameAction::ToggleSlowMotion => playback_speed.is_slow_motion(),
+            GameAction::ToggleLoopFill => sequence.loop_fill(),
+            _ => continue,
+        };
+        if !(action.is_added() || playback_speed.is_changed() || sequence.is_changed()) {
+            continue;
         }
+
+        let none = if selected {
+            SELECTED_BUTTON_BACKGROUND
+        } else {
+            NODE_BACKGROUND
+        };
+        *palette = InteractionPalette {
+            none,
+            hovered: BUTTON_HOVERED_BACKGROUND,
+            pressed: BUTTON_PRESSED_BACKGROUND,
+            disabled: greyed_out(none),
+        };
     }
 }
 
 #[derive(Resource)]
 pub struct SequenceState {
-    beat_timer: Timer,
+    /// Works out when each tick of the sequence clock goes off
+    clock: SequenceClock,
+    /// How many ticks into the current cycle the sequence is
+    tick: usize,
     beat: usize,
+    /// Whether the notes on the current tick have been played yet, so they're never played twice
+    tick_played: bool,
 }
 
 impl SequenceState {
     fn new() -> SequenceState {
-        let mut beat_timer = Timer::from_seconds(0.15, TimerMode::Repeating);
-        beat_timer.pause();
         SequenceState {
-            beat_timer,
+            clock: SequenceClock::new(Duration::from_secs_f32(0.15 / TICKS_PER_BEAT as f32)),
+            tick: 0,
             beat: 0,
+            tick_played: false,
+        }
+    }
+
+    /// Sets the time between beats
+    fn set_beat_interval(&mut self, beat_interval: Duration) {
+        self.clock
+            .set_tick_interval(beat_interval / TICKS_PER_BEAT as u32);
+    }
+
+    /// Gets the time between ticks of the sequence clock
+    pub fn tick_interval(&self) -> Duration {
+        self.clock.tick_interval()
+    }
+
+    /// Gets the time between beats
+    pub fn beat_interval(&self) -> Duration {
+        self.tick_interval() * TICKS_PER_BEAT as u32
+    }
+
+    /// Gets the beat the sequence is on
+    pub fn beat(&self) -> usize {
+        self.beat
+    }
+
+    /// Gets how many ticks into the current cycle the sequence is
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Gets how long it's been since the last tick of the sequence clock
+    pub fn tick_elapsed(&self) -> Duration {
+        self.clock.tick_elapsed()
+    }
+
+    /// Whether the notes on the tick the sequence is on have been played yet
+    pub fn tick_played(&self) -> bool {
+        self.tick_played
+    }
+
+    /// Moves the sequence to the start of the provided beat, if it's not already there
+    fn jump_to_beat(&mut self, beat: usize) {
+        let tick = beat * TICKS_PER_BEAT;
+        if self.tick == tick && self.clock.tick_elapsed().is_zero() {
+            return;
         }
+
+        self.tick = tick;
+        self.beat = beat;
+        self.tick_played = false;
+        self.clock.reset();
+    }
+
+    /// Whether the sequence is at the very beginning and hasn't started playing yet
+    pub fn is_stopped(&self) -> bool {
+        self.tick == 0 && self.clock.paused() && !self.tick_played
     }
 }
 
@@ -162,119 +828,337 @@ pub struct PlaySequence;
 fn play_sequence(
     _: Trigger<PlaySequence>,
     mut sequence_state: ResMut<SequenceState>,
+    mut pre_roll: ResMut<PreRoll>,
     dead: Res<Dead>,
+    settings: Res<Settings>,
     mut commands: Commands,
 ) {
-    if dead.0 {
+    if dead.0 || pre_roll.is_running() {
+        return;
+    }
+
+    if matches!(*pre_roll, PreRoll::Waiting) && sequence_state.is_stopped() {
+        *pre_roll = if settings.reduced_motion {
+            commands.trigger(PlaySfx::new(SfxKey::HiHat));
+            PreRoll::counting_in(sequence_state.beat_interval(), PRE_ROLL_BEATS)
+        } else {
+            PreRoll::Panning(Timer::from_seconds(PRE_ROLL_PAN_DURATION, TimerMode::Once))
+        };
+        commands.trigger(SetBeatButtonsEnabled(false));
+        return;
+    }
+    // the sequence stays where it was paused while the beats are counted in, so the run picks back up on the beat
+    if matches!(*pre_roll, PreRoll::Paused) && settings.resume_count_in {
+        commands.trigger(PlaySfx::new(SfxKey::HiHat));
+        *pre_roll = PreRoll::counting_in(sequence_state.beat_interval(), RESUME_COUNT_IN_BEATS);
+        commands.trigger(SetBeatButtonsEnabled(false));
         return;
     }
+    *pre_roll = PreRoll::Done;
 
-    if sequence_state.beat_timer.elapsed().is_zero() {
-        commands.trigger(PlayBeat(0));
+    // the clock only plays the notes on a tick as it reaches it, so the notes on the tick the sequence is starting from have to be played here.
+    // if the clock already played them, like when it just looped back around to the start, they aren't played again.
+    if !sequence_state.tick_played {
+        if sequence_state.tick == 0 {
+            commands.trigger(SequenceStarted);
+        }
+        commands.trigger(PlayTick(sequence_state.tick));
+        if sequence_state.tick.is_multiple_of(TICKS_PER_BEAT) {
+            commands.trigger(PlayBeat);
+        }
+        sequence_state.tick_played = true;
     }
-    sequence_state.beat_timer.unpause();
+    sequence_state.clock.unpause();
     commands.trigger(SetBeatButtonsEnabled(false));
+    commands.trigger(SequenceUnpaused);
+}
+
+/// Event sent when the sequence actually starts moving, after any pre-roll
+#[derive(Event)]
+pub struct SequenceUnpaused;
+
+fn pre_roll_running(pre_roll: Res<PreRoll>) -> bool {
+    pre_roll.is_running()
+}
+
+/// Moves the pre-roll along, clicking off each beat of the count-in and starting the run once it's over
+fn advance_pre_roll(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    sequence_state: Res<SequenceState>,
+    mut pre_roll: ResMut<PreRoll>,
+    mut commands: Commands,
+) {
+    match pre_roll.as_mut() {
+        PreRoll::Panning(timer) => {
+            if !timer.tick(time.delta()).finished() {
+                return;
+            }
+            *pre_roll = PreRoll::counting_in(sequence_state.beat_interval(), PRE_ROLL_BEATS);
+        }
+        PreRoll::CountingIn { timer, beats_left } => {
+            // counted in at the speed the run will go
+            if !timer.tick(playback_speed.delta(&time)).just_finished() {
+                return;
+            }
+            *beats_left = beats_left.saturating_sub(1);
+            if *beats_left == 0 {
+                *pre_roll = PreRoll::Done;
+                commands.trigger(PlaySequence);
+                return;
+            }
+        }
+        PreRoll::Waiting | PreRoll::Done | PreRoll::Paused => return,
+    }
+
+    commands.trigger(PlaySfx::new(SfxKey::HiHat));
+}
+
+/// Calls off any pre-roll left over from the last time a level was played
+fn reset_pre_roll(_: Trigger<SpawnLevel>, mut pre_roll: ResMut<PreRoll>) {
+    *pre_roll = PreRoll::Waiting;
+}
+
+/// Shows what's happening during the pre-roll, and takes it down once the pre-roll is over
+fn show_pre_roll(
+    pre_roll: Res<PreRoll>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    display_query: Query<Entity, With<PreRollDisplay>>,
+    pre_roll_text_query: Query<&Children, With<PreRollText>>,
+    mut text_query: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    if !pre_roll.is_running() {
+        for entity in &display_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let value = match *pre_roll {
+        PreRoll::CountingIn { beats_left, .. } => beats_left.to_string(),
+        _ => localization.get("sequencer.get_ready"),
+    };
+    if display_query.is_empty() {
+        commands
+            .ui_root()
+            .insert((
+                Name::new("Pre-roll"),
+                PreRollDisplay,
+                StateScoped(Screen::Playing),
+            ))
+            .with_children(|children| {
+                children.header(value, &font_handles).insert(PreRollText);
+            });
+        return;
+    }
+
+    for children in &pre_roll_text_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != value {
+                text.sections[0].value.clone_from(&value);
+            }
+        }
+    }
 }
 
 /// Event that stops the sequence and without resetting it to the beginning
 #[derive(Event)]
 pub struct PauseSequence;
 
-fn pause_sequence(_: Trigger<PauseSequence>, mut sequence_state: ResMut<SequenceState>) {
-    sequence_state.beat_timer.pause();
+fn pause_sequence(
+    _: Trigger<PauseSequence>,
+    mut sequence_state: ResMut<SequenceState>,
+    mut pre_roll: ResMut<PreRoll>,
+) {
+    sequence_state.clock.pause();
+
+    // pausing during the pre-roll calls it off, so it starts over the next time the sequence is played.
+    // Partway through a run, it picks back up from the same beat instead, counted in again if that's turned on.
+    if sequence_state.is_stopped() {
+        if pre_roll.is_running() {
+            *pre_roll = PreRoll::Waiting;
+        }
+    } else if pre_roll.is_running() || matches!(*pre_roll, PreRoll::Done) {
+        *pre_roll = PreRoll::Paused;
+    }
 }
 
 /// Event that stops the sequence and resets it to the beginning
 #[derive(Event)]
-struct ResetSequence;
+pub struct ResetSequence;
 
 fn reset_sequence(
     _: Trigger<ResetSequence>,
     mut sequence_state: ResMut<SequenceState>,
-    mut button_query: Query<(&InteractionPalette, &mut BackgroundColor), With<BeatButton>>,
-    game_over_query: Query<Entity, With<GameOver>>,
+    mut pre_roll: ResMut<PreRoll>,
     mut current_level: ResMut<CurrentLevel>,
+    starting_level: Res<StartingLevel>,
+    run_mode: Res<RunMode>,
+    mut game_mode: ResMut<GameMode>,
     mut dead: ResMut<Dead>,
     mut distance: ResMut<TotalDistance>,
     mut commands: Commands,
 ) {
+    sequence_state.tick = 0;
     sequence_state.beat = 0;
-    sequence_state.beat_timer.pause();
-    sequence_state.beat_timer.reset();
-
-    for entity in &game_over_query {
-        commands.entity(entity).despawn_recursive();
-    }
+    sequence_state.tick_played = false;
+    sequence_state.clock.pause();
+    sequence_state.clock.reset();
+    *pre_roll = PreRoll::Waiting;
 
-    for (palette, mut background_color) in button_query.iter_mut() {
-        *background_color = BackgroundColor(palette.none);
+    current_level.0 = starting_level.0;
+    if let Some(mode) = run_mode.0 {
+        game_mode.set_if_neq(mode);
     }
-
-    current_level.0 = 0;
     dead.0 = false;
     distance.0 = 0.0;
     commands.trigger(SpawnPlayer);
-    commands.trigger(SpawnObstacles(0));
+    commands.trigger(SpawnObstacles::new(starting_level.0));
     commands.trigger(SetBeatButtonsEnabled(true));
 }
 
-/// Event that plays all the active notes on a single beat
-#[derive(Event)]
-struct PlayBeat(usize);
+fn remember_run_mode(game_mode: Res<GameMode>, mut run_mode: ResMut<RunMode>) {
+    run_mode.0 = Some(*game_mode);
+}
 
-fn update_sequence_timer(
-    time: Res<Time>,
+fn forget_run_mode(mut run_mode: ResMut<RunMode>) {
+    run_mode.0 = None;
+}
+
+/// Speeds up the sequence for harder levels
+fn apply_level_tempo(
+    trigger: Trigger<SpawnObstacles>,
+    game_mode: Res<GameMode>,
+    difficulty_curve: Res<DifficultyCurve>,
+    mutators: Res<Mutators>,
     mut sequence_state: ResMut<SequenceState>,
-    mut commands: Commands,
 ) {
-    sequence_state.beat_timer.tick(time.delta());
-    if sequence_state.beat_timer.just_finished() {
-        sequence_state.beat = (sequence_state.beat + 1) % NUM_BEATS_IN_SEQUENCE;
-        commands.trigger(PlayBeat(sequence_state.beat))
-    }
+    let beat_interval = match *game_mode {
+        GameMode::Campaign | GameMode::Tutorial | GameMode::EditorTest | GameMode::Custom => {
+            difficulty_curve.base_beat_interval
+        }
+        GameMode::Endless | GameMode::Scrolling => {
+            difficulty_curve.at(trigger.event().level).beat_interval
+        }
+    };
+    sequence_state.set_beat_interval(Duration::from_secs_f32(
+        beat_interval / mutators.tempo_multiplier(),
+    ));
 }
 
-fn play_beat(
-    trigger: Trigger<PlayBeat>,
-    sequence: Res<Sequence>,
-    mut button_query: Query<(&BeatButton, &InteractionPalette, &mut BackgroundColor)>,
-    mut commands: Commands,
+/// Event sent when the sequence reaches a beat
+#[derive(Event)]
+pub struct PlayBeat;
+
+/// Event sent when the sequence starts playing from the very beginning, before its first notes play
+#[derive(Event)]
+pub struct SequenceStarted;
+
+/// Event sent when the sequence gets to the end and goes back around to the beginning, before its first notes play again
+#[derive(Event)]
+pub struct SequenceLooped;
+
+/// Event that plays all the active notes that start on a single tick of the sequence clock
+#[derive(Event)]
+pub struct PlayTick(pub usize);
+
+fn update_sequence_timer(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    sequence: Res<Sequence>,
+    mut sequence_state: ResMut<SequenceState>,
+    mut commands: Commands,
 ) {
-    let beat = trigger.event().0;
-    let mut max_speed_change = None;
-    for row in &sequence.0[beat] {
-        commands.trigger(PlaySfx(row.to_sfx_key()));
-        let action = row.to_player_action();
+    sequence_state.clock.set_speed(playback_speed.speed());
+    let ticks = sequence_state.clock.advance(time.elapsed());
+    if ticks == 0 {
+        return;
+    }
 
-        if let PlayerAction::SetSpeed(speed) = action {
-            if let Some(PlayerAction::SetSpeed(max_speed)) = max_speed_change {
-                if speed > max_speed {
-                    max_speed_change = Some(action);
-                }
-            } else {
-                max_speed_change = Some(action);
-            }
-            continue;
+    // if the frame was long enough for more than one tick to go by, only the latest one is played,
+    // so the sequence stays in time instead of falling behind
+    for _ in 0..ticks {
+        sequence_state.tick += 1;
+        // compared with >= rather than wrapped around, in case a row was taken off half time partway through the second loop
+        if sequence_state.tick >= sequence.loop_ticks() {
+            sequence_state.tick = 0;
+            commands.trigger(SequenceLooped);
         }
+    }
+    sequence_state.beat = (sequence_state.tick / TICKS_PER_BEAT) % NUM_BEATS_IN_SEQUENCE;
+    commands.trigger(PlayTick(sequence_state.tick));
+    if sequence_state.tick.is_multiple_of(TICKS_PER_BEAT) {
+        commands.trigger(PlayBeat);
+    }
+    sequence_state.tick_played = true;
+}
+
+fn play_tick(
+    trigger: Trigger<PlayTick>,
+    sequence: Res<Sequence>,
+    multiplayer: Res<Multiplayer>,
+    row_owners: Res<RowOwners>,
+    row_shuffle: Res<RowShuffle>,
+    runner_query: Query<(Entity, &PlayerSlot)>,
+    mut commands: Commands,
+) {
+    let tick = trigger.event().0;
+    let mut rng = rand::thread_rng();
+    let rows: Vec<SequencerRow> = sequence
+        .notes_at_tick(tick)
+        .filter(|(_, chance)| rng.gen::<f32>() < *chance)
+        .map(|(row, _)| row)
+        .collect();
+    if rows.is_empty() {
+        return;
+    }
 
-        commands.trigger(row.to_player_action());
+    // keep beats with lots of notes from being much louder than beats with only a few
+    let gain = 1.0 / (rows.len().max(1) as f32).sqrt();
+    for row in &rows {
+        let volume = DEFAULT_SFX_VOLUME * gain * accent_volume(sequence.accent_at_tick(*row, tick));
+        commands.trigger(PlaySfx::new(row.to_sfx_key()).with_volume(volume));
     }
 
-    if let Some(speed_change) = max_speed_change {
-        commands.trigger(speed_change);
+    // each runner only listens to its own rows in co-op
+    for (runner, slot) in &runner_query {
+        let runner_rows = rows
+            .iter()
+            .copied()
+            .filter(|row| *multiplayer != Multiplayer::Coop || row_owners.controls(*row, *slot))
+            .map(|row| row_shuffle.acting_row(row));
+        trigger_row_actions(runner_rows, runner, &mut commands);
     }
+}
 
-    for (button, palette, mut background_color) in button_query.iter_mut() {
-        if button.beat == beat {
-            if button.active {
-                *background_color = BackgroundColor(PLAYING_ACTIVE_BEAT_BUTTON);
-            } else {
-                *background_color = BackgroundColor(PLAYING_INACTIVE_BEAT_BUTTON);
+/// Makes the provided runner do what each of the provided rows does.
+/// If more than one of them sets the speed, the fastest one wins.
+pub fn trigger_row_actions(
+    rows: impl IntoIterator<Item = SequencerRow>,
+    runner: Entity,
+    commands: &mut Commands,
+) {
+    let mut max_speed = None;
+    for row in rows {
+        match row.to_player_action() {
+            PlayerAction::SetSpeed(speed) => {
+                max_speed = Some(max_speed.map_or(speed, |max_speed: f32| max_speed.max(speed)));
             }
-        } else {
-            *background_color = BackgroundColor(palette.none);
+            action => commands.trigger_targets(action, runner),
         }
     }
+
+    if let Some(speed) = max_speed {
+        commands.trigger_targets(PlayerAction::SetSpeed(speed), runner);
+    }
+}
+
+/// Gets how loud a note with the provided accent level plays, relative to its normal volume
+pub fn accent_volume(accent: u8) -> f32 {
+    1.0 + f32::from(accent) * ACCENT_VOLUME_STEP
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
@@ -284,48 +1168,101 @@ enum SequencerAction {
 }
 
 fn handle_sequencer_action(
-    mut button_query: InteractionQuery<(
-        &SequencerAction,
-        &mut InteractionPalette,
-        &mut BeatButton,
+    mut activated_events: EventReader<ButtonActivated>,
+    mut button_query: Query<(&SequencerAction, &mut InteractionPalette, &mut BeatButton)>,
+    input: Res<ButtonInput<KeyCode>>,
+    mut sequence: ResMut<Sequence>,
+    mut velocity_lane: ResMut<VelocityLane>,
+    beat_palette: Res<BeatPalette>,
+    mut commands: Commands,
+) {
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let alt = input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let mut buttons = button_query.iter_many_mut(activated_events.read().map(|event| event.0));
+    while let Some((action, mut palette, mut beat_button)) = buttons.fetch_next() {
+        match action {
+            SequencerAction::ToggleBeat => {
+                let was_active = beat_button.active;
+                if alt {
+                    beat_button.cycle_accent();
+                } else if shift {
+                    beat_button.toggle_maybe();
+                } else {
+                    beat_button.toggle();
+                }
+                sequence.set(&beat_button);
+                if beat_button.active {
+                    commands.trigger(beat_button.preview_sfx());
+                }
+                if beat_button.active && !was_active {
+                    commands.trigger(NotePlaced(beat_button.row));
+                }
+                // the velocity lane switches over to whichever row is being worked on
+                if velocity_lane.0.is_some() {
+                    velocity_lane.set_if_neq(VelocityLane(Some(beat_button.row)));
+                }
+                *palette = beat_button.palette(&beat_palette);
+            }
+        }
+    }
+}
+
+/// Clears beats that are right-clicked, and accents beats that are middle-clicked.
+/// Buttons are only pressed with the left mouse button, so this goes by which beat button is hovered instead.
+fn handle_beat_button_mouse_buttons(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut button_query: Query<(
+        &Interaction,
         &Enabled,
+        &mut BeatButton,
+        &mut InteractionPalette,
     )>,
     mut sequence: ResMut<Sequence>,
+    beat_palette: Res<BeatPalette>,
     mut commands: Commands,
 ) {
-    for (interaction, (action, mut palette, mut beat_button, enabled)) in &mut button_query {
-        if !enabled.0 {
-            return;
+    let clear = mouse.just_pressed(MouseButton::Right);
+    let accent = mouse.just_pressed(MouseButton::Middle);
+    if !clear && !accent {
+        return;
+    }
+
+    for (interaction, enabled, mut beat_button, mut palette) in &mut button_query {
+        if !enabled.0 || *interaction != Interaction::Hovered {
+            continue;
         }
 
-        if matches!(interaction, Interaction::Pressed) {
-            match action {
-                SequencerAction::ToggleBeat => {
-                    beat_button.toggle();
-                    if beat_button.active {
-                        sequence.0[beat_button.beat].insert(beat_button.row);
-                        commands.trigger(PlaySfx(beat_button.row.to_sfx_key()));
-                        palette.none = ACTIVE_BEAT_BUTTON;
-                        palette.hovered = HOVERED_ACTIVE_BEAT_BUTTON;
-                        palette.pressed = INACTIVE_BEAT_BUTTON;
-                    } else {
-                        sequence.0[beat_button.beat].remove(&beat_button.row);
-                        palette.none = INACTIVE_BEAT_BUTTON;
-                        palette.hovered = HOVERED_INACTIVE_BEAT_BUTTON;
-                        palette.pressed = ACTIVE_BEAT_BUTTON;
-                    }
-                }
-            }
+        if clear {
+            beat_button.clear();
+        } else {
+            beat_button.cycle_accent();
+            commands.trigger(beat_button.preview_sfx());
         }
+        sequence.set(&beat_button);
+        *palette = beat_button.palette(&beat_palette);
     }
 }
 
-fn spawn_controls(parent: &mut ChildBuilder, font_handles: &HandleMap<FontKey>) {
+fn spawn_controls(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+    input_map: &InputMap,
+    zoom: &GridZoom,
+) {
+    let photo_mode_binding = [BindingKind::Key, BindingKind::Gamepad]
+        .into_iter()
+        .find_map(|kind| input_map.binding(InputAction::PhotoMode, kind))
+        .map_or_else(
+            || localization.get("keybindings.unbound"),
+            InputBinding::label,
+        );
+
     parent
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
-                height: Val::Px(40.0),
+                height: Val::Px(CONTROLS_HEIGHT),
                 top: Val::Px(0.0),
                 left: Val::Px(5.0),
                 justify_self: JustifySelf::Start,
@@ -342,22 +1279,407 @@ fn spawn_controls(parent: &mut ChildBuilder, font_handles: &HandleMap<FontKey>)
         .with_children(|children| {
             // play button
             children
-                .small_button("Play", font_handles)
+                .small_button(localization.get("sequencer.play"), font_handles)
                 .insert(GameAction::Play);
 
             // pause button
             children
-                .small_button("Pause", font_handles)
-                .insert(GameAction::Pause);
+                .small_button(localization.get("sequencer.pause"), font_handles)
+                .insert((
+                    GameAction::Pause,
+                    Tooltip(
+                        localization
+                            .format("sequencer.pause_tooltip", &[("key", &photo_mode_binding)]),
+                    ),
+                ));
 
             // stop button
             children
-                .small_button("Stop", font_handles)
+                .small_button(localization.get("sequencer.stop"), font_handles)
                 .insert(GameAction::Stop);
+
+            // export button
+            children
+                .small_button(localization.get("sequencer.export_audio"), font_handles)
+                .insert((
+                    GameAction::ExportAudio,
+                    Style {
+                        width: Val::Px(150.0),
+                        height: Val::Px(35.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ));
+
+            // slow motion button
+            children.small_button("0.5x", font_handles).insert((
+                GameAction::ToggleSlowMotion,
+                Tooltip(localization.get("sequencer.slow_motion_tooltip")),
+            ));
+
+            // loop fill button
+            children
+                .small_button(localization.get("sequencer.loop_fill"), font_handles)
+                .insert((
+                    GameAction::ToggleLoopFill,
+                    Tooltip(localization.get("sequencer.loop_fill_tooltip")),
+                ));
+
+            // save pattern button
+            children
+                .small_button(localization.get("sequencer.save_pattern"), font_handles)
+                .insert((
+                    GameAction::SavePattern,
+                    Tooltip(localization.get("sequencer.save_pattern_tooltip")),
+                ));
+
+            // suggest button
+            children
+                .small_button(localization.get("sequencer.suggest"), font_handles)
+                .insert((
+                    GameAction::Suggest,
+                    Tooltip(localization.get("sequencer.suggest_tooltip")),
+                ));
+
+            // zoom button
+            children
+                .small_button(zoom.label(localization), font_handles)
+                .insert((
+                    GameAction::CycleZoom,
+                    Tooltip(localization.get("sequencer.zoom_tooltip")),
+                    Style {
+                        width: Val::Px(130.0),
+                        height: Val::Px(35.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ));
+
+            // page buttons, only there while zoomed in
+            for (text, action) in [("<", GameAction::PreviousPage), (">", GameAction::NextPage)] {
+                children.small_button(text, font_handles).insert((
+                    action,
+                    Style {
+                        width: Val::Px(35.0),
+                        height: Val::Px(35.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        display: zoom.page_button_display(),
+                        ..default()
+                    },
+                ));
+            }
+
+            // collapse button
+            children
+                .small_button(localization.get("sequencer.hide"), font_handles)
+                .insert(GameAction::ToggleCollapsed);
+
+            spawn_hits_slider(children, font_handles, localization);
+        });
+}
+
+/// How many beats the grid shows at a time, and which stretch of the sequence it's showing.
+/// The sequence is always the same length; zooming in only changes how much of it is shown.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+struct GridZoom {
+    /// How many beats are shown at a time
+    beats: usize,
+    /// Which stretch of that many beats is shown, counting from the start of the sequence
+    page: usize,
+    /// Whether the page turns along with the sequence while it's playing.
+    /// Turning the page by hand stops this until the sequence is stopped.
+    following: bool,
+}
+
+impl Default for GridZoom {
+    fn default() -> Self {
+        GridZoom {
+            beats: NUM_BEATS_IN_SEQUENCE,
+            page: 0,
+            following: true,
+        }
+    }
+}
+
+impl GridZoom {
+    /// Gets whether the provided beat is shown
+    fn shows(&self, beat: usize) -> bool {
+        beat / self.beats == self.page
+    }
+
+    /// Gets whether fewer than all the beats are shown
+    fn zoomed_in(&self) -> bool {
+        self.beats < NUM_BEATS_IN_SEQUENCE
+    }
+
+    /// Gets how many stretches the sequence is split into at this zoom level
+    fn pages(&self) -> usize {
+        NUM_BEATS_IN_SEQUENCE / self.beats
+    }
+
+    /// Zooms in to the next level, going back out to all the beats after the closest one.
+    /// The first beat shown stays in view.
+    fn cycle(&mut self) {
+        let first_beat = self.page * self.beats;
+        let level = ZOOM_LEVELS
+            .iter()
+            .position(|beats| *beats == self.beats)
+            .unwrap_or(0);
+        self.beats = ZOOM_LEVELS[(level + 1) % ZOOM_LEVELS.len()];
+        self.page = first_beat / self.beats;
+    }
+
+    /// Moves the provided number of stretches later in the sequence, wrapping around at either end
+    fn turn_page(&mut self, pages: isize) {
+        self.page = (self.page as isize + pages).rem_euclid(self.pages() as isize) as usize;
+        self.following = false;
+    }
+
+    fn label(&self, localization: &Localization) -> String {
+        localization.format("sequencer.zoom", &[("beats", &self.beats)])
+    }
+
+    fn page_button_display(&self) -> Display {
+        if self.zoomed_in() {
+            Display::Flex
+        } else {
+            Display::None
+        }
+    }
+}
+
+/// Turns the page along with the sequence while it's playing, so the beat it's on is always in view,
+/// unless the page has been turned by hand since the sequence was last stopped.
+/// This goes by the beat numbers, so rows going at other rates can be on a step that isn't shown.
+fn follow_playback_page(sequence_state: Res<SequenceState>, mut zoom: ResMut<GridZoom>) {
+    if sequence_state.clock.paused() {
+        if !zoom.following {
+            zoom.following = true;
+        }
+        return;
+    }
+    if !zoom.following || !zoom.zoomed_in() {
+        return;
+    }
+
+    let page = sequence_state.beat / zoom.beats;
+    if zoom.page != page {
+        zoom.page = page;
+    }
+}
+
+/// Shows the zoom level on the zoom button, and only shows the page buttons while zoomed in
+fn update_zoom_controls(
+    zoom: Res<GridZoom>,
+    localization: Res<Localization>,
+    mut button_query: Query<(&GameAction, &Children, &mut Style)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (action, children, mut style) in &mut button_query {
+        match action {
+            GameAction::CycleZoom => {
+                let mut texts = text_query.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
+                    text.sections[0].value = zoom.label(&localization);
+                }
+            }
+            GameAction::PreviousPage | GameAction::NextPage => {
+                style.display = zoom.page_button_display();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Spawns the slider for picking how many beats the row tools fill in
+fn spawn_hits_slider(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    parent.spawn((
+        Name::new("Hits text"),
+        HitsText,
+        TextBundle::from_section(
+            hits_text(DEFAULT_ROW_FILL_HITS, localization),
+            TextStyle {
+                font: font_handles.get(FontKey::General),
+                font_size: 24.0,
+                color: LABEL_TEXT,
+            },
+        ),
+    ));
+
+    parent
+        .spawn((
+            Name::new("Hits slider"),
+            HitsSlider,
+            Interaction::default(),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(HITS_SLIDER_WIDTH),
+                    height: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(INACTIVE_BEAT_BUTTON),
+                border_radius: BorderRadius::all(Val::Px(3.0)),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Hits slider fill"),
+                HitsSliderFill,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(hits_fraction(DEFAULT_ROW_FILL_HITS) * 100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(NODE_BACKGROUND),
+                    border_radius: BorderRadius::all(Val::Px(3.0)),
+                    ..default()
+                },
+            ));
         });
 }
 
-fn spawn_synth_section(parent: &mut ChildBuilder, font_handles: &HandleMap<FontKey>) {
+#[derive(Component)]
+struct HitsText;
+
+#[derive(Component)]
+struct HitsSlider;
+
+#[derive(Component)]
+struct HitsSliderFill;
+
+fn hits_text(hits: usize, localization: &Localization) -> String {
+    localization.format("sequencer.hits", &[("hits", &hits)])
+}
+
+/// Gets how much of the hits slider is filled in for the provided number of hits
+fn hits_fraction(hits: usize) -> f32 {
+    hits as f32 / NUM_BEATS_IN_SEQUENCE as f32
+}
+
+/// Sets the number of hits from where the cursor is on the slider while it's held down
+fn drag_hits_slider(
+    ui_scale: Res<UiScale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    slider_query: Query<(&Interaction, &Node, &GlobalTransform), With<HitsSlider>>,
+    mut hits: ResMut<RowFillHits>,
+) {
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let cursor = cursor / ui_scale.0;
+
+    for (interaction, node, transform) in &slider_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        let rect = node.logical_rect(transform);
+        let fraction = ((cursor.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+        let new_hits = ((fraction * NUM_BEATS_IN_SEQUENCE as f32).round() as usize).max(1);
+        hits.set_if_neq(RowFillHits(new_hits));
+    }
+}
+
+fn update_hits_slider(
+    hits: Res<RowFillHits>,
+    localization: Res<Localization>,
+    mut text_query: Query<&mut Text, With<HitsText>>,
+    mut fill_query: Query<&mut Style, With<HitsSliderFill>>,
+) {
+    for mut text in &mut text_query {
+        text.sections[0].value = hits_text(hits.0, &localization);
+    }
+
+    for mut style in &mut fill_query {
+        style.width = Val::Percent(hits_fraction(hits.0) * 100.0);
+    }
+}
+
+fn collapse_on_tab(input: ActionInput, mut commands: Commands) {
+    if input.just_pressed(InputAction::ToggleSequencer) {
+        commands.trigger(ToggleSequencerCollapsed);
+    }
+}
+
+fn toggle_sequencer_collapsed(
+    _trigger: Trigger<ToggleSequencerCollapsed>,
+    mut collapse_query: Query<&mut SequencerCollapse>,
+    button_query: Query<(&GameAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+    localization: Res<Localization>,
+) {
+    let mut collapsed = false;
+    for mut collapse in &mut collapse_query {
+        collapse.collapsed = !collapse.collapsed;
+        collapsed = collapse.collapsed;
+    }
+
+    for (action, children) in &button_query {
+        if *action != GameAction::ToggleCollapsed {
+            continue;
+        }
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = localization.get(if collapsed {
+                "sequencer.show"
+            } else {
+                "sequencer.hide"
+            });
+        }
+    }
+}
+
+/// Slides the sequencer towards where it should be, leaving the controls on screen when it's collapsed.
+/// The camera moves along with it so the level fills the space the sequencer was taking up,
+/// except in photo mode, where the camera is the player's to move.
+pub fn animate_sequencer_collapse(
+    time: Res<Time>,
+    ui_scale: Res<UiScale>,
+    photo_mode: Res<PhotoMode>,
+    mut sequencer_query: Query<(&mut SequencerCollapse, &mut Style, &Node)>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<WorldCamera>>,
+) {
+    for (mut collapse, mut style, node) in &mut sequencer_query {
+        let target = if collapse.collapsed { 1.0 } else { 0.0 };
+        let step = time.delta_seconds() / COLLAPSE_DURATION;
+        collapse.progress = if collapse.progress < target {
+            (collapse.progress + step).min(target)
+        } else {
+            (collapse.progress - step).max(target)
+        };
+
+        let hidden_height = (node.size().y - CONTROLS_HEIGHT).max(0.0) * collapse.progress;
+        style.bottom = Val::Px(-hidden_height);
+
+        if photo_mode.is_active() {
+            continue;
+        }
+        for (mut camera_transform, projection) in &mut camera_query {
+            camera_transform.translation.y = hidden_height * ui_scale.0 * projection.scale / 2.0;
+        }
+    }
+}
+
+fn spawn_synth_section(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+    beat_palette: &BeatPalette,
+    row_owners: Option<&RowOwners>,
+) {
     parent
         .spawn(NodeBundle {
             style: Style {
@@ -376,12 +1698,25 @@ fn spawn_synth_section(parent: &mut ChildBuilder, font_handles: &HandleMap<FontK
         })
         .with_children(|children| {
             for i in (0..NUM_SYNTH_NOTES).rev() {
-                spawn_sequencer_row(children, SequencerRow::SynthNote(i), font_handles);
+                spawn_sequencer_row(
+                    children,
+                    SequencerRow::SynthNote(i),
+                    font_handles,
+                    localization,
+                    beat_palette,
+                    row_owners,
+                );
             }
         });
 }
 
-fn spawn_percussion_section(parent: &mut ChildBuilder, font_handles: &HandleMap<FontKey>) {
+fn spawn_percussion_section(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+    beat_palette: &BeatPalette,
+    row_owners: Option<&RowOwners>,
+) {
     parent
         .spawn(NodeBundle {
             style: Style {
@@ -399,13 +1734,34 @@ fn spawn_percussion_section(parent: &mut ChildBuilder, font_handles: &HandleMap<
             ..default()
         })
         .with_children(|children| {
-            spawn_sequencer_row(children, SequencerRow::HiHat, font_handles);
-            spawn_sequencer_row(children, SequencerRow::Snare, font_handles);
-            spawn_sequencer_row(children, SequencerRow::Kick, font_handles);
+            spawn_sequencer_row(
+                children,
+                SequencerRow::HiHat,
+                font_handles,
+                localization,
+                beat_palette,
+                row_owners,
+            );
+            spawn_sequencer_row(
+                children,
+                SequencerRow::Snare,
+                font_handles,
+                localization,
+                beat_palette,
+                row_owners,
+            );
+            spawn_sequencer_row(
+                children,
+                SequencerRow::Kick,
+                font_handles,
+                localization,
+                beat_palette,
+                row_owners,
+            );
         });
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
 pub enum SequencerRow {
     SynthNote(usize),
     HiHat,
@@ -414,8 +1770,16 @@ pub enum SequencerRow {
 }
 
 impl SequencerRow {
+    /// Gets every row, in the order they go down the grid
+    pub fn grid_order() -> impl Iterator<Item = SequencerRow> {
+        (0..NUM_SYNTH_NOTES)
+            .rev()
+            .map(SequencerRow::SynthNote)
+            .chain([SequencerRow::HiHat, SequencerRow::Snare, SequencerRow::Kick])
+    }
+
     /// Gets the sfx corresponding to this row
-    fn to_sfx_key(self) -> SfxKey {
+    pub fn to_sfx_key(self) -> SfxKey {
         match self {
             SequencerRow::SynthNote(x) => SfxKey::Synth(x),
             SequencerRow::HiHat => SfxKey::HiHat,
@@ -425,7 +1789,7 @@ impl SequencerRow {
     }
 
     /// Gets the player action corresponding to this row
-    fn to_player_action(self) -> PlayerAction {
+    pub fn to_player_action(self) -> PlayerAction {
         match self {
             SequencerRow::SynthNote(x) => PlayerAction::SetSpeed(x as f32 * SPEED_MULTIPLIER),
             SequencerRow::HiHat => PlayerAction::Float,
@@ -435,13 +1799,27 @@ impl SequencerRow {
     }
 }
 
-impl std::fmt::Display for SequencerRow {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl SequencerRow {
+    /// Gets the name of this row in the player's language
+    pub fn name(self, localization: &Localization) -> String {
         match self {
-            SequencerRow::SynthNote(i) => format!("Note {i}").fmt(f),
-            SequencerRow::HiHat => "Hi-hat".fmt(f),
-            SequencerRow::Snare => "Snare".fmt(f),
-            SequencerRow::Kick => "Kick".fmt(f),
+            SequencerRow::SynthNote(i) => localization.format("row.note", &[("note", &i)]),
+            SequencerRow::HiHat => localization.get("row.hi_hat"),
+            SequencerRow::Snare => localization.get("row.snare"),
+            SequencerRow::Kick => localization.get("row.kick"),
+        }
+    }
+
+    /// Describes what playing a note on this row makes the player do
+    fn description(self, localization: &Localization) -> String {
+        let name = self.name(localization);
+        match self.to_player_action() {
+            PlayerAction::SetSpeed(speed) => {
+                localization.format("row.action.speed", &[("row", &name), ("speed", &speed)])
+            }
+            PlayerAction::Jump => localization.format("row.action.jump", &[("row", &name)]),
+            PlayerAction::Dive => localization.format("row.action.dive", &[("row", &name)]),
+            PlayerAction::Float => localization.format("row.action.float", &[("row", &name)]),
         }
     }
 }
@@ -451,12 +1829,81 @@ pub struct BeatButton {
     row: SequencerRow,
     beat: usize,
     active: bool,
+    /// Whether the note only plays some of the time
+    maybe: bool,
+    /// How accented the note is, from 0 for not accented to [`ACCENT_LEVELS`] - 1
+    accent: u8,
+    /// Whether the note was filled in by a suggestion and hasn't been changed since
+    suggested: bool,
 }
 
 impl BeatButton {
     /// Toggles whether a note will be played on this beat or not
     fn toggle(&mut self) {
         self.active = !self.active;
+        self.maybe = false;
+        self.accent = 0;
+        self.suggested = false;
+    }
+
+    /// Turns off the note on this beat, whether it's on or not
+    fn clear(&mut self) {
+        self.active = false;
+        self.maybe = false;
+        self.accent = 0;
+        self.suggested = false;
+    }
+
+    /// Moves the note on this beat to the next accent level, turning it on with the first accent if it's off
+    fn cycle_accent(&mut self) {
+        self.suggested = false;
+        if self.active {
+            self.accent = (self.accent + 1) % ACCENT_LEVELS;
+        } else {
+            self.active = true;
+            self.accent = 1;
+        }
+    }
+
+    /// Toggles whether the note on this beat only plays some of the time, turning it on if it's off
+    fn toggle_maybe(&mut self) {
+        self.suggested = false;
+        self.maybe = !self.active || !self.maybe;
+        self.active = true;
+    }
+
+    /// Gets the chance of the note on this beat playing, or `None` if it's off
+    fn chance(&self) -> Option<f32> {
+        match (self.active, self.maybe) {
+            (false, _) => None,
+            (true, false) => Some(1.0),
+            (true, true) => Some(MAYBE_CHANCE),
+        }
+    }
+
+    /// Gets the sound to play when the note on this beat is changed, so the player can hear what they did
+    fn preview_sfx(&self) -> PlaySfx {
+        PlaySfx::new(self.row.to_sfx_key())
+            .with_volume(DEFAULT_SFX_VOLUME * accent_volume(self.accent))
+            .unducked()
+    }
+
+    /// Gets the colors for this button
+    fn palette(&self, beat_palette: &BeatPalette) -> InteractionPalette {
+        let mut palette =
+            beat_palette.interaction_palette(self.active, self.maybe, is_shaded_beat(self.beat));
+        if self.active && self.suggested {
+            palette.none = beat_palette.suggested;
+            palette.hovered = beat_palette.hovered_suggested;
+            palette.disabled = greyed_out(beat_palette.suggested);
+        }
+        if self.active && self.accent > 0 {
+            let highlight = f32::from(self.accent) * ACCENT_HIGHLIGHT_STEP;
+            palette.none = palette.none.mix(&Color::WHITE, highlight);
+            palette.hovered = palette.hovered.mix(&Color::WHITE, highlight);
+            palette.disabled = palette.disabled.mix(&Color::WHITE, highlight);
+        }
+        palette
     }
 }
 
@@ -464,112 +1911,1470 @@ fn spawn_sequencer_row(
     parent: &mut ChildBuilder,
     row: SequencerRow,
     font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+    beat_palette: &BeatPalette,
+    row_owners: Option<&RowOwners>,
 ) {
-    parent
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                height: Val::Auto,
-                justify_self: JustifySelf::Start,
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                flex_direction: FlexDirection::Row,
-                column_gap: Val::Px(3.0),
-                position_type: PositionType::Relative,
-                ..default()
-            },
-            background_color: BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
-            ..default()
-        })
-        .with_children(|children| {
-            children.label(row.to_string(), font_handles);
-            for i in 0..NUM_BEATS_IN_SEQUENCE {
-                children.spawn((
-                    Name::new("Button"),
-                    ButtonBundle {
+    spawn_grid_row(
+        parent,
+        Some(row),
+        Color::srgb(0.1, 0.1, 0.1),
+        |children| {
+            children
+                .label(row.name(localization), font_handles)
+                .insert(Tooltip(localization.format(
+                    "sequencer.row_tooltip",
+                    &[
+                        ("description", &row.description(localization)),
+                        ("chance", &(MAYBE_CHANCE * 100.0)),
+                    ],
+                )));
+            if let Some(row_owners) = row_owners {
+                let owner = row_owners.owner(row);
+                let palette = owner_palette(owner);
+                spawn_row_tool_button(
+                    children,
+                    &owner_label(owner, localization),
+                    RowTool {
+                        row,
+                        action: RowToolAction::CycleOwner,
+                    },
+                    ROW_OWNER_WIDTH,
+                    font_handles,
+                )
+                .insert((
+                    BackgroundColor(palette.none),
+                    palette,
+                    Tooltip(localization.get("sequencer.owner_tooltip")),
+                ));
+            }
+            spawn_row_tool_button(
+                children,
+                &localization.get("sequencer.preview"),
+                RowTool {
+                    row,
+                    action: RowToolAction::Preview,
+                },
+                ROW_PREVIEW_WIDTH,
+                font_handles,
+            )
+            .insert(Tooltip(localization.get("sequencer.preview_tooltip")));
+            spawn_row_tool_button(
+                children,
+                &localization.get("sequencer.randomize"),
+                RowTool {
+                    row,
+                    action: RowToolAction::Randomize,
+                },
+                ROW_TOOL_WIDTH,
+                font_handles,
+            );
+            spawn_row_tool_button(
+                children,
+                &localization.get("sequencer.euclidean"),
+                RowTool {
+                    row,
+                    action: RowToolAction::Euclidean,
+                },
+                ROW_TOOL_WIDTH,
+                font_handles,
+            );
+            spawn_row_tool_button(
+                children,
+                "<",
+                RowTool {
+                    row,
+                    action: RowToolAction::ShiftEarlier,
+                },
+                ROW_SHIFT_WIDTH,
+                font_handles,
+            )
+            .insert(Repeatable::default());
+            spawn_row_tool_button(
+                children,
+                ">",
+                RowTool {
+                    row,
+                    action: RowToolAction::ShiftLater,
+                },
+                ROW_SHIFT_WIDTH,
+                font_handles,
+            )
+            .insert(Repeatable::default());
+            spawn_row_tool_button(
+                children,
+                StepRate::default().label(),
+                RowTool {
+                    row,
+                    action: RowToolAction::CycleRate,
+                },
+                ROW_RATE_WIDTH,
+                font_handles,
+            )
+            .insert(Tooltip(localization.get("sequencer.rate_tooltip")));
+        },
+        |children, beat| {
+            let palette = beat_palette.interaction_palette(false, false, is_shaded_beat(beat));
+            children.spawn((
+                Name::new("Button"),
+                ButtonBundle {
+                    style: beat_cell_style(beat),
+                    background_color: BackgroundColor(palette.none),
+                    border_radius: BorderRadius::all(Val::Px(3.0)),
+                    ..default()
+                },
+                palette,
+                SequencerAction::ToggleBeat,
+                BeatButton {
+                    row,
+                    beat,
+                    active: false,
+                    maybe: false,
+                    accent: 0,
+                    suggested: false,
+                },
+                BeatColumn(beat),
+                Enabled(true),
+            ));
+        },
+    );
+}
+
+/// Spawns the numbers above each column of beat buttons.
+fn spawn_beat_numbers(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    spawn_grid_row(
+        parent,
+        None,
+        Color::BLACK,
+        |children| {
+            children.label("", font_handles);
+        },
+        |children, beat| {
+            children
+                .spawn((
+                    Name::new("Beat number"),
+                    BeatNumber,
+                    BeatColumn(beat),
+                    Tooltip(localization.get("sequencer.beat_number_tooltip")),
+                    NodeBundle {
                         style: Style {
-                            width: Val::Px(30.0),
-                            height: Val::Px(30.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            ..default()
+                            height: Val::Auto,
+                            ..beat_cell_style(beat)
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    let color = if beat.is_multiple_of(BEATS_PER_GROUP) {
+                        LABEL_TEXT
+                    } else {
+                        BEAT_NUMBER_TEXT
+                    };
+                    children.spawn(TextBundle::from_section(
+                        (beat + 1).to_string(),
+                        TextStyle {
+                            font: font_handles.get(FontKey::General),
+                            font_size: 14.0,
+                            color,
+                        },
+                    ));
+                });
+        },
+    );
+}
+
+/// Spawns the lane under the grid that shows how accented each note in one row is, as a bar for each beat.
+/// Dragging up and down over the bars sets how accented the notes are.
+fn spawn_velocity_lane(
+    parent: &mut ChildBuilder,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    spawn_grid_row(
+        parent,
+        None,
+        Color::srgb(0.15, 0.15, 0.15),
+        |children| {
+            spawn_row_tool_button(
+                children,
+                "",
+                VelocityLaneButton,
+                ROW_HEADER_WIDTH - BEAT_BUTTON_GAP,
+                font_handles,
+            )
+            .insert(Tooltip(localization.get("sequencer.velocity_lane_tooltip")));
+        },
+        |children, beat| {
+            children
+                .spawn((
+                    Name::new("Velocity cell"),
+                    VelocityCell,
+                    BeatColumn(beat),
+                    Tooltip(localization.get("sequencer.velocity_cell_tooltip")),
+                    NodeBundle {
+                        style: Style {
+                            height: Val::Px(VELOCITY_LANE_HEIGHT),
+                            align_items: AlignItems::End,
+                            display: Display::None,
+                            ..beat_cell_style(beat)
                         },
                         background_color: BackgroundColor(INACTIVE_BEAT_BUTTON),
                         border_radius: BorderRadius::all(Val::Px(3.0)),
                         ..default()
                     },
-                    InteractionPalette {
-                        none: INACTIVE_BEAT_BUTTON,
-                        hovered: HOVERED_INACTIVE_BEAT_BUTTON,
-                        pressed: ACTIVE_BEAT_BUTTON,
+                ))
+                .with_children(|children| {
+                    children.spawn((
+                        Name::new("Velocity bar"),
+                        VelocityBar,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(0.0),
+                                ..default()
+                            },
+                            border_radius: BorderRadius::all(Val::Px(3.0)),
+                            ..default()
+                        },
+                    ));
+                });
+        },
+    );
+}
+
+/// Spawns a row of the beat grid, with a header at the start and a cell for each beat.
+fn spawn_grid_row(
+    parent: &mut ChildBuilder,
+    row: Option<SequencerRow>,
+    background_color: Color,
+    spawn_header: impl FnOnce(&mut ChildBuilder),
+    mut spawn_cell: impl FnMut(&mut ChildBuilder, usize),
+) {
+    parent
+        .spawn((
+            SequencerRowNode(row),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Auto,
+                    justify_self: JustifySelf::Start,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(BEAT_BUTTON_GAP),
+                    row_gap: Val::Px(BEAT_BUTTON_GAP),
+                    position_type: PositionType::Relative,
+                    ..default()
+                },
+                background_color: BackgroundColor(background_color),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Playhead"),
+                Playhead,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        display: Display::None,
+                        ..default()
                     },
-                    SequencerAction::ToggleBeat,
-                    BeatButton {
-                        row,
-                        beat: i,
-                        active: false,
+                    background_color: BackgroundColor(PLAYHEAD),
+                    border_radius: BorderRadius::all(Val::Px(3.0)),
+                    z_index: ZIndex::Local(1),
+                    ..default()
+                },
+            ));
+
+            children
+                .spawn((
+                    Name::new("Row header"),
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(ROW_HEADER_WIDTH),
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(BEAT_BUTTON_GAP),
+                            ..default()
+                        },
+                        ..default()
                     },
-                    Enabled(true),
-                ));
+                ))
+                .with_children(spawn_header);
+
+            for beat in 0..NUM_BEATS_IN_SEQUENCE {
+                if beat == NUM_BEATS_IN_SEQUENCE / 2 {
+                    // lines the second bank up under the first one when the grid is split
+                    children.spawn((
+                        Name::new("Bank spacer"),
+                        BankSpacer,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(ROW_HEADER_WIDTH - GROUP_SEPARATOR_WIDTH),
+                                display: Display::None,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                }
+
+                spawn_cell(children, beat);
             }
         });
 }
 
-fn handle_death(
-    _trigger: Trigger<DeathEvent>,
-    mut dead: ResMut<Dead>,
+/// A button that fills in a whole row at once
+#[derive(Component, Debug, Clone, Copy)]
+struct RowTool {
+    row: SequencerRow,
+    action: RowToolAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowToolAction {
+    /// Turns on a random selection of beats
+    Randomize,
+    /// Spreads the beats out as evenly as possible
+    Euclidean,
+    /// Moves every beat one step earlier, wrapping around to the end
+    ShiftEarlier,
+    /// Moves every beat one step later, wrapping around to the start
+    ShiftLater,
+    /// Changes how fast the row goes through its steps
+    CycleRate,
+    /// Changes which runners the row controls in co-op
+    CycleOwner,
+    /// Loops the row on its own for a couple of bars, or stops it if it's already going
+    Preview,
+}
+
+fn spawn_row_tool_button<'a>(
+    parent: &'a mut ChildBuilder,
+    text: &str,
+    tool: impl Bundle,
+    width: f32,
+    font_handles: &HandleMap<FontKey>,
+) -> EntityCommands<'a> {
+    let mut entity = parent.spawn((
+        Name::new("Row tool button"),
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(22.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: BackgroundColor(NODE_BACKGROUND),
+            border_radius: BorderRadius::all(Val::Px(3.0)),
+            ..default()
+        },
+        InteractionPalette {
+            none: NODE_BACKGROUND,
+            hovered: BUTTON_HOVERED_BACKGROUND,
+            pressed: BUTTON_PRESSED_BACKGROUND,
+            disabled: DISABLED_BUTTON_BACKGROUND,
+        },
+        tool,
+        Enabled(true),
+    ));
+    entity.with_children(|children| {
+        children.spawn((
+            Name::new("Row tool button text"),
+            TextBundle::from_section(
+                text,
+                TextStyle {
+                    font: font_handles.get(FontKey::General),
+                    font_size: 16.0,
+                    color: BUTTON_TEXT,
+                },
+            ),
+        ));
+    });
+    entity
+}
+
+/// The empty beat the cursor just moved onto, whose row's sound is played if the cursor stays there long enough
+#[derive(Resource, Debug)]
+struct HoverPreview {
+    button: Option<Entity>,
+    timer: Timer,
+}
+
+impl Default for HoverPreview {
+    fn default() -> Self {
+        HoverPreview {
+            button: None,
+            timer: Timer::from_seconds(HOVER_PREVIEW_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+fn preview_on_hover_enabled(settings: Res<Settings>) -> bool {
+    settings.preview_on_hover
+}
+
+fn start_hover_preview(
+    mut hover_events: EventReader<HoverEntered>,
+    button_query: Query<&BeatButton>,
+    mut preview: ResMut<HoverPreview>,
+) {
+    for event in hover_events.read() {
+        if !button_query.get(event.0).is_ok_and(|button| !button.active) {
+            continue;
+        }
+        // waiting to play the sound keeps it from going off for every beat the cursor passes over
+        *preview = HoverPreview {
+            button: Some(event.0),
+            ..default()
+        };
+    }
+}
+
+fn play_hover_preview(
+    time: Res<Time>,
+    mut preview: ResMut<HoverPreview>,
+    button_query: Query<(&BeatButton, &Interaction, &Enabled)>,
+    mut commands: Commands,
+) {
+    let Some(entity) = preview.button else {
+        return;
+    };
+    if !preview.timer.tick(time.delta()).finished() {
+        return;
+    }
+    preview.button = None;
+
+    // the cursor has to still be there, and the sequence can't be playing
+    let Ok((button, interaction, enabled)) = button_query.get(entity) else {
+        return;
+    };
+    if !button.active && enabled.0 && *interaction == Interaction::Hovered {
+        commands.trigger(
+            PlaySfx::new(button.row.to_sfx_key())
+                .with_volume(DEFAULT_SFX_VOLUME * HOVER_PREVIEW_VOLUME)
+                .unducked(),
+        );
+    }
+}
+
+/// Replaces every beat in a row with a generated pattern, shifts the row's beats, or changes its step rate or owner
+fn handle_row_tool(
+    mut activated_events: EventReader<ButtonActivated>,
+    tool_query: Query<(&RowTool, &Children)>,
+    mut text_query: Query<&mut Text>,
+    time: Res<Time>,
+    hits: Res<RowFillHits>,
+    beat_palette: Res<BeatPalette>,
+    sequence_state: Res<SequenceState>,
+    mut sequence: ResMut<Sequence>,
+    mut row_owners: ResMut<RowOwners>,
+    mut row_preview: ResMut<RowPreview>,
+    mut button_query: Query<(
+        &mut BeatButton,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    mut commands: Commands,
+) {
+    for (tool, children) in tool_query.iter_many(activated_events.read().map(|event| event.0)) {
+        // whether each beat is on, whether it only plays some of the time, and how accented it is
+        let pattern: [(bool, bool, u8); NUM_BEATS_IN_SEQUENCE] = match tool.action {
+            RowToolAction::Randomize => random_pattern(hits.0).map(|active| (active, false, 0)),
+            RowToolAction::Euclidean => euclidean_pattern(hits.0).map(|active| (active, false, 0)),
+            RowToolAction::ShiftEarlier | RowToolAction::ShiftLater => {
+                let mut pattern = [(false, false, 0); NUM_BEATS_IN_SEQUENCE];
+                for (button, ..) in &button_query {
+                    if button.row == tool.row {
+                        pattern[button.beat] = (button.active, button.maybe, button.accent);
+                    }
+                }
+                if tool.action == RowToolAction::ShiftEarlier {
+                    pattern.rotate_left(1);
+                } else {
+                    pattern.rotate_right(1);
+                }
+                pattern
+            }
+            RowToolAction::CycleRate => {
+                let rate = sequence.cycle_rate(tool.row);
+                let mut texts = text_query.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
+                    text.sections[0].value = rate.label().to_string();
+                }
+                continue;
+            }
+            RowToolAction::CycleOwner => {
+                row_owners.cycle(tool.row);
+                continue;
+            }
+            RowToolAction::Preview => {
+                row_preview.toggle(
+                    tool.row,
+                    sequence.rate(tool.row),
+                    sequence_state.tick_interval(),
+                    time.elapsed(),
+                );
+                continue;
+            }
+        };
+
+        for (mut button, mut palette, mut background_color) in &mut button_query {
+            if button.row != tool.row {
+                continue;
+            }
+
+            let was_active = button.active;
+            (button.active, button.maybe, button.accent) = pattern[button.beat];
+            sequence.set(&button);
+            // shifting only moves notes that were already placed
+            let filled = matches!(
+                tool.action,
+                RowToolAction::Randomize | RowToolAction::Euclidean
+            );
+            if filled && button.active && !was_active {
+                commands.trigger(NotePlaced(button.row));
+            }
+            *palette = button.palette(&beat_palette);
+            *background_color = BackgroundColor(palette.none);
+        }
+    }
+}
+
+/// A row being looped on its own to hear how it sounds, without the sequence going or the runners doing anything.
+/// It keeps time with a clock of its own, so the sequence stays right where it is.
+#[derive(Resource, Debug, Default)]
+struct RowPreview(Option<PreviewedRow>);
+
+#[derive(Debug)]
+struct PreviewedRow {
+    row: SequencerRow,
+    clock: SequenceClock,
+    /// How many ticks have gone by since the preview started
+    ticks: usize,
+    /// The next tick whose note hasn't been played yet
+    next_tick: usize,
+    /// How many ticks the preview lasts
+    length: usize,
+}
+
+impl RowPreview {
+    /// Starts looping the provided row at the provided tempo, or stops it if it's already going
+    fn toggle(
+        &mut self,
+        row: SequencerRow,
+        rate: StepRate,
+        tick_interval: Duration,
+        now: Duration,
+    ) {
+        if self
+            .0
+            .as_ref()
+            .is_some_and(|previewed| previewed.row == row)
+        {
+            self.0 = None;
+            return;
+        }
+
+        let mut clock = SequenceClock::new(tick_interval);
+        clock.advance(now);
+        clock.unpause();
+        self.0 = Some(PreviewedRow {
+            row,
+            clock,
+            ticks: 0,
+            next_tick: 0,
+            length: ROW_PREVIEW_BARS * NUM_BEATS_IN_SEQUENCE * rate.ticks_per_step(),
+        });
+    }
+}
+
+/// Plays the notes of the row being previewed as its clock reaches them, and stops once it's looped for long enough.
+/// Starting the sequence cuts it off.
+fn play_row_preview(
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+    sequence: Res<Sequence>,
+    sequence_state: Res<SequenceState>,
+    pre_roll: Res<PreRoll>,
+    mut row_preview: ResMut<RowPreview>,
+    mut commands: Commands,
+) {
+    if row_preview.0.is_none() {
+        return;
+    }
+    if !sequence_state.clock.paused() || pre_roll.is_running() {
+        row_preview.0 = None;
+        return;
+    }
+    let Some(previewed) = &mut row_preview.0 else {
+        return;
+    };
+
+    previewed.clock.set_speed(playback_speed.speed());
+    previewed.ticks += previewed.clock.advance(time.elapsed()) as usize;
+    if previewed.ticks >= previewed.length {
+        row_preview.0 = None;
+        return;
+    }
+    if previewed.next_tick > previewed.ticks {
+        return;
+    }
+
+    // like the sequence, only the latest tick is played if more than one went by in the same frame
+    let tick = previewed.ticks;
+    previewed.next_tick = tick + 1;
+    let row = previewed.row;
+    let Some((_, chance)) = sequence
+        .notes_at_tick(tick)
+        .find(|(note_row, _)| *note_row == row)
+    else {
+        return;
+    };
+    if rand::thread_rng().gen::<f32>() < chance {
+        let volume = DEFAULT_SFX_VOLUME * accent_volume(sequence.accent_at_tick(row, tick));
+        commands.trigger(
+            PlaySfx::new(row.to_sfx_key())
+                .with_volume(volume)
+                .unducked(),
+        );
+    }
+}
+
+/// Shows which row is being previewed, on that row's preview button
+fn update_row_preview_buttons(
+    row_preview: Res<RowPreview>,
+    localization: Res<Localization>,
+    tool_query: Query<(&RowTool, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let previewed_row = row_preview.0.as_ref().map(|previewed| previewed.row);
+    let preview_label = localization.get("sequencer.preview");
+    let stop_label = localization.get("sequencer.stop_preview");
+    for (tool, children) in &tool_query {
+        if tool.action != RowToolAction::Preview {
+            continue;
+        }
+
+        let label = if previewed_row == Some(tool.row) {
+            &stop_label
+        } else {
+            &preview_label
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != *label {
+                text.sections[0].value.clone_from(label);
+            }
+        }
+    }
+}
+
+fn stop_row_preview(mut row_preview: ResMut<RowPreview>) {
+    row_preview.0 = None;
+}
+
+/// The row whose accents are shown and set in the velocity lane under the grid, if the lane is open
+#[derive(Resource, Debug, Default, PartialEq)]
+struct VelocityLane(Option<SequencerRow>);
+
+impl VelocityLane {
+    /// Moves the lane on to the next row down the grid, closing it after the last one and opening it on the first one
+    fn cycle(&mut self) {
+        let mut rows = SequencerRow::grid_order();
+        self.0 = match self.0 {
+            Some(current) => rows.skip_while(|row| *row != current).nth(1),
+            None => rows.next(),
+        };
+    }
+}
+
+/// The button at the start of the velocity lane that picks which row it's for
+#[derive(Component, Debug)]
+struct VelocityLaneButton;
+
+/// A cell in the velocity lane, for the beat in the same column
+#[derive(Component, Debug)]
+struct VelocityCell;
+
+/// The bar in a cell of the velocity lane, as tall as the note on that beat is accented
+#[derive(Component, Debug)]
+struct VelocityBar;
+
+fn handle_velocity_lane_button(
+    mut activated_events: EventReader<ButtonActivated>,
+    button_query: Query<(), With<VelocityLaneButton>>,
+    mut velocity_lane: ResMut<VelocityLane>,
+) {
+    for _ in button_query.iter_many(activated_events.read().map(|event| event.0)) {
+        velocity_lane.cycle();
+    }
+}
+
+/// Shows which row the velocity lane is for on the button at the start of it
+fn update_velocity_lane_button(
+    velocity_lane: Res<VelocityLane>,
+    localization: Res<Localization>,
+    button_query: Query<Ref<Children>, With<VelocityLaneButton>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for children in &button_query {
+        if !velocity_lane.is_changed() && !children.is_added() {
+            continue;
+        }
+
+        let label = match velocity_lane.0 {
+            Some(row) => localization.format(
+                "sequencer.velocity_lane.row",
+                &[("row", &row.name(&localization))],
+            ),
+            None => localization.get("sequencer.velocity_lane.closed"),
+        };
+        let mut texts = text_query.iter_many_mut(&*children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+/// Sizes each bar in the velocity lane to how accented the note on its beat is, colored like the note's beat button.
+/// Beats without a note don't get a bar.
+fn update_velocity_bars(
+    velocity_lane: Res<VelocityLane>,
+    zoom: Res<GridZoom>,
+    button_query: Query<(&BeatButton, &InteractionPalette)>,
+    mut cell_query: Query<(&BeatColumn, &Children, &mut Style), With<VelocityCell>>,
+    mut bar_query: Query<
+        (&mut Style, &mut BackgroundColor),
+        (With<VelocityBar>, Without<VelocityCell>),
+    >,
+) {
+    let mut notes = [None; NUM_BEATS_IN_SEQUENCE];
+    if let Some(row) = velocity_lane.0 {
+        for (button, palette) in &button_query {
+            if button.row == row && button.active {
+                notes[button.beat] = Some((button.accent, palette.none));
+            }
+        }
+    }
+
+    for (column, children, mut cell_style) in &mut cell_query {
+        let display = if velocity_lane.0.is_some() && zoom.shows(column.0) {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if cell_style.display != display {
+            cell_style.display = display;
+        }
+
+        let mut bars = bar_query.iter_many_mut(children);
+        while let Some((mut style, mut background_color)) = bars.fetch_next() {
+            let (height, color) = match notes[column.0] {
+                Some((accent, color)) => (
+                    f32::from(accent + 1) / f32::from(ACCENT_LEVELS) * 100.0,
+                    color,
+                ),
+                None => (0.0, Color::NONE),
+            };
+            if style.height != Val::Percent(height) {
+                style.height = Val::Percent(height);
+            }
+            background_color.set_if_neq(BackgroundColor(color));
+        }
+    }
+}
+
+/// Sets how accented the note on each beat of the velocity lane's row is while the lane is held down,
+/// going by how far up the beat's cell the cursor is
+fn drag_velocity_lane(
+    ui_scale: Res<UiScale>,
+    velocity_lane: Res<VelocityLane>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    cell_query: Query<(&Interaction, &BeatColumn, &Node, &GlobalTransform), With<VelocityCell>>,
+    mut button_query: Query<(&Enabled, &mut BeatButton, &mut InteractionPalette)>,
+    beat_palette: Res<BeatPalette>,
+    mut sequence: ResMut<Sequence>,
+) {
+    let Some(row) = velocity_lane.0 else {
+        return;
+    };
+    if !cell_query
+        .iter()
+        .any(|(interaction, ..)| matches!(interaction, Interaction::Pressed))
+    {
+        return;
+    }
+
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let cursor = cursor / ui_scale.0;
+
+    // include the gaps between cells, so dragging across them doesn't skip anything
+    let Some((beat, rect)) = cell_query
+        .iter()
+        .map(|(_, column, node, transform)| (column.0, node.logical_rect(transform)))
+        .find(|(_, rect)| !rect.is_empty() && rect.inflate(BEAT_BUTTON_GAP).contains(cursor))
+    else {
+        return;
+    };
+    let height = ((rect.max.y - cursor.y) / rect.height()).clamp(0.0, 1.0);
+    let accent = ((height * f32::from(ACCENT_LEVELS)) as u8).min(ACCENT_LEVELS - 1);
+
+    for (enabled, mut button, mut palette) in &mut button_query {
+        if button.row != row || button.beat != beat {
+            continue;
+        }
+        // only notes that are already there can be accented, and not while the sequence is playing
+        if !enabled.0 || !button.active || button.accent == accent {
+            continue;
+        }
+
+        button.accent = accent;
+        sequence.set(&button);
+        *palette = button.palette(&beat_palette);
+    }
+}
+
+/// Gets the text for the button that shows which runners a row controls
+fn owner_label(owner: RowOwner, localization: &Localization) -> String {
+    match owner {
+        RowOwner::Both => localization.get("sequencer.owner.both"),
+        RowOwner::Only(PlayerSlot::One) => localization.get("sequencer.owner.one"),
+        RowOwner::Only(PlayerSlot::Two) => localization.get("sequencer.owner.two"),
+    }
+}
+
+/// Gets the colors for the button that shows which runners a row controls, so each player's rows stand out
+fn owner_palette(owner: RowOwner) -> InteractionPalette {
+    let none = match owner {
+        RowOwner::Both => NODE_BACKGROUND,
+        RowOwner::Only(PlayerSlot::One) => PLAYER_ONE_ROW,
+        RowOwner::Only(PlayerSlot::Two) => PLAYER_TWO_ROW,
+    };
+    InteractionPalette {
+        none,
+        hovered: BUTTON_HOVERED_BACKGROUND,
+        pressed: BUTTON_PRESSED_BACKGROUND,
+        disabled: DISABLED_BUTTON_BACKGROUND,
+    }
+}
+
+/// Updates the row owner buttons to match who owns each row
+fn update_row_owner_buttons(
+    row_owners: Res<RowOwners>,
+    localization: Res<Localization>,
+    mut tool_query: Query<(
+        &RowTool,
+        &Children,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (tool, children, mut palette, mut background_color) in &mut tool_query {
+        if tool.action != RowToolAction::CycleOwner {
+            continue;
+        }
+        let owner = row_owners.owner(tool.row);
+        *palette = owner_palette(owner);
+        *background_color = BackgroundColor(palette.none);
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = owner_label(owner, &localization);
+        }
+    }
+}
+
+/// Picks the provided number of beats at random
+fn random_pattern(hits: usize) -> [bool; NUM_BEATS_IN_SEQUENCE] {
+    let mut pattern = [false; NUM_BEATS_IN_SEQUENCE];
+    let hits = hits.min(NUM_BEATS_IN_SEQUENCE);
+    for beat in rand::seq::index::sample(&mut rand::thread_rng(), NUM_BEATS_IN_SEQUENCE, hits) {
+        pattern[beat] = true;
+    }
+    pattern
+}
+
+/// Spreads the provided number of beats as evenly as possible across the sequence, starting on the first beat
+fn euclidean_pattern(hits: usize) -> [bool; NUM_BEATS_IN_SEQUENCE] {
+    let hits = hits.min(NUM_BEATS_IN_SEQUENCE);
+    std::array::from_fn(|beat| (beat * hits) % NUM_BEATS_IN_SEQUENCE < hits)
+}
+
+/// Whether the provided beat is in one of the groups of beats that gets shaded
+pub fn is_shaded_beat(beat: usize) -> bool {
+    (beat / BEATS_PER_GROUP) % 2 == 1
+}
+
+/// Gets the style for a cell in the beat grid, with extra space before it if it starts a new group
+fn beat_cell_style(beat: usize) -> Style {
+    let separator = if beat.is_multiple_of(BEATS_PER_GROUP) && beat != 0 {
+        GROUP_SEPARATOR_WIDTH
+    } else {
+        0.0
+    };
+
+    Style {
+        width: Val::Px(MAX_BEAT_BUTTON_SIZE),
+        height: Val::Px(MAX_BEAT_BUTTON_SIZE),
+        margin: UiRect::left(Val::Px(separator)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+fn handle_death(_trigger: Trigger<DeathEvent>, mut dead: ResMut<Dead>, mut commands: Commands) {
+    dead.0 = true;
+    commands.trigger(PauseSequence);
+    commands.trigger(SetBeatButtonsEnabled(false));
+}
+
+fn show_game_over(
+    _trigger: Trigger<ShowGameOver>,
+    dead: Res<Dead>,
     font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
     distance: Res<TotalDistance>,
     current_level: Res<CurrentLevel>,
+    stats: Res<RunStats>,
+    mutators: Res<Mutators>,
+    settings: Res<Settings>,
+    sequence_state: Res<SequenceState>,
+    judgements_handles: Res<HandleMap<JudgementsKey>>,
+    judgements_assets: Res<Assets<Judgements>>,
     mut commands: Commands,
 ) {
-    dead.0 = true;
+    // the run was started over before the death finished playing out
+    if !dead.0 {
+        return;
+    }
+
+    let run = JudgedRun {
+        level: current_level.0,
+        distance: distance.feet(),
+        stats: &stats,
+    };
+    let header = match judgements_assets
+        .get(&judgements_handles.get(JudgementsKey::Judgements))
+        .and_then(|judgements| judgements.judge(&run))
+    {
+        Some(judgement) => localization.format(
+            "game_over.header",
+            &[
+                ("distance", &*distance),
+                ("judgement", &localization.get(judgement)),
+            ],
+        ),
+        None => localization.format(
+            "game_over.header_without_judgement",
+            &[("distance", &*distance)],
+        ),
+    };
+
+    spawn_end_of_run_panel(&mut commands)
+        .insert((Name::new("Game over Root"), GameOver))
+        .with_children(|children| {
+            children.header(header, &font_handles);
+            spawn_run_stats(children, &stats, &font_handles, &localization);
+            children.label(
+                score_text(&distance, &stats, &mutators, &localization),
+                &font_handles,
+            );
+            let active_assists = settings.assists.active_names(&localization);
+            if !active_assists.is_empty() {
+                children.label(
+                    localization.format(
+                        "game_over.assists",
+                        &[("assists", &active_assists.join(", "))],
+                    ),
+                    &font_handles,
+                );
+            }
+            children
+                .button(localization.get("game_over.try_again"), &font_handles)
+                .insert(GameAction::Stop);
+
+            if settings.auto_restart {
+                children
+                    .label(
+                        restart_countdown_text(RESTART_COUNTDOWN_BEATS, &localization),
+                        &font_handles,
+                    )
+                    .insert((
+                        RestartCountdown {
+                            timer: Timer::new(sequence_state.beat_interval(), TimerMode::Repeating),
+                            beats_left: RESTART_COUNTDOWN_BEATS,
+                        },
+                        Style {
+                            width: Val::Auto,
+                            ..default()
+                        },
+                    ));
+            }
+        });
+}
+
+fn restart_countdown_text(beats_left: u32, localization: &Localization) -> String {
+    localization.format("game_over.restarting", &[("beats", &beats_left)])
+}
+
+/// Ticks off a beat of the restart countdown, starting the run over once it runs out
+fn count_down_restart(
+    time: Res<Time>,
+    mut countdown_query: Query<(&mut RestartCountdown, &Children)>,
+    mut text_query: Query<&mut Text>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    for (mut countdown, children) in &mut countdown_query {
+        countdown.timer.tick(time.delta());
+        if !countdown.timer.just_finished() {
+            continue;
+        }
+
+        countdown.beats_left = countdown.beats_left.saturating_sub(1);
+        if countdown.beats_left == 0 {
+            commands.trigger(ResetSequence);
+            commands.trigger(PlaySequence);
+            return;
+        }
+
+        // the countdown is ticked off over the game over panel, so it shouldn't be ducked with everything else
+        commands.trigger(PlaySfx::new(SfxKey::HiHat).unducked());
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = restart_countdown_text(countdown.beats_left, &localization);
+        }
+    }
+}
+
+fn handle_victory(_trigger: Trigger<VictoryEvent>, mut commands: Commands) {
     commands.trigger(PauseSequence);
     commands.trigger(SetBeatButtonsEnabled(false));
+}
 
-    commands
+fn show_victory(
+    _trigger: Trigger<VictoryEvent>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    distance: Res<TotalDistance>,
+    stats: Res<RunStats>,
+    mutators: Res<Mutators>,
+    mut commands: Commands,
+) {
+    spawn_end_of_run_panel(&mut commands)
+        .insert((Name::new("Victory Root"), Victory))
+        .with_children(|children| {
+            children.header(
+                localization.format("victory.header", &[("distance", &*distance)]),
+                &font_handles,
+            );
+            spawn_run_stats(children, &stats, &font_handles, &localization);
+            children.label(
+                score_text(&distance, &stats, &mutators, &localization),
+                &font_handles,
+            );
+            children
+                .button(localization.get("victory.keep_going"), &font_handles)
+                .insert(GameAction::KeepGoing);
+            children
+                .button(localization.get("victory.start_over"), &font_handles)
+                .insert(GameAction::Stop);
+        });
+}
+
+/// Takes down the panel shown at the end of the last run, if there is one
+fn close_end_of_run_panels(
+    _trigger: Trigger<ResetSequence>,
+    panel_query: Query<Entity, Or<(With<GameOver>, With<Victory>)>>,
+    mut commands: Commands,
+) {
+    for entity in &panel_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Spawns the panel shown over the level when a run ends
+fn spawn_end_of_run_panel<'a>(commands: &'a mut Commands) -> EntityCommands<'a> {
+    commands.spawn((
+        FocusScope,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(50.0),
+                height: Val::Percent(50.0),
+                left: Val::Percent(25.0),
+                top: Val::Percent(25.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            border_radius: BorderRadius::all(Val::Px(10.0)),
+            ..default()
+        },
+    ))
+}
+
+fn spawn_run_stats(
+    parent: &mut ChildBuilder,
+    stats: &RunStats,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    parent
         .spawn((
-            Name::new("Game over Root"),
-            GameOver,
+            Name::new("Run stats"),
             NodeBundle {
                 style: Style {
-                    width: Val::Percent(50.0),
-                    height: Val::Percent(50.0),
-                    left: Val::Percent(25.0),
-                    top: Val::Percent(25.0),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    flex_direction: FlexDirection::Column,
-                    row_gap: Val::Px(10.0),
-                    position_type: PositionType::Absolute,
+                    display: Display::Grid,
+                    grid_template_columns: vec![GridTrack::auto(); 2],
+                    column_gap: Val::Px(120.0),
+                    row_gap: Val::Px(4.0),
                     ..default()
                 },
-                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
-                border_radius: BorderRadius::all(Val::Px(10.0)),
                 ..default()
             },
         ))
         .with_children(|children| {
-            let judgement = match current_level.0 {
-                0 => "Pathetic.",
-                1..=3 => "You can do better.",
-                4..=5 => "Not bad!",
-                6..=7 => "Pretty good!",
-                _ => "I'm proud of you.",
-            };
-            children.header(
-                format!("You ran {} feet.\n{judgement}", *distance),
-                &font_handles,
-            );
-            children
-                .button("Try Again", &font_handles)
-                .insert(GameAction::Stop);
+            let time_survived = format!("{:.1}", stats.time_survived.as_secs_f32());
+            let longest_air_time = format!("{:.2}", stats.longest_air_time.as_secs_f32());
+            for text in [
+                localization.format("run_stats.beats_played", &[("count", &stats.beats_played)]),
+                localization.format(
+                    "run_stats.levels_cleared",
+                    &[("count", &stats.levels_cleared)],
+                ),
+                localization.format("run_stats.jumps", &[("count", &stats.jumps)]),
+                localization.format("run_stats.dives", &[("count", &stats.dives)]),
+                localization.format("run_stats.time_survived", &[("seconds", &time_survived)]),
+                localization.format(
+                    "run_stats.longest_air_time",
+                    &[("seconds", &longest_air_time)],
+                ),
+                localization.format("run_stats.bonus_score", &[("score", &stats.bonus_score)]),
+            ] {
+                children.label(text, font_handles);
+            }
         });
 }
 
+/// Gets the text showing the run's score, along with what it was multiplied by if any mutators were on
+fn score_text(
+    distance: &TotalDistance,
+    stats: &RunStats,
+    mutators: &Mutators,
+    localization: &Localization,
+) -> String {
+    let score = mutators.score(distance, stats);
+    if mutators.0.is_empty() {
+        return localization.format("run_stats.score", &[("score", &score)]);
+    }
+
+    localization.format(
+        "run_stats.score_with_multiplier",
+        &[
+            ("score", &score),
+            ("multiplier", &format!("{:.2}", mutators.score_multiplier())),
+        ],
+    )
+}
+
+/// A row of the beat grid, with the instrument it's for if it has one
+#[derive(Component)]
+pub struct SequencerRowNode(pub Option<SequencerRow>);
+
+/// The number shown above a column of beat buttons
+#[derive(Component)]
+struct BeatNumber;
+
+/// Which beat a cell in the beat grid is for
+#[derive(Component)]
+struct BeatColumn(usize);
+
+/// The highlight over the cell for the current beat in a row of the beat grid.
+/// Together, the playheads in each row make up a column that follows the sequence as it plays.
+#[derive(Component)]
+struct Playhead;
+
+/// Empty space at the start of the second bank of beat buttons, only shown when the grid is split
+#[derive(Component)]
+struct BankSpacer;
+
+/// Jumps the paused sequence to the beat under the cursor while the beat numbers are held down,
+/// so a section of the loop can be played without waiting for it to come around.
+fn scrub_timeline(
+    ui_scale: Res<UiScale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    number_query: Query<(&Interaction, &BeatColumn, &Node, &GlobalTransform), With<BeatNumber>>,
+    mut sequence_state: ResMut<SequenceState>,
+) {
+    if !sequence_state.clock.paused()
+        || !number_query
+            .iter()
+            .any(|(interaction, ..)| matches!(interaction, Interaction::Pressed))
+    {
+        return;
+    }
+
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let cursor = cursor / ui_scale.0;
+
+    // include the gaps between numbers, so dragging across them doesn't skip anything
+    let beat = number_query
+        .iter()
+        .find(|(_, _, node, transform)| {
+            let rect = node.logical_rect(transform);
+            !rect.is_empty() && rect.inflate(BEAT_BUTTON_GAP).contains(cursor)
+        })
+        .map(|(_, column, _, _)| column.0);
+    if let Some(beat) = beat {
+        sequence_state.jump_to_beat(beat);
+    }
+}
+
+/// Moves each row's playhead over the cell for the step that row is on, or hides it if the sequence is stopped.
+fn move_playhead(
+    sequence: Res<Sequence>,
+    sequence_state: Res<SequenceState>,
+    row_query: Query<(&SequencerRowNode, &Node, &GlobalTransform)>,
+    cell_query: Query<(&BeatColumn, &Parent, &Node, &GlobalTransform)>,
+    mut playhead_query: Query<(&Parent, &mut Style), With<Playhead>>,
+) {
+    let stopped = sequence_state.is_stopped();
+    for (row, mut style) in &mut playhead_query {
+        let mut new_style = style.clone();
+        new_style.display = Display::None;
+
+        let Ok((row_node, row_node_ui, row_transform)) = row_query.get(row.get()) else {
+            continue;
+        };
+        let row_rect = Some(row_node_ui.logical_rect(row_transform));
+        let step = match row_node.0 {
+            Some(sequencer_row) => sequence
+                .rate(sequencer_row)
+                .current_step(sequence_state.tick),
+            None => sequence_state.beat,
+        };
+
+        let cell = cell_query
+            .iter()
+            .find(|(column, parent, _, _)| column.0 == step && parent.get() == row.get())
+            .map(|(_, _, node, transform)| node.logical_rect(transform))
+            // cells that are hidden, like the velocity lane's while it's closed, don't get a playhead
+            .filter(|cell| !cell.is_empty());
+
+        if let (false, Some(cell), Some(row_rect)) = (stopped, cell, row_rect) {
+            // cover half the gap on each side, so the playheads in neighboring rows join up
+            new_style.display = Display::Flex;
+            new_style.left = Val::Px(cell.min.x - row_rect.min.x - BEAT_BUTTON_GAP / 2.0);
+            new_style.top = Val::Px(cell.min.y - row_rect.min.y - BEAT_BUTTON_GAP / 2.0);
+            new_style.width = Val::Px(cell.width() + BEAT_BUTTON_GAP);
+            new_style.height = Val::Px(cell.height() + BEAT_BUTTON_GAP);
+        }
+
+        // only touch the style when something moved, so the layout isn't recalculated every frame
+        style.set_if_neq(new_style);
+    }
+}
+
+/// Sizes the beat buttons to fit the window, splitting the grid into two banks of beats if they'd get too small.
+/// While the grid is zoomed in, only the beats being shown are laid out, and it's never split.
+fn layout_beat_grid(
+    mut resize_events: EventReader<WindowResized>,
+    ui_scale: Res<UiScale>,
+    zoom: Res<GridZoom>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    added_button_query: Query<(), Added<BeatButton>>,
+    mut row_query: Query<&mut Style, With<SequencerRowNode>>,
+    mut button_query: Query<
+        (&BeatColumn, &mut Style),
+        (With<BeatButton>, Without<SequencerRowNode>),
+    >,
+    mut number_query: Query<
+        (&BeatColumn, &mut Style, Has<BeatNumber>),
+        (
+            Or<(With<BeatNumber>, With<VelocityCell>)>,
+            Without<SequencerRowNode>,
+            Without<BeatButton>,
+        ),
+    >,
+    mut spacer_query: Query<
+        &mut Style,
+        (
+            With<BankSpacer>,
+            Without<SequencerRowNode>,
+            Without<BeatButton>,
+            Without<BeatNumber>,
+            Without<VelocityCell>,
+        ),
+    >,
+) {
+    let resized = resize_events.read().count() > 0;
+    if !resized && !ui_scale.is_changed() && !zoom.is_changed() && added_button_query.is_empty() {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let available_width = window.width() / ui_scale.0;
+
+    let separators_for =
+        |beats: usize| (beats / BEATS_PER_GROUP - 1) as f32 * GROUP_SEPARATOR_WIDTH;
+    let size_for = |beats: usize| {
+        (available_width
+            - ROW_HEADER_WIDTH
+            - (beats as f32 * BEAT_BUTTON_GAP)
+            - separators_for(beats))
+            / beats as f32
+    };
+    let full_size = size_for(zoom.beats);
+    let split = !zoom.zoomed_in() && full_size < MIN_BEAT_BUTTON_SIZE;
+    let size = if split {
+        size_for(NUM_BEATS_IN_SEQUENCE / 2)
+    } else {
+        full_size
+    }
+    .clamp(MIN_BEAT_BUTTON_SIZE, MAX_BEAT_BUTTON_SIZE);
+    let display_for = |beat: usize| {
+        if zoom.shows(beat) {
+            Display::Flex
+        } else {
+            Display::None
+        }
+    };
+    // the first beat shown doesn't need to be set apart from anything before it
+    let margin_for = |beat: usize| {
+        if beat.is_multiple_of(BEATS_PER_GROUP) && beat != zoom.page * zoom.beats {
+            UiRect::left(Val::Px(GROUP_SEPARATOR_WIDTH))
+        } else {
+            UiRect::ZERO
+        }
+    };
+
+    for (column, mut style) in &mut button_query {
+        style.width = Val::Px(size);
+        style.height = Val::Px(size);
+        style.margin = margin_for(column.0);
+        style.display = display_for(column.0);
+    }
+
+    for (column, mut style, is_number) in &mut number_query {
+        style.width = Val::Px(size);
+        style.margin = margin_for(column.0);
+        // the velocity lane's cells are only shown while it's open, which it takes care of itself
+        if is_number {
+            style.display = display_for(column.0);
+        }
+    }
+
+    for mut style in &mut spacer_query {
+        style.display = if split { Display::Flex } else { Display::None };
+    }
+
+    for mut style in &mut row_query {
+        style.width = if split {
+            // just wide enough for the label and one bank, so the spacer and the second bank wrap onto the next line
+            let beats = NUM_BEATS_IN_SEQUENCE / 2;
+            Val::Px(
+                ROW_HEADER_WIDTH
+                    + beats as f32 * (size + BEAT_BUTTON_GAP)
+                    + separators_for(beats)
+                    + 1.0,
+            )
+        } else {
+            Val::Percent(100.0)
+        };
+    }
+}
+
+/// Recolors the beat buttons when the palette is swapped out.
+fn apply_beat_palette(
+    beat_palette: Res<BeatPalette>,
+    mut button_query: Query<(&BeatButton, &mut InteractionPalette, &mut BackgroundColor)>,
+) {
+    for (button, mut palette, mut background_color) in &mut button_query {
+        *palette = button.palette(&beat_palette);
+        *background_color = BackgroundColor(palette.none);
+    }
+}
+
+/// Highlights the current beat with the accent color of the level's theme.
+fn apply_theme_accent(
+    active_theme: Res<ActiveTheme>,
+    mut playhead_query: Query<(Ref<Playhead>, &mut BackgroundColor)>,
+) {
+    for (playhead, mut background_color) in &mut playhead_query {
+        if active_theme.is_changed() || playhead.is_added() {
+            background_color.0 = active_theme
+                .theme
+                .accent_color()
+                .with_alpha(PLAYHEAD.alpha());
+        }
+    }
+}
+
+/// Sets the state of every beat button and step rate button from the current sequence.
+fn refresh_beat_buttons(
+    _trigger: Trigger<RefreshBeatButtons>,
+    sequence: Res<Sequence>,
+    suggested_beats: Res<SuggestedBeats>,
+    beat_palette: Res<BeatPalette>,
+    mut button_query: Query<(
+        &mut BeatButton,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    tool_query: Query<(&RowTool, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (mut button, mut palette, mut background_color) in &mut button_query {
+        let chance = sequence.chance(button.row, button.beat);
+        button.active = chance.is_some();
+        button.maybe = chance.is_some_and(|chance| chance < 1.0);
+        button.accent = sequence.accent(button.row, button.beat);
+        button.suggested = suggested_beats.contains(button.row, button.beat);
+        *palette = button.palette(&beat_palette);
+        *background_color = BackgroundColor(palette.none);
+    }
+
+    for (tool, children) in &tool_query {
+        if tool.action != RowToolAction::CycleRate {
+            continue;
+        }
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = sequence.rate(tool.row).label().to_string();
+        }
+    }
+}
+
 fn set_beat_buttons_enabled(
     trigger: Trigger<SetBeatButtonsEnabled>,
-    mut button_query: Query<&mut Enabled, With<BeatButton>>,
+    mut button_query: Query<&mut Enabled, Or<(With<BeatButton>, With<RowTool>)>>,
 ) {
     for mut enabled in &mut button_query {
         enabled.0 = trigger.event().0;
@@ -0,0 +1,132 @@
+//! Split times for each level cleared in a run, compared against the best times saved from earlier runs.
+//! Only solo runs from the first level are compared, so every run being compared covers the same ground.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{self, Saver};
+
+use super::{
+    coop::Multiplayer,
+    spawn::{
+        level::{GameMode, LevelCleared, StartingLevel},
+        sequencer::ResetSequence,
+    },
+    stats::RunStats,
+};
+
+const BEST_SPLITS_SAVE_KEY: &str = "splits";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(persistence::load::<BestSplits>(BEST_SPLITS_SAVE_KEY).unwrap_or_default());
+    app.init_resource::<Splits>();
+    app.observe(record_split);
+    app.observe(reset_splits);
+}
+
+/// The kinds of runs that keep their own best splits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SplitCategory {
+    Campaign,
+    Endless,
+}
+
+impl SplitCategory {
+    /// Gets the category the current run is in, or `None` if its splits shouldn't be compared with other runs
+    fn of_run(game_mode: GameMode, starting_level: u32, multiplayer: Multiplayer) -> Option<Self> {
+        if starting_level != 0 || multiplayer != Multiplayer::Solo {
+            return None;
+        }
+
+        match game_mode {
+            GameMode::Campaign => Some(SplitCategory::Campaign),
+            GameMode::Endless => Some(SplitCategory::Endless),
+            GameMode::Scrolling | GameMode::Tutorial | GameMode::EditorTest | GameMode::Custom => {
+                None
+            }
+        }
+    }
+}
+
+/// The fastest time each level has been cleared by, counted from the start of the run, for each category.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct BestSplits(BTreeMap<SplitCategory, Vec<Duration>>);
+
+/// The time a level was cleared at in the current run.
+#[derive(Debug, Clone, Copy)]
+pub struct Split {
+    /// The level that was cleared
+    pub level: u32,
+    /// How far into the run the level was cleared, not counting time spent paused
+    pub time: Duration,
+    /// How much slower (positive) or faster (negative) this was than the best split, in seconds.
+    /// `None` if there's no best split to compare with.
+    pub delta: Option<f32>,
+}
+
+/// The splits for each level cleared so far in the current run. Reset whenever the sequence is reset.
+#[derive(Resource, Debug, Default)]
+pub struct Splits(pub Vec<Split>);
+
+fn record_split(
+    trigger: Trigger<LevelCleared>,
+    stats: Res<RunStats>,
+    game_mode: Res<GameMode>,
+    starting_level: Res<StartingLevel>,
+    multiplayer: Res<Multiplayer>,
+    mut splits: ResMut<Splits>,
+    mut best_splits: ResMut<BestSplits>,
+    saver: Saver,
+) {
+    let time = stats.time_survived;
+    let category = SplitCategory::of_run(*game_mode, starting_level.0, *multiplayer);
+    let index = splits.0.len();
+
+    let mut delta = None;
+    if let Some(category) = category {
+        let bests = best_splits.0.entry(category).or_default();
+        let improved = match bests.get_mut(index) {
+            Some(best) => {
+                delta = Some(time.as_secs_f32() - best.as_secs_f32());
+                let improved = time < *best;
+                *best = (*best).min(time);
+                improved
+            }
+            None => {
+                bests.push(time);
+                true
+            }
+        };
+        if improved {
+            saver.save(BEST_SPLITS_SAVE_KEY, &*best_splits);
+        }
+    }
+
+    splits.0.push(Split {
+        level: trigger.event().0,
+        time,
+        delta,
+    });
+}
+
+fn reset_splits(_trigger: Trigger<ResetSequence>, mut splits: ResMut<Splits>) {
+    splits.0.clear();
+}
+
+/// Formats a time into a run as minutes, seconds, and hundredths, like `1:05.42`
+pub fn format_run_time(time: Duration) -> String {
+    let hundredths = time.as_millis() / 10;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        (hundredths / 100) % 60,
+        hundredths % 100
+    )
+}
+
+/// Formats how far ahead of or behind a best split a run is, with its sign, like `+1.25` or `-0.40`
+pub fn format_split_delta(delta: f32) -> String {
+    format!("{delta:+.2}")
+}
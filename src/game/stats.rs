@@ -0,0 +1,65 @@
+//! Statistics about the current run, shown when the player dies.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    movement::{GroundState, Paused},
+    spawn::sequencer::{Dead, PlayBeat, ResetSequence},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunStats>();
+    app.observe(count_beat);
+    app.observe(reset_stats);
+    app.add_systems(Update, track_time.in_set(AppSet::Update));
+}
+
+/// Stats for the current run. Reset whenever the sequence is reset.
+#[derive(Resource, Debug, Default)]
+pub struct RunStats {
+    pub beats_played: u32,
+    pub jumps: u32,
+    pub dives: u32,
+    pub levels_cleared: u32,
+    pub time_survived: Duration,
+    pub longest_air_time: Duration,
+    current_air_time: Duration,
+    /// Score earned on top of the distance run, from reaching milestones
+    pub bonus_score: u32,
+}
+
+fn count_beat(_trigger: Trigger<PlayBeat>, mut stats: ResMut<RunStats>) {
+    stats.beats_played += 1;
+}
+
+fn reset_stats(_trigger: Trigger<ResetSequence>, mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn track_time(
+    time: Res<Time>,
+    paused: Res<Paused>,
+    dead: Res<Dead>,
+    ground_state_query: Query<&GroundState>,
+    mut stats: ResMut<RunStats>,
+) {
+    if paused.0 || dead.0 {
+        return;
+    }
+
+    stats.time_survived += time.delta();
+
+    let airborne = ground_state_query
+        .iter()
+        .any(|ground_state| *ground_state == GroundState::Airborne);
+    if airborne {
+        stats.current_air_time += time.delta();
+        stats.longest_air_time = stats.longest_air_time.max(stats.current_air_time);
+    } else {
+        stats.current_air_time = Duration::ZERO;
+    }
+}
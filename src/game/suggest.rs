@@ -0,0 +1,259 @@
+//! Suggesting a sequence that gets through the current level, for players who are stuck.
+//! Runs through the level are simulated a little each frame, adding whichever note gets the runner furthest one at a time
+//! until the level is cleared. The notes that were suggested are shown in their own color, so the player can see what did it.
+
+use std::{collections::HashSet, task::Poll};
+
+use bevy::{prelude::*, utils::Instant};
+
+use crate::{ui::banner::Banners, AppSet};
+
+use super::{
+    assets::{FontKey, HandleMap},
+    difficulty::DifficultyCurve,
+    level_definition::{CustomLevels, EditorLevel},
+    localization::Localization,
+    mutators::{Mutators, RowShuffle},
+    spawn::{
+        level::{CurrentLevel, GameMode},
+        sequencer::{RefreshBeatButtons, Sequence, SequencerRow, NUM_BEATS_IN_SEQUENCE},
+    },
+    validation::{
+        SimulatedLayout, SimulatedLevel, Simulation, CANDIDATE_SPEEDS, SEARCH_TIME_PER_FRAME,
+    },
+};
+
+/// The most notes added on top of the speed before giving up on finding a way through
+const MAX_SUGGESTED_NOTES: usize = 12;
+
+/// The rows notes are tried on, besides the one setting the speed
+const SEARCH_ROWS: [SequencerRow; 3] =
+    [SequencerRow::Kick, SequencerRow::HiHat, SequencerRow::Snare];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SuggestedBeats>();
+    app.init_resource::<SuggestionSearch>();
+    app.observe(start_suggestion);
+    app.add_systems(
+        Update,
+        (
+            continue_suggestion_search,
+            forget_removed_suggestions.run_if(resource_changed::<Sequence>),
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Event that starts looking for a sequence that clears the current level, and puts it in the sequencer once one is found
+#[derive(Event, Debug)]
+pub struct SuggestSequence;
+
+/// The beats that were filled in by the last suggestion and haven't been turned off since, by row and step
+#[derive(Resource, Debug, Default)]
+pub struct SuggestedBeats(HashSet<(SequencerRow, usize)>);
+
+impl SuggestedBeats {
+    pub fn contains(&self, row: SequencerRow, step: usize) -> bool {
+        self.0.contains(&(row, step))
+    }
+}
+
+/// The search for a suggestion, while there is one going
+#[derive(Resource, Default)]
+struct SuggestionSearch(Option<Suggester>);
+
+fn start_suggestion(
+    _trigger: Trigger<SuggestSequence>,
+    mut search: ResMut<SuggestionSearch>,
+    game_mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+    difficulty_curve: Res<DifficultyCurve>,
+    editor_level: Res<EditorLevel>,
+    custom_levels: Res<CustomLevels>,
+    mutators: Res<Mutators>,
+    row_shuffle: Res<RowShuffle>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    if search.0.is_some() {
+        return;
+    }
+
+    let layout = match *game_mode {
+        // the tutorial is there to teach the player how to do it themselves
+        GameMode::Tutorial => {
+            commands.banner(localization.get("suggest.unavailable"), &font_handles);
+            return;
+        }
+        GameMode::EditorTest => SimulatedLayout::Defined(editor_level.0.clone()),
+        GameMode::Custom => match custom_levels.for_level(current_level.0) {
+            Some(definition) => SimulatedLayout::Defined(definition.clone()),
+            None => return,
+        },
+        GameMode::Campaign | GameMode::Endless | GameMode::Scrolling => SimulatedLayout::Built {
+            game_mode: *game_mode,
+            level: current_level.0,
+            difficulty_curve: difficulty_curve.clone(),
+        },
+    };
+
+    // the mutators change how runs play out, so the suggestion has to be found with them on too
+    search.0 = Some(Suggester::new(SimulatedLevel {
+        layout,
+        mutators: mutators.clone(),
+        row_shuffle: row_shuffle.clone(),
+    }));
+    commands.banner(localization.get("suggest.searching"), &font_handles);
+}
+
+/// Looks for a sequence that clears a level, starting from the speed that gets furthest on its own
+/// and adding whichever note gets the runner furthest until one gets all the way through.
+/// It's done a little at a time, so it doesn't hold up the frame it's going on in.
+struct Suggester {
+    simulation: Simulation,
+    /// The sequences left to try this round
+    candidates: std::vec::IntoIter<Sequence>,
+    /// The sequence the run going on right now is playing
+    trying: Option<Sequence>,
+    /// The sequence that's gotten furthest this round, and how far it got
+    best_this_round: Option<(Sequence, f32)>,
+    /// How far the sequence the notes this round are added to got, or `None` during the round picking the speed
+    distance_to_beat: Option<f32>,
+    /// How many rounds of adding notes have been done
+    notes_added: usize,
+}
+
+impl Suggester {
+    fn new(level: SimulatedLevel) -> Suggester {
+        let speed_candidates = CANDIDATE_SPEEDS
+            .into_iter()
+            .map(|speed| {
+                let mut sequence = Sequence::new();
+                sequence.add_note(SequencerRow::SynthNote(speed), 0);
+                sequence
+            })
+            .collect::<Vec<_>>();
+
+        Suggester {
+            simulation: Simulation::new(level),
+            candidates: speed_candidates.into_iter(),
+            trying: None,
+            best_this_round: None,
+            distance_to_beat: None,
+            notes_added: 0,
+        }
+    }
+
+    /// Keeps simulating runs until the provided deadline, and returns the suggestion once the search is over.
+    /// That's `None` if adding notes stopped helping before the level was cleared.
+    fn continue_until(&mut self, deadline: Instant) -> Poll<Option<Sequence>> {
+        while Instant::now() < deadline {
+            if self.trying.is_none() {
+                let next = match self.candidates.next() {
+                    Some(next) => next,
+                    None => match self.next_round() {
+                        Some(next) => next,
+                        None => return Poll::Ready(None),
+                    },
+                };
+                self.simulation.start(&next);
+                self.trying = Some(next);
+            }
+
+            let Some(run) = self.simulation.step() else {
+                continue;
+            };
+            let Some(tried) = self.trying.take() else {
+                continue;
+            };
+            if run.cleared {
+                return Poll::Ready(Some(tried));
+            }
+            if self
+                .best_this_round
+                .as_ref()
+                .is_none_or(|(_, distance)| run.distance > *distance)
+            {
+                self.best_this_round = Some((tried, run.distance));
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// Builds on the best sequence from the round that just ended, and returns the first sequence to try in the next one.
+    /// Returns `None` if the search should give up.
+    fn next_round(&mut self) -> Option<Sequence> {
+        let (sequence, distance) = self.best_this_round.take()?;
+        if let Some(distance_to_beat) = self.distance_to_beat {
+            if distance <= distance_to_beat {
+                return None;
+            }
+            self.notes_added += 1;
+        }
+        self.distance_to_beat = Some(distance);
+        if self.notes_added >= MAX_SUGGESTED_NOTES {
+            return None;
+        }
+
+        let mut candidates = Vec::new();
+        for row in SEARCH_ROWS {
+            for step in 0..NUM_BEATS_IN_SEQUENCE {
+                if sequence.chance(row, step).is_some() {
+                    continue;
+                }
+
+                let mut candidate = sequence.clone();
+                candidate.add_note(row, step);
+                candidates.push(candidate);
+            }
+        }
+        self.candidates = candidates.into_iter();
+        self.candidates.next()
+    }
+}
+
+/// Keeps the search going for as long as it gets each frame, and puts the suggestion in the sequencer once it's done
+fn continue_suggestion_search(
+    mut search: ResMut<SuggestionSearch>,
+    mut sequence: ResMut<Sequence>,
+    mut suggested_beats: ResMut<SuggestedBeats>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut commands: Commands,
+) {
+    let Some(suggester) = &mut search.0 else {
+        return;
+    };
+    let Poll::Ready(suggestion) = suggester.continue_until(Instant::now() + SEARCH_TIME_PER_FRAME)
+    else {
+        return;
+    };
+    search.0 = None;
+
+    let Some(suggestion) = suggestion else {
+        commands.banner(localization.get("suggest.not_found"), &font_handles);
+        return;
+    };
+
+    suggested_beats.0 = suggestion
+        .beats()
+        .enumerate()
+        .flat_map(|(step, beat)| beat.keys().map(move |row| (*row, step)))
+        .collect();
+    *sequence = suggestion;
+    commands.trigger(RefreshBeatButtons);
+    commands.banner(localization.get("suggest.found"), &font_handles);
+}
+
+/// Stops treating beats as suggested once the player turns them off
+fn forget_removed_suggestions(
+    sequence: Res<Sequence>,
+    mut suggested_beats: ResMut<SuggestedBeats>,
+) {
+    suggested_beats
+        .0
+        .retain(|(row, step)| sequence.chance(*row, *step).is_some());
+}
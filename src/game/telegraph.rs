@@ -0,0 +1,217 @@
+//! Warnings shown near the end of a level, pointing out where the spikes the player will run into first in the next
+//! level are, so going through to a new set of obstacles isn't a blind guess.
+//! Arrows show up along the right edge of the level at the heights of those spikes, then fade away.
+//! In levels more than one screen long, the view has followed the player to the right edge by then.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use crate::{screen::Screen, AppSet};
+
+use super::{
+    settings::Settings,
+    spawn::{
+        level::{
+            CurrentLevel, GameMode, LevelLayouts, LevelWidth, SpawnObstacles, BOX_SIZE,
+            CAMPAIGN_LENGTH, LEVEL_WIDTH,
+        },
+        player::Player,
+    },
+    versus::Opponent,
+};
+
+/// How far through the level the player has to get before the next level's spikes are pointed out, from 0 to 1
+const TELEGRAPH_PROGRESS: f32 = 0.8;
+
+/// How long the arrows stay up, in seconds
+const TELEGRAPH_DURATION: f32 = 1.2;
+
+/// How many times the arrows blink while they're up
+const TELEGRAPH_BLINKS: f32 = 3.0;
+
+/// How far past the first spikes other spikes can be and still count as being first, in pixels
+const FIRST_HAZARD_RANGE: f32 = BOX_SIZE * 2.0;
+
+/// How many pixels wide and tall the image arrows are drawn with is
+const ARROW_IMAGE_SIZE: u32 = 16;
+
+/// How big arrows are drawn, in pixels
+const ARROW_SIZE: f32 = 36.0;
+
+const ARROW_COLOR: Color = Color::srgb(0.95, 0.3, 0.25);
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ArrowImage>();
+    app.init_resource::<TelegraphedLevel>();
+    app.observe(clear_telegraph);
+    app.add_systems(
+        Update,
+        (show_telegraph, fade_telegraph)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// An arrow pointing out where spikes are in the next level.
+#[derive(Component, Debug)]
+struct TelegraphArrow {
+    timer: Timer,
+}
+
+/// The level whose spikes have already been pointed out, if any
+#[derive(Resource, Debug, Default)]
+struct TelegraphedLevel(Option<u32>);
+
+/// The image arrows are drawn with, pointing to the right.
+#[derive(Resource, Debug)]
+struct ArrowImage(Handle<Image>);
+
+impl FromWorld for ArrowImage {
+    fn from_world(world: &mut World) -> Self {
+        // a triangle with its point in the middle of the right side
+        let size = ARROW_IMAGE_SIZE as f32;
+        let data = (0..ARROW_IMAGE_SIZE * ARROW_IMAGE_SIZE)
+            .flat_map(|i| {
+                let x = (i % ARROW_IMAGE_SIZE) as f32 + 0.5;
+                let y = (i / ARROW_IMAGE_SIZE) as f32 + 0.5;
+                let half_height = (size - x) / 2.0;
+                let inside = (y - size / 2.0).abs() <= half_height;
+                [255, 255, 255, if inside { 255 } else { 0 }]
+            })
+            .collect();
+
+        let image = Image::new(
+            Extent3d {
+                width: ARROW_IMAGE_SIZE,
+                height: ARROW_IMAGE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        ArrowImage(world.resource_mut::<Assets<Image>>().add(image))
+    }
+}
+
+/// Points out the next level's first spikes once the player is far enough through the current one
+fn show_telegraph(
+    current_level: Res<CurrentLevel>,
+    game_mode: Res<GameMode>,
+    layouts: LevelLayouts,
+    level_width: Res<LevelWidth>,
+    arrow_image: Res<ArrowImage>,
+    mut telegraphed_level: ResMut<TelegraphedLevel>,
+    runner_query: Query<&Transform, (With<Player>, Without<Opponent>)>,
+    mut commands: Commands,
+) {
+    let next_level = current_level.0 + 1;
+    if telegraphed_level.0 == Some(next_level) {
+        return;
+    }
+    // the next tutorial step's level isn't picked until this one's done, and there's no next level after winning.
+    // Scrolling mode's level doesn't end, so the spikes coming up are already there to see.
+    if matches!(*game_mode, GameMode::Tutorial | GameMode::Scrolling)
+        || (*game_mode == GameMode::Campaign && next_level >= CAMPAIGN_LENGTH)
+    {
+        return;
+    }
+
+    let Some(leader_x) = runner_query
+        .iter()
+        .map(|transform| transform.translation.x)
+        .max_by(f32::total_cmp)
+    else {
+        return;
+    };
+    let progress = (leader_x + (LEVEL_WIDTH / 2.0)) / level_width.0;
+    if progress < TELEGRAPH_PROGRESS {
+        return;
+    }
+    telegraphed_level.0 = Some(next_level);
+
+    let hazards = layouts
+        .layout(next_level)
+        .into_iter()
+        .filter_map(|placement| placement.hazard_position())
+        .collect::<Vec<_>>();
+    let Some(first_x) = hazards.iter().map(|hazard| hazard.x).min_by(f32::total_cmp) else {
+        return;
+    };
+    let mut heights = hazards
+        .iter()
+        .filter(|hazard| hazard.x <= first_x + FIRST_HAZARD_RANGE)
+        .map(|hazard| hazard.y)
+        .collect::<Vec<_>>();
+    heights.sort_by(f32::total_cmp);
+    // spikes side by side only need one arrow
+    heights.dedup_by(|a, b| (*a - *b).abs() < ARROW_SIZE / 2.0);
+
+    for y in heights {
+        commands.spawn((
+            Name::new("Telegraph arrow"),
+            TelegraphArrow {
+                timer: Timer::from_seconds(TELEGRAPH_DURATION, TimerMode::Once),
+            },
+            StateScoped(Screen::Playing),
+            SpriteBundle {
+                texture: arrow_image.0.clone(),
+                sprite: Sprite {
+                    color: ARROW_COLOR,
+                    custom_size: Some(Vec2::splat(ARROW_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(
+                    level_width.right_edge() - (ARROW_SIZE / 2.0),
+                    y,
+                    0.8,
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Blinks the arrows and fades them out, then gets rid of them once their time is up
+fn fade_telegraph(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut arrow_query: Query<(Entity, &mut TelegraphArrow, &mut Sprite)>,
+    mut commands: Commands,
+) {
+    for (entity, mut arrow, mut sprite) in &mut arrow_query {
+        arrow.timer.tick(time.delta());
+        if arrow.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let fraction = arrow.timer.fraction();
+        let blink = if settings.reduced_flashing {
+            1.0
+        } else {
+            0.6 + 0.4 * (fraction * TELEGRAPH_BLINKS * std::f32::consts::TAU).cos()
+        };
+        sprite.color.set_alpha(blink * (1.0 - fraction * fraction));
+    }
+}
+
+/// Gets rid of any arrows once the next level is spawned, and lets the level after that be pointed out
+fn clear_telegraph(
+    _trigger: Trigger<SpawnObstacles>,
+    arrow_query: Query<Entity, With<TelegraphArrow>>,
+    mut telegraphed_level: ResMut<TelegraphedLevel>,
+    mut commands: Commands,
+) {
+    for entity in &arrow_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    telegraphed_level.0 = None;
+}
@@ -0,0 +1,187 @@
+//! Anonymous records of how runs go, so the game's designer can learn where players get stuck.
+//! Nothing is recorded unless the player turns it on in the settings, and nothing identifying the player is recorded.
+//! Events are kept with the rest of the save data, and with the `telemetry` feature they're also sent to the server
+//! at `LOOPRUNNER_TELEMETRY_URL`. Only runs the player actually plays are recorded.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{persistence, screen::Screen};
+
+use super::{
+    settings::Settings,
+    spawn::{
+        level::{CurrentLevel, GameMode, LevelCleared},
+        sequencer::{DeathEvent, Sequence, SequencerRow},
+    },
+};
+
+const TELEMETRY_SAVE_KEY: &str = "telemetry";
+
+/// The most events kept at once. Once there are this many, the oldest ones are dropped to make room.
+const MAX_RECORDED_EVENTS: usize = 1000;
+
+/// The server to send events to if one isn't provided at compile time via `LOOPRUNNER_TELEMETRY_URL`
+#[cfg(feature = "telemetry")]
+const DEFAULT_ENDPOINT: &str = "http://localhost:8080/telemetry";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(persistence::load::<TelemetryLog>(TELEMETRY_SAVE_KEY).unwrap_or_default());
+    #[cfg(feature = "telemetry")]
+    app.insert_resource(TelemetryConfig {
+        endpoint: option_env!("LOOPRUNNER_TELEMETRY_URL")
+            .unwrap_or(DEFAULT_ENDPOINT)
+            .to_string(),
+    });
+    app.observe(record_death);
+    app.observe(record_level_reached);
+    app.observe(clear_telemetry);
+    app.add_systems(OnExit(Screen::Playing), save_telemetry);
+    app.add_systems(Last, save_telemetry.run_if(on_event::<AppExit>()));
+}
+
+/// Where events are sent, on top of being kept locally.
+#[cfg(feature = "telemetry")]
+#[derive(Resource, Debug)]
+pub struct TelemetryConfig {
+    /// The URL each event is posted to
+    pub endpoint: String,
+}
+
+/// Every event recorded so far, oldest first.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryLog {
+    pub events: Vec<TelemetryEvent>,
+}
+
+impl TelemetryLog {
+    fn record(&mut self, event: TelemetryEvent) {
+        if self.events.len() >= MAX_RECORDED_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push(event);
+    }
+}
+
+/// Something that happened during a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TelemetryEvent {
+    /// The player made it to the start of a level
+    LevelReached {
+        mode: GameMode,
+        level: u32,
+        /// The rows with any notes on them when the level was reached
+        rows_used: Vec<SequencerRow>,
+    },
+    /// The player died
+    Death {
+        mode: GameMode,
+        level: u32,
+        /// Where the runner was when they died
+        x: f32,
+        y: f32,
+        /// The rows with any notes on them when the player died
+        rows_used: Vec<SequencerRow>,
+    },
+}
+
+/// Trigger this to throw away every event recorded so far
+#[derive(Event, Debug)]
+pub struct ClearTelemetry;
+
+/// Gets the rows with any notes on them, in order
+fn rows_used(sequence: &Sequence) -> Vec<SequencerRow> {
+    sequence
+        .beats()
+        .flat_map(|beat| beat.keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn record_death(
+    trigger: Trigger<DeathEvent>,
+    transform_query: Query<&Transform>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+    current_level: Res<CurrentLevel>,
+    sequence: Res<Sequence>,
+    mut log: ResMut<TelemetryLog>,
+    #[cfg(feature = "telemetry")] config: Res<TelemetryConfig>,
+) {
+    if !settings.telemetry {
+        return;
+    }
+
+    // it's not always known which runner died, in which case there's no position worth recording
+    let Ok(transform) = transform_query.get(trigger.entity()) else {
+        return;
+    };
+
+    let event = TelemetryEvent::Death {
+        mode: *game_mode,
+        level: current_level.0,
+        x: transform.translation.x,
+        y: transform.translation.y,
+        rows_used: rows_used(&sequence),
+    };
+    #[cfg(feature = "telemetry")]
+    send(&config, &event);
+    log.record(event);
+}
+
+fn record_level_reached(
+    trigger: Trigger<LevelCleared>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+    sequence: Res<Sequence>,
+    mut log: ResMut<TelemetryLog>,
+    #[cfg(feature = "telemetry")] config: Res<TelemetryConfig>,
+) {
+    if !settings.telemetry {
+        return;
+    }
+
+    let event = TelemetryEvent::LevelReached {
+        mode: *game_mode,
+        level: trigger.event().0 + 1,
+        rows_used: rows_used(&sequence),
+    };
+    #[cfg(feature = "telemetry")]
+    send(&config, &event);
+    log.record(event);
+}
+
+/// Posts the event to the telemetry server in the background, without waiting to hear back
+#[cfg(feature = "telemetry")]
+fn send(config: &TelemetryConfig, event: &TelemetryEvent) {
+    use bevy::tasks::IoTaskPool;
+
+    let request = match ehttp::Request::json(&config.endpoint, event) {
+        Ok(x) => x,
+        Err(e) => {
+            warn!("could not serialize telemetry event: {e}");
+            return;
+        }
+    };
+
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(e) = ehttp::fetch_async(request).await {
+                warn!("could not send telemetry event: {e}");
+            }
+        })
+        .detach();
+}
+
+fn clear_telemetry(_trigger: Trigger<ClearTelemetry>, mut log: ResMut<TelemetryLog>) {
+    log.events.clear();
+    persistence::save(TELEMETRY_SAVE_KEY, &*log);
+}
+
+fn save_telemetry(log: Res<TelemetryLog>) {
+    persistence::save(TELEMETRY_SAVE_KEY, &*log);
+}
@@ -0,0 +1,224 @@
+//! The colors each level is drawn with, loaded from theme files in the assets folder.
+//! Generated levels tint the endless theme with their own hue, so every one of them looks a little different.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+
+use crate::AppSet;
+
+use super::{
+    assets::{HandleMap, ImageKey, RonAssetLoader, ThemeKey},
+    boss::is_boss_level,
+    level_definition::{CustomLevels, EditorLevel},
+    scenery::SceneryLayer,
+    spawn::level::{
+        generated_level_hue, Background, GameMode, Obstacle, SpawnObstacles, TOTAL_LEVELS,
+    },
+};
+
+/// How many rows of pixels the background gradient is drawn with
+const GRADIENT_ROWS: u32 = 32;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<LevelTheme>();
+    app.register_asset_loader(RonAssetLoader::<LevelTheme>::new(&["theme.ron"]));
+    app.init_resource::<ActiveTheme>();
+    app.observe(choose_level_theme);
+    app.add_systems(Update, apply_level_theme.in_set(AppSet::Update));
+}
+
+/// The colors a level is drawn with. Colors are sRGB components from 0 to 1.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone)]
+pub struct LevelTheme {
+    /// The color at the top of the background, which fades into `background_bottom` going down
+    pub background_top: [f32; 3],
+    pub background_bottom: [f32; 3],
+    /// Multiplied with the images of the obstacles
+    pub obstacle_tint: [f32; 3],
+    /// The color of flashes of light, like the ones from portals
+    pub particle_color: [f32; 3],
+    /// The color the current beat is highlighted with in the sequencer
+    pub accent_color: [f32; 3],
+    /// The decorations in the background, from back to front
+    #[serde(default)]
+    pub scenery: Vec<SceneryLayer>,
+}
+
+impl Default for LevelTheme {
+    fn default() -> Self {
+        LevelTheme {
+            background_top: [0.55, 0.55, 0.55],
+            background_bottom: [0.45, 0.45, 0.45],
+            obstacle_tint: [1.0, 1.0, 1.0],
+            particle_color: [0.9, 0.8, 1.0],
+            accent_color: [1.0, 1.0, 1.0],
+            scenery: Vec::new(),
+        }
+    }
+}
+
+impl LevelTheme {
+    pub fn obstacle_tint(&self) -> Color {
+        srgb(self.obstacle_tint)
+    }
+
+    pub fn particle_color(&self) -> Color {
+        srgb(self.particle_color)
+    }
+
+    pub fn accent_color(&self) -> Color {
+        srgb(self.accent_color)
+    }
+
+    /// Gets this theme with every one of its colors turned to the provided hue, in degrees
+    fn with_hue(&self, hue: f32) -> LevelTheme {
+        let rotate = |rgb: [f32; 3]| {
+            let rotated = Srgba::from(Hsla::from(Srgba::rgb(rgb[0], rgb[1], rgb[2])).with_hue(hue));
+            [rotated.red, rotated.green, rotated.blue]
+        };
+        LevelTheme {
+            background_top: rotate(self.background_top),
+            background_bottom: rotate(self.background_bottom),
+            obstacle_tint: rotate(self.obstacle_tint),
+            particle_color: rotate(self.particle_color),
+            accent_color: rotate(self.accent_color),
+            scenery: self
+                .scenery
+                .iter()
+                .map(|layer| SceneryLayer {
+                    color: rotate(layer.color),
+                    ..layer.clone()
+                })
+                .collect(),
+        }
+    }
+
+    /// Gets this theme with its background fading into the provided color at the bottom, from a lighter version of it at the top
+    fn with_background(&self, color: [f32; 3]) -> LevelTheme {
+        let top = Srgba::from(srgb(color).lighter(0.05));
+        LevelTheme {
+            background_top: [top.red, top.green, top.blue],
+            background_bottom: color,
+            ..self.clone()
+        }
+    }
+
+    /// Builds a tall, thin image of the background gradient, to be stretched over the whole background
+    fn background_image(&self) -> Image {
+        let top = srgb(self.background_top);
+        let bottom = srgb(self.background_bottom);
+        let data = (0..GRADIENT_ROWS)
+            .flat_map(|row| {
+                let fraction = row as f32 / (GRADIENT_ROWS - 1) as f32;
+                Srgba::from(top.mix(&bottom, fraction)).to_u8_array()
+            })
+            .collect();
+
+        let mut image = Image::new(
+            Extent3d {
+                width: 1,
+                height: GRADIENT_ROWS,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::linear();
+        image
+    }
+}
+
+fn srgb([red, green, blue]: [f32; 3]) -> Color {
+    Color::srgb(red, green, blue)
+}
+
+/// The theme of the level being played.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveTheme {
+    pub theme: LevelTheme,
+    /// The background gradient for the theme
+    background_image: Handle<Image>,
+}
+
+/// Picks the theme for a level as it's spawned
+fn choose_level_theme(
+    trigger: Trigger<SpawnObstacles>,
+    game_mode: Res<GameMode>,
+    editor_level: Res<EditorLevel>,
+    custom_levels: Res<CustomLevels>,
+    theme_handles: Res<HandleMap<ThemeKey>>,
+    themes: Res<Assets<LevelTheme>>,
+    mut images: ResMut<Assets<Image>>,
+    mut active_theme: ResMut<ActiveTheme>,
+) {
+    let level = trigger.event().level;
+    let key = if is_boss_level(*game_mode, level) {
+        ThemeKey::Boss
+    } else {
+        match *game_mode {
+            GameMode::Campaign => ThemeKey::Campaign(level % TOTAL_LEVELS),
+            GameMode::Tutorial => ThemeKey::Tutorial,
+            GameMode::Endless | GameMode::Scrolling => ThemeKey::Endless,
+            GameMode::EditorTest | GameMode::Custom => ThemeKey::Custom,
+        }
+    };
+    let theme = themes
+        .get(&theme_handles[&key])
+        .cloned()
+        .unwrap_or_default();
+
+    let theme = match key {
+        ThemeKey::Endless => theme.with_hue(generated_level_hue(&mut StdRng::seed_from_u64(
+            level.into(),
+        ))),
+        ThemeKey::Custom => {
+            let definition = match *game_mode {
+                GameMode::EditorTest => Some(&editor_level.0),
+                _ => custom_levels.for_level(level),
+            };
+            match definition {
+                Some(definition) => theme.with_background(definition.background),
+                None => theme,
+            }
+        }
+        _ => theme,
+    };
+
+    active_theme.background_image = images.add(theme.background_image());
+    active_theme.theme = theme;
+}
+
+/// Colors in the background and obstacles with the active theme, as they're spawned or when the theme changes
+fn apply_level_theme(
+    active_theme: Res<ActiveTheme>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    mut background_query: Query<(Ref<Background>, &mut Handle<Image>)>,
+    mut obstacle_query: Query<(Ref<Obstacle>, &Handle<Image>, &mut Sprite), Without<Background>>,
+) {
+    for (background, mut texture) in &mut background_query {
+        if active_theme.is_changed() || background.is_added() {
+            *texture = active_theme.background_image.clone();
+        }
+    }
+
+    let tint = active_theme.theme.obstacle_tint();
+    for (obstacle, texture, mut sprite) in &mut obstacle_query {
+        // only the obstacles drawn with images are tinted, so things like colliders and flashes keep their own colors
+        let has_image = image_handles
+            .values()
+            .any(|handle| handle.id() == texture.id());
+        if (active_theme.is_changed() || obstacle.is_added()) && has_image {
+            sprite.color = tint;
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! A scripted tutorial that introduces one sequencer row at a time.
+//! Each step has its own small level, and asks the player to put a note on a particular row and
+//! then use it to get through the level. Once both are done, the sequence pauses so the player
+//! can set up for the next step.
+
+use bevy::prelude::*;
+
+use crate::{screen::Screen, ui::palette::LABEL_TEXT};
+
+use super::{
+    assets::{FontKey, HandleMap},
+    localization::Localization,
+    spawn::{
+        level::{GameMode, LevelCleared},
+        sequencer::{PauseSequence, Sequence, SequencerRow, SetBeatButtonsEnabled},
+    },
+};
+
+/// The steps of the tutorial, in order. The level for each step is spawned along with the other levels.
+pub const TUTORIAL_STEPS: [TutorialStep; 4] = [
+    TutorialStep {
+        goal: TutorialGoal::AnyNote,
+        prompt: "tutorial.notes.prompt",
+        placed_prompt: "tutorial.notes.placed_prompt",
+    },
+    TutorialStep {
+        goal: TutorialGoal::Row(SequencerRow::Kick),
+        prompt: "tutorial.kick.prompt",
+        placed_prompt: "tutorial.kick.placed_prompt",
+    },
+    TutorialStep {
+        goal: TutorialGoal::Row(SequencerRow::HiHat),
+        prompt: "tutorial.hi_hat.prompt",
+        placed_prompt: "tutorial.hi_hat.placed_prompt",
+    },
+    TutorialStep {
+        goal: TutorialGoal::Row(SequencerRow::Snare),
+        prompt: "tutorial.snare.prompt",
+        placed_prompt: "tutorial.snare.placed_prompt",
+    },
+];
+
+/// The key of what's shown once every step is done
+const TUTORIAL_DONE_PROMPT: &str = "tutorial.done";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Tutorial { step: 0 });
+    app.observe(advance_tutorial);
+}
+
+pub(super) fn presentation_plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(Screen::Playing),
+        spawn_tutorial_prompt.run_if(resource_equals(GameMode::Tutorial)),
+    );
+    app.add_systems(
+        Update,
+        update_tutorial_prompt
+            .run_if(in_state(Screen::Playing).and_then(resource_equals(GameMode::Tutorial))),
+    );
+}
+
+/// How far the player has gotten in the tutorial
+#[derive(Resource, Debug)]
+pub struct Tutorial {
+    /// The index of the current step in [`TUTORIAL_STEPS`], or its length if the tutorial is done
+    pub step: usize,
+}
+
+impl Tutorial {
+    /// Gets the step the player is currently on, if they haven't finished the tutorial
+    fn current_step(&self) -> Option<&'static TutorialStep> {
+        TUTORIAL_STEPS.get(self.step)
+    }
+}
+
+/// One part of the tutorial.
+pub struct TutorialStep {
+    /// The note that needs to be placed before the step can be finished
+    pub goal: TutorialGoal,
+    /// The key of the text explaining what to do before the required note is placed
+    pub prompt: &'static str,
+    /// The key of the text explaining what to do once the required note is placed
+    pub placed_prompt: &'static str,
+}
+
+/// A note the player needs to place somewhere in the sequence.
+pub enum TutorialGoal {
+    /// A note on any of the synth rows
+    AnyNote,
+    /// A note on a specific row
+    Row(SequencerRow),
+}
+
+impl TutorialGoal {
+    /// Determines whether the provided sequence has the required note in it
+    fn is_met(&self, sequence: &Sequence) -> bool {
+        sequence
+            .beats()
+            .flat_map(|rows| rows.keys())
+            .any(|row| match self {
+                TutorialGoal::AnyNote => matches!(row, SequencerRow::SynthNote(_)),
+                TutorialGoal::Row(goal_row) => row == goal_row,
+            })
+    }
+}
+
+#[derive(Component)]
+struct TutorialPromptText;
+
+fn spawn_tutorial_prompt(font_handles: Res<HandleMap<FontKey>>, mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Tutorial prompt"),
+            StateScoped(Screen::Playing),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(60.0),
+                    left: Val::Percent(20.0),
+                    top: Val::Px(50.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                border_radius: BorderRadius::all(Val::Px(10.0)),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Tutorial prompt text"),
+                TutorialPromptText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 24.0,
+                        color: LABEL_TEXT,
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            ));
+        });
+}
+
+fn update_tutorial_prompt(
+    tutorial: Res<Tutorial>,
+    sequence: Res<Sequence>,
+    localization: Res<Localization>,
+    mut text_query: Query<&mut Text, With<TutorialPromptText>>,
+) {
+    let key = match tutorial.current_step() {
+        Some(step) if step.goal.is_met(&sequence) => step.placed_prompt,
+        Some(step) => step.prompt,
+        None => TUTORIAL_DONE_PROMPT,
+    };
+    let prompt = localization.get(key);
+
+    for mut text in &mut text_query {
+        if text.sections[0].value != prompt {
+            text.sections[0].value.clone_from(&prompt);
+        }
+    }
+}
+
+/// Moves on to the next step when the player gets through the level with the required note placed.
+/// The sequence is paused so the player can add the next note before the new level gets them.
+fn advance_tutorial(
+    _trigger: Trigger<LevelCleared>,
+    game_mode: Res<GameMode>,
+    sequence: Res<Sequence>,
+    mut tutorial: ResMut<Tutorial>,
+    mut commands: Commands,
+) {
+    if *game_mode != GameMode::Tutorial {
+        return;
+    }
+
+    let Some(step) = tutorial.current_step() else {
+        return;
+    };
+    if !step.goal.is_met(&sequence) {
+        return;
+    }
+
+    tutorial.step += 1;
+    commands.trigger(PauseSequence);
+    commands.trigger(SetBeatButtonsEnabled(true));
+}
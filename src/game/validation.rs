@@ -0,0 +1,401 @@
+//! Checks that levels can be cleared, by simulating runs through them with simple sequences until one gets through.
+//! Custom levels are checked in the background as soon as they're read, so ones that can't be cleared can be pointed out.
+
+use std::{collections::HashMap, task::Poll, time::Duration};
+
+use bevy::{app::Main, prelude::*, utils::Instant};
+
+use crate::{build_simulation_app, AppSet, HEADLESS_FRAME_TIME};
+
+use super::{
+    difficulty::DifficultyCurve,
+    level_definition::{CustomLevels, EditorLevel, LevelDefinition, ObstaclePlacement},
+    movement::TotalDistance,
+    mutators::{Mutators, RowShuffle},
+    settings::Settings,
+    spawn::{
+        level::{CurrentLevel, GameMode, LevelCleared, SpawnLevel, StartingLevel},
+        sequencer::{
+            Dead, PauseSequence, PlaySequence, ResetSequence, Sequence, SequencerRow,
+            NUM_BEATS_IN_SEQUENCE, NUM_SYNTH_NOTES,
+        },
+    },
+};
+
+/// How long a simulated run gets to clear each screen of the level, in seconds
+const MAX_RUN_DURATION_PER_SCREEN: f32 = 15.0;
+
+/// How long the player can go without moving forward before a simulated run is given up on, in seconds
+const STUCK_DURATION: f32 = 2.0;
+
+/// The speeds tried on each level, as the synth note rows that set them, fastest first
+pub(super) const CANDIDATE_SPEEDS: [usize; 3] = [NUM_SYNTH_NOTES - 1, 5, 3];
+
+/// The notes tried a few beats after each jump, if any, and how many beats after the jump they are
+const JUMP_FOLLOW_UPS: [Option<(SequencerRow, usize)>; 7] = [
+    None,
+    Some((SequencerRow::HiHat, 1)),
+    Some((SequencerRow::HiHat, 2)),
+    Some((SequencerRow::HiHat, 3)),
+    Some((SequencerRow::Snare, 2)),
+    Some((SequencerRow::Snare, 3)),
+    Some((SequencerRow::Snare, 4)),
+];
+
+/// The longest gap between jumps tried on each level, in beats
+const MAX_JUMP_PERIOD: usize = 8;
+
+/// How long searches through simulated runs get to go on each frame.
+/// They're done a little at a time on the main thread, since web builds don't have any other threads to do them on.
+pub(super) const SEARCH_TIME_PER_FRAME: Duration = Duration::from_millis(4);
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelValidations>();
+    app.add_systems(
+        Update,
+        (
+            validate_custom_levels.run_if(resource_changed::<CustomLevels>),
+            continue_validation_searches,
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// What's known about whether each level can be cleared, by level name
+#[derive(Resource, Default)]
+pub struct LevelValidations(HashMap<String, LevelValidation>);
+
+impl LevelValidations {
+    /// Starts checking whether the provided level can be cleared, a little each frame,
+    /// unless a level with the same name and obstacles has already been checked
+    pub fn check(&mut self, name: &str, level: &LevelDefinition) {
+        if self
+            .0
+            .get(name)
+            .is_some_and(|validation| validation.obstacles == level.obstacles)
+        {
+            return;
+        }
+
+        self.0.insert(
+            name.to_string(),
+            LevelValidation {
+                obstacles: level.obstacles.clone(),
+                status: ValidationStatus::Checking(Box::new(SolutionSearch::new(level))),
+            },
+        );
+    }
+
+    /// Gets how checking the level with the provided name is going, if it's been checked
+    pub fn status(&self, name: &str) -> Option<&ValidationStatus> {
+        self.0.get(name).map(|validation| &validation.status)
+    }
+}
+
+struct LevelValidation {
+    /// The obstacles in the level when it was checked, so it can be checked again if they change
+    obstacles: Vec<ObstaclePlacement>,
+    status: ValidationStatus,
+}
+
+pub enum ValidationStatus {
+    /// Still trying sequences on the level
+    Checking(Box<SolutionSearch>),
+    /// This sequence gets through the level
+    Clearable(Sequence),
+    /// None of the sequences that were tried got through the level
+    Unclearable,
+}
+
+/// A search for a sequence that gets through a level, which simulates runs with simple sequences until one gets through.
+/// It's done a little at a time, so it doesn't hold up the frame it's going on in.
+pub struct SolutionSearch {
+    simulation: Simulation,
+    /// The sequences that haven't been tried yet
+    candidates: std::vec::IntoIter<Sequence>,
+    /// The sequence the run going on right now is playing
+    trying: Option<Sequence>,
+}
+
+impl SolutionSearch {
+    fn new(level: &LevelDefinition) -> SolutionSearch {
+        SolutionSearch {
+            simulation: Simulation::new(SimulatedLevel::plain(SimulatedLayout::Defined(
+                level.clone(),
+            ))),
+            candidates: candidate_sequences().into_iter(),
+            trying: None,
+        }
+    }
+
+    /// Keeps simulating runs until the provided deadline, and returns the sequence that got through once the search is over.
+    /// That's `None` if none of them did, which most likely means the level can't be cleared.
+    fn continue_until(&mut self, deadline: Instant) -> Poll<Option<Sequence>> {
+        while Instant::now() < deadline {
+            if self.trying.is_none() {
+                let Some(sequence) = self.candidates.next() else {
+                    return Poll::Ready(None);
+                };
+                self.simulation.start(&sequence);
+                self.trying = Some(sequence);
+            }
+
+            if let Some(run) = self.simulation.step() {
+                let tried = self.trying.take();
+                if run.cleared {
+                    return Poll::Ready(tried);
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Gets the sequences tried on each level, roughly simplest first.
+/// Each one sets a speed on the first beat, then jumps once or every few beats, maybe floating or diving a little while into each jump.
+fn candidate_sequences() -> Vec<Sequence> {
+    let mut jump_patterns = vec![Vec::new()];
+    jump_patterns.extend((0..NUM_BEATS_IN_SEQUENCE).map(|step| vec![step]));
+    for period in 2..=MAX_JUMP_PERIOD {
+        for offset in 0..period {
+            jump_patterns.push((offset..NUM_BEATS_IN_SEQUENCE).step_by(period).collect());
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for speed in CANDIDATE_SPEEDS {
+        for follow_up in JUMP_FOLLOW_UPS {
+            for jumps in &jump_patterns {
+                // there's nothing to follow up on without any jumps
+                if jumps.is_empty() && follow_up.is_some() {
+                    continue;
+                }
+
+                let mut sequence = Sequence::new();
+                sequence.add_note(SequencerRow::SynthNote(speed), 0);
+                for &step in jumps {
+                    sequence.add_note(SequencerRow::Kick, step);
+                    if let Some((row, delay)) = follow_up {
+                        sequence.add_note(row, (step + delay) % NUM_BEATS_IN_SEQUENCE);
+                    }
+                }
+                candidates.push(sequence);
+            }
+        }
+    }
+
+    candidates
+}
+
+/// A level runs can be simulated through, and the twists on the runs through it
+#[derive(Debug, Clone)]
+pub(super) struct SimulatedLevel {
+    pub layout: SimulatedLayout,
+    pub mutators: Mutators,
+    /// Kept the same for every run, so they all play out the way the player's would
+    pub row_shuffle: RowShuffle,
+}
+
+impl SimulatedLevel {
+    /// Gets the provided level with no mutators on
+    pub(super) fn plain(layout: SimulatedLayout) -> SimulatedLevel {
+        SimulatedLevel {
+            layout,
+            mutators: default(),
+            row_shuffle: default(),
+        }
+    }
+}
+
+/// Where the obstacles in a simulated level come from
+#[derive(Debug, Clone)]
+pub(super) enum SimulatedLayout {
+    /// A level made as a level file
+    Defined(LevelDefinition),
+    /// One of the levels the provided game mode lays out on its own
+    Built {
+        game_mode: GameMode,
+        level: u32,
+        difficulty_curve: DifficultyCurve,
+    },
+}
+
+/// How a simulated run went
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct SimulatedRun {
+    /// Whether the run got all the way through the level
+    pub cleared: bool,
+    /// How far the run got, in pixels
+    pub distance: f32,
+}
+
+/// A level that's played over and over with different sequences, without anything being shown
+pub(super) struct Simulation {
+    /// The world of an app built with [`build_simulation_app`].
+    /// Only the world is kept, since it can be kept in a resource and the app can't.
+    world: World,
+    /// How long a run gets to clear the level, in seconds
+    max_run_duration: f32,
+    /// How the run going on right now is going, if there is one
+    run: Option<RunProgress>,
+}
+
+/// How far along a simulated run is
+struct RunProgress {
+    /// How many more frames the run gets before it's given up on
+    frames_left: u32,
+    /// The furthest the run has gotten, in pixels
+    furthest_distance: f32,
+    /// How long the run has gone without getting any further, in seconds
+    time_stuck: f32,
+}
+
+/// Whether the level has been cleared in the current simulated run
+#[derive(Resource, Debug, Default)]
+struct Cleared(bool);
+
+impl Simulation {
+    pub(super) fn new(level: SimulatedLevel) -> Simulation {
+        let mut app = build_simulation_app();
+        let max_run_duration = match level.layout {
+            SimulatedLayout::Defined(definition) => {
+                let screens = definition.screens.max(1);
+                app.insert_resource(GameMode::EditorTest);
+                app.insert_resource(EditorLevel(definition));
+                MAX_RUN_DURATION_PER_SCREEN * screens as f32
+            }
+            SimulatedLayout::Built {
+                game_mode,
+                level,
+                difficulty_curve,
+            } => {
+                app.insert_resource(game_mode);
+                app.insert_resource(CurrentLevel(level));
+                app.insert_resource(StartingLevel(level));
+                app.insert_resource(difficulty_curve);
+                MAX_RUN_DURATION_PER_SCREEN
+            }
+        };
+        // there's nothing to see, so there's no point in panning over the level before each run
+        app.insert_resource(Settings {
+            reduced_motion: true,
+            ..default()
+        });
+        app.insert_resource(level.mutators);
+        app.insert_resource(level.row_shuffle.kept());
+        app.init_resource::<Cleared>();
+        app.observe(mark_cleared);
+        app.world_mut().trigger(SpawnLevel);
+
+        let mut simulation = Simulation {
+            world: std::mem::take(app.world_mut()),
+            max_run_duration,
+            run: None,
+        };
+        simulation.update();
+        simulation
+    }
+
+    /// Starts playing through the level from the start with the provided sequence, giving up on any run that's going
+    pub(super) fn start(&mut self, sequence: &Sequence) {
+        self.world.insert_resource(sequence.clone());
+        self.world.insert_resource(Cleared(false));
+        self.world.trigger(PauseSequence);
+        self.world.trigger(ResetSequence);
+        self.world.flush();
+        self.world.trigger(PlaySequence);
+        self.world.flush();
+
+        let frames = (self.max_run_duration / HEADLESS_FRAME_TIME.as_secs_f32()) as u32;
+        self.run = Some(RunProgress {
+            frames_left: frames.max(1),
+            furthest_distance: 0.0,
+            time_stuck: 0.0,
+        });
+    }
+
+    /// Plays one more frame of the run that's going, and returns how far it got once it's over.
+    /// Returns `None` while the run is still going, or if there isn't one.
+    pub(super) fn step(&mut self) -> Option<SimulatedRun> {
+        self.run.as_ref()?;
+        self.update();
+
+        let run = self.run.as_mut()?;
+        run.frames_left -= 1;
+        let distance = self.world.resource::<TotalDistance>().0;
+        let cleared = self.world.resource::<Cleared>().0;
+        let over = if cleared || self.world.resource::<Dead>().0 {
+            run.furthest_distance = run.furthest_distance.max(distance);
+            true
+        } else if distance > run.furthest_distance {
+            run.furthest_distance = distance;
+            run.time_stuck = 0.0;
+            run.frames_left == 0
+        } else {
+            run.time_stuck += HEADLESS_FRAME_TIME.as_secs_f32();
+            run.time_stuck >= STUCK_DURATION || run.frames_left == 0
+        };
+        if !over {
+            return None;
+        }
+
+        let distance = run.furthest_distance;
+        self.run = None;
+        Some(SimulatedRun { cleared, distance })
+    }
+
+    /// Does what updating the app the world came from would
+    fn update(&mut self) {
+        self.world.run_schedule(Main);
+        self.world.clear_trackers();
+    }
+}
+
+fn mark_cleared(_trigger: Trigger<LevelCleared>, mut cleared: ResMut<Cleared>) {
+    cleared.0 = true;
+}
+
+/// Checks any custom levels that are new or have changed since they were last checked
+fn validate_custom_levels(
+    custom_levels: Res<CustomLevels>,
+    mut validations: ResMut<LevelValidations>,
+) {
+    for level in &custom_levels.0 {
+        if let Ok(definition) = &level.definition {
+            validations.check(&level.name, definition);
+        }
+    }
+}
+
+/// Keeps checking levels for as long as checks get each frame
+fn continue_validation_searches(mut validations: ResMut<LevelValidations>) {
+    let deadline = Instant::now() + SEARCH_TIME_PER_FRAME;
+    let mut finished = false;
+    // only counts as a change once a check finishes, so anything showing the results isn't redrawn every frame
+    for (name, validation) in &mut validations.bypass_change_detection().0 {
+        let ValidationStatus::Checking(search) = &mut validation.status else {
+            continue;
+        };
+        let Poll::Ready(solution) = search.continue_until(deadline) else {
+            continue;
+        };
+
+        validation.status = match solution {
+            Some(sequence) => {
+                info!("level {name} can be cleared");
+                ValidationStatus::Clearable(sequence)
+            }
+            None => {
+                warn!("could not find a way through level {name}, so it might not be possible to clear");
+                ValidationStatus::Unclearable
+            }
+        };
+        finished = true;
+    }
+
+    if finished {
+        validations.set_changed();
+    }
+}
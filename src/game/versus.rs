@@ -0,0 +1,174 @@
+//! Versus, where the player races an opponent that plays its own sequence for each level of the campaign.
+//! The opponent is a ghost: it runs into walls like the player does, but spikes go right through it.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    coop::Multiplayer,
+    movement::{level_end, FinishLineQuery, MovementController},
+    spawn::{
+        level::{LevelWidth, SpawnObstacles, TOTAL_LEVELS},
+        player::{OPPONENT_Z, PLAYER_IMAGE_SIZE, PLAYER_START},
+        sequencer::{trigger_row_actions, PlayTick, Sequence, SequencerRow},
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<OpponentSequence>();
+    app.observe(play_opponent_tick);
+    app.observe(start_opponent_on_level);
+    app.add_systems(Update, finish_opponent_level.in_set(AppSet::Update));
+}
+
+/// The runner the player races against in versus.
+#[derive(Component, Debug, Default)]
+pub struct Opponent {
+    /// Whether the opponent has made it through the current level, and is waiting for the player to catch up
+    pub finished: bool,
+}
+
+/// The sequence the opponent is playing for the current level.
+#[derive(Resource, Default)]
+pub struct OpponentSequence(pub Sequence);
+
+/// Plays the opponent's sequence along with the player's, on the same clock
+fn play_opponent_tick(
+    trigger: Trigger<PlayTick>,
+    opponent_sequence: Res<OpponentSequence>,
+    opponent_query: Query<(Entity, &Opponent)>,
+    mut commands: Commands,
+) {
+    for (entity, opponent) in &opponent_query {
+        if opponent.finished {
+            continue;
+        }
+
+        let rows = opponent_sequence
+            .0
+            .notes_at_tick(trigger.event().0)
+            .map(|(row, _)| row);
+        trigger_row_actions(rows, entity, &mut commands);
+    }
+}
+
+/// Puts the opponent back at the start whenever a level is spawned, with the sequence for that level
+fn start_opponent_on_level(
+    trigger: Trigger<SpawnObstacles>,
+    multiplayer: Res<Multiplayer>,
+    mut opponent_sequence: ResMut<OpponentSequence>,
+    mut opponent_query: Query<(
+        &mut Opponent,
+        &mut Transform,
+        &mut MovementController,
+        &mut Visibility,
+    )>,
+) {
+    if *multiplayer != Multiplayer::Versus {
+        return;
+    }
+
+    opponent_sequence.0 = opponent_sequence_for(trigger.event().level);
+    for (mut opponent, mut transform, mut controller, mut visibility) in &mut opponent_query {
+        opponent.finished = false;
+        transform.translation = PLAYER_START.extend(OPPONENT_Z);
+        // how far the opponent has gone carries over from level to level
+        *controller = MovementController {
+            distance: controller.distance,
+            ..MovementController::new()
+        };
+        *visibility = Visibility::Inherited;
+    }
+}
+
+/// Stops the opponent once it's made it all the way through the level, until the player gets through it too.
+/// The level ends in the same place for the opponent as it does for the player.
+fn finish_opponent_level(
+    level_width: Res<LevelWidth>,
+    finish_line_query: FinishLineQuery,
+    mut opponent_query: Query<(
+        &mut Opponent,
+        &Transform,
+        &mut MovementController,
+        &mut Visibility,
+    )>,
+) {
+    let level_end = level_end(*level_width, &finish_line_query);
+    for (mut opponent, transform, mut controller, mut visibility) in &mut opponent_query {
+        let left_edge = transform.translation.x - (PLAYER_IMAGE_SIZE / 2.0);
+        if opponent.finished || left_edge <= level_end {
+            continue;
+        }
+
+        opponent.finished = true;
+        controller.speed = 0.0;
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Gets the sequence the opponent plays through the provided level of the campaign.
+/// These get through each level at a steady pace, without being the fastest way through.
+fn opponent_sequence_for(level: u32) -> Sequence {
+    let notes: &[(SequencerRow, &[usize])] = match level % TOTAL_LEVELS {
+        0 => &[
+            (SequencerRow::SynthNote(6), &[0]),
+            (SequencerRow::Kick, &[3, 11, 19, 27]),
+        ],
+        1 => &[
+            (SequencerRow::SynthNote(6), &[0]),
+            (SequencerRow::Kick, &[2, 6, 10, 14, 18, 22, 26, 30]),
+        ],
+        2 => &[
+            (SequencerRow::SynthNote(6), &[0]),
+            (SequencerRow::Kick, &[6, 22]),
+        ],
+        3 => &[
+            (SequencerRow::SynthNote(6), &[0]),
+            (SequencerRow::Kick, &[5, 21]),
+            (SequencerRow::Snare, &[7, 23]),
+        ],
+        _ => unreachable!(),
+    };
+
+    let mut sequence = Sequence::new();
+    for (row, steps) in notes {
+        for &step in *steps {
+            sequence.add_note(*row, step);
+        }
+    }
+    sequence
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{
+        difficulty::DifficultyCurve,
+        spawn::level::GameMode,
+        validation::{SimulatedLayout, SimulatedLevel, Simulation},
+    };
+
+    use super::*;
+
+    #[test]
+    fn opponent_clears_every_campaign_level() {
+        for level in 0..TOTAL_LEVELS {
+            let mut simulation = Simulation::new(SimulatedLevel::plain(SimulatedLayout::Built {
+                game_mode: GameMode::Campaign,
+                level,
+                difficulty_curve: DifficultyCurve::default(),
+            }));
+            simulation.start(&opponent_sequence_for(level));
+            let run = loop {
+                if let Some(run) = simulation.step() {
+                    break run;
+                }
+            };
+
+            assert!(
+                run.cleared,
+                "the opponent doesn't get through level {level}"
+            );
+        }
+    }
+}
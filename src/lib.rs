@@ -1,29 +1,36 @@
 #[cfg(feature = "dev")]
 mod dev_tools;
-mod game;
+pub mod game;
+#[cfg(target_family = "wasm")]
+mod panic_overlay;
+mod persistence;
 mod screen;
 mod ui;
 
+use std::time::Duration;
+
 use bevy::{
     asset::AssetMetaCheck,
     audio::{AudioPlugin, Volume},
     prelude::*,
+    render::view::RenderLayers,
+    time::TimeUpdateStrategy,
     window::WindowResolution,
 };
 
+use game::{
+    assets::{HandleMap, ImageKey},
+    spawn::level::SpawnLevel,
+};
+use persistence::NoPersistence;
+
+/// How much time passes each time a headless app is updated
+pub const HEADLESS_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
 pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
-        // Order new `AppStep` variants by adding them here:
-        app.configure_sets(
-            Update,
-            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
-        );
-
-        // Spawn the main camera.
-        app.add_systems(Startup, spawn_camera);
-
         // Add Bevy plugins.
         app.add_plugins(
             DefaultPlugins
@@ -54,8 +61,40 @@ impl Plugin for AppPlugin {
                 }),
         );
 
+        // Bevy's log plugin replaces the panic hook, so this has to come after it.
+        #[cfg(target_family = "wasm")]
+        panic_overlay::install();
+
         // Add other plugins.
-        app.add_plugins((game::plugin, screen::plugin, ui::plugin));
+        app.add_plugins((CoreLogicPlugin, PresentationPlugin));
+    }
+}
+
+/// The rules of the game: the sequence, movement, and levels.
+/// This runs without a window, rendering, or audio, so runs can be simulated and checked on their own.
+pub struct CoreLogicPlugin;
+
+impl Plugin for CoreLogicPlugin {
+    fn build(&self, app: &mut App) {
+        // Order new `AppStep` variants by adding them here:
+        app.configure_sets(
+            Update,
+            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
+        );
+
+        app.add_plugins(game::logic_plugin);
+    }
+}
+
+/// Everything the player sees and hears on top of [`CoreLogicPlugin`]: the UI, screens, audio, and animation.
+pub struct PresentationPlugin;
+
+impl Plugin for PresentationPlugin {
+    fn build(&self, app: &mut App) {
+        // Spawn the main camera.
+        app.add_systems(Startup, spawn_camera);
+
+        app.add_plugins((game::presentation_plugin, screen::plugin, ui::plugin));
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
@@ -63,9 +102,46 @@ impl Plugin for AppPlugin {
     }
 }
 
+/// Builds the game with only [`CoreLogicPlugin`], so runs can be simulated by integration tests.
+/// The level is spawned on the first update, time moves forward by [`HEADLESS_FRAME_TIME`] every update,
+/// and nothing the player has saved is loaded or overwritten.
+pub fn build_headless_app() -> App {
+    persistence::disable();
+
+    let mut app = build_simulation_app();
+    app.add_systems(Startup, spawn_headless_level);
+
+    app
+}
+
+/// Builds an app with [`CoreLogicPlugin`] and only as much of Bevy as it needs to run,
+/// so runs can be simulated alongside the game itself. Time moves forward by [`HEADLESS_FRAME_TIME`] every update.
+/// Nothing is drawn, so the level's images aren't loaded, and nothing that happens in the simulated runs is saved.
+pub fn build_simulation_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            watch_for_changes_override: Some(false),
+            ..default()
+        },
+    ));
+    app.init_asset::<TextureAtlasLayout>();
+    app.insert_resource(HandleMap::<ImageKey>::placeholders());
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_FRAME_TIME));
+    app.insert_resource(NoPersistence);
+    app.add_plugins(CoreLogicPlugin);
+
+    app
+}
+
+fn spawn_headless_level(mut commands: Commands) {
+    commands.trigger(SpawnLevel);
+}
+
 /// High-level groupings of systems for the app in the `Update` schedule.
 /// When adding a new variant, make sure to order it in the `configure_sets`
-/// call above.
+/// call in [`CoreLogicPlugin`].
 #[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
 enum AppSet {
     /// Tick timers.
@@ -79,7 +155,16 @@ enum AppSet {
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         Name::new("Camera"),
-        Camera2dBundle::default(),
+        // the level is drawn by its own camera, so this one only draws the UI on top of it
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::none(),
         // Render all UI to this camera.
         // Not strictly necessary since we only use one camera,
         // but if we don't use this component, our UI will disappear as soon
@@ -0,0 +1,73 @@
+//! Covering the game with an error message when it panics on web builds, since otherwise the canvas just freezes
+//! and the player is left wondering whether it's still loading.
+
+use std::panic::{self, PanicHookInfo};
+
+use web_sys::Document;
+
+/// The ID of the element holding the error message, which `style.css` styles
+const OVERLAY_ID: &str = "panic-overlay";
+
+/// Shows the error overlay whenever the game panics, after doing whatever was already set up to happen on a panic.
+/// This needs to happen after Bevy's log plugin is added, since that sets up logging panics to the browser console.
+pub fn install() {
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous_hook(info);
+        show_overlay(info);
+    }));
+}
+
+fn show_overlay(info: &PanicHookInfo) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+
+    // only the first panic is worth showing, since anything after it is probably caused by it
+    if document.get_element_by_id(OVERLAY_ID).is_some() {
+        return;
+    }
+
+    let message = match info.payload().downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match info.payload().downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown error".to_string(),
+        },
+    };
+    let location = info
+        .location()
+        .map(|location| format!(" ({location})"))
+        .unwrap_or_default();
+
+    if build_overlay(&document, &format!("{message}{location}")).is_none() {
+        web_sys::console::error_1(&"could not show the error overlay".into());
+    }
+}
+
+/// Adds the overlay to the page, with the provided message and a button to reload the page.
+/// Returns `None` if any of its elements couldn't be created.
+fn build_overlay(document: &Document, message: &str) -> Option<()> {
+    let overlay = document.create_element("div").ok()?;
+    overlay.set_id(OVERLAY_ID);
+
+    let header = document.create_element("h1").ok()?;
+    header.set_text_content(Some("LoopRunner crashed"));
+    overlay.append_child(&header).ok()?;
+
+    let details = document.create_element("pre").ok()?;
+    details.set_text_content(Some(message));
+    overlay.append_child(&details).ok()?;
+
+    let restart_button = document.create_element("button").ok()?;
+    restart_button.set_text_content(Some("Restart"));
+    // the sequence is autosaved, so reloading loses at most the last couple seconds of it
+    restart_button
+        .set_attribute("onclick", "window.location.reload()")
+        .ok()?;
+    overlay.append_child(&restart_button).ok()?;
+
+    document.body()?.append_child(&overlay).ok()?;
+
+    Some(())
+}
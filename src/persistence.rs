@@ -0,0 +1,170 @@
+//! Saving and loading data that should stick around between sessions.
+//! On native builds, data is stored as RON files in the user's data directory.
+//! On web builds, data is stored as RON strings in the browser's local storage.
+//! Files the player asks to keep, like exported audio, are downloaded by the browser on web builds.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use bevy::{asset::ron, ecs::system::SystemParam, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Whether anything is actually loaded from or saved to storage
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The keys of saved values that were found but couldn't be read, which haven't been told to the player yet
+static UNREADABLE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Stops anything from being loaded or saved for the rest of the session,
+/// so simulated runs start from scratch and don't touch the player's save data.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Keeps anything from being saved by the app it's in, without affecting any other apps in the same process.
+/// Simulated runs alongside the game use this, since [`disable`] would stop the game itself from saving too.
+#[derive(Resource, Debug, Default)]
+pub struct NoPersistence;
+
+/// Saves values on behalf of systems, unless the app they're running in has [`NoPersistence`].
+#[derive(SystemParam)]
+pub struct Saver<'w> {
+    no_persistence: Option<Res<'w, NoPersistence>>,
+}
+
+impl Saver<'_> {
+    /// Saves the provided value under the provided key, overwriting any previous value, unless this app doesn't save anything.
+    pub fn save<T: Serialize>(&self, key: &str, value: &T) {
+        if self.no_persistence.is_none() {
+            save(key, value);
+        }
+    }
+}
+
+/// Loads the value saved under the provided key, if there is one.
+/// Returns `None` if nothing was saved or the saved value couldn't be read.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let serialized = read(key)?;
+    match ron::from_str(&serialized) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("could not parse saved {key}: {e}");
+            if let Ok(mut unreadable) = UNREADABLE.lock() {
+                unreadable.push(key.to_string());
+            }
+            None
+        }
+    }
+}
+
+/// Takes the keys of every saved value that couldn't be read since the last time this was called.
+/// Those values will be overwritten with fresh ones the next time they're saved.
+pub fn take_unreadable() -> Vec<String> {
+    UNREADABLE
+        .lock()
+        .map(|mut unreadable| std::mem::take(&mut *unreadable))
+        .unwrap_or_default()
+}
+
+/// Saves the provided value under the provided key, overwriting any previous value.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let serialized = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(x) => x,
+        Err(e) => {
+            warn!("could not serialize {key}: {e}");
+            return;
+        }
+    };
+
+    write(key, &serialized);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_path(key: &str) -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_default()
+        .join("looprunner")
+        .join(format!("{key}.ron"))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(save_path(key)).ok()
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write(key: &str, serialized: &str) {
+    let path = save_path(key);
+    if let Some(dir) = path.parent() {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            warn!("could not create save directory {}: {e}", dir.display());
+            return;
+        }
+    }
+
+    if let Err(e) = std::fs::write(&path, serialized) {
+        warn!("could not save {key} to {}: {e}", path.display());
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_family = "wasm")]
+fn storage_key(key: &str) -> String {
+    format!("looprunner.{key}")
+}
+
+#[cfg(target_family = "wasm")]
+fn read(key: &str) -> Option<String> {
+    local_storage()?.get_item(&storage_key(key)).ok()?
+}
+
+#[cfg(target_family = "wasm")]
+fn write(key: &str, serialized: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("could not save {key}: local storage is unavailable");
+        return;
+    };
+
+    if storage.set_item(&storage_key(key), serialized).is_err() {
+        warn!("could not save {key} to local storage");
+    }
+}
+
+/// Has the browser download the provided bytes as a file.
+/// Returns `None` if the download couldn't be started.
+#[cfg(target_family = "wasm")]
+pub fn download(bytes: &[u8], file_name: &str, mime_type: &str) -> Option<()> {
+    use web_sys::{js_sys, wasm_bindgen::JsCast};
+
+    let document = web_sys::window()?.document()?;
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options).ok()?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).ok()?;
+
+    let anchor = document
+        .create_element("a")
+        .ok()?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .ok()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url).ok()
+}
@@ -0,0 +1,112 @@
+//! A screen for picking how the runner looks, from the skins the player has unlocked.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        localization::Localization,
+        progress::Progress,
+        settings::Settings,
+        skins::Skin,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Cosmetics), enter_cosmetics);
+    // shows which skin is being worn as soon as it's picked
+    app.add_systems(
+        Update,
+        (despawn_cosmetics, enter_cosmetics)
+            .chain()
+            .run_if(in_state(Screen::Cosmetics).and_then(resource_changed::<Settings>)),
+    );
+
+    app.register_type::<CosmeticsAction>();
+    app.add_systems(
+        Update,
+        handle_cosmetics_action.run_if(in_state(Screen::Cosmetics)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CosmeticsAction {
+    Wear(Skin),
+    Back,
+}
+
+/// Holds everything on the cosmetics screen
+#[derive(Component, Debug)]
+struct CosmeticsRoot;
+
+fn enter_cosmetics(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    settings: Res<Settings>,
+    progress: Res<Progress>,
+    localization: Res<Localization>,
+) {
+    commands
+        .ui_root()
+        .insert((CosmeticsRoot, StateScoped(Screen::Cosmetics)))
+        .with_children(|children| {
+            children.header(localization.get("cosmetics.header"), &font_handles);
+
+            for skin in Skin::ALL {
+                // locked ones are still listed, so the player knows how to get them
+                if !progress.has_skin(skin) {
+                    children.label(
+                        localization.format(
+                            "cosmetics.locked",
+                            &[
+                                ("skin", &skin.name(&localization)),
+                                ("hint", &skin.unlock_hint(&localization)),
+                            ],
+                        ),
+                        &font_handles,
+                    );
+                    continue;
+                }
+
+                let text = if skin == settings.skin {
+                    localization.format("cosmetics.worn", &[("skin", &skin.name(&localization))])
+                } else {
+                    skin.name(&localization)
+                };
+                children
+                    .button(text, &font_handles)
+                    .insert(CosmeticsAction::Wear(skin));
+            }
+
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(CosmeticsAction::Back);
+        });
+}
+
+fn despawn_cosmetics(root_query: Query<Entity, With<CosmeticsRoot>>, mut commands: Commands) {
+    for entity in &root_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn handle_cosmetics_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&CosmeticsAction>,
+    mut settings: ResMut<Settings>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            CosmeticsAction::Wear(skin) => {
+                if settings.skin != *skin {
+                    settings.skin = *skin;
+                }
+            }
+            CosmeticsAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
@@ -1,25 +1,34 @@
 //! A credits screen that can be accessed from the title screen.
 
-use bevy::prelude::*;
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
 
 use super::Screen;
 use crate::{
     game::{
-        assets::{FontKey, HandleMap},
+        assets::{CreditsKey, FontKey, HandleMap, SoundtrackKey},
         audio::soundtrack::PlaySoundtrack,
+        credits::Credits,
+        localization::Localization,
     },
     ui::prelude::*,
 };
 
+/// How far a single mouse wheel line scrolls the credits, in pixels
+const SCROLL_LINE_HEIGHT: f32 = 30.0;
+
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Credits), enter_credits);
     app.add_systems(OnExit(Screen::Credits), exit_credits);
 
     app.add_systems(
         Update,
-        handle_credits_action.run_if(in_state(Screen::Credits)),
+        (handle_credits_action, scroll_credits).run_if(in_state(Screen::Credits)),
     );
     app.register_type::<CreditsAction>();
+    app.register_type::<CreditsScroll>();
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
@@ -28,21 +37,90 @@ enum CreditsAction {
     Back,
 }
 
-fn enter_credits(mut commands: Commands, font_handles: Res<HandleMap<FontKey>>) {
+/// The scrolling list of credits, and how far it has scrolled.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+struct CreditsScroll {
+    offset: f32,
+}
+
+fn enter_credits(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    credits_handles: Res<HandleMap<CreditsKey>>,
+    credits_assets: Res<Assets<Credits>>,
+    localization: Res<Localization>,
+) {
+    let credits = credits_assets.get(&credits_handles.get(CreditsKey::Credits));
+
     commands
         .ui_root()
         .insert(StateScoped(Screen::Credits))
         .with_children(|children| {
-            children.header("Tools Used", &font_handles);
-            children.label("GIMP", &font_handles);
-            children.label("Pixilart.com", &font_handles);
-            children.label("Bfxr", &font_handles);
-            children.label("Audacity", &font_handles);
+            children
+                .spawn((
+                    Name::new("Credits viewport"),
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(800.0),
+                            height: Val::Percent(70.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            overflow: Overflow::clip_y(),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .spawn((
+                            Name::new("Credits list"),
+                            CreditsScroll::default(),
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    row_gap: Val::Px(10.0),
+                                    position_type: PositionType::Relative,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                        ))
+                        .with_children(|children| {
+                            let Some(credits) = credits else {
+                                children
+                                    .label(localization.get("credits.unavailable"), &font_handles);
+                                return;
+                            };
+
+                            for section in &credits.sections {
+                                children.header(&section.title, &font_handles);
+                                for entry in &section.entries {
+                                    let text = match &entry.role {
+                                        Some(role) => format!("{} - {role}", entry.name),
+                                        None => entry.name.clone(),
+                                    };
+                                    children.label(text, &font_handles);
+                                    if let Some(license) = &entry.license {
+                                        children.label(license, &font_handles);
+                                    }
+                                    if let Some(link) = &entry.link {
+                                        children.label(link, &font_handles);
+                                    }
+                                }
+                            }
+                        });
+                });
 
             children
-                .button("Back", &font_handles)
+                .button(localization.get("common.back"), &font_handles)
                 .insert(CreditsAction::Back);
         });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Credits));
 }
 
 fn exit_credits(mut commands: Commands) {
@@ -51,13 +129,52 @@ fn exit_credits(mut commands: Commands) {
 
 fn handle_credits_action(
     mut next_screen: ResMut<NextState<Screen>>,
-    mut button_query: InteractionQuery<&CreditsAction>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&CreditsAction>,
 ) {
-    for (interaction, action) in &mut button_query {
-        if matches!(interaction, Interaction::Pressed) {
-            match action {
-                CreditsAction::Back => next_screen.set(Screen::Title),
-            }
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            CreditsAction::Back => next_screen.set(Screen::Title),
         }
     }
 }
+
+/// Scrolls the credits in time with the soundtrack, and with the mouse wheel.
+/// Once everything has scrolled out of view, the credits start over from the bottom.
+fn scroll_credits(
+    time: Res<Time>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    credits_handles: Res<HandleMap<CreditsKey>>,
+    credits_assets: Res<Assets<Credits>>,
+    mut scroll_query: Query<(&mut CreditsScroll, &mut Style, &Node, &Parent)>,
+    viewport_query: Query<&Node>,
+) {
+    let Some(credits) = credits_assets.get(&credits_handles.get(CreditsKey::Credits)) else {
+        return;
+    };
+
+    let wheel_scroll: f32 = mouse_wheel_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => -event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => -event.y,
+        })
+        .sum();
+
+    for (mut scroll, mut style, node, parent) in &mut scroll_query {
+        let Ok(viewport) = viewport_query.get(parent.get()) else {
+            continue;
+        };
+        let viewport_height = viewport.size().y;
+        let content_height = node.size().y;
+
+        scroll.offset += (credits.scroll_speed() * time.delta_seconds()) + wheel_scroll;
+        if scroll.offset > content_height {
+            scroll.offset = -viewport_height;
+        } else if scroll.offset < -viewport_height {
+            scroll.offset = content_height;
+        }
+
+        style.top = Val::Px(-scroll.offset);
+    }
+}
@@ -0,0 +1,224 @@
+//! A screen listing the custom levels that were found, and why any of them can't be played or might not be possible to clear.
+//! Level files can also be dropped onto the window to add them to the list.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        level_definition::{CustomLevels, LevelError},
+        localization::Localization,
+        spawn::level::{CurrentLevel, GameMode, StartingLevel},
+        validation::{LevelValidations, ValidationStatus},
+    },
+    ui::{
+        palette::{ERROR_TEXT, LABEL_TEXT},
+        prelude::*,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::CustomLevels), enter_custom_levels);
+
+    app.register_type::<CustomLevelsAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_custom_levels_action,
+            read_dropped_levels,
+            update_level_list,
+        )
+            .chain()
+            .run_if(in_state(Screen::CustomLevels)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CustomLevelsAction {
+    Play,
+    Back,
+}
+
+/// Holds an entry for each custom level, which are respawned whenever the levels change
+#[derive(Component)]
+struct LevelList;
+
+fn enter_custom_levels(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    #[cfg(not(target_family = "wasm"))] mut custom_levels: ResMut<CustomLevels>,
+) {
+    // pick up any levels that were saved or added to the folder since the last time
+    #[cfg(not(target_family = "wasm"))]
+    custom_levels.scan();
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::CustomLevels))
+        .with_children(|children| {
+            children.header(localization.get("custom_levels.header"), &font_handles);
+            #[cfg(not(target_family = "wasm"))]
+            let hint = localization.format(
+                "custom_levels.hint",
+                &[(
+                    "dir",
+                    &crate::game::level_definition::custom_levels_dir().display(),
+                )],
+            );
+            #[cfg(target_family = "wasm")]
+            let hint = localization.get("custom_levels.hint_web");
+            children.label(hint, &font_handles).insert(Style {
+                width: Val::Auto,
+                ..default()
+            });
+            children.spawn((
+                Name::new("Level list"),
+                LevelList,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+            children
+                .button(localization.get("custom_levels.play"), &font_handles)
+                .insert(CustomLevelsAction::Play);
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(CustomLevelsAction::Back);
+        });
+}
+
+fn handle_custom_levels_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&CustomLevelsAction>,
+    custom_levels: Res<CustomLevels>,
+    mut game_mode: ResMut<GameMode>,
+    mut starting_level: ResMut<StartingLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            CustomLevelsAction::Play => {
+                if custom_levels.playable().next().is_none() {
+                    continue;
+                }
+
+                *game_mode = GameMode::Custom;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(Screen::Playing);
+            }
+            CustomLevelsAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
+
+/// Adds level files that are dragged onto the window to the custom levels
+fn read_dropped_levels(
+    mut drop_events: EventReader<FileDragAndDrop>,
+    mut custom_levels: ResMut<CustomLevels>,
+) {
+    for event in drop_events.read() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            custom_levels.read(path_buf);
+        }
+    }
+}
+
+fn update_level_list(
+    custom_levels: Res<CustomLevels>,
+    validations: Res<LevelValidations>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    list_query: Query<Entity, With<LevelList>>,
+    added_list_query: Query<(), Added<LevelList>>,
+    mut commands: Commands,
+) {
+    if !custom_levels.is_changed()
+        && !validations.is_changed()
+        && !localization.is_changed()
+        && added_list_query.is_empty()
+    {
+        return;
+    }
+
+    for entity in &list_query {
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|children| {
+                if custom_levels.0.is_empty() {
+                    children
+                        .label(localization.get("custom_levels.none"), &font_handles)
+                        .insert(Style {
+                            width: Val::Auto,
+                            ..default()
+                        });
+                }
+
+                for level in &custom_levels.0 {
+                    let (text, color) = match &level.definition {
+                        Ok(_) => match validations.status(&level.name) {
+                            Some(ValidationStatus::Checking(_)) => (
+                                localization
+                                    .format("custom_levels.checking", &[("name", &level.name)]),
+                                LABEL_TEXT,
+                            ),
+                            Some(ValidationStatus::Unclearable) => (
+                                localization
+                                    .format("custom_levels.unclearable", &[("name", &level.name)]),
+                                ERROR_TEXT,
+                            ),
+                            Some(ValidationStatus::Clearable(_)) | None => {
+                                (level.name.clone(), LABEL_TEXT)
+                            }
+                        },
+                        Err(e) => (
+                            format!("{}: {}", level.name, describe_level_error(e, &localization)),
+                            ERROR_TEXT,
+                        ),
+                    };
+                    children.spawn((
+                        Name::new("Custom level"),
+                        TextBundle::from_section(
+                            text,
+                            TextStyle {
+                                font: font_handles.get(FontKey::General),
+                                font_size: 24.0,
+                                color,
+                            },
+                        )
+                        .with_style(Style {
+                            max_width: Val::Px(900.0),
+                            ..default()
+                        }),
+                    ));
+                }
+            });
+    }
+}
+
+/// Describes what's wrong with a level file in the player's language
+fn describe_level_error(error: &LevelError, localization: &Localization) -> String {
+    match error {
+        LevelError::Io(e) => localization.format("level_error.io", &[("error", e)]),
+        LevelError::Ron(e) => localization.format("level_error.ron", &[("error", e)]),
+        LevelError::OutOfBounds(cell) => localization.format(
+            "level_error.out_of_bounds",
+            &[("column", &cell.column), ("row", &cell.row)],
+        ),
+        LevelError::Overlapping(cell) => localization.format(
+            "level_error.overlapping",
+            &[("column", &cell.column), ("row", &cell.row)],
+        ),
+    }
+}
@@ -0,0 +1,409 @@
+//! A level editor, where obstacles can be dragged onto a level, tried out, and saved to a file.
+
+use bevy::{asset::ron, prelude::*, window::PrimaryWindow};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap, ImageKey},
+        camera::WorldCamera,
+        level_definition::{
+            EditorLevel, GridCell, LevelDefinition, ObstacleKind, ObstaclePlacement,
+        },
+        localization::Localization,
+        spawn::level::{
+            CurrentLevel, GameMode, StartingLevel, FLOOR_HEIGHT, FLOOR_Y, IMAGE_SCALE, LEVEL_WIDTH,
+        },
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Editor), enter_editor);
+    app.insert_resource(Dragging(None));
+
+    app.register_type::<EditorAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_editor_action,
+            pick_up_obstacle,
+            drop_obstacle,
+            move_placement_ghost,
+            spawn_editor_obstacles,
+        )
+            .chain()
+            .run_if(in_state(Screen::Editor)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum EditorAction {
+    /// Starts dragging a new obstacle onto the level
+    Drag(ObstacleKind),
+    Test,
+    Save,
+    Clear,
+    Back,
+}
+
+/// The obstacle being dragged around, if any
+#[derive(Resource, Debug)]
+struct Dragging(Option<ObstacleKind>);
+
+/// An obstacle that's been placed in the level being edited
+#[derive(Component)]
+struct EditorObstacle;
+
+/// Shows where the obstacle being dragged will go when it's dropped
+#[derive(Component)]
+struct PlacementGhost;
+
+/// Tells the player what happened when they saved
+#[derive(Component)]
+struct EditorStatusText;
+
+fn enter_editor(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    editor_level: Res<EditorLevel>,
+) {
+    commands
+        .spawn((
+            Name::new("Editor UI Root"),
+            StateScoped(Screen::Editor),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    top: Val::Px(5.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(5.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(5.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    for (key, action) in [
+                        ("editor.box", EditorAction::Drag(ObstacleKind::Box)),
+                        ("editor.spikes", EditorAction::Drag(ObstacleKind::Spikes)),
+                        ("editor.test", EditorAction::Test),
+                        ("editor.save", EditorAction::Save),
+                        ("editor.clear", EditorAction::Clear),
+                        ("common.back", EditorAction::Back),
+                    ] {
+                        let mut button =
+                            children.small_button(localization.get(key), &font_handles);
+                        button.insert((
+                            action,
+                            Style {
+                                width: Val::Px(100.0),
+                                height: Val::Px(35.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                        ));
+                        // obstacles are dragged out of these, so they have to start the drag as soon as they're pressed
+                        if matches!(action, EditorAction::Drag(_)) {
+                            button.insert(ActivateOnPress);
+                        }
+                    }
+                });
+
+            children
+                .label(localization.get("editor.hint"), &font_handles)
+                .insert((
+                    EditorStatusText,
+                    Style {
+                        width: Val::Auto,
+                        ..default()
+                    },
+                ));
+        });
+
+    commands.spawn((
+        Name::new("Editor background"),
+        StateScoped(Screen::Editor),
+        SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.0)),
+            sprite: Sprite {
+                color: editor_level.0.background_color(),
+                custom_size: Some(Vec2::new(LEVEL_WIDTH, LEVEL_WIDTH)),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        Name::new("Editor floor"),
+        StateScoped(Screen::Editor),
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(LEVEL_WIDTH, FLOOR_HEIGHT)),
+                color: Color::BLACK,
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(0.0, FLOOR_Y, 0.0)),
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        Name::new("Placement ghost"),
+        PlacementGhost,
+        StateScoped(Screen::Editor),
+        SpriteBundle {
+            visibility: Visibility::Hidden,
+            sprite: Sprite {
+                color: Color::srgba(1.0, 1.0, 1.0, 0.5),
+                ..default()
+            },
+            transform: Transform::from_scale(Vec2::splat(IMAGE_SCALE).extend(1.0)),
+            ..default()
+        },
+    ));
+}
+
+fn handle_editor_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&EditorAction>,
+    mut dragging: ResMut<Dragging>,
+    mut editor_level: ResMut<EditorLevel>,
+    mut game_mode: ResMut<GameMode>,
+    mut starting_level: ResMut<StartingLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut status_query: Query<&Children, With<EditorStatusText>>,
+    mut text_query: Query<&mut Text>,
+    localization: Res<Localization>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            EditorAction::Drag(kind) => dragging.0 = Some(*kind),
+            EditorAction::Test => {
+                *game_mode = GameMode::EditorTest;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(Screen::Playing);
+            }
+            EditorAction::Save => {
+                let status = save_level(&editor_level.0, &localization);
+                for children in &mut status_query {
+                    let mut texts = text_query.iter_many_mut(children);
+                    while let Some(mut text) = texts.fetch_next() {
+                        text.sections[0].value.clone_from(&status);
+                    }
+                }
+            }
+            EditorAction::Clear => editor_level.0.obstacles.clear(),
+            EditorAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
+
+/// Gets where the cursor is in the world, if it's in the window
+fn cursor_world_position(
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+) -> Option<Vec2> {
+    let cursor = window_query.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+    // the camera only covers part of the window when the window is wider than the level
+    let viewport_min = camera
+        .logical_viewport_rect()
+        .map_or(Vec2::ZERO, |viewport| viewport.min);
+    camera.viewport_to_world_2d(camera_transform, cursor - viewport_min)
+}
+
+/// Picks up a placed obstacle so it can be moved, or removes it if it's right-clicked
+fn pick_up_obstacle(
+    mouse: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+    button_query: Query<&Interaction, With<Button>>,
+    mut dragging: ResMut<Dragging>,
+    mut editor_level: ResMut<EditorLevel>,
+) {
+    let left = mouse.just_pressed(MouseButton::Left);
+    let right = mouse.just_pressed(MouseButton::Right);
+    if (!left && !right) || dragging.0.is_some() {
+        return;
+    }
+
+    // clicks on the buttons shouldn't reach the level behind them
+    if button_query
+        .iter()
+        .any(|interaction| !matches!(interaction, Interaction::None))
+    {
+        return;
+    }
+
+    let Some(cell) = cursor_world_position(&window_query, &camera_query).and_then(GridCell::at)
+    else {
+        return;
+    };
+    if editor_level.0.obstacle_at(cell).is_none() {
+        return;
+    }
+
+    let removed = editor_level.0.remove(cell);
+    if left {
+        dragging.0 = removed.map(|obstacle| obstacle.kind);
+    }
+}
+
+/// Places the obstacle being dragged in the cell under the cursor, or drops it if it's outside the level
+fn drop_obstacle(
+    mouse: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+    mut dragging: ResMut<Dragging>,
+    mut editor_level: ResMut<EditorLevel>,
+) {
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(kind) = dragging.0.take() else {
+        return;
+    };
+
+    if let Some(cell) = cursor_world_position(&window_query, &camera_query).and_then(GridCell::at) {
+        editor_level.0.place(ObstaclePlacement { kind, cell });
+    }
+}
+
+/// Snaps the ghost of the obstacle being dragged to the cell under the cursor
+fn move_placement_ghost(
+    dragging: Res<Dragging>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+    mut ghost_query: Query<
+        (&mut Transform, &mut Handle<Image>, &mut Visibility),
+        With<PlacementGhost>,
+    >,
+) {
+    let cell = cursor_world_position(&window_query, &camera_query).and_then(GridCell::at);
+    for (mut transform, mut texture, mut visibility) in &mut ghost_query {
+        let (Some(kind), Some(cell)) = (dragging.0, cell) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Visible;
+        *texture = image_handles.get(obstacle_image(kind));
+        transform.translation = cell.center().extend(0.5);
+    }
+}
+
+/// Respawns the sprites for the placed obstacles whenever the level changes
+fn spawn_editor_obstacles(
+    editor_level: Res<EditorLevel>,
+    image_handles: Res<HandleMap<ImageKey>>,
+    obstacle_query: Query<Entity, With<EditorObstacle>>,
+    added_ghost_query: Query<(), Added<PlacementGhost>>,
+    mut commands: Commands,
+) {
+    // the ghost is spawned when the editor is opened, so this also spawns the obstacles of a level that's already been started
+    if !editor_level.is_changed() && added_ghost_query.is_empty() {
+        return;
+    }
+
+    for entity in &obstacle_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for obstacle in &editor_level.0.obstacles {
+        commands.spawn((
+            Name::new("Editor obstacle"),
+            EditorObstacle,
+            StateScoped(Screen::Editor),
+            SpriteBundle {
+                texture: image_handles.get(obstacle_image(obstacle.kind)),
+                transform: Transform::from_scale(Vec2::splat(IMAGE_SCALE).extend(1.0))
+                    .with_translation(obstacle.cell.center().extend(0.0)),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn obstacle_image(kind: ObstacleKind) -> ImageKey {
+    match kind {
+        ObstacleKind::Box => ImageKey::Box,
+        ObstacleKind::Spikes => ImageKey::Spikes,
+    }
+}
+
+/// Saves the level as a RON file, and returns a message saying how it went
+fn save_level(level: &LevelDefinition, localization: &Localization) -> String {
+    let serialized = match ron::ser::to_string_pretty(level, ron::ser::PrettyConfig::default()) {
+        Ok(x) => x,
+        Err(e) => {
+            warn!("could not serialize level: {e}");
+            return localization.get("editor.save_failed");
+        }
+    };
+
+    write_level(&serialized, localization)
+}
+
+/// Writes the level to the custom levels folder in the assets folder
+#[cfg(not(target_family = "wasm"))]
+fn write_level(serialized: &str, localization: &Localization) -> String {
+    use crate::game::level_definition::{custom_levels_dir, LEVEL_EXTENSION};
+
+    let dir = custom_levels_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("could not create level directory {}: {e}", dir.display());
+        return localization.get("editor.save_failed");
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = dir.join(format!("level-{timestamp}.{LEVEL_EXTENSION}"));
+    match std::fs::write(&path, serialized) {
+        Ok(()) => {
+            info!("saved level to {}", path.display());
+            localization.format("editor.saved", &[("path", &path.display())])
+        }
+        Err(e) => {
+            warn!("could not save level to {}: {e}", path.display());
+            localization.get("editor.save_failed")
+        }
+    }
+}
+
+/// Has the browser download the level
+#[cfg(target_family = "wasm")]
+fn write_level(serialized: &str, localization: &Localization) -> String {
+    use crate::game::level_definition::LEVEL_EXTENSION;
+
+    let file_name = format!("looprunner-level.{LEVEL_EXTENSION}");
+    match crate::persistence::download(serialized.as_bytes(), &file_name, "text/plain") {
+        Some(()) => localization.format("editor.downloaded", &[("file", &file_name)]),
+        None => {
+            warn!("could not save level: the browser wouldn't start the download");
+            localization.get("editor.save_failed")
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! A screen telling the player about something that went wrong but that the game can keep going after,
+//! like a save file that couldn't be read.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        localization::Localization,
+    },
+    persistence,
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<UnseenErrors>();
+    app.observe(report_error);
+    app.add_systems(Update, report_unreadable_saves);
+
+    app.add_systems(OnEnter(Screen::Error), enter_error);
+
+    app.register_type::<ErrorAction>();
+    app.add_systems(Update, handle_error_action.run_if(in_state(Screen::Error)));
+}
+
+/// Something that went wrong that the player should know about, even though the game can keep going
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoverableError {
+    /// The value saved under the contained key couldn't be read, so it was replaced with a fresh one
+    UnreadableSave(String),
+}
+
+impl RecoverableError {
+    /// Describes what went wrong in the player's language
+    fn message(&self, localization: &Localization) -> String {
+        match self {
+            RecoverableError::UnreadableSave(key) => {
+                localization.format("error.unreadable_save", &[("name", &key.replace('_', " "))])
+            }
+        }
+    }
+}
+
+/// Trigger this to show the player the error screen.
+/// If the game is still starting up, the screen is shown once it's done loading instead.
+#[derive(Event, Debug)]
+pub struct ReportError(pub RecoverableError);
+
+/// Errors that have been reported but not shown on the error screen yet
+#[derive(Resource, Debug, Default)]
+pub(super) struct UnseenErrors(Vec<RecoverableError>);
+
+impl UnseenErrors {
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ErrorAction {
+    Continue,
+}
+
+fn report_error(
+    trigger: Trigger<ReportError>,
+    mut unseen_errors: ResMut<UnseenErrors>,
+    screen: Res<State<Screen>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let error = &trigger.event().0;
+    warn!("reported error: {error:?}");
+    unseen_errors.0.push(error.clone());
+
+    // the loading screen checks for errors before moving on, so it doesn't need to be interrupted
+    if !matches!(screen.get(), Screen::Splash | Screen::Loading) {
+        next_screen.set(Screen::Error);
+    }
+}
+
+/// Reports any saved values that couldn't be read since the last time this ran
+fn report_unreadable_saves(mut commands: Commands) {
+    for key in persistence::take_unreadable() {
+        commands.trigger(ReportError(RecoverableError::UnreadableSave(key)));
+    }
+}
+
+fn enter_error(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    unseen_errors: Res<UnseenErrors>,
+    localization: Res<Localization>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Error))
+        .with_children(|children| {
+            children.header(localization.get("error.header"), &font_handles);
+            for error in &unseen_errors.0 {
+                children.label(error.message(&localization), &font_handles);
+            }
+            children
+                .button(localization.get("error.continue"), &font_handles)
+                .insert(ErrorAction::Continue);
+        });
+}
+
+fn handle_error_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&ErrorAction>,
+    mut unseen_errors: ResMut<UnseenErrors>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            ErrorAction::Continue => {
+                unseen_errors.0.clear();
+                next_screen.set(Screen::Title);
+            }
+        }
+    }
+}
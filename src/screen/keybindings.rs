@@ -0,0 +1,293 @@
+//! A screen for changing which keys and gamepad buttons do what, accessed from the settings screen.
+
+use bevy::{input::InputSystem, prelude::*, ui::UiSystem};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        input::{BindingKind, InputAction, InputBinding, InputMap},
+        localization::Localization,
+    },
+    ui::{
+        palette::{ERROR_TEXT, NODE_BACKGROUND},
+        prelude::*,
+    },
+};
+
+/// How wide the buttons showing what an action is bound to are, in pixels
+const BINDING_BUTTON_WIDTH: f32 = 180.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PendingRebind>();
+    app.add_systems(OnEnter(Screen::Keybindings), enter_keybindings);
+    app.add_systems(OnExit(Screen::Keybindings), cancel_rebind);
+
+    app.register_type::<KeybindingsAction>();
+    // before focus is moved around, so the key that's picked doesn't also move focus or press a button
+    app.add_systems(
+        PreUpdate,
+        capture_rebind
+            .after(InputSystem)
+            .before(UiSystem::Focus)
+            .run_if(in_state(Screen::Keybindings)),
+    );
+    app.add_systems(
+        Update,
+        (handle_keybindings_action, update_binding_buttons)
+            .chain()
+            .run_if(in_state(Screen::Keybindings)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum KeybindingsAction {
+    Reset,
+    Back,
+}
+
+/// A button showing what an action is bound to, which can be pressed to change it
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct BindingButton {
+    action: InputAction,
+    kind: BindingKind,
+}
+
+/// The text listing any keys or buttons that more than one action is bound to
+#[derive(Component, Debug)]
+struct ConflictText;
+
+/// The binding that's waiting for the player to press a new key or button, if any
+#[derive(Resource, Debug, Default)]
+struct PendingRebind(Option<BindingButton>);
+
+fn enter_keybindings(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Keybindings))
+        .with_children(|children| {
+            children.header(localization.get("keybindings.header"), &font_handles);
+            children
+                .label(localization.get("keybindings.hint"), &font_handles)
+                .insert(Style {
+                    width: Val::Auto,
+                    ..default()
+                });
+            children
+                .spawn((
+                    Name::new("Bindings"),
+                    NodeBundle {
+                        style: Style {
+                            display: Display::Grid,
+                            grid_template_columns: vec![GridTrack::auto(); 3],
+                            column_gap: Val::Px(20.0),
+                            row_gap: Val::Px(4.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.spawn(NodeBundle::default());
+                    for key in ["keybindings.keyboard", "keybindings.gamepad"] {
+                        children.label(localization.get(key), &font_handles);
+                    }
+
+                    for action in InputAction::ALL.into_iter().filter(|a| a.is_available()) {
+                        children
+                            .label(localization.get(action.localization_key()), &font_handles)
+                            .insert(Style {
+                                width: Val::Px(220.0),
+                                justify_content: JustifyContent::End,
+                                ..default()
+                            });
+                        for kind in [BindingKind::Key, BindingKind::Gamepad] {
+                            // the text is filled in once the buttons are spawned
+                            children
+                                .small_button("", &font_handles)
+                                .insert(Style {
+                                    width: Val::Px(BINDING_BUTTON_WIDTH),
+                                    height: Val::Px(35.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                })
+                                .insert(BindingButton { action, kind });
+                        }
+                    }
+                });
+            children.spawn((
+                Name::new("Conflicts"),
+                ConflictText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 24.0,
+                        color: ERROR_TEXT,
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            ));
+            children
+                .spawn((
+                    Name::new("Keybindings buttons"),
+                    NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .button(localization.get("keybindings.reset"), &font_handles)
+                        .insert(KeybindingsAction::Reset);
+                    children
+                        .button(localization.get("common.back"), &font_handles)
+                        .insert(KeybindingsAction::Back);
+                });
+        });
+}
+
+fn handle_keybindings_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&KeybindingsAction>,
+    binding_button_query: Query<&BindingButton>,
+    mut input_map: ResMut<InputMap>,
+    mut pending: ResMut<PendingRebind>,
+) {
+    let activated = activated_events
+        .read()
+        .map(|event| event.0)
+        .collect::<Vec<_>>();
+
+    for action in action_query.iter_many(&activated) {
+        match action {
+            KeybindingsAction::Reset => {
+                *input_map = InputMap::default();
+                pending.0 = None;
+            }
+            KeybindingsAction::Back => next_screen.set(Screen::Settings),
+        }
+    }
+
+    for button in binding_button_query.iter_many(&activated) {
+        // pressing the button that's waiting again stops waiting
+        pending.0 = if pending.0 == Some(*button) {
+            None
+        } else {
+            Some(*button)
+        };
+    }
+}
+
+/// Binds the action that's waiting to the next key or button pressed.
+/// Backspace and delete take the binding off instead, and escape stops waiting for a gamepad button.
+fn capture_rebind(
+    mut pending: ResMut<PendingRebind>,
+    mut input_map: ResMut<InputMap>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut gamepad_buttons: ResMut<ButtonInput<GamepadButton>>,
+) {
+    let Some(BindingButton { action, kind }) = pending.0 else {
+        return;
+    };
+
+    let Some(key) = keys.get_just_pressed().next().copied() else {
+        let button = gamepad_buttons.get_just_pressed().next().copied();
+        if let (Some(button), BindingKind::Gamepad) = (button, kind) {
+            gamepad_buttons.clear_just_pressed(button);
+            input_map.bind(action, InputBinding::Gamepad(button.button_type));
+            pending.0 = None;
+        }
+        return;
+    };
+
+    // so nothing else sees the key that was picked
+    keys.clear_just_pressed(key);
+    match (key, kind) {
+        (KeyCode::Backspace | KeyCode::Delete, _) => input_map.unbind(action, kind),
+        (_, BindingKind::Key) => input_map.bind(action, InputBinding::Key(key)),
+        (KeyCode::Escape, BindingKind::Gamepad) => (),
+        // other keys don't mean anything when picking a gamepad button
+        (_, BindingKind::Gamepad) => return,
+    }
+    pending.0 = None;
+}
+
+/// Shows what each action is bound to, and points out any keys or buttons bound to more than one action
+fn update_binding_buttons(
+    input_map: Res<InputMap>,
+    pending: Res<PendingRebind>,
+    localization: Res<Localization>,
+    mut button_query: Query<(&BindingButton, &Children, &mut InteractionPalette)>,
+    added_button_query: Query<(), Added<BindingButton>>,
+    mut text_query: Query<&mut Text, Without<ConflictText>>,
+    mut conflict_text_query: Query<&mut Text, With<ConflictText>>,
+) {
+    if !input_map.is_changed() && !pending.is_changed() && added_button_query.is_empty() {
+        return;
+    }
+
+    let conflicts = input_map.conflicts();
+
+    for (button, children, mut palette) in &mut button_query {
+        let binding = input_map.binding(button.action, button.kind);
+        let text = if pending.0 == Some(*button) {
+            localization.get("keybindings.waiting")
+        } else {
+            binding.map_or_else(
+                || localization.get("keybindings.unbound"),
+                InputBinding::label,
+            )
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut child_text) = texts.fetch_next() {
+            child_text.sections[0].value.clone_from(&text);
+        }
+
+        let conflicting = binding.is_some_and(|binding| {
+            conflicts
+                .iter()
+                .any(|(conflict, actions)| *conflict == binding && actions.contains(&button.action))
+        });
+        palette.none = if conflicting {
+            ERROR_TEXT
+        } else {
+            NODE_BACKGROUND
+        };
+    }
+
+    let conflict_lines = conflicts
+        .iter()
+        .map(|(binding, actions)| {
+            let names = actions
+                .iter()
+                .map(|action| localization.get(action.localization_key()))
+                .collect::<Vec<_>>();
+            localization.format(
+                "keybindings.conflict",
+                &[
+                    ("actions", &names.join(", ")),
+                    ("binding", &binding.label()),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+    for mut text in &mut conflict_text_query {
+        text.sections[0].value = conflict_lines.join("\n");
+    }
+}
+
+fn cancel_rebind(mut pending: ResMut<PendingRebind>) {
+    pending.0 = None;
+}
@@ -0,0 +1,159 @@
+//! A screen for picking which campaign level to start on, showing the star rating earned on each.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        localization::Localization,
+        progress::{Progress, MAX_STARS},
+        spawn::level::{CurrentLevel, GameMode, StartingLevel, TOTAL_LEVELS},
+    },
+    ui::{
+        palette::{EARNED_STAR, UNEARNED_STAR},
+        prelude::*,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelSelect), enter_level_select);
+
+    app.register_type::<LevelSelectAction>();
+    app.add_systems(
+        Update,
+        handle_level_select_action.run_if(in_state(Screen::LevelSelect)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelSelectAction {
+    Play(u32),
+    Back,
+}
+
+fn enter_level_select(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    progress: Res<Progress>,
+    localization: Res<Localization>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelSelect))
+        .with_children(|children| {
+            children.header(localization.get("level_select.header"), &font_handles);
+            children
+                .spawn((
+                    Name::new("Level list"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    for level in 0..TOTAL_LEVELS {
+                        spawn_level_card(
+                            children,
+                            level,
+                            progress.stars(level),
+                            &font_handles,
+                            &localization,
+                        );
+                    }
+                });
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(LevelSelectAction::Back);
+        });
+}
+
+fn spawn_level_card(
+    parent: &mut ChildBuilder,
+    level: u32,
+    stars: u8,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    parent
+        .spawn((
+            Name::new("Level card"),
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children
+                .button(
+                    localization.format("level_select.level", &[("level", &(level + 1))]),
+                    font_handles,
+                )
+                .insert(LevelSelectAction::Play(level));
+            children
+                .spawn((
+                    Name::new("Stars"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(6.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    for i in 0..MAX_STARS {
+                        let color = if i < stars {
+                            EARNED_STAR
+                        } else {
+                            UNEARNED_STAR
+                        };
+                        children.spawn((
+                            Name::new("Star"),
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(20.0),
+                                    height: Val::Px(20.0),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(color),
+                                border_radius: BorderRadius::all(Val::Px(10.0)),
+                                ..default()
+                            },
+                        ));
+                    }
+                });
+        });
+}
+
+fn handle_level_select_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&LevelSelectAction>,
+    mut game_mode: ResMut<GameMode>,
+    mut starting_level: ResMut<StartingLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            LevelSelectAction::Play(level) => {
+                *game_mode = GameMode::Campaign;
+                starting_level.0 = *level;
+                current_level.0 = *level;
+                next_screen.set(Screen::Playing);
+            }
+            LevelSelectAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
@@ -1,42 +1,129 @@
 //! A loading screen during which game assets are loaded.
 //! This reduces stuttering, especially for audio on WASM.
 
+use std::time::Duration;
+
 use bevy::prelude::*;
 
-use super::Screen;
+use super::{error::UnseenErrors, Screen};
 use crate::{
-    game::assets::{FontKey, HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::{
+        assets::{
+            CreditsKey, DayCycleKey, FontKey, HandleMap, ImageKey, JudgementsKey, SampleBank,
+            SfxKey, SoundtrackKey, ThemeKey,
+        },
+        audio::sfx::{all_sfx_started, WarmUpSfx},
+        localization::{Language, Localization},
+    },
     ui::prelude::*,
 };
 
+/// How long to wait for the sound effects to warm up before continuing without them,
+/// in case there's nothing to play them on
+const AUDIO_WARM_UP_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Loading), enter_loading);
     app.add_systems(
         Update,
-        continue_to_title.run_if(in_state(Screen::Loading).and_then(all_assets_loaded)),
+        (
+            update_loading_text.run_if(resource_changed::<Localization>),
+            (
+                start_audio_warm_up
+                    .run_if(all_assets_loaded.and_then(not(resource_exists::<AudioWarmUp>))),
+                continue_to_title.run_if(
+                    resource_exists::<AudioWarmUp>
+                        .and_then(audio_warm_up_timed_out.or_else(all_sfx_started)),
+                ),
+            )
+                .chain(),
+        )
+            .run_if(in_state(Screen::Loading)),
     );
+    app.add_systems(OnExit(Screen::Loading), end_audio_warm_up);
 }
 
-fn enter_loading(mut commands: Commands, font_handles: Res<HandleMap<FontKey>>) {
+/// When the sound effects started warming up, relative to app startup.
+/// This is only around once all the assets have loaded.
+#[derive(Resource, Debug)]
+struct AudioWarmUp(Duration);
+
+/// The text saying the game is loading
+#[derive(Component)]
+struct LoadingText;
+
+fn enter_loading(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Loading))
         .with_children(|children| {
-            children.label("Loading...", &font_handles);
+            children
+                .label(localization.get("loading.label"), &font_handles)
+                .insert(LoadingText);
         });
 }
 
-fn all_assets_loaded(
+/// Puts the text in the player's language once the language files have loaded
+fn update_loading_text(
+    localization: Res<Localization>,
+    label_query: Query<&Children, With<LoadingText>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for children in &label_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = localization.get("loading.label");
+        }
+    }
+}
+
+pub(super) fn all_assets_loaded(
     asset_server: Res<AssetServer>,
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
+    sample_bank: Res<SampleBank>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    credits_handles: Res<HandleMap<CreditsKey>>,
+    judgements_handles: Res<HandleMap<JudgementsKey>>,
+    locale_handles: Res<HandleMap<Language>>,
+    theme_handles: Res<HandleMap<ThemeKey>>,
+    day_cycle_handles: Res<HandleMap<DayCycleKey>>,
 ) -> bool {
     image_handles.all_loaded(&asset_server)
         && sfx_handles.all_loaded(&asset_server)
+        && sample_bank.all_loaded(&asset_server)
         && soundtrack_handles.all_loaded(&asset_server)
+        && credits_handles.all_loaded(&asset_server)
+        && judgements_handles.all_loaded(&asset_server)
+        && locale_handles.all_loaded(&asset_server)
+        && theme_handles.all_loaded(&asset_server)
+        && day_cycle_handles.all_loaded(&asset_server)
+}
+
+/// Plays every sound effect silently, so the first beat of a run doesn't hitch while its sounds decode
+fn start_audio_warm_up(time: Res<Time>, mut commands: Commands) {
+    commands.trigger(WarmUpSfx);
+    commands.insert_resource(AudioWarmUp(time.elapsed()));
+}
+
+/// Whether it's been long enough since the sound effects started warming up to stop waiting for them
+fn audio_warm_up_timed_out(time: Res<Time>, warm_up: Res<AudioWarmUp>) -> bool {
+    time.elapsed() - warm_up.0 >= AUDIO_WARM_UP_TIMEOUT
+}
+
+fn end_audio_warm_up(mut commands: Commands) {
+    commands.remove_resource::<AudioWarmUp>();
 }
 
-fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
+/// Goes on to the title screen, unless something went wrong while starting up that the player should hear about first
+fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>, unseen_errors: Res<UnseenErrors>) {
+    if unseen_errors.is_empty() {
+        next_screen.set(Screen::Title);
+    } else {
+        next_screen.set(Screen::Error);
+    }
 }
@@ -1,9 +1,21 @@
 //! The game's main screen states and transitions between them.
 
+mod cosmetics;
 mod credits;
+mod custom_levels;
+mod editor;
+mod error;
+mod keybindings;
+mod level_select;
 mod loading;
+mod mutators;
+mod pattern_library;
 mod playing;
+mod settings;
+mod splash;
+mod stats;
 mod title;
+mod transition;
 
 use bevy::prelude::*;
 
@@ -12,10 +24,21 @@ pub(super) fn plugin(app: &mut App) {
     app.enable_state_scoped_entities::<Screen>();
 
     app.add_plugins((
+        splash::plugin,
         loading::plugin,
         title::plugin,
+        cosmetics::plugin,
         credits::plugin,
+        custom_levels::plugin,
+        editor::plugin,
+        error::plugin,
+        level_select::plugin,
+        mutators::plugin,
+        pattern_library::plugin,
+        (settings::plugin, keybindings::plugin),
+        stats::plugin,
         playing::plugin,
+        transition::plugin,
     ));
 }
 
@@ -23,8 +46,19 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(States, Debug, Hash, PartialEq, Eq, Clone, Default)]
 pub enum Screen {
     #[default]
+    Splash,
     Loading,
     Title,
     Credits,
+    LevelSelect,
+    CustomLevels,
+    PatternLibrary,
+    Settings,
+    Keybindings,
+    Stats,
+    Cosmetics,
+    Editor,
+    Mutators,
     Playing,
+    Error,
 }
@@ -0,0 +1,144 @@
+//! A screen for picking which mutators to play with, shown before a run once any of them have been unlocked.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        localization::Localization,
+        mutators::{Mutator, Mutators},
+        progress::Progress,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Mutators), enter_mutators);
+
+    app.register_type::<MutatorsAction>();
+    app.observe(apply_mutator_toggle);
+    app.add_systems(
+        Update,
+        (
+            handle_mutators_action,
+            update_multiplier_text.run_if(resource_changed::<Mutators>),
+        )
+            .run_if(in_state(Screen::Mutators)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum MutatorsAction {
+    Start,
+    Back,
+}
+
+/// The toggle for turning a mutator on or off
+#[derive(Component, Debug)]
+struct MutatorToggle(Mutator);
+
+/// The label showing what the score will be multiplied by
+#[derive(Component, Debug)]
+struct MultiplierText;
+
+fn enter_mutators(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    mutators: Res<Mutators>,
+    progress: Res<Progress>,
+    localization: Res<Localization>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Mutators))
+        .with_children(|children| {
+            children.header(localization.get("mutators.header"), &font_handles);
+
+            for mutator in Mutator::ALL {
+                // locked ones are still listed, so the player knows what there is to work towards
+                if !mutator.is_unlocked(&progress) {
+                    children.label(
+                        localization.format(
+                            "mutators.locked",
+                            &[
+                                ("mutator", &mutator.name(&localization)),
+                                ("stars", &mutator.stars_to_unlock()),
+                            ],
+                        ),
+                        &font_handles,
+                    );
+                    continue;
+                }
+
+                children
+                    .toggle(
+                        mutator.name(&localization),
+                        mutators.contains(mutator),
+                        &font_handles,
+                    )
+                    .insert(MutatorToggle(mutator));
+            }
+
+            children
+                .label(multiplier_text(&mutators, &localization), &font_handles)
+                .insert(MultiplierText);
+            children
+                .button(localization.get("mutators.start"), &font_handles)
+                .insert(MutatorsAction::Start);
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(MutatorsAction::Back);
+        });
+}
+
+fn multiplier_text(mutators: &Mutators, localization: &Localization) -> String {
+    localization.format(
+        "mutators.multiplier",
+        &[("multiplier", &format!("{:.2}", mutators.score_multiplier()))],
+    )
+}
+
+fn apply_mutator_toggle(
+    trigger: Trigger<ValueChanged<bool>>,
+    toggle_query: Query<&MutatorToggle>,
+    mut mutators: ResMut<Mutators>,
+) {
+    let Ok(MutatorToggle(mutator)) = toggle_query.get(trigger.entity()) else {
+        return;
+    };
+
+    if trigger.event().0 {
+        mutators.0.insert(*mutator);
+    } else {
+        mutators.0.remove(mutator);
+    }
+}
+
+fn update_multiplier_text(
+    mutators: Res<Mutators>,
+    localization: Res<Localization>,
+    label_query: Query<&Children, With<MultiplierText>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for children in &label_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = multiplier_text(&mutators, &localization);
+        }
+    }
+}
+
+fn handle_mutators_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&MutatorsAction>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            MutatorsAction::Start => next_screen.set(Screen::Playing),
+            MutatorsAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
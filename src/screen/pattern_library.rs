@@ -0,0 +1,372 @@
+//! A screen listing the patterns the player has saved, with a tiny picture of each one's grid.
+//! Patterns can be loaded into the sequencer for the next run, renamed, or deleted from here.
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        localization::Localization,
+        pattern_library::{pattern_thumbnail, PatternLibrary, MAX_PATTERN_NAME_LENGTH},
+        spawn::sequencer::Sequence,
+    },
+    ui::{
+        banner::Banners,
+        focus::UiFocus,
+        palette::{BeatPalette, LABEL_TEXT},
+        prelude::*,
+    },
+};
+
+/// How many times bigger pattern thumbnails are drawn than one pixel per beat
+const THUMBNAIL_SCALE: f32 = 4.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RenamingPattern>();
+    app.add_systems(OnEnter(Screen::PatternLibrary), enter_pattern_library);
+
+    app.register_type::<PatternLibraryAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_pattern_library_action,
+            type_pattern_name,
+            update_pattern_list,
+            update_pattern_names,
+        )
+            .chain()
+            .run_if(in_state(Screen::PatternLibrary)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum PatternLibraryAction {
+    Load(usize),
+    Rename(usize),
+    Delete(usize),
+    Back,
+}
+
+/// Holds an entry for each saved pattern, which are respawned whenever patterns are saved or deleted
+#[derive(Component)]
+struct PatternList;
+
+/// An entry in the pattern list
+#[derive(Component)]
+struct PatternEntry;
+
+/// The text showing the name of the pattern at this index in the library
+#[derive(Component)]
+struct PatternName(usize);
+
+/// The pattern whose name is being typed in, if any
+#[derive(Resource, Debug, Default)]
+struct RenamingPattern(Option<usize>);
+
+fn enter_pattern_library(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut renaming: ResMut<RenamingPattern>,
+) {
+    renaming.0 = None;
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::PatternLibrary))
+        .with_children(|children| {
+            children.header(localization.get("pattern_library.header"), &font_handles);
+            children.spawn((
+                Name::new("Pattern list"),
+                PatternList,
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Start,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(PatternLibraryAction::Back);
+        });
+}
+
+fn handle_pattern_library_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<(Entity, &PatternLibraryAction)>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    mut library: ResMut<PatternLibrary>,
+    mut sequence: ResMut<Sequence>,
+    mut renaming: ResMut<RenamingPattern>,
+    mut focus: ResMut<UiFocus>,
+    mut commands: Commands,
+) {
+    let activated = activated_events.read().map(|event| event.0);
+    for (entity, action) in action_query.iter_many(activated) {
+        match *action {
+            PatternLibraryAction::Load(index) => {
+                let Some(pattern) = library.0.get(index) else {
+                    continue;
+                };
+                *sequence = pattern.sequence.clone();
+                commands.banner(
+                    localization.format("pattern_library.loaded", &[("name", &pattern.name)]),
+                    &font_handles,
+                );
+            }
+            PatternLibraryAction::Rename(index) => {
+                if renaming.0 == Some(index) {
+                    renaming.0 = None;
+                } else {
+                    renaming.0 = Some(index);
+                    // so pressing enter presses this button again, finishing the name
+                    focus.0 = Some(entity);
+                }
+            }
+            PatternLibraryAction::Delete(index) => {
+                if index < library.0.len() {
+                    library.0.remove(index);
+                }
+                renaming.0 = None;
+            }
+            PatternLibraryAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
+
+/// Types into the name of the pattern being renamed
+fn type_pattern_name(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    renaming: Res<RenamingPattern>,
+    mut library: ResMut<PatternLibrary>,
+) {
+    let keys = keyboard_events
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .map(|event| &event.logical_key)
+        .collect::<Vec<_>>();
+    // the library is saved whenever it changes, so it's only touched when there's something to type
+    if keys.is_empty() {
+        return;
+    }
+    let Some(pattern) = renaming.0.and_then(|index| library.0.get_mut(index)) else {
+        return;
+    };
+
+    for key in keys {
+        match key {
+            Key::Backspace => {
+                pattern.name.pop();
+            }
+            Key::Space => pattern.name.push(' '),
+            Key::Character(text) => pattern
+                .name
+                .extend(text.chars().filter(|c| !c.is_control())),
+            _ => (),
+        }
+        if pattern.name.chars().count() > MAX_PATTERN_NAME_LENGTH {
+            pattern.name = pattern.name.chars().take(MAX_PATTERN_NAME_LENGTH).collect();
+        }
+    }
+}
+
+fn update_pattern_list(
+    library: Res<PatternLibrary>,
+    beat_palette: Res<BeatPalette>,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+    list_query: Query<Entity, With<PatternList>>,
+    added_list_query: Query<(), Added<PatternList>>,
+    entry_query: Query<(), With<PatternEntry>>,
+    mut images: ResMut<Assets<Image>>,
+    mut commands: Commands,
+) {
+    // renaming a pattern only changes its name, which is kept up to date on its own
+    if entry_query.iter().count() == library.0.len()
+        && !localization.is_changed()
+        && added_list_query.is_empty()
+    {
+        return;
+    }
+
+    for entity in &list_query {
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|children| {
+                if library.0.is_empty() {
+                    children
+                        .label(localization.get("pattern_library.none"), &font_handles)
+                        .insert(Style {
+                            width: Val::Auto,
+                            ..default()
+                        });
+                }
+
+                for (index, pattern) in library.0.iter().enumerate() {
+                    let thumbnail = pattern_thumbnail(&pattern.sequence, &beat_palette);
+                    let size = thumbnail.size_f32() * THUMBNAIL_SCALE;
+                    let thumbnail = images.add(thumbnail);
+                    spawn_pattern_entry(
+                        children,
+                        index,
+                        &pattern.saved_date(),
+                        thumbnail,
+                        size,
+                        &font_handles,
+                        &localization,
+                    );
+                }
+            });
+    }
+}
+
+fn spawn_pattern_entry(
+    parent: &mut ChildBuilder,
+    index: usize,
+    date: &str,
+    thumbnail: Handle<Image>,
+    thumbnail_size: Vec2,
+    font_handles: &HandleMap<FontKey>,
+    localization: &Localization,
+) {
+    let button_style = Style {
+        width: Val::Px(130.0),
+        height: Val::Px(35.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    parent
+        .spawn((
+            Name::new("Pattern entry"),
+            PatternEntry,
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Pattern thumbnail"),
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(thumbnail_size.x),
+                        height: Val::Px(thumbnail_size.y),
+                        ..default()
+                    },
+                    image: UiImage::new(thumbnail),
+                    ..default()
+                },
+            ));
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|children| {
+                    children.spawn((
+                        Name::new("Pattern name"),
+                        PatternName(index),
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: font_handles.get(FontKey::General),
+                                font_size: 24.0,
+                                color: LABEL_TEXT,
+                            },
+                        ),
+                    ));
+                    children.spawn((
+                        Name::new("Pattern date"),
+                        TextBundle::from_section(
+                            localization.format("pattern_library.saved_on", &[("date", &date)]),
+                            TextStyle {
+                                font: font_handles.get(FontKey::General),
+                                font_size: 16.0,
+                                color: LABEL_TEXT,
+                            },
+                        ),
+                    ));
+                });
+            children
+                .small_button(localization.get("pattern_library.load"), font_handles)
+                .insert((PatternLibraryAction::Load(index), button_style.clone()));
+            children
+                .small_button(localization.get("pattern_library.rename"), font_handles)
+                .insert((PatternLibraryAction::Rename(index), button_style.clone()));
+            children
+                .small_button(localization.get("pattern_library.delete"), font_handles)
+                .insert((PatternLibraryAction::Delete(index), button_style));
+        });
+}
+
+/// Shows each pattern's name, with a cursor at the end of the one being renamed,
+/// and has the rename button of the one being renamed finish it instead
+fn update_pattern_names(
+    library: Res<PatternLibrary>,
+    renaming: Res<RenamingPattern>,
+    localization: Res<Localization>,
+    mut name_query: Query<(Ref<PatternName>, &mut Text)>,
+    button_query: Query<(&PatternLibraryAction, &Children)>,
+    mut text_query: Query<&mut Text, Without<PatternName>>,
+) {
+    let changed = library.is_changed() || renaming.is_changed();
+    for (name, mut text) in &mut name_query {
+        if !changed && !name.is_added() {
+            continue;
+        }
+        let Some(pattern) = library.0.get(name.0) else {
+            continue;
+        };
+
+        text.sections[0].value = if renaming.0 == Some(name.0) {
+            format!("{}_", pattern.name)
+        } else {
+            pattern.name.clone()
+        };
+    }
+
+    if !renaming.is_changed() {
+        return;
+    }
+    for (action, children) in &button_query {
+        let PatternLibraryAction::Rename(index) = *action else {
+            continue;
+        };
+
+        let label = if renaming.0 == Some(index) {
+            localization.get("pattern_library.done")
+        } else {
+            localization.get("pattern_library.rename")
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
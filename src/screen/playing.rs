@@ -1,9 +1,13 @@
 //! The screen state for the main game loop.
 
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 
 use super::Screen;
-use crate::game::{audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel};
+use crate::game::{
+    audio::soundtrack::PlaySoundtrack,
+    input::{action_just_pressed, InputAction},
+    spawn::level::{GameMode, SpawnLevel},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Playing), enter_playing);
@@ -11,8 +15,8 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        return_to_title_screen
-            .run_if(in_state(Screen::Playing).and_then(input_just_pressed(KeyCode::Escape))),
+        leave_playing
+            .run_if(in_state(Screen::Playing).and_then(action_just_pressed(InputAction::LeaveRun))),
     );
 }
 
@@ -25,6 +29,11 @@ fn exit_playing(mut commands: Commands) {
     commands.trigger(PlaySoundtrack::Disable);
 }
 
-fn return_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
+/// Goes back to the title screen, or back to the level editor if a level from it is being tried out
+fn leave_playing(game_mode: Res<GameMode>, mut next_screen: ResMut<NextState<Screen>>) {
+    if *game_mode == GameMode::EditorTest {
+        next_screen.set(Screen::Editor);
+    } else {
+        next_screen.set(Screen::Title);
+    }
 }
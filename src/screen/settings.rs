@@ -0,0 +1,340 @@
+//! A settings screen that can be accessed from the title screen.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        audio::sfx::SoundBank,
+        localization::{Language, Localization},
+        onboarding::Onboarding,
+        progress::Progress,
+        settings::Settings,
+        telemetry::{ClearTelemetry, TelemetryLog},
+    },
+    ui::prelude::*,
+};
+
+/// The UI scales the player can pick from
+const UI_SCALES: [f32; 4] = [0.75, 1.0, 1.25, 1.5];
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), enter_settings);
+    // shows everything in the new language as soon as it's picked
+    app.add_systems(
+        Update,
+        (despawn_settings, enter_settings)
+            .chain()
+            .run_if(in_state(Screen::Settings).and_then(resource_changed::<Localization>)),
+    );
+
+    app.register_type::<SettingsAction>();
+    app.observe(apply_setting_toggle);
+    app.observe(send_dropdown_value::<f32>);
+    app.observe(send_dropdown_value::<Language>);
+    app.observe(send_dropdown_value::<SoundBank>);
+    app.observe(apply_ui_scale);
+    app.observe(apply_language);
+    app.observe(apply_sound_bank);
+    app.add_systems(
+        Update,
+        handle_settings_action.run_if(in_state(Screen::Settings)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SettingsAction {
+    Keybindings,
+    ResetHints,
+    ClearTelemetry,
+    Back,
+}
+
+/// Holds everything on the settings screen
+#[derive(Component, Debug)]
+struct SettingsRoot;
+
+/// The dropdown for picking the UI scale
+#[derive(Component, Debug)]
+struct UiScaleDropdown;
+
+/// The dropdown for picking the language
+#[derive(Component, Debug)]
+struct LanguageDropdown;
+
+/// The dropdown for picking the sound bank
+#[derive(Component, Debug)]
+struct SoundBankDropdown;
+
+/// A setting that's turned on or off with a toggle
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingToggle {
+    ReducedFlashing,
+    ColorblindPalette,
+    ReducedMotion,
+    CameraZoom,
+    PreviewOnHover,
+    AutoRestart,
+    BeatMarkers,
+    ResumeCountIn,
+    Telemetry,
+    ReducedGravity,
+    SlowHazards,
+    ExtraHealth,
+    Invincible,
+}
+
+impl SettingToggle {
+    fn value(self, settings: &Settings) -> bool {
+        match self {
+            SettingToggle::ReducedFlashing => settings.reduced_flashing,
+            SettingToggle::ColorblindPalette => settings.colorblind_palette,
+            SettingToggle::ReducedMotion => settings.reduced_motion,
+            SettingToggle::CameraZoom => settings.camera_zoom,
+            SettingToggle::PreviewOnHover => settings.preview_on_hover,
+            SettingToggle::AutoRestart => settings.auto_restart,
+            SettingToggle::BeatMarkers => settings.beat_markers,
+            SettingToggle::ResumeCountIn => settings.resume_count_in,
+            SettingToggle::Telemetry => settings.telemetry,
+            SettingToggle::ReducedGravity => settings.assists.reduced_gravity,
+            SettingToggle::SlowHazards => settings.assists.slow_hazards,
+            SettingToggle::ExtraHealth => settings.assists.extra_health,
+            SettingToggle::Invincible => settings.assists.invincible,
+        }
+    }
+
+    fn value_mut(self, settings: &mut Settings) -> &mut bool {
+        match self {
+            SettingToggle::ReducedFlashing => &mut settings.reduced_flashing,
+            SettingToggle::ColorblindPalette => &mut settings.colorblind_palette,
+            SettingToggle::ReducedMotion => &mut settings.reduced_motion,
+            SettingToggle::CameraZoom => &mut settings.camera_zoom,
+            SettingToggle::PreviewOnHover => &mut settings.preview_on_hover,
+            SettingToggle::AutoRestart => &mut settings.auto_restart,
+            SettingToggle::BeatMarkers => &mut settings.beat_markers,
+            SettingToggle::ResumeCountIn => &mut settings.resume_count_in,
+            SettingToggle::Telemetry => &mut settings.telemetry,
+            SettingToggle::ReducedGravity => &mut settings.assists.reduced_gravity,
+            SettingToggle::SlowHazards => &mut settings.assists.slow_hazards,
+            SettingToggle::ExtraHealth => &mut settings.assists.extra_health,
+            SettingToggle::Invincible => &mut settings.assists.invincible,
+        }
+    }
+}
+
+fn enter_settings(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    settings: Res<Settings>,
+    progress: Res<Progress>,
+    onboarding: Res<Onboarding>,
+    telemetry_log: Res<TelemetryLog>,
+    localization: Res<Localization>,
+) {
+    commands
+        .ui_root()
+        .insert((SettingsRoot, StateScoped(Screen::Settings)))
+        .with_children(|children| {
+            children.header(localization.get("settings.header"), &font_handles);
+
+            for (key, setting) in [
+                ("settings.reduced_flashing", SettingToggle::ReducedFlashing),
+                (
+                    "settings.colorblind_palette",
+                    SettingToggle::ColorblindPalette,
+                ),
+                ("settings.reduced_motion", SettingToggle::ReducedMotion),
+                ("settings.camera_zoom", SettingToggle::CameraZoom),
+                ("settings.preview_on_hover", SettingToggle::PreviewOnHover),
+                ("settings.auto_restart", SettingToggle::AutoRestart),
+                ("settings.beat_markers", SettingToggle::BeatMarkers),
+                ("settings.resume_count_in", SettingToggle::ResumeCountIn),
+                ("settings.telemetry", SettingToggle::Telemetry),
+            ] {
+                children
+                    .toggle(
+                        localization.get(key),
+                        setting.value(&settings),
+                        &font_handles,
+                    )
+                    .insert(setting);
+            }
+
+            // the assists are kept together, two to a row, so they don't make the screen too tall
+            children.label(localization.get("settings.assists"), &font_handles);
+            children
+                .spawn((
+                    Name::new("Assists"),
+                    NodeBundle {
+                        style: Style {
+                            display: Display::Grid,
+                            grid_template_columns: vec![GridTrack::auto(); 2],
+                            column_gap: Val::Px(10.0),
+                            row_gap: Val::Px(4.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    for (key, setting) in [
+                        ("assists.reduced_gravity", SettingToggle::ReducedGravity),
+                        ("assists.slow_hazards", SettingToggle::SlowHazards),
+                        ("assists.extra_health", SettingToggle::ExtraHealth),
+                        ("assists.invincible", SettingToggle::Invincible),
+                    ] {
+                        children
+                            .toggle(
+                                localization.get(key),
+                                setting.value(&settings),
+                                &font_handles,
+                            )
+                            .insert(setting);
+                    }
+                });
+
+            let current_scale = UI_SCALES
+                .iter()
+                .position(|scale| *scale == settings.ui_scale)
+                .unwrap_or(1);
+            children
+                .dropdown(
+                    localization.get("settings.ui_scale"),
+                    UI_SCALES
+                        .map(|scale| format!("{}%", (scale * 100.0).round()))
+                        .to_vec(),
+                    current_scale,
+                    &font_handles,
+                )
+                .insert((UiScaleDropdown, DropdownValues(UI_SCALES.to_vec())));
+
+            let current_language = Language::ALL
+                .iter()
+                .position(|language| *language == settings.language)
+                .unwrap_or(0);
+            children
+                .dropdown(
+                    localization.get("settings.language"),
+                    Language::ALL
+                        .map(|language| language.native_name().to_string())
+                        .to_vec(),
+                    current_language,
+                    &font_handles,
+                )
+                .insert((LanguageDropdown, DropdownValues(Language::ALL.to_vec())));
+
+            // there's nothing to pick until another sound bank has been unlocked
+            let sound_banks = SoundBank::ALL
+                .into_iter()
+                .filter(|bank| progress.has_sound_bank(*bank))
+                .collect::<Vec<_>>();
+            if sound_banks.len() > 1 {
+                let current_sound_bank = sound_banks
+                    .iter()
+                    .position(|bank| *bank == settings.sound_bank)
+                    .unwrap_or(0);
+                children
+                    .dropdown(
+                        localization.get("settings.sound_bank"),
+                        sound_banks
+                            .iter()
+                            .map(|bank| bank.name(&localization))
+                            .collect(),
+                        current_sound_bank,
+                        &font_handles,
+                    )
+                    .insert((SoundBankDropdown, DropdownValues(sound_banks)));
+            }
+
+            children
+                .button(localization.get("settings.keybindings"), &font_handles)
+                .insert(SettingsAction::Keybindings);
+            // nothing to reset until the hints have been shown
+            if onboarding.seen_tutorial {
+                children
+                    .button(localization.get("settings.reset_hints"), &font_handles)
+                    .insert(SettingsAction::ResetHints);
+            }
+            // nothing to clear until something has been recorded
+            if !telemetry_log.events.is_empty() {
+                children
+                    .button(localization.get("settings.clear_telemetry"), &font_handles)
+                    .insert(SettingsAction::ClearTelemetry);
+            }
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(SettingsAction::Back);
+        });
+}
+
+fn despawn_settings(root_query: Query<Entity, With<SettingsRoot>>, mut commands: Commands) {
+    for entity in &root_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn handle_settings_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<(Entity, &SettingsAction)>,
+    mut onboarding: ResMut<Onboarding>,
+    mut commands: Commands,
+) {
+    for (entity, action) in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            SettingsAction::Keybindings => next_screen.set(Screen::Keybindings),
+            SettingsAction::ResetHints => {
+                onboarding.seen_tutorial = false;
+                // the hints will show again on the next run, so there's nothing left to reset
+                commands.entity(entity).despawn_recursive();
+            }
+            SettingsAction::ClearTelemetry => {
+                commands.trigger(ClearTelemetry);
+                commands.entity(entity).despawn_recursive();
+            }
+            SettingsAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
+
+fn apply_setting_toggle(
+    trigger: Trigger<ValueChanged<bool>>,
+    toggle_query: Query<&SettingToggle>,
+    mut settings: ResMut<Settings>,
+) {
+    if let Ok(setting) = toggle_query.get(trigger.entity()) {
+        *setting.value_mut(&mut settings) = trigger.event().0;
+    }
+}
+
+fn apply_ui_scale(
+    trigger: Trigger<SelectionChanged<f32>>,
+    dropdown_query: Query<(), With<UiScaleDropdown>>,
+    mut settings: ResMut<Settings>,
+) {
+    if dropdown_query.contains(trigger.entity()) {
+        settings.ui_scale = trigger.event().0;
+    }
+}
+
+fn apply_language(
+    trigger: Trigger<SelectionChanged<Language>>,
+    dropdown_query: Query<(), With<LanguageDropdown>>,
+    mut settings: ResMut<Settings>,
+) {
+    if dropdown_query.contains(trigger.entity()) {
+        settings.language = trigger.event().0;
+    }
+}
+
+fn apply_sound_bank(
+    trigger: Trigger<SelectionChanged<SoundBank>>,
+    dropdown_query: Query<(), With<SoundBankDropdown>>,
+    mut settings: ResMut<Settings>,
+) {
+    if dropdown_query.contains(trigger.entity()) {
+        settings.sound_bank = trigger.event().0;
+    }
+}
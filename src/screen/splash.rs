@@ -0,0 +1,127 @@
+//! A splash screen that shows the team and jam logo before the game starts.
+
+use bevy::{
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ButtonState},
+    prelude::*,
+    render::texture::{ImageLoaderSettings, ImageSampler},
+};
+
+use super::{loading::all_assets_loaded, Screen};
+use crate::ui::prelude::*;
+
+/// How long the splash screen stays up, in seconds
+const SPLASH_DURATION: f32 = 2.5;
+
+/// How long the logo takes to fade in or out, in seconds
+const SPLASH_FADE_DURATION: f32 = 0.6;
+
+const SPLASH_BACKGROUND: Color = Color::srgb(0.1, 0.1, 0.1);
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Splash), enter_splash);
+    app.add_systems(OnExit(Screen::Splash), exit_splash);
+    app.add_systems(
+        Update,
+        (
+            (skip_splash, fade_logo).chain(),
+            // skips the loading screen if everything finished loading while the logo was up
+            continue_to_title.run_if(splash_finished.and_then(all_assets_loaded)),
+            continue_to_loading.run_if(splash_finished.and_then(not(all_assets_loaded))),
+        )
+            .chain()
+            .run_if(in_state(Screen::Splash)),
+    );
+}
+
+/// Counts down until the splash screen is over
+#[derive(Resource, Debug)]
+struct SplashTimer(Timer);
+
+/// The logo on the splash screen, which fades in and out as [`SplashTimer`] counts down
+#[derive(Component)]
+struct SplashLogo;
+
+fn enter_splash(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SplashTimer(Timer::from_seconds(
+        SPLASH_DURATION,
+        TimerMode::Once,
+    )));
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(Screen::Splash),
+            BackgroundColor(SPLASH_BACKGROUND),
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Splash logo"),
+                SplashLogo,
+                ImageBundle {
+                    style: Style {
+                        width: Val::Percent(60.0),
+                        margin: UiRect::all(Val::Auto),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load_with_settings(
+                        // the logo is loaded here rather than with the rest of the assets so it can be shown while they load
+                        "images/splash.png",
+                        |settings: &mut ImageLoaderSettings| {
+                            settings.sampler = ImageSampler::nearest();
+                        },
+                    ))
+                    .with_color(Color::WHITE.with_alpha(0.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn exit_splash(mut commands: Commands) {
+    commands.remove_resource::<SplashTimer>();
+}
+
+fn fade_logo(
+    time: Res<Time>,
+    mut timer: ResMut<SplashTimer>,
+    mut logo_query: Query<&mut UiImage, With<SplashLogo>>,
+) {
+    timer.0.tick(time.delta());
+
+    let elapsed = timer.0.elapsed_secs();
+    let remaining = timer.0.remaining_secs();
+    let alpha = (elapsed.min(remaining) / SPLASH_FADE_DURATION).clamp(0.0, 1.0);
+    for mut image in &mut logo_query {
+        image.color.set_alpha(alpha);
+    }
+}
+
+/// Ends the splash screen early if any key or mouse button is pressed
+fn skip_splash(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut mouse_button_events: EventReader<MouseButtonInput>,
+    mut timer: ResMut<SplashTimer>,
+) {
+    let pressed_key = keyboard_events
+        .read()
+        .any(|event| event.state == ButtonState::Pressed);
+    let pressed_mouse_button = mouse_button_events
+        .read()
+        .any(|event| event.state == ButtonState::Pressed);
+    if pressed_key || pressed_mouse_button {
+        let duration = timer.0.duration();
+        timer.0.set_elapsed(duration);
+    }
+}
+
+/// Run conditions are checked even when the splash screen isn't up, so the timer isn't always there
+fn splash_finished(timer: Option<Res<SplashTimer>>) -> bool {
+    timer.is_some_and(|timer| timer.0.elapsed() >= timer.0.duration())
+}
+
+fn continue_to_loading(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Loading);
+}
+
+fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -0,0 +1,93 @@
+//! A screen showing stats about every run the player has ever played.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::{FontKey, HandleMap},
+        lifetime_stats::LifetimeStats,
+        localization::Localization,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Stats), enter_stats);
+
+    app.register_type::<StatsAction>();
+    app.add_systems(Update, handle_stats_action.run_if(in_state(Screen::Stats)));
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum StatsAction {
+    Back,
+}
+
+fn enter_stats(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    stats: Res<LifetimeStats>,
+    localization: Res<Localization>,
+) {
+    let favorite_row = stats.favorite_row().map_or_else(
+        || localization.get("stats.no_favorite_row"),
+        |row| row.name(&localization),
+    );
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Stats))
+        .with_children(|children| {
+            children.header(localization.get("stats.header"), &font_handles);
+            children
+                .spawn((
+                    Name::new("Lifetime stats"),
+                    NodeBundle {
+                        style: Style {
+                            display: Display::Grid,
+                            grid_template_columns: vec![GridTrack::auto(); 2],
+                            column_gap: Val::Px(120.0),
+                            row_gap: Val::Px(4.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.label(
+                        localization.format("stats.distance", &[("feet", &stats.distance_feet())]),
+                        &font_handles,
+                    );
+                    children.label(
+                        localization.format("stats.deaths", &[("deaths", &stats.deaths)]),
+                        &font_handles,
+                    );
+                    children.label(
+                        localization
+                            .format("stats.notes_placed", &[("count", &stats.notes_placed())]),
+                        &font_handles,
+                    );
+                    children.label(
+                        localization.format("stats.favorite_row", &[("row", &favorite_row)]),
+                        &font_handles,
+                    );
+                });
+            children
+                .button(localization.get("common.back"), &font_handles)
+                .insert(StatsAction::Back);
+        });
+}
+
+fn handle_stats_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&StatsAction>,
+) {
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            StatsAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
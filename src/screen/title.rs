@@ -8,6 +8,12 @@ use crate::{
     game::{
         assets::{FontKey, HandleMap, SoundtrackKey},
         audio::soundtrack::PlaySoundtrack,
+        coop::Multiplayer,
+        localization::Localization,
+        mutators::Mutator,
+        progress::Progress,
+        spawn::level::{CurrentLevel, GameMode, StartingLevel},
+        tutorial::Tutorial,
     },
     ui::prelude::*,
 };
@@ -23,13 +29,29 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Component)]
 enum TitleAction {
     Play,
+    Endless,
+    Scrolling,
+    Coop,
+    Versus,
+    Tutorial,
+    LevelSelect,
+    CustomLevels,
+    Editor,
+    Patterns,
+    Settings,
+    Stats,
+    Cosmetics,
     Credits,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
 }
 
-fn enter_title(mut commands: Commands, font_handles: Res<HandleMap<FontKey>>) {
+fn enter_title(
+    mut commands: Commands,
+    font_handles: Res<HandleMap<FontKey>>,
+    localization: Res<Localization>,
+) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Title))
@@ -61,12 +83,96 @@ fn enter_title(mut commands: Commands, font_handles: Res<HandleMap<FontKey>>) {
                     ));
                 });
             children
-                .button("Let's Jam", &font_handles)
+                .button(localization.get("title.play"), &font_handles)
                 .insert(TitleAction::Play);
+            children
+                .button(localization.get("title.endless"), &font_handles)
+                .insert(TitleAction::Endless);
+            children
+                .button(localization.get("title.scrolling"), &font_handles)
+                .insert(TitleAction::Scrolling);
+            // the two-runner modes share a row, to keep the menu from getting too tall
+            children
+                .spawn((
+                    Name::new("Two-runner modes"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .button(localization.get("title.coop"), &font_handles)
+                        .insert(TitleAction::Coop);
+                    children
+                        .button(localization.get("title.versus"), &font_handles)
+                        .insert(TitleAction::Versus);
+                });
+            children
+                .button(localization.get("title.tutorial"), &font_handles)
+                .insert(TitleAction::Tutorial);
+            children
+                .button(localization.get("title.levels"), &font_handles)
+                .insert(TitleAction::LevelSelect);
+            children
+                .button(localization.get("title.custom"), &font_handles)
+                .insert(TitleAction::CustomLevels);
+            // so do the level editor and the pattern library
+            children
+                .spawn((
+                    Name::new("Creation tools"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .button(localization.get("title.editor"), &font_handles)
+                        .insert(TitleAction::Editor);
+                    children
+                        .button(localization.get("title.patterns"), &font_handles)
+                        .insert(TitleAction::Patterns);
+                });
+            children
+                .button(localization.get("title.settings"), &font_handles)
+                .insert(TitleAction::Settings);
+            // and so do the stats and the cosmetics
+            children
+                .spawn((
+                    Name::new("Player records"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .button(localization.get("title.stats"), &font_handles)
+                        .insert(TitleAction::Stats);
+                    children
+                        .button(localization.get("title.cosmetics"), &font_handles)
+                        .insert(TitleAction::Cosmetics);
+                });
+            children
+                .button(localization.get("title.credits"), &font_handles)
+                .insert(TitleAction::Credits);
 
             #[cfg(not(target_family = "wasm"))]
             children
-                .button("Exit", &font_handles)
+                .button(localization.get("title.exit"), &font_handles)
                 .insert(TitleAction::Exit);
         });
 
@@ -75,23 +181,86 @@ fn enter_title(mut commands: Commands, font_handles: Res<HandleMap<FontKey>>) {
 
 fn handle_title_action(
     mut next_screen: ResMut<NextState<Screen>>,
-    mut button_query: InteractionQuery<&TitleAction>,
+    mut activated_events: EventReader<ButtonActivated>,
+    action_query: Query<&TitleAction>,
+    mut game_mode: ResMut<GameMode>,
+    mut starting_level: ResMut<StartingLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut tutorial: ResMut<Tutorial>,
+    mut multiplayer: ResMut<Multiplayer>,
+    progress: Res<Progress>,
     mut commands: Commands,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
 ) {
-    for (interaction, action) in &mut button_query {
-        if matches!(interaction, Interaction::Pressed) {
-            match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
-                TitleAction::Credits => next_screen.set(Screen::Credits),
-
-                #[cfg(not(target_family = "wasm"))]
-                TitleAction::Exit => {
-                    app_exit.send(AppExit::Success);
-                }
+    for action in action_query.iter_many(activated_events.read().map(|event| event.0)) {
+        match action {
+            TitleAction::Play => {
+                *game_mode = GameMode::Campaign;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(pre_run_screen(&progress));
+            }
+            TitleAction::Endless => {
+                *game_mode = GameMode::Endless;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(pre_run_screen(&progress));
+            }
+            TitleAction::Scrolling => {
+                *game_mode = GameMode::Scrolling;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(pre_run_screen(&progress));
+            }
+            TitleAction::Coop => {
+                *game_mode = GameMode::Endless;
+                *multiplayer = Multiplayer::Coop;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(Screen::Playing);
+            }
+            TitleAction::Versus => {
+                // the opponent only knows how to get through the campaign's levels
+                *game_mode = GameMode::Campaign;
+                *multiplayer = Multiplayer::Versus;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                next_screen.set(Screen::Playing);
+            }
+            TitleAction::Tutorial => {
+                *game_mode = GameMode::Tutorial;
+                starting_level.0 = 0;
+                current_level.0 = 0;
+                tutorial.step = 0;
+                next_screen.set(Screen::Playing);
             }
+            TitleAction::LevelSelect => next_screen.set(Screen::LevelSelect),
+            TitleAction::CustomLevels => next_screen.set(Screen::CustomLevels),
+            TitleAction::Editor => next_screen.set(Screen::Editor),
+            TitleAction::Patterns => next_screen.set(Screen::PatternLibrary),
+            TitleAction::Settings => next_screen.set(Screen::Settings),
+            TitleAction::Stats => next_screen.set(Screen::Stats),
+            TitleAction::Cosmetics => next_screen.set(Screen::Cosmetics),
+            TitleAction::Credits => next_screen.set(Screen::Credits),
 
-            commands.trigger(PlaySoundtrack::Disable);
+            #[cfg(not(target_family = "wasm"))]
+            TitleAction::Exit => {
+                app_exit.send(AppExit::Success);
+            }
         }
+
+        commands.trigger(PlaySoundtrack::Disable);
+    }
+}
+
+/// Gets the screen to go to before starting a single-runner run, which lets the player pick mutators if they've unlocked any
+fn pre_run_screen(progress: &Progress) -> Screen {
+    if Mutator::ALL
+        .iter()
+        .any(|mutator| mutator.is_unlocked(progress))
+    {
+        Screen::Mutators
+    } else {
+        Screen::Playing
     }
 }
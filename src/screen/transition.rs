@@ -0,0 +1,132 @@
+//! Fades to black and back whenever the screen changes.
+//! Screen changes are held back until the screen has faded out, so the switch happens out of sight.
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use super::Screen;
+use crate::game::settings::Settings;
+
+/// How long it takes to fade out or back in, in seconds
+const FADE_DURATION: f32 = 0.25;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ScreenTransition>();
+    app.add_systems(Startup, spawn_transition_overlay);
+    app.add_systems(
+        Last,
+        (hold_back_screen_changes, advance_transition, update_overlay).chain(),
+    );
+}
+
+/// How far along switching screens is
+#[derive(Resource, Debug, Default)]
+enum ScreenTransition {
+    #[default]
+    Idle,
+    /// Fading out, and then switching to the provided screen
+    Out { target: Screen, timer: Timer },
+    /// Fading back in on the new screen
+    In { timer: Timer },
+}
+
+impl ScreenTransition {
+    /// Gets how much the screen is covered up, from 0 to 1
+    fn coverage(&self) -> f32 {
+        match self {
+            ScreenTransition::Idle => 0.0,
+            ScreenTransition::Out { timer, .. } => timer.fraction(),
+            ScreenTransition::In { timer } => timer.fraction_remaining(),
+        }
+    }
+}
+
+/// Covers the screen while it's fading out or in
+#[derive(Component)]
+struct TransitionOverlay;
+
+fn spawn_transition_overlay(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Transition overlay"),
+        TransitionOverlay,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::BLACK.with_alpha(0.0)),
+            // keeps anything from being clicked on while the screen is changing
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+    ));
+}
+
+/// Takes screen changes and fades out first instead, picking up from however far it's already faded
+fn hold_back_screen_changes(
+    settings: Res<Settings>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut transition: ResMut<ScreenTransition>,
+) {
+    if settings.reduced_motion {
+        return;
+    }
+    let NextState::Pending(target) = next_screen.as_ref() else {
+        return;
+    };
+    let target = target.clone();
+    next_screen.reset();
+
+    let mut timer = Timer::from_seconds(FADE_DURATION, TimerMode::Once);
+    timer.set_elapsed(timer.duration().mul_f32(transition.coverage()));
+    *transition = ScreenTransition::Out { target, timer };
+}
+
+fn advance_transition(
+    time: Res<Time<Real>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut transition: ResMut<ScreenTransition>,
+) {
+    if matches!(*transition, ScreenTransition::Idle) {
+        return;
+    }
+
+    match transition.as_mut() {
+        ScreenTransition::Idle => (),
+        ScreenTransition::Out { target, timer } => {
+            if timer.tick(time.delta()).finished() {
+                next_screen.set(target.clone());
+                *transition = ScreenTransition::In {
+                    timer: Timer::from_seconds(FADE_DURATION, TimerMode::Once),
+                };
+            }
+        }
+        ScreenTransition::In { timer } => {
+            if timer.tick(time.delta()).finished() {
+                *transition = ScreenTransition::Idle;
+            }
+        }
+    }
+}
+
+fn update_overlay(
+    transition: Res<ScreenTransition>,
+    mut overlay_query: Query<(&mut Style, &mut BackgroundColor), With<TransitionOverlay>>,
+) {
+    if !transition.is_changed() {
+        return;
+    }
+
+    let coverage = transition.coverage();
+    for (mut style, mut background_color) in &mut overlay_query {
+        style.display = if coverage > 0.0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        background_color.0.set_alpha(coverage);
+    }
+}
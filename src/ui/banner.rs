@@ -0,0 +1,100 @@
+//! Banners that flash up across the screen to celebrate something, then fade away on their own.
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+use super::palette::{BANNER_BACKGROUND, HEADER_TEXT};
+use crate::game::assets::{FontKey, HandleMap};
+
+/// How long banners stay up, in seconds
+const BANNER_DURATION: f32 = 2.0;
+
+/// How long banners take to fade out at the end, in seconds
+const BANNER_FADE_DURATION: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, fade_banners);
+}
+
+/// A banner that fades out and despawns once its time is up.
+#[derive(Component, Debug)]
+pub struct Banner {
+    timer: Timer,
+}
+
+/// An extension trait for spawning banners.
+pub trait Banners {
+    /// Spawns a banner across the upper part of the screen
+    fn banner(
+        &mut self,
+        text: impl Into<String>,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands;
+}
+
+impl Banners for Commands<'_, '_> {
+    fn banner(
+        &mut self,
+        text: impl Into<String>,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Banner"),
+            Banner {
+                timer: Timer::from_seconds(BANNER_DURATION, TimerMode::Once),
+            },
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    top: Val::Percent(20.0),
+                    padding: UiRect::vertical(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor(BANNER_BACKGROUND),
+                ..default()
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Banner Text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 55.0,
+                        color: HEADER_TEXT,
+                    },
+                ),
+            ));
+        });
+        entity
+    }
+}
+
+fn fade_banners(
+    time: Res<Time>,
+    mut banner_query: Query<(Entity, &mut Banner, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+    mut commands: Commands,
+) {
+    for (entity, mut banner, mut background_color, children) in &mut banner_query {
+        banner.timer.tick(time.delta());
+        if banner.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let opacity = (banner.timer.remaining_secs() / BANNER_FADE_DURATION).min(1.0);
+        background_color
+            .0
+            .set_alpha(BANNER_BACKGROUND.alpha() * opacity);
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            for section in &mut text.sections {
+                section.style.color.set_alpha(opacity);
+            }
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Behavior for the toggle, slider, and dropdown widgets, which send [`ValueChanged`] or [`SelectionChanged`] to themselves
+//! when the player changes them.
+
+use std::ops::RangeInclusive;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::{
+    interaction::{Enabled, InteractionPalette},
+    palette::{
+        greyed_out, BUTTON_HOVERED_BACKGROUND, BUTTON_PRESSED_BACKGROUND, NODE_BACKGROUND,
+        SELECTED_BUTTON_BACKGROUND,
+    },
+};
+use crate::game::localization::Localization;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            (flip_toggles, update_toggle_switches).chain(),
+            (drag_sliders, update_slider_fills).chain(),
+            // navigating comes first so the enter press that expands a dropdown doesn't also pick from it
+            (
+                navigate_dropdowns,
+                toggle_dropdowns,
+                pick_dropdown_options,
+                update_dropdowns,
+            )
+                .chain(),
+        ),
+    );
+}
+
+/// Event sent to a toggle or slider when the player changes its value.
+/// Toggles send a `bool`, and sliders send an `f32`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ValueChanged<T>(pub T);
+
+/// A setting that can be turned on or off. Changing this directly updates how it looks, without sending [`ValueChanged`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Toggle(pub bool);
+
+/// The button that flips the toggle it's in
+#[derive(Component)]
+pub(super) struct ToggleSwitch;
+
+/// A value that can be picked from a range by dragging along a track.
+/// Changing this directly updates how it looks, without sending [`ValueChanged`].
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Slider {
+    pub value: f32,
+    pub range: RangeInclusive<f32>,
+}
+
+impl Slider {
+    /// Gets how far along the range the value is, from 0 to 1
+    pub(super) fn fraction(&self) -> f32 {
+        let span = self.range.end() - self.range.start();
+        if span <= 0.0 {
+            return 0.0;
+        }
+
+        ((self.value - self.range.start()) / span).clamp(0.0, 1.0)
+    }
+}
+
+/// The part of a slider that's dragged along
+#[derive(Component)]
+pub(super) struct SliderTrack;
+
+/// The filled-in part of a slider's track, up to its value
+#[derive(Component)]
+pub(super) struct SliderFill;
+
+pub(super) fn toggle_text(on: bool, localization: &Localization) -> String {
+    localization.get(if on { "common.on" } else { "common.off" })
+}
+
+/// Gets the colors for a toggle's switch or a dropdown's option, which stays highlighted while it's on
+pub(super) fn toggle_palette(on: bool) -> InteractionPalette {
+    let none = if on {
+        SELECTED_BUTTON_BACKGROUND
+    } else {
+        NODE_BACKGROUND
+    };
+    InteractionPalette {
+        none,
+        hovered: BUTTON_HOVERED_BACKGROUND,
+        pressed: BUTTON_PRESSED_BACKGROUND,
+        disabled: greyed_out(none),
+    }
+}
+
+fn flip_toggles(
+    switch_query: Query<(&Interaction, &Parent), (Changed<Interaction>, With<ToggleSwitch>)>,
+    mut toggle_query: Query<&mut Toggle>,
+    mut commands: Commands,
+) {
+    for (interaction, parent) in &switch_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let Ok(mut toggle) = toggle_query.get_mut(parent.get()) else {
+            continue;
+        };
+
+        toggle.0 = !toggle.0;
+        commands.trigger_targets(ValueChanged(toggle.0), parent.get());
+    }
+}
+
+/// Updates how toggles' switches look when they're flipped, and puts their text in the player's language
+fn update_toggle_switches(
+    localization: Res<Localization>,
+    toggle_query: Query<(Ref<Toggle>, &Children)>,
+    mut switch_query: Query<
+        (
+            &Interaction,
+            &Children,
+            &mut InteractionPalette,
+            &mut BackgroundColor,
+        ),
+        With<ToggleSwitch>,
+    >,
+    mut text_query: Query<&mut Text>,
+) {
+    for (toggle, children) in &toggle_query {
+        if !toggle.is_changed() && !localization.is_changed() {
+            continue;
+        }
+
+        let mut switches = switch_query.iter_many_mut(children);
+        while let Some((interaction, switch_children, mut palette, mut background_color)) =
+            switches.fetch_next()
+        {
+            *palette = toggle_palette(toggle.0);
+            if matches!(interaction, Interaction::None) {
+                *background_color = BackgroundColor(palette.none);
+            }
+
+            let mut texts = text_query.iter_many_mut(switch_children);
+            while let Some(mut text) = texts.fetch_next() {
+                text.sections[0].value = toggle_text(toggle.0, &localization);
+            }
+        }
+    }
+}
+
+/// Sets sliders' values from where the cursor is on their tracks while they're held down
+fn drag_sliders(
+    ui_scale: Res<UiScale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    track_query: Query<(&Interaction, &Node, &GlobalTransform, &Parent), With<SliderTrack>>,
+    mut slider_query: Query<&mut Slider>,
+    mut commands: Commands,
+) {
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+    let cursor = cursor / ui_scale.0;
+
+    for (interaction, node, transform, parent) in &track_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let Ok(mut slider) = slider_query.get_mut(parent.get()) else {
+            continue;
+        };
+
+        let rect = node.logical_rect(transform);
+        let fraction = ((cursor.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+        let value = slider.range.start() + fraction * (slider.range.end() - slider.range.start());
+        if slider.value != value {
+            slider.value = value;
+            commands.trigger_targets(ValueChanged(value), parent.get());
+        }
+    }
+}
+
+fn update_slider_fills(
+    slider_query: Query<(&Slider, &Children), Changed<Slider>>,
+    track_query: Query<&Children, With<SliderTrack>>,
+    mut fill_query: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in &slider_query {
+        for track_children in track_query.iter_many(children) {
+            let mut fills = fill_query.iter_many_mut(track_children);
+            while let Some(mut style) = fills.fetch_next() {
+                style.width = Val::Percent(slider.fraction() * 100.0);
+            }
+        }
+    }
+}
+
+/// Event sent to a dropdown when the player picks one of its options.
+/// Dropdowns send the index of the option, and [`send_dropdown_value`] can also send the value it stands for.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SelectionChanged<T>(pub T);
+
+/// A list of options that can be expanded to pick one of them, by clicking or with the arrow keys and enter.
+/// Changing which option is selected directly updates how it looks, without sending [`SelectionChanged`].
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Dropdown {
+    pub selected: usize,
+    options: Vec<String>,
+    /// The option that's picked if enter is pressed
+    highlighted: usize,
+    expanded: bool,
+}
+
+impl Dropdown {
+    pub(super) fn new(options: Vec<String>, selected: usize) -> Dropdown {
+        Dropdown {
+            selected,
+            options,
+            highlighted: selected,
+            expanded: false,
+        }
+    }
+
+    pub(super) fn selected_text(&self) -> &str {
+        self.options
+            .get(self.selected)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    pub(super) fn expanded(&self) -> bool {
+        self.expanded
+    }
+
+    fn expand(&mut self) {
+        self.expanded = true;
+        self.highlighted = self.selected;
+    }
+}
+
+/// Lets a dropdown's options stand for values, so [`SelectionChanged`] can be sent with the picked value rather than its index.
+#[derive(Component, Debug, Clone)]
+pub struct DropdownValues<T>(pub Vec<T>);
+
+/// The button that expands the dropdown it's for
+#[derive(Component)]
+pub(super) struct DropdownButton(pub Entity);
+
+/// Holds the options of the dropdown it's for, and is only shown while it's expanded
+#[derive(Component)]
+pub(super) struct DropdownList(pub Entity);
+
+/// One of the options of a dropdown
+#[derive(Component)]
+pub(super) struct DropdownOption {
+    pub dropdown: Entity,
+    pub index: usize,
+}
+
+fn toggle_dropdowns(
+    button_query: Query<(&Interaction, &DropdownButton), Changed<Interaction>>,
+    mut dropdown_query: Query<(Entity, &mut Dropdown)>,
+) {
+    for (interaction, button) in &button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        // only one dropdown is expanded at a time
+        for (entity, mut dropdown) in &mut dropdown_query {
+            if entity == button.0 && !dropdown.expanded {
+                dropdown.expand();
+            } else if dropdown.expanded {
+                dropdown.expanded = false;
+            }
+        }
+    }
+}
+
+fn pick_dropdown_options(
+    option_query: Query<(&Interaction, &DropdownOption), Changed<Interaction>>,
+    mut dropdown_query: Query<&mut Dropdown>,
+    mut commands: Commands,
+) {
+    for (interaction, option) in &option_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+        let Ok(mut dropdown) = dropdown_query.get_mut(option.dropdown) else {
+            continue;
+        };
+
+        dropdown.highlighted = option.index;
+        pick_highlighted(&mut dropdown, option.dropdown, &mut commands);
+    }
+}
+
+/// Moves through the options of the expanded dropdown with the arrow keys, picking one with enter
+fn navigate_dropdowns(
+    input: Res<ButtonInput<KeyCode>>,
+    mut dropdown_query: Query<(Entity, &mut Dropdown)>,
+    mut commands: Commands,
+) {
+    for (entity, mut dropdown) in &mut dropdown_query {
+        if !dropdown.expanded || dropdown.options.is_empty() {
+            continue;
+        }
+
+        let last = dropdown.options.len() - 1;
+        if input.just_pressed(KeyCode::ArrowDown) {
+            dropdown.highlighted = (dropdown.highlighted + 1).min(last);
+        }
+        if input.just_pressed(KeyCode::ArrowUp) {
+            dropdown.highlighted = dropdown.highlighted.saturating_sub(1);
+        }
+        if input.just_pressed(KeyCode::Enter) {
+            pick_highlighted(&mut dropdown, entity, &mut commands);
+        }
+        if input.just_pressed(KeyCode::Escape) {
+            dropdown.expanded = false;
+        }
+    }
+}
+
+fn pick_highlighted(dropdown: &mut Dropdown, entity: Entity, commands: &mut Commands) {
+    dropdown.expanded = false;
+    if dropdown.selected != dropdown.highlighted {
+        dropdown.selected = dropdown.highlighted;
+        commands.trigger_targets(SelectionChanged(dropdown.selected), entity);
+    }
+}
+
+fn update_dropdowns(
+    dropdown_query: Query<Ref<Dropdown>>,
+    button_query: Query<(&DropdownButton, &Children)>,
+    mut list_query: Query<(&DropdownList, &mut Style)>,
+    mut option_query: Query<(
+        &DropdownOption,
+        &Interaction,
+        &mut InteractionPalette,
+        &mut BackgroundColor,
+    )>,
+    mut text_query: Query<&mut Text>,
+) {
+    let changed = |entity: Entity| {
+        dropdown_query
+            .get(entity)
+            .ok()
+            .filter(|dropdown| dropdown.is_changed())
+    };
+
+    for (button, children) in &button_query {
+        let Some(dropdown) = changed(button.0) else {
+            continue;
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = dropdown.selected_text().to_string();
+        }
+    }
+
+    for (list, mut style) in &mut list_query {
+        if let Some(dropdown) = changed(list.0) {
+            style.display = if dropdown.expanded {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+    }
+
+    for (option, interaction, mut palette, mut background_color) in &mut option_query {
+        let Some(dropdown) = changed(option.dropdown) else {
+            continue;
+        };
+        *palette = toggle_palette(option.index == dropdown.highlighted);
+        if matches!(interaction, Interaction::None) {
+            *background_color = BackgroundColor(palette.none);
+        }
+    }
+}
+
+/// Sends [`SelectionChanged`] with the picked value to dropdowns with [`DropdownValues`] of the provided type.
+/// Add this as an observer for each type of value dropdowns are used with.
+pub fn send_dropdown_value<T: Clone + Send + Sync + 'static>(
+    trigger: Trigger<SelectionChanged<usize>>,
+    values_query: Query<&DropdownValues<T>>,
+    mut commands: Commands,
+) {
+    let Ok(values) = values_query.get(trigger.entity()) else {
+        return;
+    };
+    if let Some(value) = values.0.get(trigger.event().0) {
+        commands.trigger_targets(SelectionChanged(value.clone()), trigger.entity());
+    }
+}
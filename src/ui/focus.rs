@@ -0,0 +1,135 @@
+//! Keyboard focus for interactive widgets, so menus can be used without a mouse.
+//! Tab and the arrow keys move focus between widgets in reading order, and enter presses the focused one.
+
+use bevy::{prelude::*, ui::UiSystem};
+
+use super::{
+    controls::Dropdown,
+    interaction::{suppress_disabled_presses, Enabled, InteractionPalette},
+};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<UiFocus>();
+    app.add_systems(
+        PreUpdate,
+        (release_key_presses, move_focus, press_focused)
+            .chain()
+            .after(UiSystem::Focus)
+            .before(suppress_disabled_presses)
+            // the arrow keys and tab do other things while playing, unless something like the game over panel is up
+            .run_if(not(in_state(Screen::Playing)).or_else(any_with_component::<FocusScope>)),
+    );
+}
+
+/// The widget that has keyboard focus, if any
+#[derive(Resource, Debug, Default)]
+pub struct UiFocus(pub Option<Entity>);
+
+/// Limits focus to widgets inside this node while it exists, like for a panel shown over the rest of the screen.
+#[derive(Component, Debug)]
+pub struct FocusScope;
+
+/// A widget that was pressed with the keyboard, which is released on the next frame as if the cursor was over it
+#[derive(Component, Debug)]
+struct KeyPressed;
+
+fn release_key_presses(
+    mut pressed_query: Query<(Entity, &mut Interaction), With<KeyPressed>>,
+    mut commands: Commands,
+) {
+    for (entity, mut interaction) in &mut pressed_query {
+        interaction.set_if_neq(Interaction::Hovered);
+        commands.entity(entity).remove::<KeyPressed>();
+    }
+}
+
+fn move_focus(
+    input: Res<ButtonInput<KeyCode>>,
+    mut focus: ResMut<UiFocus>,
+    widget_query: Query<
+        (
+            Entity,
+            &Node,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&Enabled>,
+        ),
+        (With<Interaction>, With<InteractionPalette>),
+    >,
+    scope_query: Query<(), With<FocusScope>>,
+    parent_query: Query<&Parent>,
+    dropdown_query: Query<&Dropdown>,
+) {
+    // expanded dropdowns use the arrow keys to pick an option
+    if dropdown_query.iter().any(Dropdown::expanded) {
+        return;
+    }
+
+    let mut widgets = widget_query
+        .iter()
+        .filter(|(entity, node, _, view_visibility, enabled)| {
+            view_visibility.get()
+                && node.size().min_element() > 0.0
+                && enabled.is_none_or(|enabled| enabled.0)
+                && (scope_query.is_empty()
+                    || parent_query
+                        .iter_ancestors(*entity)
+                        .any(|ancestor| scope_query.contains(ancestor)))
+        })
+        .map(|(entity, _, transform, ..)| (entity, transform.translation().truncate()))
+        .collect::<Vec<_>>();
+    widgets.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let current = focus
+        .0
+        .and_then(|focused| widgets.iter().position(|(entity, _)| *entity == focused));
+    if focus.0.is_some() && current.is_none() {
+        // the focused widget went away
+        focus.0 = None;
+    }
+    if widgets.is_empty() {
+        return;
+    }
+
+    let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let forward = (input.just_pressed(KeyCode::Tab) && !shift)
+        || input.any_just_pressed([KeyCode::ArrowDown, KeyCode::ArrowRight]);
+    let backward = (input.just_pressed(KeyCode::Tab) && shift)
+        || input.any_just_pressed([KeyCode::ArrowUp, KeyCode::ArrowLeft]);
+
+    let last = widgets.len() - 1;
+    let next = match current {
+        Some(i) if forward => Some(if i == last { 0 } else { i + 1 }),
+        Some(i) if backward => Some(if i == 0 { last } else { i - 1 }),
+        None if forward => Some(0),
+        None if backward => Some(last),
+        _ => None,
+    };
+    if let Some(next) = next {
+        focus.0 = Some(widgets[next].0);
+    }
+}
+
+fn press_focused(
+    input: Res<ButtonInput<KeyCode>>,
+    focus: Res<UiFocus>,
+    mut interaction_query: Query<&mut Interaction>,
+    dropdown_query: Query<&Dropdown>,
+    mut commands: Commands,
+) {
+    // expanded dropdowns use enter to pick an option
+    if !input.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter])
+        || dropdown_query.iter().any(Dropdown::expanded)
+    {
+        return;
+    }
+    let Some(focused) = focus.0 else {
+        return;
+    };
+
+    if let Ok(mut interaction) = interaction_query.get_mut(focused) {
+        *interaction = Interaction::Pressed;
+        commands.entity(focused).insert(KeyPressed);
+    }
+}
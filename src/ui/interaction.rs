@@ -1,37 +1,208 @@
-use bevy::prelude::*;
+use std::time::Duration;
 
+use bevy::{prelude::*, ui::UiSystem};
+
+use super::focus::UiFocus;
 use crate::game::{assets::SfxKey, audio::sfx::PlaySfx};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<InteractionPalette>();
-    app.add_systems(Update, apply_interaction_palette);
+    app.add_event::<ButtonActivated>();
+    app.add_event::<HoverEntered>();
+    app.add_systems(
+        PreUpdate,
+        (
+            suppress_disabled_presses,
+            send_button_activations,
+            repeat_held_buttons,
+            send_hover_events,
+        )
+            .chain()
+            .after(UiSystem::Focus),
+    );
+    // after everything else, so widgets that get new palettes or are disabled partway through a frame end up the right color
+    app.add_systems(PostUpdate, apply_interaction_palette);
 }
 
 pub type InteractionQuery<'w, 's, T> =
     Query<'w, 's, (&'static Interaction, T), Changed<Interaction>>;
 
-/// Palette for widget interactions.
+/// Palette for widget interactions. Focused widgets use the hovered color.
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
 pub struct InteractionPalette {
     pub none: Color,
     pub hovered: Color,
     pub pressed: Color,
+    pub disabled: Color,
 }
 
 /// Whether a button is enabled or not.
+/// Disabled buttons are never seen as pressed, so handlers don't need to check this themselves.
 #[derive(Component)]
 pub struct Enabled(pub bool);
 
+/// Event sent when a button is pressed and then released while the cursor is still over it
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ButtonActivated(pub Entity);
+
+/// Event sent when the cursor moves onto a button, but not when the button goes back to being hovered after it's pressed
+#[derive(Event, Debug, Clone, Copy)]
+pub struct HoverEntered(pub Entity);
+
+/// A button the cursor is over
+#[derive(Component)]
+struct Hovering;
+
+/// Makes a button activate as soon as it's pressed, then keep activating while it's held down.
+#[derive(Component, Debug)]
+pub struct Repeatable {
+    /// How long the button has to be held before it starts repeating
+    pub delay: Duration,
+    /// How long to wait between each repeat after that
+    pub interval: Duration,
+    timer: Timer,
+}
+
+impl Repeatable {
+    pub fn new(delay: Duration, interval: Duration) -> Repeatable {
+        Repeatable {
+            delay,
+            interval,
+            timer: Timer::new(delay, TimerMode::Once),
+        }
+    }
+}
+
+impl Default for Repeatable {
+    fn default() -> Self {
+        Repeatable::new(Duration::from_millis(400), Duration::from_millis(80))
+    }
+}
+
+/// Makes a button activate as soon as it's pressed, for buttons that start something that goes on while they're held down
+#[derive(Component, Debug)]
+pub struct ActivateOnPress;
+
+/// A button that's being held down, which is activated if it's released while it's hovered
+#[derive(Component)]
+struct Armed;
+
+/// Keeps disabled widgets from being pressed, so it's never seen by the systems that handle presses
+pub(super) fn suppress_disabled_presses(
+    mut interaction_query: Query<(&mut Interaction, &Enabled), Changed<Interaction>>,
+) {
+    for (mut interaction, enabled) in &mut interaction_query {
+        if !enabled.0 && *interaction == Interaction::Pressed {
+            *interaction = Interaction::Hovered;
+        }
+    }
+}
+
+fn send_button_activations(
+    mut interaction_query: Query<
+        (
+            Entity,
+            &Interaction,
+            Has<Armed>,
+            Has<ActivateOnPress>,
+            Option<&mut Repeatable>,
+        ),
+        Changed<Interaction>,
+    >,
+    mut activated_events: EventWriter<ButtonActivated>,
+    mut commands: Commands,
+) {
+    for (entity, interaction, armed, activate_on_press, repeatable) in &mut interaction_query {
+        let activated_on_press = activate_on_press || repeatable.is_some();
+        match interaction {
+            Interaction::Pressed if !armed => {
+                commands.entity(entity).insert(Armed);
+                if let Some(mut repeatable) = repeatable {
+                    let delay = repeatable.delay;
+                    repeatable.timer = Timer::new(delay, TimerMode::Once);
+                }
+                if activated_on_press {
+                    activated_events.send(ButtonActivated(entity));
+                }
+            }
+            Interaction::Hovered if armed => {
+                // these were already activated when they were pressed
+                if !activated_on_press {
+                    activated_events.send(ButtonActivated(entity));
+                }
+                commands.entity(entity).remove::<Armed>();
+            }
+            // released somewhere else
+            Interaction::None if armed => {
+                commands.entity(entity).remove::<Armed>();
+            }
+            _ => (),
+        }
+    }
+}
+
+fn repeat_held_buttons(
+    time: Res<Time>,
+    mut repeatable_query: Query<(Entity, &Interaction, &mut Repeatable), With<Armed>>,
+    mut activated_events: EventWriter<ButtonActivated>,
+) {
+    for (entity, interaction, mut repeatable) in &mut repeatable_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        repeatable.timer.tick(time.delta());
+        if repeatable.timer.finished() {
+            activated_events.send(ButtonActivated(entity));
+            let interval = repeatable.interval;
+            repeatable.timer = Timer::new(interval, TimerMode::Once);
+        }
+    }
+}
+
+fn send_hover_events(
+    interaction_query: Query<(Entity, &Interaction, Has<Hovering>), Changed<Interaction>>,
+    mut hover_events: EventWriter<HoverEntered>,
+    mut commands: Commands,
+) {
+    for (entity, interaction, hovering) in &interaction_query {
+        match interaction {
+            Interaction::None if hovering => {
+                commands.entity(entity).remove::<Hovering>();
+            }
+            Interaction::Hovered | Interaction::Pressed if !hovering => {
+                hover_events.send(HoverEntered(entity));
+                commands.entity(entity).insert(Hovering);
+            }
+            _ => (),
+        }
+    }
+}
+
 fn apply_interaction_palette(
-    mut palette_query: InteractionQuery<(&InteractionPalette, &mut BackgroundColor, &Enabled)>,
+    focus: Res<UiFocus>,
+    mut palette_query: Query<(
+        Entity,
+        Ref<Interaction>,
+        Ref<InteractionPalette>,
+        Ref<Enabled>,
+        &mut BackgroundColor,
+    )>,
 ) {
-    for (interaction, (palette, mut background, enabled)) in &mut palette_query {
-        if !enabled.0 {
+    for (entity, interaction, palette, enabled, mut background) in &mut palette_query {
+        if !(interaction.is_changed()
+            || palette.is_changed()
+            || enabled.is_changed()
+            || focus.is_changed())
+        {
             continue;
         }
 
-        *background = match interaction {
+        let focused = focus.0 == Some(entity);
+        *background = match *interaction {
+            _ if !enabled.0 => palette.disabled,
+            Interaction::None if focused => palette.hovered,
             Interaction::None => palette.none,
             Interaction::Hovered => palette.hovered,
             Interaction::Pressed => palette.pressed,
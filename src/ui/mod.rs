@@ -3,14 +3,27 @@
 // Unused utilities and re-exports may trigger these lints undesirably.
 #![allow(dead_code, unused_imports)]
 
+pub mod banner;
+pub mod controls;
+pub mod focus;
 pub mod interaction;
 pub mod palette;
+pub mod tooltip;
 pub mod widgets;
 
 pub mod prelude {
     pub use super::{
-        interaction::{InteractionPalette, InteractionQuery},
+        controls::{
+            send_dropdown_value, Dropdown, DropdownValues, SelectionChanged, Slider, Toggle,
+            ValueChanged,
+        },
+        focus::FocusScope,
+        interaction::{
+            ActivateOnPress, ButtonActivated, HoverEntered, InteractionPalette, InteractionQuery,
+            Repeatable,
+        },
         palette as ui_palette,
+        tooltip::Tooltip,
         widgets::{Containers as _, Widgets as _},
     };
 }
@@ -18,5 +31,11 @@ pub mod prelude {
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(interaction::plugin);
+    app.add_plugins((
+        banner::plugin,
+        controls::plugin,
+        focus::plugin,
+        interaction::plugin,
+        tooltip::plugin,
+    ));
 }
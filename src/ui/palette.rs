@@ -1,18 +1,143 @@
 use bevy::prelude::*;
 
+use super::interaction::InteractionPalette;
+
 pub const BUTTON_HOVERED_BACKGROUND: Color = Color::srgb(0.6, 0.4, 0.7);
 pub const BUTTON_PRESSED_BACKGROUND: Color = Color::srgb(0.7, 0.5, 0.8);
 
 pub const BUTTON_TEXT: Color = Color::srgb(0.925, 0.925, 0.925);
 pub const LABEL_TEXT: Color = Color::srgb(0.9, 0.9, 0.9);
+pub const BEAT_NUMBER_TEXT: Color = Color::srgb(0.55, 0.55, 0.55);
 pub const HEADER_TEXT: Color = Color::srgb(0.9, 0.9, 0.9);
 pub const TITLE_TEXT: Color = Color::srgb(0.9, 0.2, 0.2);
+pub const ERROR_TEXT: Color = Color::srgb(0.95, 0.45, 0.4);
 
 pub const NODE_BACKGROUND: Color = Color::srgb(0.5, 0.3, 0.6);
+pub const DISABLED_BUTTON_BACKGROUND: Color = Color::srgb(0.35, 0.33, 0.37);
+/// Used for buttons that stay highlighted to show which option is picked
+pub const SELECTED_BUTTON_BACKGROUND: Color = Color::srgb(0.3, 0.55, 0.35);
+pub const TOOLTIP_BACKGROUND: Color = Color::srgba(0.1, 0.1, 0.1, 0.9);
+pub const BANNER_BACKGROUND: Color = Color::srgba(0.85, 0.6, 0.1, 0.85);
 
 pub const INACTIVE_BEAT_BUTTON: Color = Color::srgb(0.3, 0.3, 0.3);
 pub const ACTIVE_BEAT_BUTTON: Color = Color::srgb(0.3, 0.65, 0.3);
 pub const HOVERED_INACTIVE_BEAT_BUTTON: Color = Color::srgb(0.4, 0.4, 0.4);
+pub const SHADED_INACTIVE_BEAT_BUTTON: Color = Color::srgb(0.37, 0.37, 0.37);
+pub const HOVERED_SHADED_INACTIVE_BEAT_BUTTON: Color = Color::srgb(0.47, 0.47, 0.47);
 pub const HOVERED_ACTIVE_BEAT_BUTTON: Color = Color::srgb(0.3, 0.8, 0.3);
-pub const PLAYING_ACTIVE_BEAT_BUTTON: Color = Color::srgb(0.65, 0.3, 0.3);
-pub const PLAYING_INACTIVE_BEAT_BUTTON: Color = Color::srgb(0.2, 0.2, 0.2);
+pub const MAYBE_BEAT_BUTTON: Color = Color::srgb(0.65, 0.6, 0.2);
+pub const HOVERED_MAYBE_BEAT_BUTTON: Color = Color::srgb(0.8, 0.75, 0.3);
+/// Purple stands apart from the active colors in both beat palettes
+pub const SUGGESTED_BEAT_BUTTON: Color = Color::srgb(0.6, 0.35, 0.8);
+pub const HOVERED_SUGGESTED_BEAT_BUTTON: Color = Color::srgb(0.72, 0.5, 0.9);
+pub const PLAYHEAD: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+
+/// The colors of the rows each player controls in co-op
+pub const PLAYER_ONE_ROW: Color = Color::srgb(0.25, 0.45, 0.8);
+pub const PLAYER_TWO_ROW: Color = Color::srgb(0.85, 0.45, 0.2);
+
+pub const COLORBLIND_ACTIVE_BEAT_BUTTON: Color = Color::srgb(0.2, 0.45, 0.85);
+pub const COLORBLIND_HOVERED_ACTIVE_BEAT_BUTTON: Color = Color::srgb(0.35, 0.6, 0.95);
+pub const COLORBLIND_MAYBE_BEAT_BUTTON: Color = Color::srgb(0.9, 0.55, 0.1);
+pub const COLORBLIND_HOVERED_MAYBE_BEAT_BUTTON: Color = Color::srgb(0.95, 0.7, 0.3);
+
+/// The grey that disabled widgets are faded toward
+const DISABLED_GREY: Color = Color::srgb(0.25, 0.25, 0.25);
+
+pub const EARNED_STAR: Color = Color::srgb(0.95, 0.8, 0.2);
+pub const UNEARNED_STAR: Color = Color::srgb(0.3, 0.3, 0.3);
+
+pub const BOSS_HEALTH: Color = Color::srgb(0.8, 0.15, 0.2);
+pub const BOSS_HEALTH_BACKGROUND: Color = Color::srgba(0.1, 0.1, 0.1, 0.8);
+
+/// The colors of splits that are faster or slower than the best ones
+pub const SPLIT_AHEAD: Color = Color::srgb(0.4, 0.85, 0.4);
+pub const SPLIT_BEHIND: Color = Color::srgb(0.95, 0.45, 0.4);
+
+/// The colors used for the sequencer's beat buttons, which can be swapped out at runtime.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct BeatPalette {
+    pub inactive: Color,
+    pub active: Color,
+    pub hovered_inactive: Color,
+    /// Used instead of `inactive` for every other group of beats
+    pub shaded_inactive: Color,
+    /// Used instead of `hovered_inactive` for every other group of beats
+    pub hovered_shaded_inactive: Color,
+    pub hovered_active: Color,
+    /// Used instead of `active` for notes that only play some of the time
+    pub maybe: Color,
+    /// Used instead of `hovered_active` for notes that only play some of the time
+    pub hovered_maybe: Color,
+    /// Used instead of `active` for notes filled in by a suggestion
+    pub suggested: Color,
+    /// Used instead of `hovered_active` for notes filled in by a suggestion
+    pub hovered_suggested: Color,
+}
+
+impl BeatPalette {
+    pub const STANDARD: BeatPalette = BeatPalette {
+        inactive: INACTIVE_BEAT_BUTTON,
+        active: ACTIVE_BEAT_BUTTON,
+        hovered_inactive: HOVERED_INACTIVE_BEAT_BUTTON,
+        shaded_inactive: SHADED_INACTIVE_BEAT_BUTTON,
+        hovered_shaded_inactive: HOVERED_SHADED_INACTIVE_BEAT_BUTTON,
+        hovered_active: HOVERED_ACTIVE_BEAT_BUTTON,
+        maybe: MAYBE_BEAT_BUTTON,
+        hovered_maybe: HOVERED_MAYBE_BEAT_BUTTON,
+        suggested: SUGGESTED_BEAT_BUTTON,
+        hovered_suggested: HOVERED_SUGGESTED_BEAT_BUTTON,
+    };
+
+    /// Blue and orange instead of green and yellow, so active and maybe beats stand out without relying on red-green vision
+    pub const COLORBLIND: BeatPalette = BeatPalette {
+        active: COLORBLIND_ACTIVE_BEAT_BUTTON,
+        hovered_active: COLORBLIND_HOVERED_ACTIVE_BEAT_BUTTON,
+        maybe: COLORBLIND_MAYBE_BEAT_BUTTON,
+        hovered_maybe: COLORBLIND_HOVERED_MAYBE_BEAT_BUTTON,
+        ..BeatPalette::STANDARD
+    };
+
+    /// Gets the interaction colors for a beat button that's on or off, that only plays some of the time or not,
+    /// and in a shaded group of beats or not
+    pub fn interaction_palette(
+        &self,
+        active: bool,
+        maybe: bool,
+        shaded: bool,
+    ) -> InteractionPalette {
+        let (inactive, hovered_inactive) = if shaded {
+            (self.shaded_inactive, self.hovered_shaded_inactive)
+        } else {
+            (self.inactive, self.hovered_inactive)
+        };
+
+        if active && maybe {
+            InteractionPalette {
+                none: self.maybe,
+                hovered: self.hovered_maybe,
+                pressed: inactive,
+                disabled: greyed_out(self.maybe),
+            }
+        } else if active {
+            InteractionPalette {
+                none: self.active,
+                hovered: self.hovered_active,
+                pressed: inactive,
+                disabled: greyed_out(self.active),
+            }
+        } else {
+            InteractionPalette {
+                none: inactive,
+                hovered: hovered_inactive,
+                pressed: self.active,
+                disabled: greyed_out(inactive),
+            }
+        }
+    }
+}
+
+/// Fades a color toward grey, so a disabled widget still shows what it's set to
+pub fn greyed_out(color: Color) -> Color {
+    color.mix(&DISABLED_GREY, 0.6)
+}
@@ -0,0 +1,133 @@
+//! Small popups that explain what something does when it's hovered for a moment.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use super::palette::{LABEL_TEXT, TOOLTIP_BACKGROUND};
+use crate::game::assets::{FontKey, HandleMap};
+
+/// How long something has to be hovered before its tooltip shows up, in seconds
+const TOOLTIP_DELAY: f32 = 0.5;
+
+/// How far the tooltip is from the cursor, horizontally and vertically, in pixels
+const TOOLTIP_OFFSET: f32 = 15.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Tooltip>();
+    app.insert_resource(HoveredTooltip {
+        target: None,
+        timer: Timer::from_seconds(TOOLTIP_DELAY, TimerMode::Once),
+        popup: None,
+    });
+    app.add_systems(
+        Update,
+        (add_tooltip_interaction, track_hovered_tooltip, show_tooltip).chain(),
+    );
+}
+
+/// Shows a popup with some text when the entity it's on is hovered for a moment.
+/// The entity is made hoverable if it isn't already.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Tooltip(pub String);
+
+/// The entity with a tooltip that's currently hovered, if any, and how long it's been hovered for.
+#[derive(Resource)]
+struct HoveredTooltip {
+    target: Option<Entity>,
+    timer: Timer,
+    /// The popup showing the tooltip for the target, once it's been hovered long enough
+    popup: Option<Entity>,
+}
+
+fn add_tooltip_interaction(
+    tooltip_query: Query<Entity, (Added<Tooltip>, Without<Interaction>)>,
+    mut commands: Commands,
+) {
+    for entity in &tooltip_query {
+        commands.entity(entity).insert(Interaction::default());
+    }
+}
+
+/// Keeps track of which tooltip is hovered, starting the delay over whenever it changes.
+fn track_hovered_tooltip(
+    tooltip_query: Query<(Entity, &Interaction), With<Tooltip>>,
+    mut hovered: ResMut<HoveredTooltip>,
+    mut commands: Commands,
+) {
+    let target = tooltip_query
+        .iter()
+        .find(|(_, interaction)| matches!(interaction, Interaction::Hovered))
+        .map(|(entity, _)| entity);
+    if target == hovered.target {
+        return;
+    }
+
+    if let Some(popup) = hovered.popup.take() {
+        commands.entity(popup).despawn_recursive();
+    }
+    hovered.target = target;
+    hovered.timer.reset();
+}
+
+fn show_tooltip(
+    time: Res<Time>,
+    ui_scale: Res<UiScale>,
+    font_handles: Res<HandleMap<FontKey>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    tooltip_query: Query<&Tooltip>,
+    mut hovered: ResMut<HoveredTooltip>,
+    mut commands: Commands,
+) {
+    let Some(target) = hovered.target else {
+        return;
+    };
+    if hovered.popup.is_some() || !hovered.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let Ok(tooltip) = tooltip_query.get(target) else {
+        return;
+    };
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    // show it above the cursor, since most things with tooltips are near the bottom of the screen
+    let left = cursor.x / ui_scale.0 + TOOLTIP_OFFSET;
+    let bottom = (window.height() - cursor.y) / ui_scale.0 + TOOLTIP_OFFSET;
+
+    let popup = commands
+        .spawn((
+            Name::new("Tooltip"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(left),
+                    bottom: Val::Px(bottom),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(TOOLTIP_BACKGROUND),
+                border_radius: BorderRadius::all(Val::Px(3.0)),
+                z_index: ZIndex::Global(i32::MAX),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Tooltip Text"),
+                TextBundle::from_section(
+                    tooltip.0.clone(),
+                    TextStyle {
+                        font: font_handles.get(FontKey::General),
+                        font_size: 20.0,
+                        color: LABEL_TEXT,
+                    },
+                ),
+            ));
+        })
+        .id();
+    hovered.popup = Some(popup);
+}
@@ -1,14 +1,23 @@
 //! Helper traits for creating common widgets.
 
+use std::ops::RangeInclusive;
+
 use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
 
 use super::{
+    controls::{
+        toggle_palette, Dropdown, DropdownButton, DropdownList, DropdownOption, Slider, SliderFill,
+        SliderTrack, Toggle, ToggleSwitch,
+    },
     interaction::{Enabled, InteractionPalette},
     palette::*,
 };
 
 use crate::game::assets::{FontKey, HandleMap};
 
+/// The width of a dropdown's button and options, in pixels
+const DROPDOWN_WIDTH: f32 = 150.0;
+
 /// An extension trait for spawning UI widgets.
 pub trait Widgets {
     /// Spawn a simple button with text.
@@ -38,6 +47,32 @@ pub trait Widgets {
         text: impl Into<String>,
         font_handles: &HandleMap<FontKey>,
     ) -> EntityCommands;
+
+    /// Spawn a labeled switch that can be turned on or off.
+    fn toggle(
+        &mut self,
+        label: impl Into<String>,
+        initial: bool,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands;
+
+    /// Spawn a labeled slider for picking a value from a range.
+    fn slider(
+        &mut self,
+        label: impl Into<String>,
+        range: RangeInclusive<f32>,
+        initial: f32,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands;
+
+    /// Spawn a labeled dropdown for picking one of the provided options.
+    fn dropdown(
+        &mut self,
+        label: impl Into<String>,
+        options: Vec<String>,
+        selected: usize,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands;
 }
 
 impl<T: Spawn> Widgets for T {
@@ -64,6 +99,7 @@ impl<T: Spawn> Widgets for T {
                 none: NODE_BACKGROUND,
                 hovered: BUTTON_HOVERED_BACKGROUND,
                 pressed: BUTTON_PRESSED_BACKGROUND,
+                disabled: DISABLED_BUTTON_BACKGROUND,
             },
             Enabled(true),
         ));
@@ -106,6 +142,7 @@ impl<T: Spawn> Widgets for T {
                 none: NODE_BACKGROUND,
                 hovered: BUTTON_HOVERED_BACKGROUND,
                 pressed: BUTTON_PRESSED_BACKGROUND,
+                disabled: DISABLED_BUTTON_BACKGROUND,
             },
             Enabled(true),
         ));
@@ -192,6 +229,162 @@ impl<T: Spawn> Widgets for T {
         });
         entity
     }
+    fn toggle(
+        &mut self,
+        label: impl Into<String>,
+        initial: bool,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands {
+        let mut entity = self.spawn((Name::new("Toggle"), Toggle(initial), widget_row()));
+        entity.with_children(|children| {
+            children.label(label, font_handles);
+            let palette = toggle_palette(initial);
+            // the switch's text is filled in once the toggle is spawned, in the player's language
+            children.small_button("", font_handles).insert((
+                ToggleSwitch,
+                BackgroundColor(palette.none),
+                palette,
+            ));
+        });
+        entity
+    }
+
+    fn slider(
+        &mut self,
+        label: impl Into<String>,
+        range: RangeInclusive<f32>,
+        initial: f32,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands {
+        let slider = Slider {
+            value: initial,
+            range,
+        };
+        let fraction = slider.fraction();
+        let mut entity = self.spawn((Name::new("Slider"), slider, widget_row()));
+        entity.with_children(|children| {
+            children.label(label, font_handles);
+            children
+                .spawn((
+                    Name::new("Slider track"),
+                    SliderTrack,
+                    Interaction::default(),
+                    NodeBundle {
+                        style: Style {
+                            width: Px(150.0),
+                            height: Px(16.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(INACTIVE_BEAT_BUTTON),
+                        border_radius: BorderRadius::all(Val::Px(3.0)),
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children.spawn((
+                        Name::new("Slider fill"),
+                        SliderFill,
+                        NodeBundle {
+                            style: Style {
+                                width: Percent(fraction * 100.0),
+                                height: Percent(100.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(NODE_BACKGROUND),
+                            border_radius: BorderRadius::all(Val::Px(3.0)),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+        entity
+    }
+    fn dropdown(
+        &mut self,
+        label: impl Into<String>,
+        options: Vec<String>,
+        selected: usize,
+        font_handles: &HandleMap<FontKey>,
+    ) -> EntityCommands {
+        let dropdown = Dropdown::new(options.clone(), selected);
+        let selected_text = dropdown.selected_text().to_string();
+        let mut entity = self.spawn((Name::new("Dropdown"), dropdown, widget_row()));
+        let dropdown_entity = entity.id();
+        entity.with_children(|children| {
+            children.label(label, font_handles);
+            children
+                .spawn((
+                    Name::new("Dropdown anchor"),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|children| {
+                    children
+                        .small_button(selected_text, font_handles)
+                        .insert((DropdownButton(dropdown_entity), dropdown_item_style()));
+                    children
+                        .spawn((
+                            Name::new("Dropdown list"),
+                            DropdownList(dropdown_entity),
+                            NodeBundle {
+                                style: Style {
+                                    display: Display::None,
+                                    top: Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    position_type: PositionType::Absolute,
+                                    ..default()
+                                },
+                                z_index: ZIndex::Global(10),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|children| {
+                            for (index, option) in options.into_iter().enumerate() {
+                                let palette = toggle_palette(index == selected);
+                                children.small_button(option, font_handles).insert((
+                                    DropdownOption {
+                                        dropdown: dropdown_entity,
+                                        index,
+                                    },
+                                    dropdown_item_style(),
+                                    BackgroundColor(palette.none),
+                                    palette,
+                                ));
+                            }
+                        });
+                });
+        });
+        entity
+    }
+}
+
+/// The style of a dropdown's button and options
+fn dropdown_item_style() -> Style {
+    Style {
+        width: Px(DROPDOWN_WIDTH),
+        height: Px(35.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+/// The row a labeled control sits in, with its label on the left
+fn widget_row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Px(20.0),
+            ..default()
+        },
+        ..default()
+    }
 }
 
 /// An extension trait for spawning UI containers.
@@ -0,0 +1,52 @@
+//! Checks that the core logic runs on its own, without anything from the presentation side of the game.
+
+use bevy::prelude::*;
+use looprunner::{
+    build_headless_app,
+    game::{
+        movement::TotalDistance,
+        settings::Settings,
+        spawn::{
+            player::Player,
+            sequencer::{PlaySequence, Sequence, SequencerRow, NUM_SYNTH_NOTES},
+        },
+    },
+};
+
+/// How many times the app is updated once the run has started
+const FRAMES: u32 = 300;
+
+fn runner_x(app: &mut App) -> f32 {
+    let mut runner_query = app.world_mut().query_filtered::<&Transform, With<Player>>();
+    runner_query.single(app.world()).translation.x
+}
+
+#[test]
+fn core_logic_runs_without_presentation() {
+    // only `CoreLogicPlugin` on top of Bevy's own plugins,
+    // so any system that relies on something the presentation side sets up panics here
+    let mut app = build_headless_app();
+    // there's nothing to see, so there's no point in panning over the level before the run
+    app.insert_resource(Settings {
+        reduced_motion: true,
+        ..default()
+    });
+    let mut sequence = Sequence::new();
+    sequence.add_note(SequencerRow::SynthNote(NUM_SYNTH_NOTES - 1), 0);
+    sequence.add_note(SequencerRow::Kick, 4);
+    app.insert_resource(sequence);
+    // spawns the level
+    app.update();
+    let start_x = runner_x(&mut app);
+    app.world_mut().trigger(PlaySequence);
+
+    // the runner wraps back around at the end of the level, so this keeps track of how far they got before that
+    let mut furthest_x = start_x;
+    for _ in 0..FRAMES {
+        app.update();
+        furthest_x = furthest_x.max(runner_x(&mut app));
+    }
+
+    assert!(app.world().resource::<TotalDistance>().0 > 0.0);
+    assert!(furthest_x > start_x);
+}
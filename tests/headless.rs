@@ -0,0 +1,115 @@
+//! Runs through levels with the headless app, checking that runs play out without a window or audio.
+
+use bevy::prelude::*;
+use looprunner::{
+    build_headless_app,
+    game::{
+        level_definition::{
+            EditorLevel, GridCell, LevelDefinition, ObstacleKind, ObstaclePlacement,
+        },
+        movement::TotalDistance,
+        settings::Settings,
+        spawn::{
+            level::{CurrentLevel, GameMode, LevelCleared},
+            player::Player,
+            sequencer::{
+                Dead, PlaySequence, ResetSequence, Sequence, SequencerRow, NUM_SYNTH_NOTES,
+            },
+        },
+    },
+    HEADLESS_FRAME_TIME,
+};
+
+/// How long a run gets to do what it's expected to, in seconds
+const MAX_RUN_DURATION: f32 = 15.0;
+
+/// The levels cleared so far, in order
+#[derive(Resource, Debug, Default)]
+struct ClearedLevels(Vec<u32>);
+
+/// Builds a headless app that runs through a one-screen level with the provided obstacles
+fn app_with_level(obstacles: Vec<ObstaclePlacement>) -> App {
+    let mut app = build_headless_app();
+    app.insert_resource(GameMode::EditorTest);
+    app.insert_resource(EditorLevel(LevelDefinition {
+        obstacles,
+        ..default()
+    }));
+    // there's nothing to see, so there's no point in panning over the level before the run
+    app.insert_resource(Settings {
+        reduced_motion: true,
+        ..default()
+    });
+    app.init_resource::<ClearedLevels>();
+    app.observe(
+        |trigger: Trigger<LevelCleared>, mut cleared: ResMut<ClearedLevels>| {
+            cleared.0.push(trigger.event().0);
+        },
+    );
+    // spawns the level
+    app.update();
+
+    app
+}
+
+/// Starts a run that goes as fast as it can without ever jumping
+fn start_running(app: &mut App) {
+    let mut sequence = Sequence::new();
+    sequence.add_note(SequencerRow::SynthNote(NUM_SYNTH_NOTES - 1), 0);
+
+    let world = app.world_mut();
+    world.insert_resource(sequence);
+    world.trigger(ResetSequence);
+    world.flush();
+    world.trigger(PlaySequence);
+    world.flush();
+}
+
+/// Updates the app until the provided condition holds, returning whether it did before the run ran out of time
+fn run_until(app: &mut App, condition: impl Fn(&World) -> bool) -> bool {
+    let frames = (MAX_RUN_DURATION / HEADLESS_FRAME_TIME.as_secs_f32()) as u32;
+    for _ in 0..frames {
+        app.update();
+        if condition(app.world()) {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn runner_x(app: &mut App) -> f32 {
+    let mut runner_query = app.world_mut().query_filtered::<&Transform, With<Player>>();
+    runner_query.single(app.world()).translation.x
+}
+
+#[test]
+fn running_into_spikes_kills_the_runner() {
+    let mut app = app_with_level(vec![ObstaclePlacement {
+        kind: ObstacleKind::Spikes,
+        cell: GridCell { column: 0, row: 0 },
+    }]);
+    let start_x = runner_x(&mut app);
+
+    start_running(&mut app);
+
+    assert!(run_until(&mut app, |world| world.resource::<Dead>().0));
+    assert!(runner_x(&mut app) > start_x);
+    assert!(app.world().resource::<ClearedLevels>().0.is_empty());
+}
+
+#[test]
+fn running_through_an_empty_level_clears_it() {
+    let mut app = app_with_level(Vec::new());
+
+    start_running(&mut app);
+
+    assert!(run_until(&mut app, |world| {
+        !world.resource::<ClearedLevels>().0.is_empty()
+    }));
+    let world = app.world();
+    assert_eq!(world.resource::<ClearedLevels>().0, vec![0]);
+    assert_eq!(world.resource::<CurrentLevel>().0, 1);
+    assert!(!world.resource::<Dead>().0);
+    assert!(world.resource::<TotalDistance>().0 > 0.0);
+}